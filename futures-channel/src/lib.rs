@@ -40,6 +40,8 @@ cfg_target_has_atomic! {
     mod lock;
     #[cfg(feature = "std")]
     pub mod mpsc;
+    #[cfg(feature = "std")]
+    pub mod multi_oneshot;
     #[cfg(feature = "alloc")]
     pub mod oneshot;
 }
@@ -12,6 +12,14 @@
 //!
 //! Unbounded channels are also available using the `unbounded` constructor.
 //!
+//! Channels whose capacity is measured in a user-defined weight of the
+//! messages, such as their size in bytes, rather than in their number can be
+//! created with the `weighted_channel` constructor.
+//!
+//! By default, senders wait for capacity to become available. Bounded
+//! channels which reject or drop messages instead of providing backpressure
+//! can be created with the `channel_with_overflow` constructor.
+//!
 //! # Disconnection
 //!
 //! When all [`Sender`] handles have been dropped, it is no longer
@@ -158,6 +166,24 @@ pub struct TrySendError<T> {
     val: T,
 }
 
+/// What the senders of a bounded channel do when it is full.
+///
+/// This is selected when the channel is created with
+/// [`channel_with_overflow`](channel_with_overflow).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// Wait for capacity to become available. This is the behavior of
+    /// channels created with [`channel`](channel).
+    Wait,
+    /// Fail to send the message with a [`SendError`] for which
+    /// [`is_full`](SendError::is_full) returns `true`.
+    Reject,
+    /// Drop the oldest message in the channel to make room for the new one.
+    DropOldest,
+    /// Silently drop the message being sent.
+    DropNewest,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 enum SendErrorKind {
     Full,
@@ -261,6 +287,17 @@ struct Inner<T> {
     // Max buffer size of the channel. If `None` then the channel is unbounded.
     buffer: Option<usize>,
 
+    // Weight of each message counted against `buffer`. If `None` then each
+    // message weighs 1.
+    weigher: Option<Weigher<T>>,
+
+    // What senders do when the channel is full.
+    overflow: Overflow,
+
+    // Held while popping from `message_queue` when senders may evict
+    // messages, so that there is only ever one consumer at a time.
+    pop_lock: Mutex<()>,
+
     // Internal channel state. Consists of the number of messages stored in the
     // channel as well as a flag signalling that the channel is closed.
     state: AtomicUsize,
@@ -284,10 +321,21 @@ struct State {
     // `true` when the channel is open
     is_open: bool,
 
-    // Number of messages in the channel
+    // Number of messages in the channel, or their total weight for weighted
+    // channels
     num_messages: usize,
 }
 
+// Function computing the weight of a message in a weighted channel.
+struct Weigher<T>(Box<dyn Fn(&T) -> usize + Send + Sync>);
+
+impl<T> fmt::Debug for Weigher<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Weigher")
+            .finish()
+    }
+}
+
 // The `is_open` flag is stored in the left-most bit of `Inner::state`
 const OPEN_MASK: usize = usize::max_value() - (usize::max_value() >> 1);
 
@@ -341,7 +389,71 @@ pub fn channel<T>(buffer: usize) -> (Sender<T>, Receiver<T>) {
     // Check that the requested buffer size does not exceed the maximum buffer
     // size permitted by the system.
     assert!(buffer < MAX_BUFFER, "requested buffer size too large");
-    let (tx, rx) = channel2(Some(buffer));
+    let (tx, rx) = channel2(Some(buffer), None, Overflow::Wait);
+    (Sender(Some(tx)), rx)
+}
+
+/// Creates a bounded mpsc channel whose senders handle a full channel as
+/// specified by `overflow`.
+///
+/// With [`Overflow::Wait`] this is the same as [`channel`](channel). With any
+/// other strategy, senders never wait for capacity: their
+/// [`poll_ready`](Sender::poll_ready) method always returns `Ready`, which
+/// makes such channels suitable for telemetry or other messages which must
+/// never hold up the sender. The channel then holds at most `buffer`
+/// messages, give or take those being sent concurrently, and always has room
+/// for at least one.
+///
+/// ```
+/// use futures::channel::mpsc::{self, Overflow};
+///
+/// let (mut tx, mut rx) = mpsc::channel_with_overflow(2, Overflow::DropOldest);
+///
+/// for i in 0..5 {
+///     tx.try_send(i).unwrap();
+/// }
+///
+/// assert_eq!(rx.try_next().unwrap(), Some(3));
+/// assert_eq!(rx.try_next().unwrap(), Some(4));
+/// ```
+pub fn channel_with_overflow<T>(buffer: usize, overflow: Overflow) -> (Sender<T>, Receiver<T>) {
+    assert!(buffer < MAX_BUFFER, "requested buffer size too large");
+    let (tx, rx) = channel2(Some(buffer), None, overflow);
+    (Sender(Some(tx)), rx)
+}
+
+/// Creates a bounded mpsc channel whose capacity is measured in the weight of
+/// its messages rather than in their number.
+///
+/// The weight of each message is computed by `weigher`, for instance as its
+/// size in bytes, so that a channel of variable-size frames bounds the memory
+/// they use rather than their number. Messages weigh at least 1, even if
+/// `weigher` returns 0, and `weigher` must return the same weight every time
+/// it is called on a given message.
+///
+/// Like with [`channel`](channel), each sender is guaranteed to be able to
+/// send one message whatever its weight, after which it has to wait until the
+/// total weight of the buffered messages falls back to `capacity`. A message
+/// too heavy for the channel to keep count of is rejected as if the channel
+/// was full.
+///
+/// ```
+/// use futures::channel::mpsc;
+///
+/// let (mut tx, mut rx) = mpsc::weighted_channel(8, |frame: &Vec<u8>| frame.len());
+///
+/// tx.try_send(vec![0; 6]).unwrap();
+/// tx.try_send(vec![0; 6]).unwrap();
+/// assert!(tx.try_send(vec![0; 1]).unwrap_err().is_full());
+///
+/// assert_eq!(rx.try_next().unwrap().unwrap().len(), 6);
+/// tx.try_send(vec![0; 1]).unwrap();
+/// ```
+pub fn weighted_channel<T, F>(capacity: usize, weigher: F) -> (Sender<T>, Receiver<T>)
+    where F: Fn(&T) -> usize + Send + Sync + 'static,
+{
+    assert!(capacity < MAX_BUFFER, "requested capacity too large");
+    let (tx, rx) = channel2(Some(capacity), Some(Weigher(Box::new(weigher))), Overflow::Wait);
     (Sender(Some(tx)), rx)
 }
 
@@ -356,13 +468,20 @@ pub fn channel<T>(buffer: usize) -> (Sender<T>, Receiver<T>) {
 /// the channel. Using an `unbounded` channel has the ability of causing the
 /// process to run out of memory. In this case, the process will be aborted.
 pub fn unbounded<T>() -> (UnboundedSender<T>, UnboundedReceiver<T>) {
-    let (tx, rx) = channel2(None);
+    let (tx, rx) = channel2(None, None, Overflow::Wait);
     (UnboundedSender(Some(tx)), UnboundedReceiver(rx))
 }
 
-fn channel2<T>(buffer: Option<usize>) -> (SenderInner<T>, Receiver<T>) {
+fn channel2<T>(
+    buffer: Option<usize>,
+    weigher: Option<Weigher<T>>,
+    overflow: Overflow,
+) -> (SenderInner<T>, Receiver<T>) {
     let inner = Arc::new(Inner {
         buffer,
+        weigher,
+        overflow,
+        pop_lock: Mutex::new(()),
         state: AtomicUsize::new(INIT_STATE),
         message_queue: Queue::new(),
         parked_queue: Queue::new(),
@@ -416,6 +535,10 @@ impl<T> SenderInner<T> {
         // but assert here for tests as a sanity check.
         debug_assert!(self.poll_unparked(None).is_ready());
 
+        if self.inner.overflow != Overflow::Wait {
+            return self.do_send_overflow(msg);
+        }
+
         // First, increment the number of messages contained by the channel.
         // This operation will also atomically determine if the sender task
         // should be parked.
@@ -423,16 +546,15 @@ impl<T> SenderInner<T> {
         // None is returned in the case that the channel has been closed by the
         // receiver. This happens when `Receiver::close` is called or the
         // receiver is dropped.
-        let park_self = match self.inc_num_messages() {
-            Some(num_messages) => {
+        let weight = self.inner.weight(&msg);
+        let park_self = match self.inc_num_messages(weight) {
+            Ok(num_messages) => {
                 // Block if the current number of pending messages has exceeded
                 // the configured buffer size
                 num_messages > self.inner.buffer.unwrap()
             }
-            None => return Err(TrySendError {
-                err: SendError {
-                    kind: SendErrorKind::Disconnected,
-                },
+            Err(kind) => return Err(TrySendError {
+                err: SendError { kind },
                 val: msg,
             }),
         };
@@ -453,6 +575,46 @@ impl<T> SenderInner<T> {
         Ok(())
     }
 
+    // Do the send without ever parking, handling a full channel according to
+    // its overflow strategy.
+    fn do_send_overflow(&self, msg: T) -> Result<(), TrySendError<T>> {
+        let weight = self.inner.weight(&msg);
+
+        loop {
+            let kind = match self.inc_num_messages_within(weight) {
+                Ok(()) => {
+                    self.queue_push_and_signal(msg);
+                    return Ok(());
+                }
+                Err(SendErrorKind::Full) => match self.inner.overflow {
+                    Overflow::DropNewest => return Ok(()),
+                    // Other senders may fill the room made by the eviction
+                    // before this message is counted, in which case evict
+                    // again.
+                    Overflow::DropOldest if self.inner.evict(weight) => continue,
+                    // Nothing could be evicted, as the oldest messages are
+                    // still being pushed by their senders. Send the message
+                    // beyond the capacity: the receiver, which is woken up by
+                    // the push, drops the excess oldest messages.
+                    Overflow::DropOldest => match self.inc_num_messages(weight) {
+                        Ok(_) => {
+                            self.queue_push_and_signal(msg);
+                            return Ok(());
+                        }
+                        Err(kind) => kind,
+                    },
+                    _ => SendErrorKind::Full,
+                },
+                Err(kind) => kind,
+            };
+
+            return Err(TrySendError {
+                err: SendError { kind },
+                val: msg,
+            });
+        }
+    }
+
     fn poll_ready_nb(&self) -> Poll<Result<(), SendError>> {
         let state = decode_state(self.inner.state.load(SeqCst));
         if state.is_open {
@@ -475,8 +637,9 @@ impl<T> SenderInner<T> {
         self.inner.recv_task.wake();
     }
 
-    // Increment the number of queued messages. Returns the resulting number.
-    fn inc_num_messages(&self) -> Option<usize> {
+    // Increment the number of queued messages by `weight`. Returns the
+    // resulting number.
+    fn inc_num_messages(&self, weight: usize) -> Result<usize, SendErrorKind> {
         let mut curr = self.inner.state.load(SeqCst);
 
         loop {
@@ -484,27 +647,60 @@ impl<T> SenderInner<T> {
 
             // The receiver end closed the channel.
             if !state.is_open {
-                return None;
+                return Err(SendErrorKind::Disconnected);
             }
 
-            // This probably is never hit? Odds are the process will run out of
-            // memory first. It may be worth to return something else in this
-            // case?
-            assert!(state.num_messages < MAX_CAPACITY, "buffer space \
-                    exhausted; sending this messages would overflow the state");
+            // The state cannot count this message, which is only possible
+            // with the weights of a weighted channel; odds are the process
+            // would run out of memory first otherwise.
+            if weight > MAX_CAPACITY - state.num_messages {
+                return Err(SendErrorKind::Full);
+            }
 
-            state.num_messages += 1;
+            state.num_messages += weight;
 
             let next = encode_state(&state);
             match self.inner.state.compare_exchange(curr, next, SeqCst, SeqCst) {
                 Ok(_) => {
-                    return Some(state.num_messages)
+                    return Ok(state.num_messages)
                 }
                 Err(actual) => curr = actual,
             }
         }
     }
 
+    // Increment the number of queued messages by `weight`, unless this would
+    // exceed the capacity of a non-empty channel.
+    fn inc_num_messages_within(&self, weight: usize) -> Result<(), SendErrorKind> {
+        let buffer = self.inner.buffer.unwrap();
+        let mut curr = self.inner.state.load(SeqCst);
+
+        loop {
+            let mut state = decode_state(curr);
+
+            if !state.is_open {
+                return Err(SendErrorKind::Disconnected);
+            }
+
+            if state.num_messages > 0 && weight > buffer.saturating_sub(state.num_messages) {
+                return Err(SendErrorKind::Full);
+            }
+
+            // See `inc_num_messages`.
+            if weight > MAX_CAPACITY - state.num_messages {
+                return Err(SendErrorKind::Full);
+            }
+
+            state.num_messages += weight;
+
+            let next = encode_state(&state);
+            match self.inner.state.compare_exchange(curr, next, SeqCst, SeqCst) {
+                Ok(_) => return Ok(()),
+                Err(actual) => curr = actual,
+            }
+        }
+    }
+
     fn park(&mut self) {
         {
             let mut sender = self.sender_task.lock().unwrap();
@@ -700,17 +896,19 @@ impl<T> UnboundedSender<T> {
 
     // Do the send without parking current task.
     fn do_send_nb(&self, msg: T) -> Result<(), TrySendError<T>> {
-        if let Some(inner) = &self.0 {
-            if inner.inc_num_messages().is_some() {
-                inner.queue_push_and_signal(msg);
-                return Ok(());
-            }
-        }
+        let kind = match &self.0 {
+            Some(inner) => match inner.inc_num_messages(1) {
+                Ok(_) => {
+                    inner.queue_push_and_signal(msg);
+                    return Ok(());
+                }
+                Err(kind) => kind,
+            },
+            None => SendErrorKind::Disconnected,
+        };
 
         Err(TrySendError {
-            err: SendError {
-                kind: SendErrorKind::Disconnected,
-            },
+            err: SendError { kind },
             val: msg,
         })
     }
@@ -839,55 +1037,97 @@ impl<T> Receiver<T> {
     }
 
     fn next_message(&mut self) -> Poll<Option<T>> {
-        let inner = self.inner.as_mut().expect("Receiver::next_message called after `None`");
+        let inner = self.inner.as_ref().expect("Receiver::next_message called after `None`");
+        // Senders may pop messages too, see `Inner::evict`.
+        let guard = match inner.overflow {
+            Overflow::DropOldest => Some(inner.pop_lock.lock().unwrap()),
+            _ => None,
+        };
         // Pop off a message
-        match unsafe { inner.message_queue.pop_spin() } {
-            Some(msg) => {
-                // If there are any parked task handles in the parked queue,
-                // pop one and unpark it.
-                self.unpark_one();
-
-                // Decrement number of messages
-                self.dec_num_messages();
-
-                Poll::Ready(Some(msg))
-            }
-            None => {
-                let state = decode_state(inner.state.load(SeqCst));
-                if state.is_open || state.num_messages != 0 {
-                    // If queue is open, we need to return Pending
-                    // to be woken up when new messages arrive.
-                    // If queue is closed but num_messages is non-zero,
-                    // it means that senders updated the state,
-                    // but didn't put message to queue yet,
-                    // so we need to park until sender unparks the task
-                    // after queueing the message.
-                    Poll::Pending
-                } else {
-                    // If closed flag is set AND there are no pending messages
-                    // it means end of stream
-                    self.inner = None;
-                    Poll::Ready(None)
+        loop {
+            match unsafe { inner.message_queue.pop_spin() } {
+                Some(msg) => {
+                    let weight = inner.weight(&msg);
+                    let weighted = inner.weigher.is_some();
+                    let buffer = inner.buffer;
+
+                    if weighted {
+                        // A parked sender of a weighted channel may only
+                        // send again once the weight of the buffered messages
+                        // is back within the capacity, whatever the weight of
+                        // the message received.
+                        let num_messages = self.dec_num_messages(weight);
+                        if num_messages <= buffer.unwrap() {
+                            self.unpark_all();
+                        }
+                    } else {
+                        // If there are any parked task handles in the parked
+                        // queue, pop one and unpark it.
+                        self.unpark_one();
+
+                        // Decrement number of messages
+                        let num_messages = self.dec_num_messages(weight);
+
+                        // Senders of a `DropOldest` channel which had nothing
+                        // to evict leave the messages beyond the capacity to
+                        // the receiver.
+                        if guard.is_some() && num_messages + weight > buffer.unwrap() {
+                            continue;
+                        }
+                    }
+
+                    return Poll::Ready(Some(msg));
+                }
+                None => {
+                    let state = decode_state(inner.state.load(SeqCst));
+                    if state.is_open || state.num_messages != 0 {
+                        // If queue is open, we need to return Pending
+                        // to be woken up when new messages arrive.
+                        // If queue is closed but num_messages is non-zero,
+                        // it means that senders updated the state,
+                        // but didn't put message to queue yet,
+                        // so we need to park until sender unparks the task
+                        // after queueing the message.
+                        return Poll::Pending;
+                    } else {
+                        // If closed flag is set AND there are no pending messages
+                        // it means end of stream
+                        drop(guard);
+                        self.inner = None;
+                        return Poll::Ready(None);
+                    }
                 }
             }
         }
     }
 
     // Unpark a single task handle if there is one pending in the parked queue
-    fn unpark_one(&mut self) {
-        if let Some(inner) = &mut self.inner {
+    fn unpark_one(&self) {
+        if let Some(inner) = &self.inner {
             if let Some(task) = unsafe { inner.parked_queue.pop_spin() } {
                 task.lock().unwrap().notify();
             }
         }
     }
 
-    fn dec_num_messages(&self) {
+    // Unpark every task handle pending in the parked queue
+    fn unpark_all(&self) {
         if let Some(inner) = &self.inner {
+            while let Some(task) = unsafe { inner.parked_queue.pop_spin() } {
+                task.lock().unwrap().notify();
+            }
+        }
+    }
+
+    // Decrement the number of queued messages by `weight`. Returns the
+    // resulting number.
+    fn dec_num_messages(&self, weight: usize) -> usize {
+        match &self.inner {
             // OPEN_MASK is highest bit, so it's unaffected by subtraction
             // unless there's underflow, and we know there's no underflow
-            // because number of messages at this point is always > 0.
-            inner.state.fetch_sub(1, SeqCst);
+            // because the weight of the message was added when it was sent.
+            Some(inner) => decode_state(inner.state.fetch_sub(weight, SeqCst) - weight).num_messages,
+            None => 0,
         }
     }
 }
@@ -995,6 +1235,39 @@ impl<T> Inner<T> {
         }
     }
 
+    // The weight of `msg`, which is at least 1 so that a closed channel with
+    // messages in flight is never mistaken for an empty one.
+    fn weight(&self, msg: &T) -> usize {
+        match &self.weigher {
+            Some(weigher) => (weigher.0)(msg).max(1),
+            None => 1,
+        }
+    }
+
+    // Drop the oldest messages until there is room for a message of the given
+    // weight. Returns whether any message was dropped.
+    fn evict(&self, weight: usize) -> bool {
+        let buffer = self.buffer.unwrap();
+        let _guard = self.pop_lock.lock().unwrap();
+        let mut evicted = false;
+
+        loop {
+            let state = decode_state(self.state.load(SeqCst));
+            if state.num_messages == 0 || weight <= buffer.saturating_sub(state.num_messages) {
+                return evicted;
+            }
+
+            match unsafe { self.message_queue.pop_spin() } {
+                Some(msg) => {
+                    self.state.fetch_sub(self.weight(&msg), SeqCst);
+                    evicted = true;
+                }
+                // The remaining messages have not been pushed yet.
+                None => return evicted,
+            }
+        }
+    }
+
     // Clear `open` flag in the state, keep `num_messages` intact.
     fn set_closed(&self) {
         let curr = self.state.load(SeqCst);
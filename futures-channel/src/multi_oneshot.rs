@@ -0,0 +1,158 @@
+//! A channel for resolving many waiters with a single value, round after
+//! round.
+//!
+//! Each call to [`Receiver::recv`] registers a waiter for the next value
+//! sent. [`Sender::send`] then resolves all the registered waiters at once
+//! with clones of the value, and starts a new round: waiters registered
+//! afterwards wait for the following value. This makes it possible to wait
+//! for the "next tick" or "next commit" of some process without receiving
+//! the values sent before.
+//!
+//! Unlike a broadcast channel, values are not queued for receivers: a value
+//! sent while nobody is waiting is simply dropped.
+
+use futures_core::future::Future;
+use futures_core::task::{Context, Poll};
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use crate::oneshot;
+
+pub use crate::oneshot::Canceled;
+
+/// The sending half of a multi-oneshot channel.
+///
+/// This is created by the [`channel`] function.
+#[derive(Debug)]
+pub struct Sender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// The receiving half of a multi-oneshot channel.
+///
+/// This is created by the [`channel`] function, and can be cloned to hand
+/// it to several tasks.
+#[derive(Debug)]
+pub struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// A future resolving to the next value sent on a multi-oneshot channel.
+///
+/// This is created by the [`Receiver::recv`] method.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct Recv<T> {
+    rx: oneshot::Receiver<T>,
+}
+
+#[derive(Debug)]
+struct Inner<T> {
+    waiters: Mutex<Waiters<T>>,
+}
+
+#[derive(Debug)]
+struct Waiters<T> {
+    senders: Vec<oneshot::Sender<T>>,
+    closed: bool,
+}
+
+/// Creates a new multi-oneshot channel.
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::multi_oneshot;
+/// use futures::executor::block_on;
+///
+/// let (tx, rx) = multi_oneshot::channel();
+///
+/// let first = rx.recv();
+/// let second = rx.clone().recv();
+/// assert_eq!(tx.send("tick"), 2);
+///
+/// // Waiters registered now wait for the next value.
+/// let third = rx.recv();
+/// assert_eq!(tx.send("tock"), 1);
+///
+/// assert_eq!(block_on(first), Ok("tick"));
+/// assert_eq!(block_on(second), Ok("tick"));
+/// assert_eq!(block_on(third), Ok("tock"));
+/// ```
+pub fn channel<T: Clone>() -> (Sender<T>, Receiver<T>) {
+    let inner = Arc::new(Inner {
+        waiters: Mutex::new(Waiters {
+            senders: Vec::new(),
+            closed: false,
+        }),
+    });
+    let sender = Sender { inner: inner.clone() };
+    let receiver = Receiver { inner };
+    (sender, receiver)
+}
+
+impl<T: Clone> Sender<T> {
+    /// Resolves all the waiters registered since the previous call with
+    /// clones of `value`, and starts a new round.
+    ///
+    /// Returns the number of waiters which were resolved, not counting the
+    /// ones whose [`Recv`] future was dropped in the meantime.
+    pub fn send(&self, value: T) -> usize {
+        let senders = {
+            let mut waiters = self.inner.waiters.lock().unwrap();
+            mem::replace(&mut waiters.senders, Vec::new())
+        };
+        senders
+            .into_iter()
+            .filter(|tx| !tx.is_canceled())
+            .filter_map(|tx| tx.send(value.clone()).ok())
+            .count()
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut waiters = self.inner.waiters.lock().unwrap();
+        waiters.closed = true;
+        // Dropping the senders resolves the pending waiters with `Canceled`.
+        waiters.senders.clear();
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Registers a waiter for the next value sent on the channel.
+    ///
+    /// The waiter is registered when this method is called, not when the
+    /// returned future is first polled, so a value sent in between is
+    /// received. If the [`Sender`] has been dropped, the future resolves to
+    /// [`Canceled`].
+    pub fn recv(&self) -> Recv<T> {
+        let (tx, rx) = oneshot::channel();
+        let mut waiters = self.inner.waiters.lock().unwrap();
+        if !waiters.closed {
+            // Forget about dropped waiters before the list doubles in size.
+            if waiters.senders.len() == waiters.senders.capacity() {
+                waiters.senders.retain(|tx| !tx.is_canceled());
+            }
+            waiters.senders.push(tx);
+        }
+        Recv { rx }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Receiver { inner: self.inner.clone() }
+    }
+}
+
+impl<T> Unpin for Recv<T> {}
+
+impl<T> Future for Recv<T> {
+    type Output = Result<T, Canceled>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.rx).poll(cx)
+    }
+}
@@ -573,3 +573,145 @@ fn send_backpressure_multi_senders() {
     let item = block_on(rx.next()).unwrap();
     assert_eq!(item, 2);
 }
+
+#[test]
+fn weighted_channel_bounds_total_weight() {
+    let (mut tx, rx) = mpsc::weighted_channel(10, |s: &String| s.len());
+
+    // A sender may always send one message, whatever its weight.
+    tx.try_send("a".repeat(20)).unwrap();
+    assert!(tx.try_send("b".to_string()).unwrap_err().is_full());
+
+    let mut rx = block_on_stream(rx);
+    assert_eq!(rx.next().unwrap().len(), 20);
+
+    tx.try_send("c".repeat(4)).unwrap();
+    tx.try_send("d".repeat(4)).unwrap();
+    tx.try_send(String::new()).unwrap();
+    tx.try_send("e".repeat(2)).unwrap();
+    assert!(tx.try_send("f".to_string()).unwrap_err().is_full());
+
+    drop(tx);
+    let lens: Vec<_> = rx.map(|s| s.len()).collect();
+    assert_eq!(lens, vec![4, 4, 0, 2]);
+}
+
+#[test]
+fn weighted_channel_keeps_sender_parked_while_over_capacity() {
+    let (mut tx, rx) = mpsc::weighted_channel(8, |v: &Vec<u8>| v.len());
+
+    tx.try_send(vec![0; 1]).unwrap();
+    tx.try_send(vec![0; 1000]).unwrap();
+    let mut rx = block_on_stream(rx);
+
+    // Receiving a light message does not bring the weight back within the
+    // capacity.
+    assert_eq!(rx.next().unwrap().len(), 1);
+    assert!(tx.try_send(vec![0; 1000]).unwrap_err().is_full());
+
+    assert_eq!(rx.next().unwrap().len(), 1000);
+    tx.try_send(vec![0; 1000]).unwrap();
+}
+
+#[test]
+fn weighted_channel_rejects_uncountable_weight() {
+    let (mut tx, _rx) = mpsc::weighted_channel(8, |_: &u32| usize::max_value());
+
+    let err = tx.try_send(1).unwrap_err();
+    assert!(err.is_full());
+    assert_eq!(err.into_inner(), 1);
+}
+
+#[test]
+fn weighted_channel_wakes_sender() {
+    let (tx, rx) = mpsc::weighted_channel(4, |v: &Vec<u8>| v.len());
+
+    let t = thread::spawn(move || {
+        let mut tx = tx;
+        for i in 1..=5 {
+            block_on(tx.send(vec![0; i])).unwrap();
+        }
+    });
+
+    let lens: Vec<_> = block_on(rx.map(|v| v.len()).collect());
+    assert_eq!(lens, vec![1, 2, 3, 4, 5]);
+    t.join().unwrap();
+}
+
+#[test]
+fn overflow_reject() {
+    let (mut tx, rx) = mpsc::channel_with_overflow(2, mpsc::Overflow::Reject);
+    let mut tx2 = tx.clone();
+
+    tx.try_send(1).unwrap();
+    tx2.try_send(2).unwrap();
+    let err = tx.try_send(3).unwrap_err();
+    assert!(err.is_full());
+    assert_eq!(err.into_inner(), 3);
+
+    // Senders are never parked.
+    block_on(poll_fn(|cx| {
+        assert!(tx.poll_ready(cx).is_ready());
+        Poll::Ready(())
+    }));
+
+    drop((tx, tx2));
+    assert_eq!(block_on(rx.collect::<Vec<_>>()), vec![1, 2]);
+}
+
+#[test]
+fn overflow_drop_newest() {
+    let (mut tx, rx) = mpsc::channel_with_overflow(2, mpsc::Overflow::DropNewest);
+
+    for i in 0..5 {
+        block_on(tx.send(i)).unwrap();
+    }
+
+    drop(tx);
+    assert_eq!(block_on(rx.collect::<Vec<_>>()), vec![0, 1]);
+}
+
+#[test]
+fn overflow_drop_oldest() {
+    let (mut tx, mut rx) = mpsc::channel_with_overflow(3, mpsc::Overflow::DropOldest);
+
+    for i in 0..10 {
+        block_on(tx.send(i)).unwrap();
+    }
+    assert_eq!(rx.try_next().unwrap(), Some(7));
+    tx.try_send(10).unwrap();
+
+    drop(tx);
+    assert_eq!(block_on(rx.collect::<Vec<_>>()), vec![8, 9, 10]);
+}
+
+#[test]
+fn overflow_drop_oldest_concurrent() {
+    const AMT: usize = 10_000;
+    const NTHREADS: usize = 4;
+
+    let (tx, rx) = mpsc::channel_with_overflow(8, mpsc::Overflow::DropOldest);
+
+    let threads: Vec<_> = (0..NTHREADS).map(|n| {
+        let mut tx = tx.clone();
+        thread::spawn(move || {
+            for i in 0..AMT {
+                tx.try_send((n, i)).unwrap();
+            }
+        })
+    }).collect();
+    drop(tx);
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    // Only the last messages are retained. Each sender's share of them is
+    // the end of the sequence it sent, in order.
+    let received = block_on(rx.collect::<Vec<_>>());
+    assert_eq!(received.len(), 8);
+    for n in 0..NTHREADS {
+        let sent: Vec<_> = received.iter().filter(|&&(m, _)| m == n).map(|&(_, i)| i).collect();
+        let expected: Vec<_> = (AMT - sent.len()..AMT).collect();
+        assert_eq!(sent, expected);
+    }
+}
@@ -0,0 +1,60 @@
+use futures::channel::multi_oneshot::{self, Canceled};
+use futures::executor::block_on;
+use futures::future::FutureExt;
+use futures_test::task::noop_context;
+use std::thread;
+
+#[test]
+fn resolves_all_waiters_of_a_round() {
+    let (tx, rx) = multi_oneshot::channel();
+    let waiters: Vec<_> = (0..3).map(|_| rx.recv()).collect();
+    assert_eq!(tx.send(7), 3);
+    for waiter in waiters {
+        assert_eq!(block_on(waiter), Ok(7));
+    }
+}
+
+#[test]
+fn waiters_register_per_round() {
+    let (tx, rx) = multi_oneshot::channel();
+    // Nobody is waiting, so the value is dropped.
+    assert_eq!(tx.send(1), 0);
+
+    let mut next = rx.recv();
+    assert!(next.poll_unpin(&mut noop_context()).is_pending());
+    assert_eq!(tx.send(2), 1);
+    assert_eq!(block_on(next), Ok(2));
+}
+
+#[test]
+fn dropped_waiters_are_not_counted() {
+    let (tx, rx) = multi_oneshot::channel();
+    let kept = rx.recv();
+    drop(rx.recv());
+    assert_eq!(tx.send('a'), 1);
+    assert_eq!(block_on(kept), Ok('a'));
+}
+
+#[test]
+fn dropping_sender_cancels_waiters() {
+    let (tx, rx) = multi_oneshot::channel::<u32>();
+    let pending = rx.recv();
+    drop(tx);
+    assert_eq!(block_on(pending), Err(Canceled));
+    assert_eq!(block_on(rx.recv()), Err(Canceled));
+}
+
+#[test]
+fn across_threads() {
+    let (tx, rx) = multi_oneshot::channel();
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let waiter = rx.recv();
+            thread::spawn(move || block_on(waiter))
+        })
+        .collect();
+    assert_eq!(tx.send(String::from("commit")), 4);
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), Ok(String::from("commit")));
+    }
+}
@@ -0,0 +1,87 @@
+use futures_core::future::Future;
+use futures_core::task::{Spawn, SpawnError};
+use futures_util::task::SpawnExt;
+use std::fmt;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::Mutex;
+
+type DefaultExecutor = Mutex<Box<dyn Spawn + Send>>;
+
+// Set at most once, and never freed afterwards.
+static DEFAULT_EXECUTOR: AtomicPtr<DefaultExecutor> = AtomicPtr::new(ptr::null_mut());
+
+/// An error returned by `set_default_executor` if a default executor has
+/// already been set.
+#[derive(Debug)]
+pub struct SetDefaultExecutorError {
+    _a: (),
+}
+
+impl fmt::Display for SetDefaultExecutorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a default executor has already been set")
+    }
+}
+
+impl std::error::Error for SetDefaultExecutorError {}
+
+/// Sets the process-wide executor used by [`spawn`](spawn()).
+///
+/// This is meant to be called once by the application, early in `main`, so
+/// that code deep inside it can occasionally spawn fire-and-forget tasks
+/// without a spawner being passed down to it. Libraries should keep taking a
+/// spawner as an argument instead, so as to remain runtime-agnostic.
+///
+/// ```
+/// use futures::executor::{set_default_executor, spawn, ThreadPool};
+/// use futures::channel::oneshot;
+/// use futures::future;
+///
+/// set_default_executor(ThreadPool::new().unwrap()).unwrap();
+///
+/// let (tx, rx) = oneshot::channel();
+/// spawn(future::lazy(move |_| tx.send(42).unwrap())).unwrap();
+/// assert_eq!(futures::executor::block_on(rx), Ok(42));
+/// ```
+///
+/// # Error
+///
+/// The default executor can only be set once. Subsequent calls return an
+/// error and drop `spawner`.
+pub fn set_default_executor<Sp>(spawner: Sp) -> Result<(), SetDefaultExecutorError>
+    where Sp: Spawn + Send + 'static,
+{
+    let executor: Box<DefaultExecutor> = Box::new(Mutex::new(Box::new(spawner)));
+    let executor = Box::into_raw(executor);
+    match DEFAULT_EXECUTOR.compare_exchange(
+        ptr::null_mut(),
+        executor,
+        Ordering::AcqRel,
+        Ordering::Acquire,
+    ) {
+        Ok(_) => Ok(()),
+        Err(_) => {
+            drop(unsafe { Box::from_raw(executor) });
+            Err(SetDefaultExecutorError { _a: () })
+        }
+    }
+}
+
+/// Spawns a task onto the executor set by
+/// [`set_default_executor`](set_default_executor()).
+///
+/// # Panics
+///
+/// This function panics if no default executor has been set.
+pub fn spawn<Fut>(future: Fut) -> Result<(), SpawnError>
+    where Fut: Future<Output = ()> + Send + 'static,
+{
+    let executor = DEFAULT_EXECUTOR.load(Ordering::Acquire);
+    if executor.is_null() {
+        panic!("`spawn` called before a default executor was set with `set_default_executor`");
+    }
+    // Safety: once set, the default executor is never freed.
+    let executor = unsafe { &*executor };
+    executor.lock().unwrap().spawn(future)
+}
@@ -26,6 +26,21 @@ mod thread_pool;
 #[cfg(feature = "std")]
 pub use crate::thread_pool::{ThreadPool, ThreadPoolBuilder};
 
+#[cfg(feature = "std")]
+mod task_time;
+#[cfg(feature = "std")]
+pub use crate::task_time::{current_task_id, TaskId};
+
+#[cfg(feature = "std")]
+mod load_samples;
+#[cfg(feature = "load-samples")]
+pub use crate::load_samples::{LoadSample, LoadSamples};
+
+#[cfg(feature = "std")]
+mod default_executor;
+#[cfg(feature = "std")]
+pub use crate::default_executor::{set_default_executor, spawn, SetDefaultExecutorError};
+
 #[cfg(feature = "std")]
 mod enter;
 #[cfg(feature = "std")]
@@ -0,0 +1,226 @@
+#[cfg(feature = "load-samples")]
+use futures_core::future::Future;
+#[cfg(feature = "load-samples")]
+use futures_core::ready;
+#[cfg(feature = "load-samples")]
+use futures_core::stream::Stream;
+#[cfg(feature = "load-samples")]
+use futures_core::task::{Context, Poll};
+#[cfg(feature = "load-samples")]
+use futures_util::timer::{sleep, Sleep};
+#[cfg(feature = "load-samples")]
+use std::fmt;
+#[cfg(feature = "load-samples")]
+use std::pin::Pin;
+#[cfg(feature = "load-samples")]
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "load-samples")]
+use std::time::Duration;
+use std::time::Instant;
+
+// Poll latencies are recorded into buckets of increasing powers of two
+// microseconds: bucket `i` counts polls which took less than `2^i` µs.
+const BUCKETS: usize = 32;
+
+/// Load counters maintained by a thread pool.
+///
+/// Poll latencies are only measured while somebody is watching, to avoid
+/// reading the clock twice per poll otherwise.
+pub(crate) struct Metrics {
+    watchers: AtomicUsize,
+    queued: AtomicUsize,
+    polls: AtomicUsize,
+    latencies: [AtomicUsize; BUCKETS],
+}
+
+impl Metrics {
+    pub(crate) fn new() -> Metrics {
+        Metrics {
+            watchers: AtomicUsize::new(0),
+            queued: AtomicUsize::new(0),
+            polls: AtomicUsize::new(0),
+            latencies: Default::default(),
+        }
+    }
+
+    pub(crate) fn enqueued(&self) {
+        self.queued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn dequeued(&self) {
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Runs `poll`, recording it.
+    pub(crate) fn record_poll<T>(&self, poll: impl FnOnce() -> T) -> T {
+        if self.watchers.load(Ordering::Relaxed) == 0 {
+            let result = poll();
+            self.polls.fetch_add(1, Ordering::Relaxed);
+            return result;
+        }
+        let start = Instant::now();
+        let result = poll();
+        let elapsed = start.elapsed();
+        self.polls.fetch_add(1, Ordering::Relaxed);
+        let micros = elapsed.as_secs()
+            .saturating_mul(1_000_000)
+            .saturating_add(u64::from(elapsed.subsec_micros()));
+        let bucket = (64 - micros.leading_zeros() as usize).min(BUCKETS - 1);
+        self.latencies[bucket].fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    #[cfg(feature = "load-samples")]
+    fn snapshot(&self) -> ([usize; BUCKETS], usize) {
+        let mut latencies = [0; BUCKETS];
+        for (count, bucket) in latencies.iter_mut().zip(self.latencies.iter()) {
+            *count = bucket.load(Ordering::Relaxed);
+        }
+        (latencies, self.polls.load(Ordering::Relaxed))
+    }
+}
+
+/// A sample of the load of a [`ThreadPool`](crate::ThreadPool), as yielded by
+/// [`ThreadPool::load_samples`](crate::ThreadPool::load_samples).
+///
+/// This type is only available when the `load-samples` feature of this
+/// library is activated.
+#[cfg(feature = "load-samples")]
+#[derive(Debug, Clone)]
+pub struct LoadSample {
+    queue_len: usize,
+    polls: usize,
+    interval: Duration,
+    latencies: [usize; BUCKETS],
+}
+
+#[cfg(feature = "load-samples")]
+impl LoadSample {
+    /// Returns the number of tasks which were ready to be polled but waiting
+    /// for a worker thread when the sample was taken.
+    pub fn queue_len(&self) -> usize {
+        self.queue_len
+    }
+
+    /// Returns the number of times a task was polled during the interval
+    /// covered by this sample.
+    pub fn polls(&self) -> usize {
+        self.polls
+    }
+
+    /// Returns the length of the interval covered by this sample.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns an upper bound of the duration of the polls at the given
+    /// percentile, between 0 and 100, over the interval covered by this
+    /// sample.
+    ///
+    /// Durations are measured with a precision of a power of two
+    /// microseconds. Zero is returned if no task was polled during the
+    /// interval.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `percentile` is not between 0 and 100.
+    pub fn poll_latency(&self, percentile: f64) -> Duration {
+        assert!((0.0..=100.0).contains(&percentile), "percentile out of range");
+        let total: usize = self.latencies.iter().sum();
+        if total == 0 {
+            return Duration::from_secs(0);
+        }
+        let rank = ((percentile / 100.0 * total as f64).ceil() as usize).max(1);
+        let mut seen = 0;
+        for (i, count) in self.latencies.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Duration::from_micros(1 << i);
+            }
+        }
+        Duration::from_micros(1 << (BUCKETS - 1))
+    }
+}
+
+/// Stream for the [`ThreadPool::load_samples`](crate::ThreadPool::load_samples)
+/// method.
+///
+/// This stream never ends. It is only available when the `load-samples`
+/// feature of this library is activated.
+#[cfg(feature = "load-samples")]
+#[must_use = "streams do nothing unless polled"]
+pub struct LoadSamples {
+    metrics: Arc<Metrics>,
+    interval: Duration,
+    sleep: Sleep,
+    last_sample: Instant,
+    last_polls: usize,
+    last_latencies: [usize; BUCKETS],
+}
+
+#[cfg(feature = "load-samples")]
+impl LoadSamples {
+    pub(crate) fn new(metrics: Arc<Metrics>, interval: Duration) -> LoadSamples {
+        metrics.watchers.fetch_add(1, Ordering::Relaxed);
+        let (last_latencies, last_polls) = metrics.snapshot();
+        LoadSamples {
+            metrics,
+            interval,
+            sleep: sleep(interval),
+            last_sample: Instant::now(),
+            last_polls,
+            last_latencies,
+        }
+    }
+}
+
+#[cfg(feature = "load-samples")]
+impl Drop for LoadSamples {
+    fn drop(&mut self) {
+        self.metrics.watchers.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "load-samples")]
+impl fmt::Debug for LoadSamples {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoadSamples")
+            .field("interval", &self.interval)
+            .field("sleep", &self.sleep)
+            .finish()
+    }
+}
+
+#[cfg(feature = "load-samples")]
+impl Stream for LoadSamples {
+    type Item = LoadSample;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<LoadSample>> {
+        ready!(Pin::new(&mut self.sleep).poll(cx));
+
+        let now = Instant::now();
+        let (latencies, polls) = self.metrics.snapshot();
+        let mut sample = LoadSample {
+            queue_len: self.metrics.queued.load(Ordering::Relaxed),
+            polls: polls.wrapping_sub(self.last_polls),
+            interval: now - self.last_sample,
+            latencies,
+        };
+        for (count, last) in sample.latencies.iter_mut().zip(self.last_latencies.iter()) {
+            *count = count.wrapping_sub(*last);
+        }
+
+        if let Some(deadline) = self.sleep.deadline() {
+            let interval = self.interval;
+            self.sleep.reset(deadline + interval);
+        }
+        self.last_sample = now;
+        self.last_polls = polls;
+        self.last_latencies = latencies;
+        Poll::Ready(Some(sample))
+    }
+}
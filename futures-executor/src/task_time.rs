@@ -0,0 +1,152 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// An identifier for a task spawned on a [`ThreadPool`](crate::ThreadPool),
+/// as returned by
+/// [`ThreadPool::spawn_with_id`](crate::ThreadPool::spawn_with_id).
+///
+/// Identifiers are unique within the process, across thread pools, and are
+/// handed out in the order tasks are spawned. The identifier of the task
+/// being polled is available from [`current_task_id`], so that log lines
+/// can be correlated per task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskId(u64);
+
+impl TaskId {
+    /// Returns the identifier as a number, e.g. to be logged.
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task-{}", self.0)
+    }
+}
+
+// `AtomicU64` is not available on every target.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local!(static CURRENT: Cell<Option<TaskId>> = Cell::new(None));
+
+/// Returns the identifier of the task being polled on this thread, if any.
+///
+/// This is the identifier of the thread pool task whose future is being
+/// polled, which lets code deep inside the future tag its log lines without
+/// having the identifier passed down to it. `None` is returned outside of
+/// thread pool tasks, e.g. within [`block_on`](crate::block_on).
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::oneshot;
+/// use futures::executor::{block_on, current_task_id, ThreadPool};
+///
+/// let pool = ThreadPool::new().unwrap();
+/// let (tx, rx) = oneshot::channel();
+/// let id = pool.spawn_with_id(async move {
+///     tx.send(current_task_id()).unwrap();
+/// });
+///
+/// assert_eq!(block_on(rx).unwrap(), Some(id));
+/// assert_eq!(current_task_id(), None);
+/// ```
+pub fn current_task_id() -> Option<TaskId> {
+    CURRENT.with(Cell::get)
+}
+
+/// Makes `id` the current task until the returned guard is dropped.
+pub(crate) fn set_current(id: TaskId) -> CurrentGuard {
+    CurrentGuard { prev: CURRENT.with(|current| current.replace(Some(id))) }
+}
+
+pub(crate) struct CurrentGuard {
+    prev: Option<TaskId>,
+}
+
+impl Drop for CurrentGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.prev));
+    }
+}
+
+pub(crate) type SlowPollHook = Arc<dyn Fn(TaskId, Duration) + Send + Sync>;
+
+// The time spent polling each live task.
+type PollTimes = Arc<Mutex<HashMap<TaskId, Arc<Mutex<Duration>>>>>;
+
+/// Time accounting for the tasks of a thread pool.
+pub(crate) struct TaskTimes {
+    // `None` unless tracking the poll time of tasks is enabled.
+    tasks: Option<PollTimes>,
+    slow_poll: Option<(Duration, SlowPollHook)>,
+}
+
+/// The time accounting of a single task, which is forgotten once it is
+/// dropped along with its task.
+pub(crate) struct TaskTime {
+    id: TaskId,
+    tracked: Option<(Arc<Mutex<Duration>>, PollTimes)>,
+}
+
+impl TaskTimes {
+    pub(crate) fn new(track: bool, slow_poll: Option<(Duration, SlowPollHook)>) -> TaskTimes {
+        TaskTimes {
+            tasks: if track { Some(Arc::new(Mutex::new(HashMap::new()))) } else { None },
+            slow_poll,
+        }
+    }
+
+    /// Returns whether polls need to be timed.
+    pub(crate) fn timed(&self) -> bool {
+        self.tasks.is_some() || self.slow_poll.is_some()
+    }
+
+    /// Registers a new task.
+    pub(crate) fn spawned(&self) -> TaskTime {
+        let id = TaskId(NEXT_ID.fetch_add(1, Ordering::Relaxed) as u64);
+        let tracked = self.tasks.as_ref().map(|tasks| {
+            let time = Arc::new(Mutex::new(Duration::from_secs(0)));
+            tasks.lock().unwrap().insert(id, time.clone());
+            (time, tasks.clone())
+        });
+        TaskTime { id, tracked }
+    }
+
+    /// Records a poll of `task` which took `elapsed`.
+    pub(crate) fn polled(&self, task: &TaskTime, elapsed: Duration) {
+        if let Some((time, _)) = &task.tracked {
+            *time.lock().unwrap() += elapsed;
+        }
+        if let Some((threshold, hook)) = &self.slow_poll {
+            if elapsed > *threshold {
+                hook(task.id, elapsed);
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, id: TaskId) -> Option<Duration> {
+        let tasks = self.tasks.as_ref()?.lock().unwrap();
+        let time = tasks.get(&id)?.lock().unwrap();
+        Some(*time)
+    }
+}
+
+impl TaskTime {
+    pub(crate) fn id(&self) -> TaskId {
+        self.id
+    }
+}
+
+impl Drop for TaskTime {
+    fn drop(&mut self) {
+        if let Some((_, tasks)) = &self.tracked {
+            tasks.lock().unwrap().remove(&self.id);
+        }
+    }
+}
@@ -1,4 +1,8 @@
 use crate::enter;
+#[cfg(feature = "load-samples")]
+use crate::load_samples::LoadSamples;
+use crate::load_samples::Metrics;
+use crate::task_time::{self, SlowPollHook, TaskId, TaskTime, TaskTimes};
 use crate::unpark_mutex::UnparkMutex;
 use futures_core::future::{Future, FutureObj};
 use futures_core::task::{Context, Poll, Spawn, SpawnError};
@@ -9,6 +13,7 @@ use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc;
 use std::thread;
+use std::time::{Duration, Instant};
 use std::fmt;
 
 /// A general-purpose thread pool for scheduling tasks that poll futures to
@@ -30,6 +35,8 @@ pub struct ThreadPoolBuilder {
     name_prefix: Option<String>,
     after_start: Option<Arc<dyn Fn(usize) + Send + Sync>>,
     before_stop: Option<Arc<dyn Fn(usize) + Send + Sync>>,
+    track_poll_time: bool,
+    slow_poll: Option<(Duration, SlowPollHook)>,
 }
 
 trait AssertSendSync: Send + Sync {}
@@ -40,6 +47,8 @@ struct PoolState {
     rx: Mutex<mpsc::Receiver<Message>>,
     cnt: AtomicUsize,
     size: usize,
+    metrics: Arc<Metrics>,
+    task_times: TaskTimes,
 }
 
 impl fmt::Debug for ThreadPool {
@@ -55,6 +64,7 @@ impl fmt::Debug for ThreadPoolBuilder {
         f.debug_struct("ThreadPoolBuilder")
             .field("pool_size", &self.pool_size)
             .field("name_prefix", &self.name_prefix)
+            .field("track_poll_time", &self.track_poll_time)
             .finish()
     }
 }
@@ -101,6 +111,17 @@ impl ThreadPool {
     /// > **Note**: This method is similar to `Spawn::spawn_obj`, except that
     /// >           it is guaranteed to always succeed.
     pub fn spawn_obj_ok(&self, future: FutureObj<'static, ()>) {
+        self.spawn_obj_with_id(future);
+    }
+
+    /// Spawns a future that will be run to completion, returning the
+    /// identifier of its task.
+    ///
+    /// The identifier can be used to query the time spent polling the task
+    /// with [`task_poll_time`](ThreadPool::task_poll_time).
+    pub fn spawn_obj_with_id(&self, future: FutureObj<'static, ()>) -> TaskId {
+        let time = self.state.task_times.spawned();
+        let id = time.id();
         let task = Task {
             future,
             wake_handle: Arc::new(WakeHandle {
@@ -108,8 +129,10 @@ impl ThreadPool {
                 mutex: UnparkMutex::new(),
             }),
             exec: self.clone(),
+            time,
         };
         self.state.send(Message::Run(task));
+        id
     }
 
     /// Spawns a task that polls the given future with output `()` to
@@ -133,6 +156,66 @@ impl ThreadPool {
     {
         self.spawn_obj_ok(FutureObj::new(Box::new(future)))
     }
+
+    /// Spawns a task that polls the given future with output `()` to
+    /// completion, returning the identifier of the task.
+    ///
+    /// See [`spawn_obj_with_id`](ThreadPool::spawn_obj_with_id) for details.
+    pub fn spawn_with_id<Fut>(&self, future: Fut) -> TaskId
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_obj_with_id(FutureObj::new(Box::new(future)))
+    }
+
+    /// Returns the total time spent polling the task `id` so far.
+    ///
+    /// This is the time spent in the `poll` method of the future of the
+    /// task, which includes any time it spent blocking its worker thread. It
+    /// is only measured if it was enabled with
+    /// [`ThreadPoolBuilder::track_poll_time`], and `None` is returned
+    /// otherwise, as well as once the task completed.
+    ///
+    /// ```
+    /// use futures::executor::ThreadPool;
+    /// use futures::future;
+    ///
+    /// let pool = ThreadPool::builder().track_poll_time(true).create().unwrap();
+    /// let id = pool.spawn_with_id(future::pending());
+    /// println!("{} was polled for {:?}", id, pool.task_poll_time(id).unwrap());
+    /// ```
+    pub fn task_poll_time(&self, id: TaskId) -> Option<Duration> {
+        self.state.task_times.get(id)
+    }
+
+    /// Returns a stream of samples of the load of this thread pool, taken
+    /// every `interval`.
+    ///
+    /// Each [`LoadSample`](crate::LoadSample) reports the number of tasks
+    /// waiting for a worker thread, and the number and latency of the polls
+    /// made since the previous sample. This makes it possible to build
+    /// health checks or autoscaling on top of the pool without external
+    /// instrumentation. Poll latencies are only measured while such a stream
+    /// exists.
+    ///
+    /// ```
+    /// use futures::executor::{block_on_stream, ThreadPool};
+    /// use std::time::Duration;
+    ///
+    /// let pool = ThreadPool::new().unwrap();
+    /// let mut samples = block_on_stream(pool.load_samples(Duration::from_millis(10)));
+    ///
+    /// let sample = samples.next().unwrap();
+    /// println!("{} tasks waiting, p99 poll latency {:?}",
+    ///          sample.queue_len(), sample.poll_latency(99.0));
+    /// ```
+    ///
+    /// This method is only available when the `load-samples` feature of this
+    /// library is activated.
+    #[cfg(feature = "load-samples")]
+    pub fn load_samples(&self, interval: Duration) -> LoadSamples {
+        LoadSamples::new(self.state.metrics.clone(), interval)
+    }
 }
 
 impl Spawn for ThreadPool {
@@ -157,6 +240,9 @@ impl Spawn for &ThreadPool {
 
 impl PoolState {
     fn send(&self, msg: Message) {
+        if let Message::Run(_) = msg {
+            self.metrics.enqueued();
+        }
         self.tx.lock().unwrap().send(msg).unwrap();
     }
 
@@ -171,7 +257,10 @@ impl PoolState {
         loop {
             let msg = self.rx.lock().unwrap().recv().unwrap();
             match msg {
-                Message::Run(task) => task.run(),
+                Message::Run(task) => {
+                    self.metrics.dequeued();
+                    task.run()
+                }
                 Message::Close => break,
             }
         }
@@ -209,6 +298,8 @@ impl ThreadPoolBuilder {
             name_prefix: None,
             after_start: None,
             before_stop: None,
+            track_poll_time: false,
+            slow_poll: None,
         }
     }
 
@@ -271,6 +362,29 @@ impl ThreadPoolBuilder {
         self
     }
 
+    /// Keep track of the time spent polling each task, to be queried with
+    /// [`ThreadPool::task_poll_time`].
+    ///
+    /// This is disabled by default, as it reads the clock twice per poll.
+    pub fn track_poll_time(&mut self, enabled: bool) -> &mut Self {
+        self.track_poll_time = enabled;
+        self
+    }
+
+    /// Execute the closure `f` after each poll of a task which took longer
+    /// than `threshold`.
+    ///
+    /// This hook is intended to surface futures which block their worker
+    /// thread by accident. The closure provided will receive the identifier
+    /// of the task and the duration of the poll, and runs on the worker
+    /// thread which polled the task.
+    pub fn slow_poll_hook<F>(&mut self, threshold: Duration, f: F) -> &mut Self
+        where F: Fn(TaskId, Duration) + Send + Sync + 'static
+    {
+        self.slow_poll = Some((threshold, Arc::new(f)));
+        self
+    }
+
     /// Create a [`ThreadPool`](ThreadPool) with the given configuration.
     ///
     /// # Panics
@@ -284,6 +398,8 @@ impl ThreadPoolBuilder {
                 rx: Mutex::new(rx),
                 cnt: AtomicUsize::new(1),
                 size: self.pool_size,
+                metrics: Arc::new(Metrics::new()),
+                task_times: TaskTimes::new(self.track_poll_time, self.slow_poll.clone()),
             }),
         };
         assert!(self.pool_size > 0);
@@ -316,6 +432,7 @@ struct Task {
     future: FutureObj<'static, ()>,
     exec: ThreadPool,
     wake_handle: Arc<WakeHandle>,
+    time: TaskTime,
 }
 
 struct WakeHandle {
@@ -327,7 +444,7 @@ impl Task {
     /// Actually run the task (invoking `poll` on the future) on the current
     /// thread.
     fn run(self) {
-        let Task { mut future, wake_handle, mut exec } = self;
+        let Task { mut future, wake_handle, mut exec, mut time } = self;
         let waker = waker_ref(&wake_handle);
         let mut cx = Context::from_waker(&waker);
 
@@ -337,7 +454,15 @@ impl Task {
             wake_handle.mutex.start_poll();
 
             loop {
-                let res = future.poll_unpin(&mut cx);
+                let task_times = &exec.state.task_times;
+                let start = if task_times.timed() { Some(Instant::now()) } else { None };
+                let res = {
+                    let _current = task_time::set_current(time.id());
+                    exec.state.metrics.record_poll(|| future.poll_unpin(&mut cx))
+                };
+                if let Some(start) = start {
+                    task_times.polled(&time, start.elapsed());
+                }
                 match res {
                     Poll::Pending => {}
                     Poll::Ready(()) => return wake_handle.mutex.complete(),
@@ -346,12 +471,14 @@ impl Task {
                     future,
                     wake_handle: wake_handle.clone(),
                     exec,
+                    time,
                 };
                 match wake_handle.mutex.wait(task) {
                     Ok(()) => return, // we've waited
                     Err(task) => { // someone's notified us
                         future = task.future;
                         exec = task.exec;
+                        time = task.time;
                     }
                 }
             }
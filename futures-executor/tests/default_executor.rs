@@ -0,0 +1,19 @@
+use futures::channel::oneshot;
+use futures::executor::{block_on, set_default_executor, spawn, ThreadPool};
+use futures::future;
+
+// The default executor is process-wide, so everything is checked from a
+// single test.
+#[test]
+fn spawn_on_default_executor() {
+    let result = std::panic::catch_unwind(|| spawn(future::ready(())));
+    assert!(result.is_err());
+
+    let pool = ThreadPool::new().unwrap();
+    set_default_executor(pool.clone()).unwrap();
+    assert!(set_default_executor(pool).is_err());
+
+    let (tx, rx) = oneshot::channel();
+    spawn(future::lazy(move |_| tx.send(1).unwrap())).unwrap();
+    assert_eq!(block_on(rx), Ok(1));
+}
@@ -0,0 +1,47 @@
+use futures::executor::{block_on_stream, ThreadPool};
+use futures::future;
+use std::sync::mpsc;
+use std::time::Duration;
+
+#[test]
+fn reports_queued_tasks_and_polls() {
+    let pool = ThreadPool::builder().pool_size(1).create().unwrap();
+    let mut samples = block_on_stream(pool.load_samples(Duration::from_millis(20)));
+
+    // Keep the only worker thread busy while more tasks are spawned.
+    let (unblock_tx, unblock_rx) = mpsc::channel::<()>();
+    let (started_tx, started_rx) = mpsc::channel();
+    pool.spawn_ok(future::lazy(move |_| {
+        started_tx.send(()).unwrap();
+        unblock_rx.recv().unwrap();
+    }));
+    started_rx.recv().unwrap();
+    for _ in 0..3 {
+        pool.spawn_ok(future::ready(()));
+    }
+
+    // The worker is still blocked, so the tasks are still queued.
+    let sample = samples.next().unwrap();
+    assert!(sample.queue_len() >= 3);
+    assert!(sample.interval() >= Duration::from_millis(20));
+
+    // On a loaded machine, the polls may spread over several samples.
+    unblock_tx.send(()).unwrap();
+    let mut polls = 0;
+    let mut max_latency = Duration::from_secs(0);
+    while polls < 4 {
+        let sample = samples.next().unwrap();
+        polls += sample.polls();
+        assert!(sample.poll_latency(0.0) <= sample.poll_latency(100.0));
+        max_latency = max_latency.max(sample.poll_latency(100.0));
+    }
+    assert_eq!(polls, 4);
+    // The first task blocked its poll for a whole sampling interval.
+    assert!(max_latency >= Duration::from_millis(1));
+
+    // Once every task has completed, nothing is queued or polled any more.
+    let sample = samples.next().unwrap();
+    assert_eq!(sample.queue_len(), 0);
+    assert_eq!(sample.polls(), 0);
+    assert_eq!(sample.poll_latency(50.0), Duration::from_secs(0));
+}
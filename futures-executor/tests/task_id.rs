@@ -0,0 +1,33 @@
+use futures::channel::oneshot;
+use futures::executor::{block_on, current_task_id, ThreadPool};
+use futures::future::FutureExt;
+
+#[test]
+fn task_ids_are_unique_across_pools() {
+    let pool1 = ThreadPool::new().unwrap();
+    let pool2 = ThreadPool::new().unwrap();
+
+    let (tx1, rx1) = oneshot::channel();
+    let (tx2, rx2) = oneshot::channel();
+    let id1 = pool1.spawn_with_id(async move { tx1.send(current_task_id()).unwrap() });
+    let id2 = pool2.spawn_with_id(async move { tx2.send(current_task_id()).unwrap() });
+
+    assert!(id1 < id2);
+    assert_eq!(block_on(rx1).unwrap(), Some(id1));
+    assert_eq!(block_on(rx2).unwrap(), Some(id2));
+}
+
+#[test]
+fn current_task_id_is_kept_across_polls() {
+    let pool = ThreadPool::builder().pool_size(1).create().unwrap();
+    let (wake_tx, wake_rx) = oneshot::channel::<()>();
+    let (tx, rx) = oneshot::channel();
+
+    let id = pool.spawn_with_id(wake_rx.map(move |_| tx.send(current_task_id()).unwrap()));
+    let other = pool.spawn_with_id(async {});
+    assert_ne!(id, other);
+
+    wake_tx.send(()).unwrap();
+    assert_eq!(block_on(rx).unwrap(), Some(id));
+    assert_eq!(current_task_id(), None);
+}
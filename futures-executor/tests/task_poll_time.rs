@@ -0,0 +1,58 @@
+use futures::channel::oneshot;
+use futures::executor::ThreadPool;
+use futures::future::{self, FutureExt};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn accumulates_poll_time_per_task() {
+    let pool = ThreadPool::builder().pool_size(1).track_poll_time(true).create().unwrap();
+
+    let (polled_tx, polled_rx) = mpsc::channel();
+    let (done_tx, done_rx) = oneshot::channel::<()>();
+    let busy = pool.spawn_with_id(future::lazy(move |_| {
+        thread::sleep(Duration::from_millis(20));
+        polled_tx.send(()).unwrap();
+    }).then(|()| done_rx).map(drop));
+    let idle = pool.spawn_with_id(future::pending());
+    assert_ne!(busy, idle);
+
+    polled_rx.recv().unwrap();
+    assert!(pool.task_poll_time(busy).unwrap() >= Duration::from_millis(20));
+    assert!(pool.task_poll_time(idle).unwrap() < Duration::from_millis(20));
+
+    // The time of a task is forgotten once it completes.
+    done_tx.send(()).unwrap();
+    while pool.task_poll_time(busy).is_some() {
+        thread::yield_now();
+    }
+}
+
+#[test]
+fn untracked_by_default() {
+    let pool = ThreadPool::builder().pool_size(1).create().unwrap();
+    let id = pool.spawn_with_id(future::pending());
+    assert_eq!(pool.task_poll_time(id), None);
+}
+
+#[test]
+fn reports_slow_polls() {
+    let (slow_tx, slow_rx) = mpsc::channel();
+    let pool = ThreadPool::builder()
+        .pool_size(1)
+        .slow_poll_hook(Duration::from_millis(10), move |id, elapsed| {
+            slow_tx.send((id, elapsed)).unwrap();
+        })
+        .create()
+        .unwrap();
+
+    pool.spawn_ok(future::ready(()));
+    let slow = pool.spawn_with_id(future::lazy(|_| thread::sleep(Duration::from_millis(20))));
+
+    let (id, elapsed) = slow_rx.recv().unwrap();
+    assert_eq!(id, slow);
+    assert!(elapsed >= Duration::from_millis(20));
+    drop(pool);
+    assert!(slow_rx.recv_timeout(Duration::from_millis(50)).is_err());
+}
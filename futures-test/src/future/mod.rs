@@ -12,6 +12,8 @@ use std::thread;
 
 pub use crate::interleave_pending::InterleavePending;
 
+pub use crate::observable::{Finished, ObservableFuture, Observer, Outcome};
+
 /// Additional combinators for testing futures.
 pub trait FutureTestExt: Future {
     /// Asserts that the given is not moved after being polled.
@@ -109,6 +111,37 @@ pub trait FutureTestExt: Future {
     {
         InterleavePending::new(self)
     }
+
+    /// Wraps this future so that the returned [`Observer`] can tell how many
+    /// times it was polled, and whether it completed or was cancelled by
+    /// being dropped first.
+    ///
+    /// This is useful to check how a combinator treats the futures it is
+    /// given, such as whether it drops the losers of a race.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::future::{self, FutureExt};
+    /// use futures_test::future::{FutureTestExt, Outcome};
+    ///
+    /// let (fast, fast_observer) = future::ready(1).observe();
+    /// let (slow, slow_observer) = future::pending::<i32>().observe();
+    ///
+    /// let winner = block_on(future::select(fast, slow).map(|either| either.factor_first().0));
+    /// assert_eq!(winner, 1);
+    /// assert_eq!(fast_observer.outcome(), Some(Outcome::Completed));
+    /// assert_eq!(slow_observer.polls(), 0);
+    /// assert!(slow_observer.is_cancelled());
+    /// assert_eq!(block_on(slow_observer.finished()), Outcome::Cancelled);
+    /// ```
+    fn observe(self) -> (ObservableFuture<Self>, Observer)
+    where
+        Self: Sized,
+    {
+        ObservableFuture::new(self)
+    }
 }
 
 impl<Fut> FutureTestExt for Fut where Fut: Future {}
@@ -38,3 +38,6 @@ pub mod stream;
 pub mod io;
 
 mod interleave_pending;
+
+#[cfg(feature = "std")]
+mod observable;
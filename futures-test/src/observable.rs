@@ -0,0 +1,221 @@
+use futures_core::future::{FusedFuture, Future};
+use futures_core::ready;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll, Waker};
+use pin_utils::unsafe_pinned;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+/// How an observed future or stream ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The future resolved, or the stream yielded its end.
+    Completed,
+    /// The future or stream was dropped before completing.
+    Cancelled,
+}
+
+/// Handle on what happened to an [`ObservableFuture`] or
+/// [`ObservableStream`].
+///
+/// This is created along with the observed value by the `observe` methods
+/// on:
+/// * [`FutureTestExt`](crate::future::FutureTestExt::observe)
+/// * [`StreamTestExt`](crate::stream::StreamTestExt::observe)
+///
+/// It lets tests check how a combinator treats the futures and streams it
+/// is given: how often it polls them, and whether it runs them to
+/// completion or cancels them by dropping them.
+#[derive(Debug, Clone)]
+pub struct Observer {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    polls: usize,
+    completed: bool,
+    dropped: bool,
+    wakers: Vec<Waker>,
+}
+
+impl Observer {
+    fn new() -> Self {
+        Observer { state: Arc::new(Mutex::new(State::default())) }
+    }
+
+    /// Returns the number of times the observed value was polled.
+    pub fn polls(&self) -> usize {
+        self.state.lock().unwrap().polls
+    }
+
+    /// Returns whether the observed value completed.
+    pub fn is_completed(&self) -> bool {
+        self.state.lock().unwrap().completed
+    }
+
+    /// Returns whether the observed value was dropped.
+    pub fn is_dropped(&self) -> bool {
+        self.state.lock().unwrap().dropped
+    }
+
+    /// Returns whether the observed value was dropped before completing.
+    pub fn is_cancelled(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.dropped && !state.completed
+    }
+
+    /// Returns how the observed value ended, if it did.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.state.lock().unwrap().outcome()
+    }
+
+    /// Returns a future resolving once the observed value completes or is
+    /// dropped.
+    pub fn finished(&self) -> Finished {
+        Finished { observer: self.clone() }
+    }
+
+    fn record_poll(&self) {
+        self.state.lock().unwrap().polls += 1;
+    }
+
+    fn record_end(&self, dropped: bool) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap();
+            if dropped {
+                state.dropped = true;
+            } else {
+                state.completed = true;
+            }
+            state.wakers.drain(..).collect::<Vec<_>>()
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl State {
+    fn outcome(&self) -> Option<Outcome> {
+        if self.completed {
+            Some(Outcome::Completed)
+        } else if self.dropped {
+            Some(Outcome::Cancelled)
+        } else {
+            None
+        }
+    }
+}
+
+/// Future for the [`finished`](Observer::finished) method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Finished {
+    observer: Observer,
+}
+
+impl Future for Finished {
+    type Output = Outcome;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Outcome> {
+        let mut state = self.observer.state.lock().unwrap();
+        match state.outcome() {
+            Some(outcome) => Poll::Ready(outcome),
+            None => {
+                state.wakers.push(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Future for the
+/// [`FutureTestExt::observe`](crate::future::FutureTestExt::observe)
+/// method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ObservableFuture<Fut> {
+    future: Fut,
+    observer: Observer,
+}
+
+impl<Fut: Unpin> Unpin for ObservableFuture<Fut> {}
+
+impl<Fut> ObservableFuture<Fut> {
+    unsafe_pinned!(future: Fut);
+
+    pub(crate) fn new(future: Fut) -> (Self, Observer) {
+        let observer = Observer::new();
+        (ObservableFuture { future, observer: observer.clone() }, observer)
+    }
+}
+
+impl<Fut: Future> Future for ObservableFuture<Fut> {
+    type Output = Fut::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.observer.record_poll();
+        let output = ready!(self.as_mut().future().poll(cx));
+        self.observer.record_end(false);
+        Poll::Ready(output)
+    }
+}
+
+impl<Fut: FusedFuture> FusedFuture for ObservableFuture<Fut> {
+    fn is_terminated(&self) -> bool {
+        self.future.is_terminated()
+    }
+}
+
+impl<Fut> Drop for ObservableFuture<Fut> {
+    fn drop(&mut self) {
+        self.observer.record_end(true);
+    }
+}
+
+/// Stream for the
+/// [`StreamTestExt::observe`](crate::stream::StreamTestExt::observe)
+/// method.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct ObservableStream<St> {
+    stream: St,
+    observer: Observer,
+}
+
+impl<St: Unpin> Unpin for ObservableStream<St> {}
+
+impl<St> ObservableStream<St> {
+    unsafe_pinned!(stream: St);
+
+    pub(crate) fn new(stream: St) -> (Self, Observer) {
+        let observer = Observer::new();
+        (ObservableStream { stream, observer: observer.clone() }, observer)
+    }
+}
+
+impl<St: Stream> Stream for ObservableStream<St> {
+    type Item = St::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.observer.record_poll();
+        let item = ready!(self.as_mut().stream().poll_next(cx));
+        if item.is_none() {
+            self.observer.record_end(false);
+        }
+        Poll::Ready(item)
+    }
+}
+
+impl<St: FusedStream> FusedStream for ObservableStream<St> {
+    fn is_terminated(&self) -> bool {
+        self.stream.is_terminated()
+    }
+}
+
+impl<St> Drop for ObservableStream<St> {
+    fn drop(&mut self) {
+        self.observer.record_end(true);
+    }
+}
@@ -4,6 +4,8 @@ use futures_core::stream::Stream;
 
 pub use crate::interleave_pending::InterleavePending;
 
+pub use crate::observable::{Finished, ObservableStream, Observer, Outcome};
+
 /// Additional combinators for testing streams.
 pub trait StreamTestExt: Stream {
     /// Introduces an extra [`Poll::Pending`](futures_core::task::Poll::Pending)
@@ -37,6 +39,30 @@ pub trait StreamTestExt: Stream {
     {
         InterleavePending::new(self)
     }
+
+    /// Wraps this stream so that the returned [`Observer`] can tell how many
+    /// times it was polled, and whether it ended or was cancelled by being
+    /// dropped first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::stream::{self, StreamExt};
+    /// use futures_test::stream::StreamTestExt;
+    ///
+    /// let (stream, observer) = stream::iter(1..=10).observe();
+    ///
+    /// assert_eq!(block_on(stream.take(2).collect::<Vec<_>>()), vec![1, 2]);
+    /// assert_eq!(observer.polls(), 2);
+    /// assert!(observer.is_cancelled());
+    /// ```
+    fn observe(self) -> (ObservableStream<Self>, Observer)
+    where
+        Self: Sized,
+    {
+        ObservableStream::new(self)
+    }
 }
 
 impl<St> StreamTestExt for St where St: Stream {}
@@ -0,0 +1,193 @@
+use futures_core::future::{Future, FutureObj};
+use futures_core::task::{Context, Poll, Spawn, SpawnError, Waker};
+use futures_util::task::{self, ArcWake};
+use pin_utils::unsafe_pinned;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+
+/// Checks that a test scenario leaves no task or waker behind.
+///
+/// Futures run through [`track`](LeakCheck::track), or spawned through a
+/// [`spawner`](LeakCheck::spawner), are polled with wakers that the check
+/// keeps track of. Once the scenario is over,
+/// [`assert_no_leaks`](LeakCheck::assert_no_leaks) panics if any of those
+/// tasks is still alive, or if any of their wakers is still registered
+/// somewhere, such as with a timer, in a channel, or in a forgotten
+/// detached task.
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::oneshot;
+/// use futures::executor::block_on;
+/// use futures::future::FutureExt;
+/// use futures_test::task::LeakCheck;
+///
+/// let check = LeakCheck::new();
+/// let (tx, rx) = oneshot::channel::<i32>();
+///
+/// // The receiver registers its waker with the channel...
+/// let mut rx = check.track(rx);
+/// assert!((&mut rx).now_or_never().is_none());
+/// assert_eq!(check.live_wakers(), 1);
+///
+/// // ...until it is done.
+/// tx.send(1).unwrap();
+/// assert_eq!(block_on(rx), Ok(1));
+/// check.assert_no_leaks();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LeakCheck {
+    inner: Arc<Mutex<Tracked>>,
+}
+
+// The tasks and wakers handed out by a leak check.
+#[derive(Debug, Default)]
+struct Tracked {
+    tasks: usize,
+    wakers: Vec<Weak<TrackedWaker>>,
+}
+
+impl LeakCheck {
+    /// Create a new instance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wraps `future` so that it is counted as a live task until it is
+    /// dropped, and polled with wakers tracked by this check.
+    pub fn track<Fut: Future>(&self, future: Fut) -> LeakCheckFuture<Fut> {
+        self.inner.lock().unwrap().tasks += 1;
+        LeakCheckFuture {
+            future,
+            check: self.clone(),
+        }
+    }
+
+    /// Wraps `spawner` so that the futures spawned on it are
+    /// [tracked](LeakCheck::track) by this check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::channel::oneshot;
+    /// use futures::executor::LocalPool;
+    /// use futures::future::FutureExt;
+    /// use futures::task::SpawnExt;
+    /// use futures_test::task::LeakCheck;
+    ///
+    /// let check = LeakCheck::new();
+    /// let mut pool = LocalPool::new();
+    /// let mut spawner = check.spawner(pool.spawner());
+    ///
+    /// // A detached task waits on a channel for as long as the sender lives.
+    /// let (tx, rx) = oneshot::channel::<()>();
+    /// spawner.spawn(rx.map(drop)).unwrap();
+    /// pool.run_until_stalled();
+    /// assert_eq!(check.live_tasks(), 1);
+    ///
+    /// drop(tx);
+    /// pool.run_until_stalled();
+    /// check.assert_no_leaks();
+    /// ```
+    pub fn spawner<Sp: Spawn>(&self, spawner: Sp) -> LeakCheckSpawner<Sp> {
+        LeakCheckSpawner {
+            spawner,
+            check: self.clone(),
+        }
+    }
+
+    /// Returns the number of tracked futures which were not dropped yet.
+    pub fn live_tasks(&self) -> usize {
+        self.inner.lock().unwrap().tasks
+    }
+
+    /// Returns the number of tracked wakers which are still alive, and may
+    /// thus still be registered somewhere.
+    pub fn live_wakers(&self) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        inner.wakers.retain(|waker| waker.upgrade().is_some());
+        inner.wakers.len()
+    }
+
+    /// Panics if any tracked task or waker is still alive.
+    pub fn assert_no_leaks(&self) {
+        let tasks = self.live_tasks();
+        let wakers = self.live_wakers();
+        assert!(
+            tasks == 0 && wakers == 0,
+            "leaked {} task(s) and {} waker(s)",
+            tasks,
+            wakers,
+        );
+    }
+
+    fn waker(&self, waker: &Waker) -> Waker {
+        let tracked = Arc::new(TrackedWaker { waker: waker.clone() });
+        let mut inner = self.inner.lock().unwrap();
+        inner.wakers.retain(|waker| waker.upgrade().is_some());
+        inner.wakers.push(Arc::downgrade(&tracked));
+        task::waker(tracked)
+    }
+}
+
+#[derive(Debug)]
+struct TrackedWaker {
+    waker: Waker,
+}
+
+impl ArcWake for TrackedWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.waker.wake_by_ref();
+    }
+}
+
+/// Future for the [`track`](LeakCheck::track) method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LeakCheckFuture<Fut> {
+    future: Fut,
+    check: LeakCheck,
+}
+
+impl<Fut: Unpin> Unpin for LeakCheckFuture<Fut> {}
+
+impl<Fut> LeakCheckFuture<Fut> {
+    unsafe_pinned!(future: Fut);
+}
+
+impl<Fut: Future> Future for LeakCheckFuture<Fut> {
+    type Output = Fut::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let waker = self.check.waker(cx.waker());
+        self.as_mut().future().poll(&mut Context::from_waker(&waker))
+    }
+}
+
+impl<Fut> Drop for LeakCheckFuture<Fut> {
+    fn drop(&mut self) {
+        self.check.inner.lock().unwrap().tasks -= 1;
+    }
+}
+
+/// Spawner for the [`spawner`](LeakCheck::spawner) method.
+#[derive(Debug)]
+pub struct LeakCheckSpawner<Sp> {
+    spawner: Sp,
+    check: LeakCheck,
+}
+
+impl<Sp: Spawn> Spawn for LeakCheckSpawner<Sp> {
+    fn spawn_obj(
+        &mut self,
+        future: FutureObj<'static, ()>,
+    ) -> Result<(), SpawnError> {
+        let future = self.check.track(future);
+        self.spawner.spawn_obj(FutureObj::new(Box::new(future)))
+    }
+
+    fn status(&self) -> Result<(), SpawnError> {
+        self.spawner.status()
+    }
+}
@@ -35,6 +35,11 @@
 //!   called.
 //! - [`RecordSpawner`](crate::task::RecordSpawner) records the spawned futures.
 //!
+//! Leak checks:
+//! - [`LeakCheck`](crate::task::LeakCheck) tracks futures and the spawners
+//!   they are spawned on, to assert that a test leaves no task or waker
+//!   behind.
+//!
 //! For convenience there additionally exist various functions that directly
 //! return waker/spawner references: [`noop_waker_ref`](crate::task::noop_waker_ref),
 //! [`panic_waker_ref`](crate::task::panic_waker_ref), [`noop_spawner_mut`](crate::task::noop_spawner_mut) and [`panic_spawner_mut`](crate::task::panic_spawner_mut).
@@ -42,6 +47,9 @@
 mod context;
 pub use self::context::{noop_context, panic_context};
 
+mod leak_check;
+pub use self::leak_check::{LeakCheck, LeakCheckFuture, LeakCheckSpawner};
+
 mod noop_spawner;
 pub use self::noop_spawner::{noop_spawner_mut, NoopSpawner};
 
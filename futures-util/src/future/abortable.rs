@@ -2,29 +2,32 @@ use crate::task::AtomicWaker;
 use futures_core::future::Future;
 use futures_core::task::{Context, Poll};
 use pin_utils::unsafe_pinned;
+use core::fmt;
 use core::pin::Pin;
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use alloc::boxed::Box;
 use alloc::sync::Arc;
 
 /// A future which can be remotely short-circuited using an `AbortHandle`.
 #[derive(Debug, Clone)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct Abortable<Fut> {
-    future: Fut,
+    future: Option<Fut>,
     inner: Arc<AbortInner>,
 }
 
 impl<Fut: Unpin> Unpin for Abortable<Fut> {}
 
 impl<Fut> Abortable<Fut> where Fut: Future {
-    unsafe_pinned!(future: Fut);
+    unsafe_pinned!(future: Option<Fut>);
 
     /// Creates a new `Abortable` future using an existing `AbortRegistration`.
     /// `AbortRegistration`s can be acquired through `AbortHandle::new`.
     ///
     /// When `abort` is called on the handle tied to `reg` or if `abort` has
     /// already been called, the future will complete immediately without making
-    /// any further progress.
+    /// any further progress, and the wrapped future is dropped.
     ///
     /// Example:
     ///
@@ -41,7 +44,7 @@ impl<Fut> Abortable<Fut> where Fut: Future {
     /// ```
     pub fn new(future: Fut, reg: AbortRegistration) -> Self {
         Abortable {
-            future,
+            future: Some(future),
             inner: reg.inner,
         }
     }
@@ -84,6 +87,7 @@ impl AbortHandle {
         let inner = Arc::new(AbortInner {
             waker: AtomicWaker::new(),
             cancel: AtomicBool::new(false),
+            reason: AtomicPtr::new(ptr::null_mut()),
         });
 
         (
@@ -97,12 +101,42 @@ impl AbortHandle {
     }
 }
 
-// Inner type storing the waker to awaken and a bool indicating that it
-// should be cancelled.
+// Inner type storing the waker to awaken, a bool indicating that it
+// should be cancelled, and the reason given for cancelling it, if any.
 #[derive(Debug)]
 struct AbortInner {
     waker: AtomicWaker,
     cancel: AtomicBool,
+    // Boxed, since `&'static str` is too wide for an atomic. Set at most
+    // once, by the first call to `abort_with`. This is usually before
+    // `cancel`, unless `abort` is called concurrently, in which case the
+    // abort may be observed before the reason.
+    reason: AtomicPtr<&'static str>,
+}
+
+impl AbortInner {
+    fn reason(&self) -> Option<&'static str> {
+        if !self.cancel.load(Ordering::Acquire) {
+            return None
+        }
+        // Synchronizes with the `AcqRel` exchange in `abort_with`, so that the
+        // boxed reason is visible.
+        let reason = self.reason.load(Ordering::Acquire);
+        if reason.is_null() {
+            None
+        } else {
+            Some(unsafe { *reason })
+        }
+    }
+}
+
+impl Drop for AbortInner {
+    fn drop(&mut self) {
+        let reason = *self.reason.get_mut();
+        if !reason.is_null() {
+            drop(unsafe { Box::from_raw(reason) });
+        }
+    }
 }
 
 /// Creates a new `Abortable` future and a `AbortHandle` which can be used to stop it.
@@ -123,21 +157,119 @@ pub fn abortable<Fut>(future: Fut) -> (Abortable<Fut>, AbortHandle)
 }
 
 /// Indicator that the `Abortable` future was aborted.
+///
+/// The reason passed to [`AbortHandle::abort_with`](AbortHandle::abort_with),
+/// if any, is available from [`AbortHandle::reason`](AbortHandle::reason), or
+/// from the [`AbortedWithReason`] error of an [`AbortableWithReason`] future.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct Aborted;
 
+impl fmt::Display for Aborted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "future was aborted")
+    }
+}
+
+/// Creates a new `AbortableWithReason` future and a `AbortHandle` which can
+/// be used to stop it.
+///
+/// This works like [`abortable`], except that once aborted, the future fails
+/// with an [`AbortedWithReason`] error, which carries the reason passed to
+/// [`AbortHandle::abort_with`](AbortHandle::abort_with), if any.
+///
+/// Example:
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::future::{abortable_with_reason, pending};
+///
+/// let (future, abort_handle) = abortable_with_reason(pending::<()>());
+/// abort_handle.abort_with("shutdown");
+/// assert_eq!(block_on(future).unwrap_err().reason(), Some("shutdown"));
+/// ```
+pub fn abortable_with_reason<Fut>(future: Fut) -> (AbortableWithReason<Fut>, AbortHandle)
+    where Fut: Future
+{
+    let (handle, reg) = AbortHandle::new_pair();
+    (
+        AbortableWithReason::new(future, reg),
+        handle,
+    )
+}
+
+/// A future which can be remotely short-circuited using an `AbortHandle`,
+/// and which fails with the reason of the abort.
+#[derive(Debug, Clone)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct AbortableWithReason<Fut> {
+    abortable: Abortable<Fut>,
+}
+
+impl<Fut: Unpin> Unpin for AbortableWithReason<Fut> {}
+
+impl<Fut> AbortableWithReason<Fut> where Fut: Future {
+    unsafe_pinned!(abortable: Abortable<Fut>);
+
+    /// Creates a new `AbortableWithReason` future using an existing
+    /// `AbortRegistration`, like [`Abortable::new`].
+    pub fn new(future: Fut, reg: AbortRegistration) -> Self {
+        AbortableWithReason {
+            abortable: Abortable::new(future, reg),
+        }
+    }
+}
+
+/// Indicator that the `AbortableWithReason` future was aborted, along with
+/// the reason for it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AbortedWithReason {
+    reason: Option<&'static str>,
+}
+
+impl AbortedWithReason {
+    /// Returns the reason given to [`AbortHandle::abort_with`], or `None` if
+    /// the future was aborted with [`AbortHandle::abort`].
+    pub fn reason(&self) -> Option<&'static str> {
+        self.reason
+    }
+}
+
+impl fmt::Display for AbortedWithReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            Some(reason) => write!(f, "future was aborted: {}", reason),
+            None => write!(f, "future was aborted"),
+        }
+    }
+}
+
+impl<Fut> Future for AbortableWithReason<Fut> where Fut: Future {
+    type Output = Result<Fut::Output, AbortedWithReason>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match ready!(self.as_mut().abortable().poll(cx)) {
+            Ok(output) => Poll::Ready(Ok(output)),
+            Err(Aborted) => Poll::Ready(Err(AbortedWithReason {
+                reason: self.abortable.inner.reason(),
+            })),
+        }
+    }
+}
+
 impl<Fut> Future for Abortable<Fut> where Fut: Future {
     type Output = Result<Fut::Output, Aborted>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         // Check if the future has been aborted
         if self.inner.cancel.load(Ordering::Relaxed) {
-            return Poll::Ready(Err(Aborted))
+            return self.aborted()
         }
 
         // attempt to complete the future
-        if let Poll::Ready(x) = self.as_mut().future().poll(cx) {
-            return Poll::Ready(Ok(x))
+        if let Some(future) = self.as_mut().future().as_pin_mut() {
+            if let Poll::Ready(x) = future.poll(cx) {
+                return Poll::Ready(Ok(x))
+            }
         }
 
         // Register to receive a wakeup if the future is aborted in the... future
@@ -148,13 +280,23 @@ impl<Fut> Future for Abortable<Fut> where Fut: Future {
         // Checking with `Relaxed` is sufficient because `register` introduces an
         // `AcqRel` barrier.
         if self.inner.cancel.load(Ordering::Relaxed) {
-            return Poll::Ready(Err(Aborted))
+            return self.aborted()
         }
 
         Poll::Pending
     }
 }
 
+impl<Fut> Abortable<Fut> where Fut: Future {
+    // Drops the wrapped future as soon as the abort is observed, rather than
+    // whenever the `Abortable` itself happens to be dropped, so that any
+    // resources it holds are released promptly.
+    fn aborted(mut self: Pin<&mut Self>) -> Poll<Result<Fut::Output, Aborted>> {
+        self.as_mut().future().set(None);
+        Poll::Ready(Err(Aborted))
+    }
+}
+
 impl AbortHandle {
     /// Abort the `Abortable` future associated with this handle.
     ///
@@ -162,8 +304,60 @@ impl AbortHandle {
     /// should abort. Note that if the future is currently being polled on
     /// another thread, it will not immediately stop running. Instead, it will
     /// continue to run until its poll method returns.
+    ///
+    /// This may be called from any thread, and works even after the
+    /// `Abortable` future has been handed off to an executor: its task is
+    /// woken, and the next time it is polled the wrapped future is dropped
+    /// and `Err(Aborted)` is returned.
     pub fn abort(&self) {
-        self.inner.cancel.store(true, Ordering::Relaxed);
+        self.inner.cancel.store(true, Ordering::Release);
         self.inner.waker.wake();
     }
+
+    /// Abort the `Abortable` future associated with this handle, giving a
+    /// reason for it.
+    ///
+    /// This works like [`abort`](AbortHandle::abort), except that `reason`
+    /// is then returned by [`reason`](AbortHandle::reason), as well as by
+    /// [`AbortedWithReason::reason`] if the future is an
+    /// [`AbortableWithReason`]. This lets the code handling the error tell
+    /// apart, say, a timeout from a shutdown. If the future was already
+    /// aborted, this has no effect.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::future::{pending, Abortable, AbortHandle};
+    ///
+    /// let (abort_handle, abort_registration) = AbortHandle::new_pair();
+    /// let future = Abortable::new(pending::<()>(), abort_registration);
+    /// abort_handle.abort_with("shutdown");
+    /// assert!(block_on(future).is_err());
+    /// assert_eq!(abort_handle.reason(), Some("shutdown"));
+    /// ```
+    pub fn abort_with(&self, reason: &'static str) {
+        if self.inner.cancel.load(Ordering::Relaxed) {
+            return
+        }
+        let reason = Box::into_raw(Box::new(reason));
+        let swapped = self.inner.reason.compare_exchange(
+            ptr::null_mut(),
+            reason,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+        if swapped.is_err() {
+            // Another call to `abort_with` won the race.
+            drop(unsafe { Box::from_raw(reason) });
+        }
+        self.abort();
+    }
+
+    /// Returns the reason given to [`abort_with`](AbortHandle::abort_with),
+    /// or `None` if the future was not aborted, or was aborted with
+    /// [`abort`](AbortHandle::abort).
+    pub fn reason(&self) -> Option<&'static str> {
+        self.inner.reason()
+    }
 }
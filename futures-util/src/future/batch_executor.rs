@@ -0,0 +1,355 @@
+use crate::future::FutureExt;
+use crate::stream::{FuturesUnordered, StreamExt};
+use crate::timer::{sleep, Sleep};
+use futures_channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures_channel::oneshot::{self, Receiver, Sender};
+use futures_core::future::{BoxFuture, FusedFuture, Future};
+use futures_core::task::{Context, Poll};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::mem;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// A handle to submit keys to, which are loaded in batches.
+///
+/// This is the DataLoader pattern: many concurrent callers each
+/// [`load`](BatchExecutor::load) a single key, while the keys they submit
+/// are collected and handed to a batch function in one call. The batch
+/// function is called once `max_batch_size` keys are collected, or once
+/// `max_delay` has elapsed since the first key of the batch was submitted,
+/// whichever comes first. The value it returns for each key is then sent back
+/// to the callers which submitted it.
+///
+/// Keys submitted several times to the same batch are only passed once to
+/// the batch function, and keys whose callers have all dropped their
+/// [`BatchResponse`] are left out.
+///
+/// The batches are run by the [`BatchDriver`] returned along with the
+/// executor, which must be spawned or otherwise polled. Batches run
+/// concurrently with each other. The driver completes once every handle to
+/// the executor has been dropped and all batches have completed.
+///
+/// This type is a clonable handle to the executor itself.
+/// Cloning it will only create a new reference, not a new executor.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::future::{self, BatchExecutor};
+/// use std::time::Duration;
+///
+/// let (executor, driver) = BatchExecutor::new(10, Duration::from_millis(5), |ids: Vec<u32>| {
+///     // A single query fetching all the users at once.
+///     future::ok::<_, ()>(ids.into_iter().map(|id| format!("user {}", id)).collect())
+/// });
+/// let (alice, bob) = (executor.load(1), executor.load(2));
+/// drop(executor);
+///
+/// let (users, ()) = block_on(future::join(future::join(alice, bob), driver));
+/// assert_eq!(users, (Ok("user 1".to_string()), Ok("user 2".to_string())));
+/// ```
+pub struct BatchExecutor<K, V, E> {
+    tx: UnboundedSender<Request<K, V, E>>,
+}
+
+type ResponseSender<V, E> = Sender<Result<V, BatchError<E>>>;
+
+type Request<K, V, E> = (K, ResponseSender<V, E>);
+
+type BatchFn<K, V, E> = Box<dyn FnMut(Vec<K>) -> BoxFuture<'static, Result<Vec<V>, E>> + Send>;
+
+impl<K, V, E> Clone for BatchExecutor<K, V, E> {
+    fn clone(&self) -> Self {
+        BatchExecutor { tx: self.tx.clone() }
+    }
+}
+
+impl<K, V, E> fmt::Debug for BatchExecutor<K, V, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchExecutor")
+            .field("closed", &self.tx.is_closed())
+            .finish()
+    }
+}
+
+impl<K, V, E> BatchExecutor<K, V, E>
+    where K: Hash + Eq + Clone,
+          V: Clone,
+          E: Clone,
+{
+    /// Creates an executor calling `batch_fn` with batches of at most
+    /// `max_batch_size` keys, collected for at most `max_delay`, along with
+    /// the driver running the batches.
+    ///
+    /// The future returned by `batch_fn` resolves to the values of the keys
+    /// it was called with, in the same order. Keys without a value, because
+    /// it returned too few of them, resolve to [`BatchError::Missing`]. If it
+    /// fails, all the keys of the batch resolve to [`BatchError::Failed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_batch_size` is zero.
+    pub fn new<F, Fut>(
+        max_batch_size: usize,
+        max_delay: Duration,
+        mut batch_fn: F,
+    ) -> (Self, BatchDriver<K, V, E>)
+        where F: FnMut(Vec<K>) -> Fut + Send + 'static,
+              Fut: Future<Output = Result<Vec<V>, E>> + Send + 'static,
+    {
+        assert!(max_batch_size > 0, "max_batch_size must be greater than zero");
+        let (tx, rx) = mpsc::unbounded();
+        let driver = BatchDriver {
+            rx: Some(rx),
+            batch_fn: Box::new(move |keys| batch_fn(keys).boxed()),
+            max_batch_size,
+            max_delay,
+            pending: Vec::new(),
+            sleep: None,
+            running: FuturesUnordered::new(),
+        };
+        (BatchExecutor { tx }, driver)
+    }
+
+    /// Submits `key` to the next batch, returning a future resolving to its
+    /// value.
+    ///
+    /// If the driver has been dropped, the returned future resolves to
+    /// [`BatchError::Canceled`].
+    pub fn load(&self, key: K) -> BatchResponse<V, E> {
+        let (tx, rx) = oneshot::channel();
+        // On failure, the sender is dropped, which cancels the response.
+        let _ = self.tx.unbounded_send((key, tx));
+        BatchResponse { rx: Some(rx) }
+    }
+}
+
+/// Future running the batches of a [`BatchExecutor`].
+///
+/// This future is returned by [`BatchExecutor::new`], and must be spawned or
+/// otherwise polled for the keys submitted to the executor to be loaded.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct BatchDriver<K, V, E> {
+    // `None` once every handle to the executor has been dropped.
+    rx: Option<UnboundedReceiver<Request<K, V, E>>>,
+    batch_fn: BatchFn<K, V, E>,
+    max_batch_size: usize,
+    max_delay: Duration,
+    // The requests of the batch being collected.
+    pending: Vec<Request<K, V, E>>,
+    // Started with the first request of the batch being collected.
+    sleep: Option<Sleep>,
+    running: FuturesUnordered<RunningBatch<V, E>>,
+}
+
+// Pinning is never projected to children
+impl<K, V, E> Unpin for BatchDriver<K, V, E> {}
+
+impl<K, V, E> fmt::Debug for BatchDriver<K, V, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchDriver")
+            .field("max_batch_size", &self.max_batch_size)
+            .field("max_delay", &self.max_delay)
+            .field("pending", &self.pending.len())
+            .field("running", &self.running.len())
+            .finish()
+    }
+}
+
+impl<K, V, E> BatchDriver<K, V, E>
+    where K: Hash + Eq + Clone,
+          V: Clone,
+          E: Clone,
+{
+    // Calls the batch function with the keys collected so far.
+    fn dispatch(&mut self) {
+        self.sleep = None;
+        let mut keys = Vec::new();
+        let mut callers: Vec<Vec<ResponseSender<V, E>>> = Vec::new();
+        let mut indices: HashMap<K, usize> = HashMap::new();
+        for (key, tx) in mem::replace(&mut self.pending, Vec::new()) {
+            if tx.is_canceled() {
+                continue;
+            }
+            match indices.get(&key) {
+                Some(&i) => callers[i].push(tx),
+                None => {
+                    indices.insert(key.clone(), keys.len());
+                    keys.push(key);
+                    callers.push(vec![tx]);
+                }
+            }
+        }
+        if !keys.is_empty() {
+            let future = (self.batch_fn)(keys);
+            self.running.push(RunningBatch { future, callers });
+        }
+    }
+}
+
+impl<K, V, E> FusedFuture for BatchDriver<K, V, E>
+    where K: Hash + Eq + Clone,
+          V: Clone,
+          E: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.rx.is_none() && self.pending.is_empty() && self.running.is_empty()
+    }
+}
+
+impl<K, V, E> Future for BatchDriver<K, V, E>
+    where K: Hash + Eq + Clone,
+          V: Clone,
+          E: Clone,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = &mut *self;
+        // Take at most a batch worth of requests per poll, so that a flood of
+        // them does not keep the driver from yielding.
+        let mut budget = this.max_batch_size;
+        while let Some(rx) = &mut this.rx {
+            if budget == 0 {
+                cx.waker().wake_by_ref();
+                break;
+            }
+            match rx.poll_next_unpin(cx) {
+                Poll::Ready(Some(request)) => {
+                    budget -= 1;
+                    this.pending.push(request);
+                    if this.pending.len() >= this.max_batch_size {
+                        this.dispatch();
+                    } else if this.sleep.is_none() {
+                        this.sleep = Some(sleep(this.max_delay));
+                    }
+                }
+                Poll::Ready(None) => this.rx = None,
+                Poll::Pending => break,
+            }
+        }
+
+        if !this.pending.is_empty() {
+            // Nothing more can join the batch once the executor is gone.
+            let expired = match &mut this.sleep {
+                Some(sleep) => this.rx.is_none() || sleep.poll_unpin(cx).is_ready(),
+                None => true,
+            };
+            if expired {
+                this.dispatch();
+            }
+        }
+
+        while let Poll::Ready(Some(())) = this.running.poll_next_unpin(cx) {}
+
+        if this.rx.is_none() && this.pending.is_empty() && this.running.is_empty() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+// A call to the batch function, sending back its values once it completes.
+struct RunningBatch<V, E> {
+    future: BoxFuture<'static, Result<Vec<V>, E>>,
+    // The callers of each key of the batch.
+    callers: Vec<Vec<ResponseSender<V, E>>>,
+}
+
+// Pinning is never projected to children
+impl<V, E> Unpin for RunningBatch<V, E> {}
+
+impl<V: Clone, E: Clone> Future for RunningBatch<V, E> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let result = ready!(self.future.poll_unpin(cx));
+        let callers = mem::replace(&mut self.callers, Vec::new());
+        match result {
+            Ok(values) => {
+                let mut values = values.into_iter();
+                for txs in callers {
+                    let value = values.next();
+                    for tx in txs {
+                        let _ = tx.send(value.clone().ok_or(BatchError::Missing));
+                    }
+                }
+            }
+            Err(e) => {
+                for tx in callers.into_iter().flatten() {
+                    let _ = tx.send(Err(BatchError::Failed(e.clone())));
+                }
+            }
+        }
+        Poll::Ready(())
+    }
+}
+
+/// Future for the [`load`](BatchExecutor::load) method, resolving to the
+/// value of a key.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct BatchResponse<V, E> {
+    rx: Option<Receiver<Result<V, BatchError<E>>>>,
+}
+
+impl<V, E> Unpin for BatchResponse<V, E> {}
+
+impl<V, E> fmt::Debug for BatchResponse<V, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchResponse")
+            .field("terminated", &self.rx.is_none())
+            .finish()
+    }
+}
+
+impl<V, E> FusedFuture for BatchResponse<V, E> {
+    fn is_terminated(&self) -> bool {
+        self.rx.is_none()
+    }
+}
+
+impl<V, E> Future for BatchResponse<V, E> {
+    type Output = Result<V, BatchError<E>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let rx = self.rx.as_mut().expect("BatchResponse polled after completion");
+        let res = ready!(rx.poll_unpin(cx));
+        self.rx = None;
+        Poll::Ready(res.unwrap_or(Err(BatchError::Canceled)))
+    }
+}
+
+/// Error returned by [`BatchResponse`] when no value was loaded for a key.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BatchError<E> {
+    /// The batch function failed with the given error.
+    Failed(E),
+    /// The batch function returned no value for the key.
+    Missing,
+    /// The [`BatchDriver`] was dropped before the key was loaded.
+    Canceled,
+}
+
+impl<E: fmt::Display> fmt::Display for BatchError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchError::Failed(e) => write!(f, "batch failed: {}", e),
+            BatchError::Missing => f.write_str("no value loaded for key"),
+            BatchError::Canceled => f.write_str("batch canceled"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for BatchError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BatchError::Failed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
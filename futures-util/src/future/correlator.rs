@@ -0,0 +1,307 @@
+use crate::future::FutureExt;
+use crate::timer::{sleep, Sleep};
+use futures_channel::oneshot::{self, Receiver, Sender};
+use futures_core::future::{FusedFuture, Future};
+use futures_core::task::{Context, Poll};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+/// Matches responses to the requests they answer, by id.
+///
+/// This is the map of pending requests at the heart of a multiplexed client,
+/// where responses can arrive in any order on a shared connection. Each
+/// request is [`register`](Correlator::register)ed under an id, which yields a
+/// [`CorrelatedResponse`] future. The task reading from the connection then
+/// hands each response to [`complete`](Correlator::complete) along with the
+/// id it answers, and should the connection fail,
+/// [`fail_all`](Correlator::fail_all) broadcasts the error to every pending
+/// request.
+///
+/// Pending requests never leak: dropping a `CorrelatedResponse` or letting it
+/// time out removes its id from the correlator.
+///
+/// This type is a clonable handle to the correlator itself.
+/// Cloning it will only create a new reference, not a new correlator.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::future::{Correlator, CorrelationError};
+///
+/// let correlator = Correlator::<u32, &str, ()>::new();
+/// let first = correlator.register(1);
+/// let second = correlator.register(2);
+///
+/// // Responses come back out of order.
+/// correlator.complete(&2, "two").unwrap();
+/// correlator.complete(&1, "one").unwrap();
+/// assert_eq!(block_on(first), Ok("one"));
+/// assert_eq!(block_on(second), Ok("two"));
+///
+/// let third = correlator.register(3);
+/// correlator.fail_all(());
+/// assert_eq!(block_on(third), Err(CorrelationError::Failed(())));
+/// ```
+pub struct Correlator<Id, Resp, E> {
+    inner: Arc<Mutex<Pending<Id, Resp, E>>>,
+}
+
+type ResponseSender<Resp, E> = Sender<Result<Resp, CorrelationError<E>>>;
+
+struct Pending<Id, Resp, E> {
+    requests: HashMap<Id, (u64, ResponseSender<Resp, E>)>,
+    next_gen: u64,
+}
+
+impl<Id, Resp, E> Clone for Correlator<Id, Resp, E> {
+    fn clone(&self) -> Self {
+        Correlator { inner: self.inner.clone() }
+    }
+}
+
+impl<Id, Resp, E> fmt::Debug for Correlator<Id, Resp, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pending = self.inner.lock().unwrap().requests.len();
+        f.debug_struct("Correlator")
+            .field("pending", &pending)
+            .finish()
+    }
+}
+
+impl<Id, Resp, E> Default for Correlator<Id, Resp, E>
+    where Id: Hash + Eq + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Id, Resp, E> Correlator<Id, Resp, E>
+    where Id: Hash + Eq + Clone,
+{
+    /// Creates a new correlator with no pending requests.
+    pub fn new() -> Self {
+        Correlator {
+            inner: Arc::new(Mutex::new(Pending {
+                requests: HashMap::new(),
+                next_gen: 0,
+            })),
+        }
+    }
+
+    /// Registers a request with the given id, returning a future resolving to
+    /// its response.
+    ///
+    /// If a request with the same id is still pending, it is replaced, and
+    /// resolves to [`CorrelationError::Canceled`].
+    pub fn register(&self, id: Id) -> CorrelatedResponse<Id, Resp, E> {
+        self.register_inner(id, None)
+    }
+
+    /// Registers a request with the given id like
+    /// [`register`](Correlator::register), expiring it if no response
+    /// arrives within `timeout`.
+    ///
+    /// Once expired, the id is removed from the correlator and the returned
+    /// future resolves to [`CorrelationError::TimedOut`].
+    pub fn register_with_timeout(
+        &self,
+        id: Id,
+        timeout: Duration,
+    ) -> CorrelatedResponse<Id, Resp, E> {
+        self.register_inner(id, Some(sleep(timeout)))
+    }
+
+    fn register_inner(
+        &self,
+        id: Id,
+        sleep: Option<Sleep>,
+    ) -> CorrelatedResponse<Id, Resp, E> {
+        let (tx, rx) = oneshot::channel();
+        let mut pending = self.inner.lock().unwrap();
+        let gen = pending.next_gen;
+        pending.next_gen += 1;
+        if let Some((_, old)) = pending.requests.insert(id.clone(), (gen, tx)) {
+            let _ = old.send(Err(CorrelationError::Canceled));
+        }
+        CorrelatedResponse {
+            correlator: Arc::downgrade(&self.inner),
+            id,
+            gen,
+            rx: Some(rx),
+            sleep,
+        }
+    }
+
+    /// Completes the pending request with the given id.
+    ///
+    /// Returns the response back if there is no such request, for instance
+    /// because it has expired or was never registered.
+    pub fn complete(&self, id: &Id, response: Resp) -> Result<(), Resp> {
+        let request = self.inner.lock().unwrap().requests.remove(id);
+        match request {
+            Some((_, tx)) => tx.send(Ok(response)).map_err(|res| match res {
+                Ok(response) => response,
+                Err(_) => unreachable!(),
+            }),
+            None => Err(response),
+        }
+    }
+
+    /// Fails every pending request with `err`, for instance because the
+    /// transport they were sent on failed.
+    pub fn fail_all(&self, err: E)
+        where E: Clone,
+    {
+        let requests: Vec<_> = self.inner.lock().unwrap().requests.drain().collect();
+        for (_, (_, tx)) in requests {
+            let _ = tx.send(Err(CorrelationError::Failed(err.clone())));
+        }
+    }
+
+    /// Returns the number of pending requests.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().requests.len()
+    }
+
+    /// Returns `true` if there are no pending requests.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+}
+
+impl<Id, Resp, E> Pending<Id, Resp, E>
+    where Id: Hash + Eq,
+{
+    // Removes the request with the given id, if it is still the one created
+    // with generation `gen`.
+    fn remove(&mut self, id: &Id, gen: u64) {
+        if let Some((current, _)) = self.requests.get(id) {
+            if *current == gen {
+                self.requests.remove(id);
+            }
+        }
+    }
+}
+
+/// Future for the [`register`](Correlator::register) and
+/// [`register_with_timeout`](Correlator::register_with_timeout) methods,
+/// resolving to the response to a request.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct CorrelatedResponse<Id, Resp, E>
+    where Id: Hash + Eq + Clone,
+{
+    correlator: Weak<Mutex<Pending<Id, Resp, E>>>,
+    id: Id,
+    gen: u64,
+    rx: Option<Receiver<Result<Resp, CorrelationError<E>>>>,
+    sleep: Option<Sleep>,
+}
+
+impl<Id: Hash + Eq + Clone, Resp, E> Unpin for CorrelatedResponse<Id, Resp, E> {}
+
+impl<Id, Resp, E> fmt::Debug for CorrelatedResponse<Id, Resp, E>
+    where Id: Hash + Eq + Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CorrelatedResponse")
+            .field("id", &self.id)
+            .field("sleep", &self.sleep)
+            .finish()
+    }
+}
+
+impl<Id, Resp, E> CorrelatedResponse<Id, Resp, E>
+    where Id: Hash + Eq + Clone,
+{
+    /// Returns the id of the request this future is waiting for a response
+    /// to.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    fn remove(&self) {
+        if let Some(pending) = self.correlator.upgrade() {
+            pending.lock().unwrap().remove(&self.id, self.gen);
+        }
+    }
+}
+
+impl<Id, Resp, E> FusedFuture for CorrelatedResponse<Id, Resp, E>
+    where Id: Hash + Eq + Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.rx.is_none()
+    }
+}
+
+impl<Id, Resp, E> Future for CorrelatedResponse<Id, Resp, E>
+    where Id: Hash + Eq + Clone,
+{
+    type Output = Result<Resp, CorrelationError<E>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let rx = self.rx.as_mut().expect("CorrelatedResponse polled after completion");
+        if let Poll::Ready(res) = rx.poll_unpin(cx) {
+            self.rx = None;
+            return Poll::Ready(res.unwrap_or(Err(CorrelationError::Canceled)));
+        }
+
+        if let Some(sleep) = &mut self.sleep {
+            ready!(sleep.poll_unpin(cx));
+            self.remove();
+            self.rx = None;
+            return Poll::Ready(Err(CorrelationError::TimedOut));
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<Id, Resp, E> Drop for CorrelatedResponse<Id, Resp, E>
+    where Id: Hash + Eq + Clone,
+{
+    fn drop(&mut self) {
+        if self.rx.is_some() {
+            self.remove();
+        }
+    }
+}
+
+/// Error returned by [`CorrelatedResponse`] when no response was received.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CorrelationError<E> {
+    /// The request was failed with [`fail_all`](Correlator::fail_all).
+    Failed(E),
+    /// The request expired before a response was received.
+    TimedOut,
+    /// The request was replaced by another one with the same id, or every
+    /// handle to the correlator was dropped.
+    Canceled,
+}
+
+impl<E: fmt::Display> fmt::Display for CorrelationError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CorrelationError::Failed(e) => write!(f, "request failed: {}", e),
+            CorrelationError::TimedOut => f.write_str("request timed out"),
+            CorrelationError::Canceled => f.write_str("request canceled"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for CorrelationError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CorrelationError::Failed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
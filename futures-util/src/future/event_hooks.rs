@@ -0,0 +1,259 @@
+use crate::future::FutureExt;
+use crate::timer::{sleep, Sleep};
+use futures_core::future::{BoxFuture, Future};
+use futures_core::task::{Context, Poll};
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// A set of listeners to notify of events, each with a future of its own.
+///
+/// This is the hook system of plugin-style applications: each plugin
+/// [`register`](EventHooks::register)s a closure, which is called with every
+/// [`fire`](EventHooks::fire)d event and returns a future doing the actual
+/// handling. The future returned by `fire` waits for all those futures, and
+/// collects the errors of the listeners which failed, rather than stopping at
+/// the first one.
+///
+/// A listener can be given a timeout, either by registering it with
+/// [`register_with_timeout`](EventHooks::register_with_timeout), or by default
+/// for all listeners of hooks created with
+/// [`with_timeout`](EventHooks::with_timeout). A listener which takes longer is
+/// cancelled, and reported as having timed out.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::future::{self, EventHooks, HookError};
+///
+/// let mut hooks = EventHooks::<&str, String>::new();
+/// hooks.register(|event: &&str| future::ok(println!("audit: {}", event)));
+/// let mailer = hooks.register(|event: &&str| {
+///     future::err(format!("cannot mail {}", event))
+/// });
+///
+/// let errors = block_on(hooks.fire(&"user created"));
+/// assert_eq!(errors, vec![(mailer, HookError::Failed("cannot mail user created".to_string()))]);
+/// ```
+pub struct EventHooks<T, E> {
+    hooks: Vec<Hook<T, E>>,
+    next_id: u64,
+    timeout: Option<Duration>,
+}
+
+type Listener<T, E> = Box<dyn FnMut(&T) -> BoxFuture<'static, Result<(), E>> + Send>;
+
+struct Hook<T, E> {
+    id: HookId,
+    timeout: Option<Duration>,
+    listener: Listener<T, E>,
+}
+
+/// Identifies a listener registered with [`EventHooks`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct HookId(u64);
+
+impl<T, E> fmt::Debug for EventHooks<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventHooks")
+            .field("hooks", &self.hooks.iter().map(|hook| hook.id).collect::<Vec<_>>())
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl<T, E> Default for EventHooks<T, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, E> EventHooks<T, E> {
+    /// Creates hooks without listeners, which wait for every listener
+    /// without limit unless it was registered with a timeout.
+    pub fn new() -> Self {
+        EventHooks {
+            hooks: Vec::new(),
+            next_id: 0,
+            timeout: None,
+        }
+    }
+
+    /// Creates hooks without listeners, which cancel the listeners still
+    /// running `timeout` after an event was fired, unless they were
+    /// registered with a timeout of their own.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        EventHooks {
+            hooks: Vec::new(),
+            next_id: 0,
+            timeout: Some(timeout),
+        }
+    }
+
+    /// Registers `listener`, to be called with every event fired from now
+    /// on.
+    pub fn register<F, Fut>(&mut self, listener: F) -> HookId
+        where F: FnMut(&T) -> Fut + Send + 'static,
+              Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        let timeout = self.timeout;
+        self.push(timeout, listener)
+    }
+
+    /// Registers `listener`, to be called with every event fired from now
+    /// on, and cancelled if it takes longer than `timeout` to handle one.
+    pub fn register_with_timeout<F, Fut>(&mut self, timeout: Duration, listener: F) -> HookId
+        where F: FnMut(&T) -> Fut + Send + 'static,
+              Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        self.push(Some(timeout), listener)
+    }
+
+    fn push<F, Fut>(&mut self, timeout: Option<Duration>, mut listener: F) -> HookId
+        where F: FnMut(&T) -> Fut + Send + 'static,
+              Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        let id = HookId(self.next_id);
+        self.next_id += 1;
+        self.hooks.push(Hook {
+            id,
+            timeout,
+            listener: Box::new(move |event: &T| listener(event).boxed()),
+        });
+        id
+    }
+
+    /// Unregisters the listener `id`, returning whether it was registered.
+    pub fn unregister(&mut self, id: HookId) -> bool {
+        let len = self.hooks.len();
+        self.hooks.retain(|hook| hook.id != id);
+        self.hooks.len() != len
+    }
+
+    /// Returns the number of registered listeners.
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    /// Returns `true` if no listener is registered.
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Calls every registered listener with `event`.
+    ///
+    /// The returned future runs the futures of all listeners concurrently,
+    /// and resolves once each of them has completed or timed out. It
+    /// resolves to the listeners which failed or timed out, in the order in
+    /// which they did, along with their error; it is empty if all of them
+    /// succeeded.
+    ///
+    /// The listeners are called right away, so the returned future borrows
+    /// neither the hooks nor the event.
+    pub fn fire(&mut self, event: &T) -> Fire<E> {
+        let pending = self.hooks.iter_mut()
+            .map(|hook| Running {
+                id: hook.id,
+                future: (hook.listener)(event),
+                sleep: hook.timeout.map(sleep),
+            })
+            .collect();
+        Fire {
+            pending,
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// Error of a listener of [`EventHooks`] which did not handle an event.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HookError<E> {
+    /// The future of the listener failed with the given error.
+    Failed(E),
+    /// The future of the listener did not complete in time.
+    TimedOut,
+}
+
+impl<E: fmt::Display> fmt::Display for HookError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookError::Failed(e) => write!(f, "listener failed: {}", e),
+            HookError::TimedOut => f.write_str("listener timed out"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for HookError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HookError::Failed(e) => Some(e),
+            HookError::TimedOut => None,
+        }
+    }
+}
+
+/// Future for the [`fire`](EventHooks::fire) method.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Fire<E> {
+    pending: Vec<Running<E>>,
+    errors: Vec<(HookId, HookError<E>)>,
+}
+
+// Pinning is never projected to children
+impl<E> Unpin for Fire<E> {}
+
+// The future of a listener handling an event.
+struct Running<E> {
+    id: HookId,
+    future: BoxFuture<'static, Result<(), E>>,
+    sleep: Option<Sleep>,
+}
+
+impl<E: fmt::Debug> fmt::Debug for Fire<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Fire")
+            .field("pending", &self.pending.iter().map(|running| running.id).collect::<Vec<_>>())
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
+
+impl<E> Future for Fire<E> {
+    type Output = Vec<(HookId, HookError<E>)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let mut i = 0;
+        while i < this.pending.len() {
+            let running = &mut this.pending[i];
+            let result = match running.future.poll_unpin(cx) {
+                Poll::Ready(result) => Some(result.map_err(HookError::Failed)),
+                Poll::Pending => match &mut running.sleep {
+                    Some(sleep) => match sleep.poll_unpin(cx) {
+                        Poll::Ready(()) => Some(Err(HookError::TimedOut)),
+                        Poll::Pending => None,
+                    },
+                    None => None,
+                },
+            };
+            match result {
+                Some(result) => {
+                    let running = this.pending.remove(i);
+                    if let Err(e) = result {
+                        this.errors.push((running.id, e));
+                    }
+                }
+                None => i += 1,
+            }
+        }
+
+        if this.pending.is_empty() {
+            Poll::Ready(mem::replace(&mut this.errors, Vec::new()))
+        } else {
+            Poll::Pending
+        }
+    }
+}
@@ -11,6 +11,18 @@ use futures_core::task::{Context, Poll};
 use alloc::boxed::Box;
 #[cfg(feature = "alloc")]
 use futures_core::future::{BoxFuture, LocalBoxFuture};
+#[cfg_attr(
+    feature = "cfg-target-has-atomic",
+    cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+)]
+#[cfg(feature = "timer")]
+use crate::timer::Timeout;
+#[cfg_attr(
+    feature = "cfg-target-has-atomic",
+    cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+)]
+#[cfg(feature = "timer")]
+use std::time::Duration;
 
 // re-export for `select!`
 #[doc(hidden)]
@@ -46,6 +58,9 @@ pub use self::join_all::{join_all, JoinAll};
 mod select;
 pub use self::select::{select, Select};
 
+mod select_with_strategy;
+pub use self::select_with_strategy::{select_with_strategy, PollNext, SelectWithStrategy};
+
 #[cfg(feature = "alloc")]
 mod select_all;
 #[cfg(feature = "alloc")]
@@ -90,7 +105,15 @@ cfg_target_has_atomic! {
     #[cfg(feature = "alloc")]
     mod abortable;
     #[cfg(feature = "alloc")]
-    pub use self::abortable::{abortable, Abortable, AbortHandle, AbortRegistration, Aborted};
+    pub use self::abortable::{
+        abortable, abortable_with_reason, Abortable, AbortableWithReason, AbortHandle,
+        AbortRegistration, Aborted, AbortedWithReason,
+    };
+
+    #[cfg(feature = "alloc")]
+    mod with_resource;
+    #[cfg(feature = "alloc")]
+    pub use self::with_resource::{with_resource, with_resource_release, ResourceGuard, WithResource};
 }
 
 #[cfg(feature = "std")]
@@ -105,6 +128,27 @@ mod remote_handle;
 #[cfg(feature = "std")]
 pub use self::remote_handle::{Remote, RemoteHandle};
 
+cfg_target_has_atomic! {
+    #[cfg(feature = "channel")]
+    #[cfg(feature = "timer")]
+    mod batch_executor;
+    #[cfg(feature = "channel")]
+    #[cfg(feature = "timer")]
+    pub use self::batch_executor::{BatchDriver, BatchError, BatchExecutor, BatchResponse};
+
+    #[cfg(feature = "channel")]
+    #[cfg(feature = "timer")]
+    mod correlator;
+    #[cfg(feature = "channel")]
+    #[cfg(feature = "timer")]
+    pub use self::correlator::{Correlator, CorrelatedResponse, CorrelationError};
+
+    #[cfg(feature = "timer")]
+    mod event_hooks;
+    #[cfg(feature = "timer")]
+    pub use self::event_hooks::{EventHooks, Fire, HookError, HookId};
+}
+
 #[cfg(feature = "std")]
 mod shared;
 #[cfg(feature = "std")]
@@ -491,8 +535,65 @@ pub trait FutureExt: Future {
         remote_handle::remote_handle(self)
     }
 
+    /// Bound the time this future may take to complete.
+    ///
+    /// The returned future resolves to `Ok` with this future's output if it
+    /// completes within `dur`, and to `Err(TimedOut)` otherwise. Once the
+    /// deadline passes this future is dropped, canceling any work it still
+    /// had in progress. If `dur` is too long for the deadline to be
+    /// represented, the returned future never times out.
+    ///
+    /// This method is only available when the `timer` feature of this
+    /// library is activated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::future::{self, FutureExt};
+    /// use futures::timer::TimedOut;
+    /// use std::time::Duration;
+    ///
+    /// let future = future::ready(1).timeout(Duration::from_secs(1));
+    /// assert_eq!(block_on(future), Ok(1));
+    ///
+    /// let future = future::pending::<i32>().timeout(Duration::from_millis(10));
+    /// assert_eq!(block_on(future), Err(TimedOut));
+    /// ```
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "timer")]
+    fn timeout(self, dur: Duration) -> Timeout<Self>
+        where Self: Sized
+    {
+        Timeout::new(self, dur)
+    }
+
     /// Wrap the future in a Box, pinning it.
     ///
+    /// Besides storing futures of different types together, this erases the
+    /// type of the combinators built up so far. Deep chains of combinators,
+    /// such as dozens of `and_then` calls, have types which grow with every
+    /// layer and can make compile times and type names explode. Calling
+    /// `boxed` every few layers bounds their size, at the cost of an
+    /// allocation and a dynamic call per poll at each such point:
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::future::{self, FutureExt, TryFutureExt};
+    ///
+    /// let future = future::ok::<u32, ()>(0)
+    ///     .and_then(|x| future::ok(x + 1))
+    ///     .and_then(|x| future::ok(x + 1))
+    ///     .boxed()
+    ///     .and_then(|x| future::ok(x + 1))
+    ///     .and_then(|x| future::ok(x + 1))
+    ///     .boxed();
+    /// assert_eq!(block_on(future), Ok(4));
+    /// ```
+    ///
     /// This method is only available when the `std` or `alloc` feature of this
     /// library is activated, and it is activated by default.
     #[cfg(feature = "alloc")]
@@ -0,0 +1,116 @@
+use core::fmt;
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::task::{Context, Poll};
+use crate::future::{Either, FutureExt};
+
+/// Which future a [`select_with_strategy`] polls first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PollNext {
+    /// Poll the first future first.
+    Left,
+    /// Poll the second future first.
+    Right,
+}
+
+impl PollNext {
+    /// Switches to the other side, returning the side it was on.
+    ///
+    /// This makes for a round-robin strategy.
+    pub fn toggle(&mut self) -> Self {
+        let old = *self;
+        *self = match old {
+            PollNext::Left => PollNext::Right,
+            PollNext::Right => PollNext::Left,
+        };
+        old
+    }
+}
+
+/// Future for the [`select_with_strategy()`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SelectWithStrategy<A, B, S> {
+    inner: Option<(A, B)>,
+    strategy: S,
+}
+
+impl<A: Unpin, B: Unpin, S> Unpin for SelectWithStrategy<A, B, S> {}
+
+impl<A: fmt::Debug, B: fmt::Debug, S> fmt::Debug for SelectWithStrategy<A, B, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SelectWithStrategy")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+/// Waits for either one of two differently-typed futures to complete,
+/// polling first the one chosen by `strategy`.
+///
+/// This is like [`select()`](super::select()), which always polls
+/// the first future first, except that `strategy` is called on each poll to
+/// choose which future gets polled first. When both futures are ready, that
+/// one wins. This lets each call site choose between being biased, fair, or
+/// reproducibly random:
+///
+/// * `|| PollNext::Left` always favors the first future.
+/// * A closure toggling a [`PollNext`] alternates between both.
+/// * A closure drawing from a seeded random number generator makes the
+///   choice random, yet deterministic across runs.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::future::{self, select_with_strategy, PollNext};
+///
+/// // When both futures are ready, the one polled first wins.
+/// let mut next = PollNext::Right;
+/// let round_robin = move || next.toggle();
+/// let either = block_on(select_with_strategy(future::ready(1), future::ready(2), round_robin));
+/// assert_eq!(either.factor_first().0, 2);
+/// ```
+pub fn select_with_strategy<A, B, S>(future1: A, future2: B, strategy: S) -> SelectWithStrategy<A, B, S>
+    where A: Future + Unpin,
+          B: Future + Unpin,
+          S: FnMut() -> PollNext,
+{
+    SelectWithStrategy { inner: Some((future1, future2)), strategy }
+}
+
+impl<A, B, S> Future for SelectWithStrategy<A, B, S>
+    where A: Future + Unpin,
+          B: Future + Unpin,
+          S: FnMut() -> PollNext,
+{
+    type Output = Either<(A::Output, B), (B::Output, A)>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let (mut a, mut b) = this.inner.take().expect("cannot poll SelectWithStrategy twice");
+        let ready = match (this.strategy)() {
+            PollNext::Left => match a.poll_unpin(cx) {
+                Poll::Ready(x) => Some(Either::Left(x)),
+                Poll::Pending => match b.poll_unpin(cx) {
+                    Poll::Ready(x) => Some(Either::Right(x)),
+                    Poll::Pending => None,
+                },
+            },
+            PollNext::Right => match b.poll_unpin(cx) {
+                Poll::Ready(x) => Some(Either::Right(x)),
+                Poll::Pending => match a.poll_unpin(cx) {
+                    Poll::Ready(x) => Some(Either::Left(x)),
+                    Poll::Pending => None,
+                },
+            },
+        };
+        match ready {
+            Some(Either::Left(x)) => Poll::Ready(Either::Left((x, b))),
+            Some(Either::Right(x)) => Poll::Ready(Either::Right((x, a))),
+            None => {
+                this.inner = Some((a, b));
+                Poll::Pending
+            }
+        }
+    }
+}
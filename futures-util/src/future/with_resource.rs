@@ -0,0 +1,253 @@
+use super::{ready, Ready};
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::pin::Pin;
+use futures_core::future::{FusedFuture, Future};
+use futures_core::task::{Context, Poll};
+use alloc::sync::Arc;
+
+/// Acquires a resource with `acquire`, runs the future returned by `body`
+/// with it, and drops it whatever happens.
+///
+/// This is the asynchronous form of the bracket pattern. The resource is
+/// handed to `body` as a [`ResourceGuard`] in an [`Arc`], and is dropped when
+/// the last handle to it is. Use [`with_resource_release`] to release it
+/// asynchronously instead.
+pub fn with_resource<A, F, B>(
+    acquire: A,
+    body: F,
+) -> WithResource<A, F, B, ReleaseByDrop<A::Output>, Ready<()>>
+    where A: Future,
+          F: FnOnce(Arc<ResourceGuard<A::Output, ReleaseByDrop<A::Output>, Ready<()>>>) -> B,
+          B: Future,
+{
+    with_resource_release(acquire, release_by_drop, body)
+}
+
+/// Acquires a resource with `acquire`, runs the future returned by `body`
+/// with it, and releases it with `release` whatever happens.
+///
+/// The resource is handed to `body` as a [`ResourceGuard`] in an [`Arc`].
+/// Once the body future completes, the future returned by `release` is run
+/// before the output of the body is yielded.
+///
+/// If the returned future is dropped while the body future is running, or if
+/// the body future leaves clones of the `Arc` behind, `release` is called
+/// when the last handle to the resource is dropped, but the future it returns
+/// is dropped without being polled. Resources which need an asynchronous
+/// release should therefore do the bulk of their cleanup in the release
+/// function itself, or on `Drop`.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::future::{self, with_resource_release};
+/// use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+///
+/// let open = AtomicUsize::new(0);
+/// let future = with_resource_release(
+///     future::lazy(|_| {
+///         open.fetch_add(1, SeqCst);
+///         "connection"
+///     }),
+///     |_conn| {
+///         open.fetch_sub(1, SeqCst);
+///         future::ready(())
+///     },
+///     |conn| future::ready(conn.len()),
+/// );
+///
+/// assert_eq!(block_on(future), 10);
+/// assert_eq!(open.load(SeqCst), 0);
+/// ```
+pub fn with_resource_release<A, Rel, RelFut, F, B>(
+    acquire: A,
+    release: Rel,
+    body: F,
+) -> WithResource<A, F, B, Rel, RelFut>
+    where A: Future,
+          Rel: FnOnce(A::Output) -> RelFut,
+          RelFut: Future<Output = ()>,
+          F: FnOnce(Arc<ResourceGuard<A::Output, Rel, RelFut>>) -> B,
+          B: Future,
+{
+    WithResource {
+        state: State::Acquiring(acquire, Some(body)),
+        release: Some(release),
+    }
+}
+
+// The release of `with_resource`, which drops the resource.
+type ReleaseByDrop<R> = fn(R) -> Ready<()>;
+
+fn release_by_drop<R>(resource: R) -> Ready<()> {
+    drop(resource);
+    ready(())
+}
+
+/// A resource acquired by [`with_resource`] or [`with_resource_release`].
+///
+/// It dereferences to the resource, and calls the release function on it
+/// when dropped, unless the resource was released already.
+pub struct ResourceGuard<R, Rel, RelFut>
+    where Rel: FnOnce(R) -> RelFut,
+{
+    resource: Option<R>,
+    release: Option<Rel>,
+    _release_fut: PhantomData<fn() -> RelFut>,
+}
+
+impl<R, Rel, RelFut> ResourceGuard<R, Rel, RelFut>
+    where Rel: FnOnce(R) -> RelFut,
+{
+    fn into_parts(mut self) -> (R, Rel) {
+        (self.resource.take().unwrap(), self.release.take().unwrap())
+    }
+}
+
+impl<R, Rel, RelFut> Deref for ResourceGuard<R, Rel, RelFut>
+    where Rel: FnOnce(R) -> RelFut,
+{
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        self.resource.as_ref().unwrap()
+    }
+}
+
+impl<R, Rel, RelFut> Drop for ResourceGuard<R, Rel, RelFut>
+    where Rel: FnOnce(R) -> RelFut,
+{
+    fn drop(&mut self) {
+        if let (Some(resource), Some(release)) = (self.resource.take(), self.release.take()) {
+            drop(release(resource));
+        }
+    }
+}
+
+impl<R, Rel, RelFut> fmt::Debug for ResourceGuard<R, Rel, RelFut>
+    where R: fmt::Debug,
+          Rel: FnOnce(R) -> RelFut,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResourceGuard")
+            .field("resource", &self.resource)
+            .finish()
+    }
+}
+
+/// Future for the [`with_resource`] and [`with_resource_release`] functions.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WithResource<A, F, B, Rel, RelFut>
+    where A: Future,
+          B: Future,
+          Rel: FnOnce(A::Output) -> RelFut,
+{
+    state: State<A, F, B, Rel, RelFut>,
+    release: Option<Rel>,
+}
+
+enum State<A, F, B, Rel, RelFut>
+    where A: Future,
+          B: Future,
+          Rel: FnOnce(A::Output) -> RelFut,
+{
+    Acquiring(A, Option<F>),
+    Using(B, Option<Arc<ResourceGuard<A::Output, Rel, RelFut>>>),
+    Releasing(RelFut, Option<B::Output>),
+    Done,
+}
+
+impl<A, F, B, Rel, RelFut> Unpin for WithResource<A, F, B, Rel, RelFut>
+    where A: Future + Unpin,
+          B: Future + Unpin,
+          Rel: FnOnce(A::Output) -> RelFut,
+          RelFut: Unpin,
+{}
+
+impl<A, F, B, Rel, RelFut> fmt::Debug for WithResource<A, F, B, Rel, RelFut>
+    where A: Future,
+          B: Future,
+          Rel: FnOnce(A::Output) -> RelFut,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self.state {
+            State::Acquiring(..) => "Acquiring",
+            State::Using(..) => "Using",
+            State::Releasing(..) => "Releasing",
+            State::Done => "Done",
+        };
+        f.debug_struct("WithResource")
+            .field("state", &state)
+            .finish()
+    }
+}
+
+impl<A, F, B, Rel, RelFut> FusedFuture for WithResource<A, F, B, Rel, RelFut>
+    where A: Future,
+          F: FnOnce(Arc<ResourceGuard<A::Output, Rel, RelFut>>) -> B,
+          B: Future,
+          Rel: FnOnce(A::Output) -> RelFut,
+          RelFut: Future<Output = ()>,
+{
+    fn is_terminated(&self) -> bool {
+        match self.state {
+            State::Done => true,
+            _ => false,
+        }
+    }
+}
+
+impl<A, F, B, Rel, RelFut> Future for WithResource<A, F, B, Rel, RelFut>
+    where A: Future,
+          F: FnOnce(Arc<ResourceGuard<A::Output, Rel, RelFut>>) -> B,
+          B: Future,
+          Rel: FnOnce(A::Output) -> RelFut,
+          RelFut: Future<Output = ()>,
+{
+    type Output = B::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safe to call `get_unchecked_mut` because we won't move the futures.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        loop {
+            match &mut this.state {
+                State::Acquiring(acquire, body) => {
+                    let resource = ready!(unsafe { Pin::new_unchecked(acquire) }.poll(cx));
+                    let resource = Arc::new(ResourceGuard {
+                        resource: Some(resource),
+                        release: this.release.take(),
+                        _release_fut: PhantomData,
+                    });
+                    let body = (body.take().unwrap())(resource.clone());
+                    this.state = State::Using(body, Some(resource));
+                }
+                State::Using(body, resource) => {
+                    let output = ready!(unsafe { Pin::new_unchecked(body) }.poll(cx));
+                    let resource = resource.take().unwrap();
+                    // Drop the body future, along with its handle to the
+                    // resource.
+                    this.state = State::Done;
+                    match Arc::try_unwrap(resource) {
+                        Ok(resource) => {
+                            let (resource, release) = resource.into_parts();
+                            this.state = State::Releasing(release(resource), Some(output));
+                        }
+                        // The last handle releases the resource when dropped.
+                        Err(_) => return Poll::Ready(output),
+                    }
+                }
+                State::Releasing(release, output) => {
+                    ready!(unsafe { Pin::new_unchecked(release) }.poll(cx));
+                    let output = output.take().unwrap();
+                    this.state = State::Done;
+                    return Poll::Ready(output);
+                }
+                State::Done => panic!("WithResource polled after completion"),
+            }
+        }
+    }
+}
@@ -5,6 +5,7 @@ use std::io;
 use std::mem;
 use std::pin::Pin;
 use super::read_line::read_line_internal;
+use super::read_until::FrameTooBig;
 
 /// Stream for the [`lines`](super::AsyncBufReadExt::lines) method.
 #[derive(Debug)]
@@ -14,6 +15,8 @@ pub struct Lines<R> {
     buf: String,
     bytes: Vec<u8>,
     read: usize,
+    limit: usize,
+    discarding: bool,
 }
 
 impl<R: Unpin> Unpin for Lines<R> {}
@@ -25,6 +28,41 @@ impl<R: AsyncBufRead> Lines<R> {
             buf: String::new(),
             bytes: Vec::new(),
             read: 0,
+            limit: usize::max_value(),
+            discarding: false,
+        }
+    }
+
+    /// Limits the length of the lines yielded by this stream to `limit`
+    /// bytes, counting the line ending.
+    ///
+    /// Without a limit, a peer which never sends a newline makes the stream
+    /// buffer data until memory runs out. With a limit, the stream instead
+    /// yields an [`io::Error`] of kind
+    /// [`InvalidData`](io::ErrorKind::InvalidData) wrapping a
+    /// [`FrameTooBig`] error as soon as a line exceeds it. The rest of that
+    /// line is then skipped, and the stream resumes with the following line.
+    pub fn max_line_length(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+fn skip_line<R: AsyncBufRead + ?Sized>(
+    mut reader: Pin<&mut R>,
+    cx: &mut Context<'_>,
+) -> Poll<io::Result<()>> {
+    loop {
+        let (done, used) = {
+            let available = ready!(reader.as_mut().poll_fill_buf(cx))?;
+            match memchr::memchr(b'\n', available) {
+                Some(i) => (true, i + 1),
+                None => (available.is_empty(), available.len()),
+            }
+        };
+        reader.as_mut().consume(used);
+        if done {
+            return Poll::Ready(Ok(()));
         }
     }
 }
@@ -33,9 +71,27 @@ impl<R: AsyncBufRead> Stream for Lines<R> {
     type Item = io::Result<String>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let Self { reader, buf, bytes, read } = unsafe { self.get_unchecked_mut() };
-        let reader = unsafe { Pin::new_unchecked(reader) };
-        let n = ready!(read_line_internal(reader, cx, buf, bytes, read))?;
+        let Self { reader, buf, bytes, read, limit, discarding } = unsafe { self.get_unchecked_mut() };
+        let mut reader = unsafe { Pin::new_unchecked(reader) };
+        if *discarding {
+            ready!(skip_line(reader.as_mut(), cx))?;
+            *discarding = false;
+        }
+        let n = match ready!(read_line_internal(reader, cx, buf, bytes, read, *limit)) {
+            Ok(n) => n,
+            Err(e) => {
+                let too_big = match e.get_ref() {
+                    Some(inner) => inner.is::<FrameTooBig>(),
+                    None => false,
+                };
+                if too_big {
+                    buf.clear();
+                    bytes.clear();
+                    *discarding = true;
+                }
+                return Poll::Ready(Some(Err(e)));
+            }
+        };
         if n == 0 && buf.is_empty() {
             return Poll::Ready(None)
         }
@@ -0,0 +1,151 @@
+use futures_core::task::{Context, Poll};
+use futures_io::AsyncWrite;
+use futures_sink::Sink;
+use std::io;
+use std::pin::Pin;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// Sink for the [`lines_sink`](super::AsyncWriteExt::lines_sink) method.
+#[must_use = "sinks do nothing unless polled"]
+#[derive(Debug)]
+pub struct LinesSink<W> {
+    writer: W,
+    /// Lines sent since the last flush, each followed by a newline.
+    buf: Vec<u8>,
+    /// How much of `buf` was already written to `writer`.
+    written: usize,
+    lines: usize,
+    batch: usize,
+}
+
+impl<W: Unpin> Unpin for LinesSink<W> {}
+
+impl<W: AsyncWrite> LinesSink<W> {
+    unsafe_pinned!(writer: W);
+    unsafe_unpinned!(buf: Vec<u8>);
+    unsafe_unpinned!(written: usize);
+    unsafe_unpinned!(lines: usize);
+
+    pub(super) fn new(writer: W) -> Self {
+        LinesSink {
+            writer,
+            buf: Vec::new(),
+            written: 0,
+            lines: 0,
+            batch: 1,
+        }
+    }
+
+    /// Writes out and flushes lines in batches of `lines` lines, instead of
+    /// one at a time.
+    ///
+    /// Lines are buffered until the batch is full or until the sink is
+    /// flushed or closed, which saves system calls and packets when many
+    /// small lines are sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lines` is zero.
+    pub fn flush_every(mut self, lines: usize) -> Self {
+        assert!(lines > 0, "batch size must be positive");
+        self.batch = lines;
+        self
+    }
+
+    /// Acquires a reference to the underlying writer that this sink is
+    /// writing to.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Acquires a mutable reference to the underlying writer that this sink
+    /// is writing to.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of
+    /// the writer which may otherwise confuse this sink.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Acquires a pinned mutable reference to the underlying writer that
+    /// this sink is writing to.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of
+    /// the writer which may otherwise confuse this sink.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+        self.writer()
+    }
+
+    /// Consumes this sink, returning the underlying writer.
+    ///
+    /// Note that any lines which were sent but not flushed yet are lost.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes out the buffered lines, without flushing the writer.
+    fn poll_write_buf(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), io::Error>> {
+        while self.written < self.buf.len() {
+            let this = unsafe { self.as_mut().get_unchecked_mut() };
+            let writer = unsafe { Pin::new_unchecked(&mut this.writer) };
+            let n = ready!(writer.poll_write(cx, &this.buf[this.written..]))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write buffered lines",
+                )));
+            }
+            this.written += n;
+        }
+        self.as_mut().buf().clear();
+        *self.as_mut().written() = 0;
+        *self.as_mut().lines() = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite, Item: AsRef<str>> Sink<Item> for LinesSink<W> {
+    type Error = io::Error;
+
+    fn poll_ready(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        if self.lines >= self.batch {
+            ready!(Sink::<Item>::poll_flush(self.as_mut(), cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(
+        mut self: Pin<&mut Self>,
+        item: Item,
+    ) -> Result<(), Self::Error> {
+        let buf = self.as_mut().buf();
+        buf.extend_from_slice(item.as_ref().as_bytes());
+        buf.push(b'\n');
+        *self.as_mut().lines() += 1;
+        Ok(())
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_write_buf(cx))?;
+        ready!(self.as_mut().writer().poll_flush(cx))?;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_write_buf(cx))?;
+        ready!(self.as_mut().writer().poll_close(cx))?;
+        Poll::Ready(Ok(()))
+    }
+}
@@ -14,6 +14,7 @@ pub use futures_io::{
 };
 
 #[cfg(feature = "io-compat")] use crate::compat::Compat;
+use std::io;
 
 // used by `BufReader` and `BufWriter`
 // https://github.com/rust-lang/rust/blob/master/src/libstd/sys_common/io.rs#L1
@@ -45,6 +46,11 @@ pub use self::into_sink::IntoSink;
 mod lines;
 pub use self::lines::Lines;
 
+#[cfg(feature = "sink")]
+mod lines_sink;
+#[cfg(feature = "sink")]
+pub use self::lines_sink::LinesSink;
+
 mod read;
 pub use self::read::Read;
 
@@ -54,6 +60,12 @@ pub use self::read_vectored::ReadVectored;
 mod read_exact;
 pub use self::read_exact::ReadExact;
 
+mod read_exact_n;
+pub use self::read_exact_n::ReadExactN;
+
+mod read_exact_vectored;
+pub use self::read_exact_vectored::ReadExactVectored;
+
 mod read_line;
 pub use self::read_line::ReadLine;
 
@@ -64,7 +76,7 @@ mod read_to_string;
 pub use self::read_to_string::ReadToString;
 
 mod read_until;
-pub use self::read_until::ReadUntil;
+pub use self::read_until::{FrameTooBig, ReadUntil};
 
 mod close;
 pub use self::close::Close;
@@ -220,6 +232,88 @@ pub trait AsyncReadExt: AsyncRead {
         ReadExact::new(self, buf)
     }
 
+    /// Creates a future which will read exactly enough bytes to fill each of
+    /// the buffers in `bufs`, in order, returning an error if end of file
+    /// (EOF) is hit sooner.
+    ///
+    /// Reads are performed with
+    /// [`poll_read_vectored`](AsyncRead::poll_read_vectored), so readers
+    /// supporting vectored I/O can fill several buffers with a single system
+    /// call. This is convenient for protocols with fixed multi-part headers.
+    ///
+    /// The returned future will resolve once the read operation is completed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::io::AsyncReadExt;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = Cursor::new([1, 2, 3, 4, 5, 6]);
+    /// let mut magic = [0u8; 2];
+    /// let mut len = [0u8; 4];
+    ///
+    /// block_on(reader.read_exact_vectored(&mut [&mut magic, &mut len]))?;
+    ///
+    /// assert_eq!(magic, [1, 2]);
+    /// assert_eq!(len, [3, 4, 5, 6]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn read_exact_vectored<'a, 'b>(
+        &'a mut self,
+        bufs: &'a mut [&'b mut [u8]],
+    ) -> ReadExactVectored<'a, 'b, Self>
+        where Self: Unpin,
+    {
+        ReadExactVectored::new(self, bufs)
+    }
+
+    /// Creates a future which will read a header of exactly `header.len()`
+    /// bytes, followed by a body whose length is computed from the header,
+    /// returning an error if end of file (EOF) is hit sooner.
+    ///
+    /// Once `header` is filled, it is passed to `body_len` to find out how
+    /// many bytes the body is made of. `body` is then resized to that length
+    /// and filled in turn. Both reads are driven by the same future, so the
+    /// body is read without going back through the executor, and `body` can
+    /// be reused across calls to avoid allocating a new buffer for every
+    /// message. `body_len` may return an error to reject the header, which
+    /// is then yielded by the future without reading the body.
+    ///
+    /// The returned future will resolve once the read operation is completed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::io::AsyncReadExt;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = Cursor::new([0, 3, 1, 2, 3, 4]);
+    /// let mut header = [0u8; 2];
+    /// let mut body = Vec::new();
+    ///
+    /// let fut = reader.read_exact_n(&mut header, &mut body, |header| {
+    ///     Ok(u16::from_be_bytes([header[0], header[1]]) as usize)
+    /// });
+    /// block_on(fut)?;
+    ///
+    /// assert_eq!(body, vec![1, 2, 3]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn read_exact_n<'a, F>(
+        &'a mut self,
+        header: &'a mut [u8],
+        body: &'a mut Vec<u8>,
+        body_len: F,
+    ) -> ReadExactN<'a, Self, F>
+        where F: FnOnce(&[u8]) -> io::Result<usize>,
+              Self: Unpin,
+    {
+        ReadExactN::new(self, header, body, body_len)
+    }
+
     /// Creates a future which will read all the bytes from this `AsyncRead`.
     ///
     /// On success the total number of bytes read is returned.
@@ -491,6 +585,40 @@ pub trait AsyncWriteExt: AsyncWrite {
     {
         IntoSink::new(self)
     }
+
+    /// Allow using an [`AsyncWrite`] as a [`Sink`](futures_sink::Sink) of
+    /// lines of text.
+    ///
+    /// This adapter produces a sink that writes each string passed to it
+    /// into the underlying writer, followed by a newline. By default every
+    /// line is written and flushed on its own; use
+    /// [`LinesSink::flush_every`] to write them in batches instead.
+    ///
+    /// Note that this function consumes the given writer, returning a wrapped
+    /// version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::io::AsyncWriteExt;
+    /// use futures::stream::{self, StreamExt};
+    ///
+    /// let stream = stream::iter(vec![Ok("HELO"), Ok("QUIT")]);
+    ///
+    /// let mut writer = vec![];
+    ///
+    /// block_on(stream.forward((&mut writer).lines_sink().flush_every(16)))?;
+    ///
+    /// assert_eq!(writer, b"HELO\nQUIT\n");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "sink")]
+    fn lines_sink(self) -> LinesSink<Self>
+        where Self: Sized,
+    {
+        LinesSink::new(self)
+    }
 }
 
 impl<W: AsyncWrite + ?Sized> AsyncWriteExt for W {}
@@ -0,0 +1,78 @@
+use crate::io::AsyncRead;
+use futures_core::future::Future;
+use futures_core::task::{Context, Poll};
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+
+/// Future for the [`read_exact_n`](super::AsyncReadExt::read_exact_n) method.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadExactN<'a, R: ?Sized + Unpin, F> {
+    reader: &'a mut R,
+    header: &'a mut [u8],
+    body: &'a mut Vec<u8>,
+    body_len: Option<F>,
+    pos: usize,
+}
+
+impl<R: ?Sized + Unpin, F> Unpin for ReadExactN<'_, R, F> {}
+
+impl<R: ?Sized + Unpin + fmt::Debug, F> fmt::Debug for ReadExactN<'_, R, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadExactN")
+            .field("reader", &self.reader)
+            .field("header", &self.header)
+            .field("body", &self.body)
+            .field("pos", &self.pos)
+            .finish()
+    }
+}
+
+impl<'a, R, F> ReadExactN<'a, R, F>
+    where R: AsyncRead + ?Sized + Unpin,
+          F: FnOnce(&[u8]) -> io::Result<usize>,
+{
+    pub(super) fn new(
+        reader: &'a mut R,
+        header: &'a mut [u8],
+        body: &'a mut Vec<u8>,
+        body_len: F,
+    ) -> Self {
+        ReadExactN { reader, header, body, body_len: Some(body_len), pos: 0 }
+    }
+}
+
+fn fill<R: AsyncRead + ?Sized + Unpin>(
+    reader: &mut R,
+    cx: &mut Context<'_>,
+    buf: &mut [u8],
+    pos: &mut usize,
+) -> Poll<io::Result<()>> {
+    while *pos < buf.len() {
+        let n = ready!(Pin::new(&mut *reader).poll_read(cx, &mut buf[*pos..]))?;
+        if n == 0 {
+            return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()))
+        }
+        *pos += n;
+    }
+    Poll::Ready(Ok(()))
+}
+
+impl<R, F> Future for ReadExactN<'_, R, F>
+    where R: AsyncRead + ?Sized + Unpin,
+          F: FnOnce(&[u8]) -> io::Result<usize>,
+{
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        if this.body_len.is_some() {
+            ready!(fill(this.reader, cx, this.header, &mut this.pos))?;
+            let len = (this.body_len.take().unwrap())(this.header)?;
+            this.body.clear();
+            this.body.resize(len, 0);
+            this.pos = 0;
+        }
+        fill(this.reader, cx, this.body, &mut this.pos)
+    }
+}
@@ -0,0 +1,61 @@
+use crate::io::AsyncRead;
+use futures_core::future::Future;
+use futures_core::task::{Context, Poll};
+use std::io::{self, IoSliceMut};
+use std::pin::Pin;
+
+/// Future for the [`read_exact_vectored`](super::AsyncReadExt::read_exact_vectored) method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadExactVectored<'a, 'b, R: ?Sized + Unpin> {
+    reader: &'a mut R,
+    bufs: &'a mut [&'b mut [u8]],
+    // index of the first buffer that isn't full yet, and how much of it is
+    idx: usize,
+    pos: usize,
+}
+
+impl<R: ?Sized + Unpin> Unpin for ReadExactVectored<'_, '_, R> {}
+
+impl<'a, 'b, R: AsyncRead + ?Sized + Unpin> ReadExactVectored<'a, 'b, R> {
+    pub(super) fn new(reader: &'a mut R, bufs: &'a mut [&'b mut [u8]]) -> Self {
+        let mut this = ReadExactVectored { reader, bufs, idx: 0, pos: 0 };
+        this.advance(0);
+        this
+    }
+
+    fn advance(&mut self, mut n: usize) {
+        while self.idx < self.bufs.len() {
+            let remaining = self.bufs[self.idx].len() - self.pos;
+            if n < remaining {
+                self.pos += n;
+                return;
+            }
+            n -= remaining;
+            self.idx += 1;
+            self.pos = 0;
+        }
+    }
+}
+
+impl<R: AsyncRead + ?Sized + Unpin> Future for ReadExactVectored<'_, '_, R> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        while this.idx < this.bufs.len() {
+            let n = {
+                let (first, rest) = this.bufs[this.idx..].split_first_mut().unwrap();
+                let mut slices = Vec::with_capacity(rest.len() + 1);
+                slices.push(IoSliceMut::new(&mut first[this.pos..]));
+                slices.extend(rest.iter_mut().map(|buf| IoSliceMut::new(buf)));
+                ready!(Pin::new(&mut this.reader).poll_read_vectored(cx, &mut slices))?
+            };
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()))
+            }
+            this.advance(n);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
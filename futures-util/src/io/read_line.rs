@@ -36,8 +36,9 @@ pub(super) fn read_line_internal<R: AsyncBufRead + ?Sized>(
     buf: &mut String,
     bytes: &mut Vec<u8>,
     read: &mut usize,
+    limit: usize,
 ) -> Poll<io::Result<usize>> {
-    let ret = ready!(read_until_internal(reader, cx, b'\n', bytes, read));
+    let ret = ready!(read_until_internal(reader, cx, b'\n', bytes, read, limit));
     if str::from_utf8(&bytes).is_err() {
         Poll::Ready(ret.and_then(|_| {
             Err(io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8"))
@@ -56,6 +57,6 @@ impl<R: AsyncBufRead + ?Sized + Unpin> Future for ReadLine<'_, R> {
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let Self { reader, buf, bytes, read } = &mut *self;
-        read_line_internal(Pin::new(reader), cx, buf, bytes, read)
+        read_line_internal(Pin::new(reader), cx, buf, bytes, read, usize::max_value())
     }
 }
@@ -1,6 +1,8 @@
 use futures_core::future::Future;
 use futures_core::task::{Context, Poll};
 use futures_io::AsyncBufRead;
+use std::error::Error;
+use std::fmt;
 use std::io;
 use std::mem;
 use std::pin::Pin;
@@ -23,25 +25,61 @@ impl<'a, R: AsyncBufRead + ?Sized + Unpin> ReadUntil<'a, R> {
     }
 }
 
+/// Error returned when a frame read from an I/O object is larger than the
+/// configured limit.
+///
+/// It is wrapped in an [`io::Error`] of kind
+/// [`InvalidData`](io::ErrorKind::InvalidData), and can be recovered through
+/// [`io::Error::get_ref`] and a downcast.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameTooBig {
+    limit: usize,
+}
+
+impl FrameTooBig {
+    /// Returns the limit which was exceeded, in bytes.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl fmt::Display for FrameTooBig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "frame exceeds the limit of {} bytes", self.limit)
+    }
+}
+
+impl Error for FrameTooBig {}
+
 pub(super) fn read_until_internal<R: AsyncBufRead + ?Sized>(
     mut reader: Pin<&mut R>,
     cx: &mut Context<'_>,
     byte: u8,
     buf: &mut Vec<u8>,
     read: &mut usize,
+    limit: usize,
 ) -> Poll<io::Result<usize>> {
     loop {
-        let (done, used) = {
+        let (done, used, too_big) = {
             let available = ready!(reader.as_mut().poll_fill_buf(cx))?;
-            if let Some(i) = memchr::memchr(byte, available) {
-                buf.extend_from_slice(&available[..=i]);
-                (true, i + 1)
+            let room = limit.saturating_sub(buf.len());
+            let window = &available[..available.len().min(room)];
+            if let Some(i) = memchr::memchr(byte, window) {
+                buf.extend_from_slice(&window[..=i]);
+                (true, i + 1, false)
             } else {
-                buf.extend_from_slice(available);
-                (false, available.len())
+                buf.extend_from_slice(window);
+                (false, window.len(), window.len() < available.len())
             }
         };
         reader.as_mut().consume(used);
+        if too_big {
+            *read = 0;
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                FrameTooBig { limit },
+            )));
+        }
         *read += used;
         if done || used == 0 {
             return Poll::Ready(Ok(mem::replace(read, 0)));
@@ -54,6 +92,6 @@ impl<R: AsyncBufRead + ?Sized + Unpin> Future for ReadUntil<'_, R> {
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let Self { reader, byte, buf, read } = &mut *self;
-        read_until_internal(Pin::new(reader), cx, *byte, buf, read)
+        read_until_internal(Pin::new(reader), cx, *byte, buf, read, usize::max_value())
     }
 }
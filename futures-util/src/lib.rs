@@ -108,6 +108,11 @@ pub mod sink;
 #[cfg(feature = "sink")]
 #[doc(hidden)] pub use crate::sink::SinkExt;
 
+#[cfg(feature = "sink")]
+pub mod transport;
+#[cfg(feature = "sink")]
+#[doc(hidden)] pub use crate::transport::TransportExt;
+
 pub mod task;
 
 #[cfg(feature = "compat")]
@@ -123,4 +128,7 @@ pub mod io;
 cfg_target_has_atomic! {
     #[cfg(feature = "alloc")]
     pub mod lock;
+
+    #[cfg(feature = "timer")]
+    pub mod timer;
 }
@@ -8,6 +8,7 @@ use core::fmt;
 use core::mem;
 use core::ops::{Deref, DerefMut};
 use core::pin::Pin;
+use core::ptr;
 use core::sync::atomic::AtomicUsize;
 use core::sync::atomic::Ordering::SeqCst;
 use alloc::boxed::Box;
@@ -153,7 +154,7 @@ impl<T> BiLock<T> {
     where
         T: Unpin,
     {
-        if &*self.arc as *const _ == &*other.arc as *const _ {
+        if ptr::eq(&*self.arc, &*other.arc) {
             drop(other);
             let inner = Arc::try_unwrap(self.arc)
                 .ok()
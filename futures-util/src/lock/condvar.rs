@@ -0,0 +1,213 @@
+use super::mutex::{Mutex, MutexGuard, MutexLockFuture};
+use futures_core::future::{FusedFuture, Future};
+use futures_core::task::{Context, Poll, Waker};
+use slab::Slab;
+use std::collections::VecDeque;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Mutex as StdMutex;
+
+/// A futures-aware condition variable, to be used along with a futures-aware
+/// [`Mutex`].
+///
+/// A task waits on a condition variable for the state protected by a mutex
+/// to change, releasing the mutex while it waits, and another task notifies
+/// it once it made the change. As with any condition variable, the waiting
+/// task should check the state again once woken up, in a loop, since the
+/// state may have changed again by the time it relocks the mutex.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::{block_on, ThreadPool};
+/// use futures::lock::{Condvar, Mutex};
+/// use futures::task::SpawnExt;
+/// use std::sync::Arc;
+///
+/// let pair = Arc::new((Mutex::new(false), Condvar::new()));
+/// let pair2 = pair.clone();
+///
+/// let mut pool = ThreadPool::new().unwrap();
+/// pool.spawn(async move {
+///     let (ready, condvar) = &*pair2;
+///     *ready.lock().await = true;
+///     condvar.notify_one();
+/// }).unwrap();
+///
+/// block_on(async {
+///     let (ready, condvar) = &*pair;
+///     let mut guard = ready.lock().await;
+///     while !*guard {
+///         guard = condvar.wait(guard).await;
+///     }
+/// });
+/// ```
+pub struct Condvar {
+    state: StdMutex<State>,
+}
+
+struct State {
+    waiters: Slab<Waiter>,
+    // Keys of the waiters which were not notified yet, in arrival order.
+    queue: VecDeque<usize>,
+}
+
+struct Waiter {
+    waker: Option<Waker>,
+    notified: bool,
+}
+
+impl State {
+    // Notifies the waiter which has been waiting the longest, returning
+    // whether there was any.
+    fn notify_one(&mut self) -> bool {
+        match self.queue.pop_front() {
+            Some(key) => {
+                let waiter = &mut self.waiters[key];
+                waiter.notified = true;
+                if let Some(waker) = waiter.waker.take() {
+                    waker.wake();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl fmt::Debug for Condvar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("Condvar")
+            .field("waiters", &state.queue.len())
+            .finish()
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Condvar {
+        Condvar::new()
+    }
+}
+
+impl Condvar {
+    /// Creates a new futures-aware condition variable.
+    pub fn new() -> Condvar {
+        Condvar {
+            state: StdMutex::new(State {
+                waiters: Slab::new(),
+                queue: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Releases the lock held by `guard` and waits for a notification.
+    ///
+    /// This method returns a future that will resolve with the mutex locked
+    /// again once this condition variable has been notified. The wait starts
+    /// right away, so that a notification sent after this call and before
+    /// the future is first polled is not missed.
+    pub fn wait<'a, T: ?Sized>(&'a self, guard: MutexGuard<'a, T>) -> CondvarWait<'a, T> {
+        let mutex = MutexGuard::mutex(&guard);
+        let wait_key = {
+            let mut state = self.state.lock().unwrap();
+            let key = state.waiters.insert(Waiter {
+                waker: None,
+                notified: false,
+            });
+            state.queue.push_back(key);
+            key
+        };
+        drop(guard);
+        CondvarWait {
+            condvar: self,
+            mutex,
+            wait_key,
+            lock: None,
+        }
+    }
+
+    /// Wakes up the task which has been waiting the longest on this
+    /// condition variable, if any.
+    pub fn notify_one(&self) {
+        self.state.lock().unwrap().notify_one();
+    }
+
+    /// Wakes up all the tasks waiting on this condition variable.
+    pub fn notify_all(&self) {
+        let mut state = self.state.lock().unwrap();
+        while state.notify_one() {}
+    }
+}
+
+// Sentinel for when no slot in the `Slab` has been dedicated to this object.
+const WAIT_KEY_NONE: usize = usize::max_value();
+
+/// Future for the [`wait`](Condvar::wait) method.
+pub struct CondvarWait<'a, T: ?Sized> {
+    condvar: &'a Condvar,
+    mutex: &'a Mutex<T>,
+    wait_key: usize,
+    // Set once notified, to lock the mutex again.
+    lock: Option<MutexLockFuture<'a, T>>,
+}
+
+impl<T: ?Sized> fmt::Debug for CondvarWait<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CondvarWait")
+            .field("condvar", &self.condvar)
+            .field("mutex", &self.mutex)
+            .field("was_notified", &self.lock.is_some())
+            .finish()
+    }
+}
+
+impl<T: ?Sized> FusedFuture for CondvarWait<'_, T> {
+    fn is_terminated(&self) -> bool {
+        match &self.lock {
+            Some(lock) => lock.is_terminated(),
+            None => false,
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Future for CondvarWait<'a, T> {
+    type Output = MutexGuard<'a, T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.lock.is_none() {
+            {
+                let mut state = self.condvar.state.lock().unwrap();
+                let waiter = &mut state.waiters[self.wait_key];
+                if !waiter.notified {
+                    match &waiter.waker {
+                        Some(w) if cx.waker().will_wake(w) => {}
+                        _ => waiter.waker = Some(cx.waker().clone()),
+                    }
+                    return Poll::Pending;
+                }
+                state.waiters.remove(self.wait_key);
+            }
+            self.wait_key = WAIT_KEY_NONE;
+            self.lock = Some(self.mutex.lock());
+        }
+        Pin::new(self.lock.as_mut().unwrap()).poll(cx)
+    }
+}
+
+impl<T: ?Sized> Drop for CondvarWait<'_, T> {
+    fn drop(&mut self) {
+        if self.wait_key != WAIT_KEY_NONE {
+            // This future was dropped while waiting. Pass its notification on
+            // to another waiter if it got one, as it would otherwise be lost.
+            let mut state = self.condvar.state.lock().unwrap();
+            let waiter = state.waiters.remove(self.wait_key);
+            if waiter.notified {
+                state.notify_one();
+            } else {
+                let key = self.wait_key;
+                state.queue.retain(|&k| k != key);
+            }
+        }
+    }
+}
@@ -3,7 +3,17 @@
 #[cfg(feature = "std")]
 mod mutex;
 #[cfg(feature = "std")]
-pub use self::mutex::{Mutex, MutexLockFuture, MutexGuard};
+pub use self::mutex::{LockAndRun, Mutex, MutexLockFuture, MutexGuard};
+
+#[cfg(feature = "std")]
+mod condvar;
+#[cfg(feature = "std")]
+pub use self::condvar::{Condvar, CondvarWait};
+
+#[cfg(feature = "std")]
+mod semaphore;
+#[cfg(feature = "std")]
+pub use self::semaphore::{Acquire, Semaphore, SemaphorePermit};
 
 #[cfg(any(feature = "sink", feature = "io"))]
 #[allow(unreachable_pub)]
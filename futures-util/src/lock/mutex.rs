@@ -91,6 +91,39 @@ impl<T: ?Sized> Mutex<T> {
         }
     }
 
+    /// Acquire the lock asynchronously, then run the future returned by `f`
+    /// with the guard.
+    ///
+    /// The guard is moved into the future, so that the lock is released as
+    /// soon as the critical section completes, and also if the returned
+    /// future is dropped at any point, whether while waiting for the lock or
+    /// in the middle of the critical section.
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::future::{self, FutureExt};
+    /// use futures::lock::Mutex;
+    ///
+    /// let mutex = Mutex::new(0);
+    ///
+    /// // Cancelled in the middle of the critical section.
+    /// let critical = mutex.lock_and_run(|mut guard| {
+    ///     *guard += 1;
+    ///     future::pending::<()>().map(move |()| drop(guard))
+    /// });
+    /// assert!(critical.now_or_never().is_none());
+    ///
+    /// assert_eq!(*block_on(mutex.lock()), 1);
+    /// ```
+    pub fn lock_and_run<'a, F, Fut>(&'a self, f: F) -> LockAndRun<'a, T, F, Fut>
+        where F: FnOnce(MutexGuard<'a, T>) -> Fut,
+              Fut: Future,
+    {
+        LockAndRun {
+            state: LockAndRunState::Locking(self.lock(), Some(f)),
+        }
+    }
+
     fn remove_waker(&self, wait_key: usize, wake_another: bool) {
         if wait_key != WAIT_KEY_NONE {
             let mut waiters = self.waiters.lock().unwrap();
@@ -194,6 +227,73 @@ impl<T: ?Sized> Drop for MutexLockFuture<'_, T> {
     }
 }
 
+/// Future for the [`lock_and_run`](Mutex::lock_and_run) method.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LockAndRun<'a, T: ?Sized, F, Fut> {
+    state: LockAndRunState<'a, T, F, Fut>,
+}
+
+enum LockAndRunState<'a, T: ?Sized, F, Fut> {
+    Locking(MutexLockFuture<'a, T>, Option<F>),
+    Running(Fut),
+    Done,
+}
+
+impl<T: ?Sized, F, Fut: Unpin> Unpin for LockAndRun<'_, T, F, Fut> {}
+
+impl<T: ?Sized, F, Fut> fmt::Debug for LockAndRun<'_, T, F, Fut> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self.state {
+            LockAndRunState::Locking(..) => "Locking",
+            LockAndRunState::Running(..) => "Running",
+            LockAndRunState::Done => "Done",
+        };
+        f.debug_struct("LockAndRun")
+            .field("state", &state)
+            .finish()
+    }
+}
+
+impl<'a, T: ?Sized, F, Fut> FusedFuture for LockAndRun<'a, T, F, Fut>
+    where F: FnOnce(MutexGuard<'a, T>) -> Fut,
+          Fut: Future,
+{
+    fn is_terminated(&self) -> bool {
+        match self.state {
+            LockAndRunState::Done => true,
+            _ => false,
+        }
+    }
+}
+
+impl<'a, T: ?Sized, F, Fut> Future for LockAndRun<'a, T, F, Fut>
+    where F: FnOnce(MutexGuard<'a, T>) -> Fut,
+          Fut: Future,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Fut::Output> {
+        // Safe to call `get_unchecked_mut` because we won't move the futures.
+        let state = unsafe { &mut self.get_unchecked_mut().state };
+
+        loop {
+            match state {
+                LockAndRunState::Locking(lock, f) => {
+                    let guard = ready!(Pin::new(lock).poll(cx));
+                    let fut = (f.take().unwrap())(guard);
+                    *state = LockAndRunState::Running(fut);
+                }
+                LockAndRunState::Running(fut) => {
+                    let output = ready!(unsafe { Pin::new_unchecked(fut) }.poll(cx));
+                    *state = LockAndRunState::Done;
+                    return Poll::Ready(output);
+                }
+                LockAndRunState::Done => panic!("LockAndRun polled after completion"),
+            }
+        }
+    }
+}
+
 /// An RAII guard returned by the `lock` and `try_lock` methods.
 /// When this structure is dropped (falls out of scope), the lock will be
 /// unlocked.
@@ -201,10 +301,18 @@ pub struct MutexGuard<'a, T: ?Sized> {
     mutex: &'a Mutex<T>,
 }
 
+impl<'a, T: ?Sized> MutexGuard<'a, T> {
+    // Returns the mutex locked by `guard`. This is an associated function so
+    // as not to shadow the methods of `T`.
+    pub(super) fn mutex(guard: &Self) -> &'a Mutex<T> {
+        guard.mutex
+    }
+}
+
 impl<T: ?Sized + fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("MutexGuard")
-            .field("value", &*self)
+            .field("value", &&**self)
             .field("mutex", &self.mutex)
             .finish()
     }
@@ -0,0 +1,272 @@
+use futures_core::future::{FusedFuture, Future};
+use futures_core::task::{Context, Poll, Waker};
+use slab::Slab;
+use std::collections::VecDeque;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Mutex as StdMutex;
+
+/// A futures-aware counting semaphore.
+///
+/// A semaphore holds a number of permits, which tasks acquire before going
+/// ahead and release when they are done, to bound how much of a resource is
+/// in use at once. Permits can be weighted: a task can acquire several of them
+/// at once with [`acquire_many`](Semaphore::acquire_many), so that for example
+/// a memory budget can be expressed in megabytes.
+///
+/// Waiting tasks are served in first-in, first-out order, whatever the number
+/// of permits they ask for: a large request at the front of the queue holds
+/// back the smaller ones behind it, which ensures that it is not starved.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::lock::Semaphore;
+///
+/// // A budget of 64 megabytes.
+/// let budget = Semaphore::new(64);
+///
+/// let big = block_on(budget.acquire_many(48));
+/// assert_eq!(budget.available_permits(), 16);
+/// assert!(budget.try_acquire_many(32).is_none());
+///
+/// drop(big);
+/// assert!(budget.try_acquire_many(32).is_some());
+/// ```
+pub struct Semaphore {
+    state: StdMutex<State>,
+}
+
+struct State {
+    permits: usize,
+    waiters: Slab<Waiter>,
+    // Keys of the waiters which were not granted their permits yet, in
+    // arrival order.
+    queue: VecDeque<usize>,
+}
+
+struct Waiter {
+    permits: usize,
+    waker: Option<Waker>,
+    granted: bool,
+}
+
+impl State {
+    // Grants permits to the waiters at the front of the queue for as long as
+    // there are enough of them.
+    fn grant(&mut self) {
+        while let Some(&key) = self.queue.front() {
+            let waiter = &mut self.waiters[key];
+            if waiter.permits > self.permits {
+                break;
+            }
+            self.permits -= waiter.permits;
+            waiter.granted = true;
+            if let Some(waker) = waiter.waker.take() {
+                waker.wake();
+            }
+            self.queue.pop_front();
+        }
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("Semaphore")
+            .field("permits", &state.permits)
+            .field("waiters", &state.queue.len())
+            .finish()
+    }
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with `permits` permits available.
+    pub fn new(permits: usize) -> Semaphore {
+        Semaphore {
+            state: StdMutex::new(State {
+                permits,
+                waiters: Slab::new(),
+                queue: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Returns the number of permits available.
+    pub fn available_permits(&self) -> usize {
+        self.state.lock().unwrap().permits
+    }
+
+    /// Adds `n` permits to the semaphore, waking up the waiters they are
+    /// enough for.
+    pub fn add_permits(&self, n: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.permits += n;
+        state.grant();
+    }
+
+    /// Attempts to acquire a permit immediately.
+    ///
+    /// If no permit is available, or if other tasks are waiting for permits,
+    /// this will return `None`.
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.try_acquire_many(1)
+    }
+
+    /// Attempts to acquire `n` permits at once immediately.
+    ///
+    /// If fewer than `n` permits are available, or if other tasks are waiting
+    /// for permits, this will return `None`.
+    pub fn try_acquire_many(&self, n: usize) -> Option<SemaphorePermit<'_>> {
+        let mut state = self.state.lock().unwrap();
+        if state.queue.is_empty() && state.permits >= n {
+            state.permits -= n;
+            Some(SemaphorePermit { semaphore: self, permits: n })
+        } else {
+            None
+        }
+    }
+
+    /// Acquires a permit asynchronously.
+    ///
+    /// This method returns a future that will resolve once the permit has
+    /// been acquired.
+    pub fn acquire(&self) -> Acquire<'_> {
+        self.acquire_many(1)
+    }
+
+    /// Acquires `n` permits at once asynchronously.
+    ///
+    /// This method returns a future that will resolve once all the permits
+    /// have been acquired. Permits are not held while waiting, so that a
+    /// request for more permits than the semaphore will ever have waits
+    /// forever, holding back the tasks queued after it.
+    pub fn acquire_many(&self, n: usize) -> Acquire<'_> {
+        Acquire {
+            semaphore: Some(self),
+            permits: n,
+            wait_key: WAIT_KEY_NONE,
+        }
+    }
+}
+
+// Sentinel for when no slot in the `Slab` has been dedicated to this object.
+const WAIT_KEY_NONE: usize = usize::max_value();
+
+/// Future for the [`acquire`](Semaphore::acquire) and
+/// [`acquire_many`](Semaphore::acquire_many) methods.
+pub struct Acquire<'a> {
+    // `None` indicates that the permits were acquired.
+    semaphore: Option<&'a Semaphore>,
+    permits: usize,
+    wait_key: usize,
+}
+
+impl fmt::Debug for Acquire<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Acquire")
+            .field("was_acquired", &self.semaphore.is_none())
+            .field("permits", &self.permits)
+            .finish()
+    }
+}
+
+impl FusedFuture for Acquire<'_> {
+    fn is_terminated(&self) -> bool {
+        self.semaphore.is_none()
+    }
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = SemaphorePermit<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let semaphore = self.semaphore.expect("polled Acquire after completion");
+        let permits = self.permits;
+        let mut state = semaphore.state.lock().unwrap();
+
+        if self.wait_key == WAIT_KEY_NONE {
+            if state.queue.is_empty() && state.permits >= permits {
+                state.permits -= permits;
+            } else {
+                let key = state.waiters.insert(Waiter {
+                    permits,
+                    waker: Some(cx.waker().clone()),
+                    granted: false,
+                });
+                state.queue.push_back(key);
+                self.wait_key = key;
+                return Poll::Pending;
+            }
+        } else {
+            let waiter = &mut state.waiters[self.wait_key];
+            if !waiter.granted {
+                match &waiter.waker {
+                    Some(w) if cx.waker().will_wake(w) => {}
+                    _ => waiter.waker = Some(cx.waker().clone()),
+                }
+                return Poll::Pending;
+            }
+            state.waiters.remove(self.wait_key);
+            self.wait_key = WAIT_KEY_NONE;
+        }
+
+        drop(state);
+        self.semaphore = None;
+        Poll::Ready(SemaphorePermit { semaphore, permits })
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        match self.semaphore {
+            Some(semaphore) if self.wait_key != WAIT_KEY_NONE => {
+                // This future was dropped while waiting for its permits. Give
+                // them back if they were granted already, and otherwise leave
+                // the queue, which may let the waiters behind it through.
+                let mut state = semaphore.state.lock().unwrap();
+                let waiter = state.waiters.remove(self.wait_key);
+                if waiter.granted {
+                    state.permits += waiter.permits;
+                } else {
+                    let key = self.wait_key;
+                    state.queue.retain(|&k| k != key);
+                }
+                state.grant();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// An RAII guard returned by the `acquire` and `try_acquire` methods of a
+/// [`Semaphore`]. When this structure is dropped (falls out of scope), its
+/// permits are released.
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+}
+
+impl fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SemaphorePermit")
+            .field("permits", &self.permits)
+            .finish()
+    }
+}
+
+impl SemaphorePermit<'_> {
+    /// Returns the number of permits held by this guard.
+    pub fn permits(&self) -> usize {
+        self.permits
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.add_permits(self.permits);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use core::pin::Pin;
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::unsafe_pinned;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Sink for the [`backpressure_probe`](super::SinkExt::backpressure_probe)
+/// method.
+///
+/// The sink is considered under pressure from the moment its
+/// `poll_ready` returns `Poll::Pending` until it returns
+/// `Poll::Ready` again.
+#[derive(Debug)]
+#[must_use = "sinks do nothing unless polled"]
+pub struct BackpressureProbe<Si> {
+    sink: Si,
+    state: Arc<Mutex<Pressure>>,
+}
+
+impl<Si: Unpin> Unpin for BackpressureProbe<Si> {}
+
+/// Handle telling whether the sink of a [`BackpressureProbe`] has been
+/// applying backpressure, and for how long.
+///
+/// This lets producers adapt to a slow consumer, for instance by lowering
+/// their sampling rate, rather than guessing from send latencies. The
+/// handle can be cloned, and outlives the sink.
+#[derive(Debug, Clone)]
+pub struct PressureWatch {
+    state: Arc<Mutex<Pressure>>,
+}
+
+#[derive(Debug, Default)]
+struct Pressure {
+    // The start of the ongoing episode of backpressure, if any.
+    since: Option<Instant>,
+    // The end of the last completed episode.
+    last_end: Option<Instant>,
+    // The duration of the completed episodes.
+    total: Duration,
+}
+
+impl<Si> BackpressureProbe<Si> {
+    unsafe_pinned!(sink: Si);
+
+    pub(super) fn new(sink: Si) -> (BackpressureProbe<Si>, PressureWatch) {
+        let state = Arc::new(Mutex::new(Pressure::default()));
+        let watch = PressureWatch { state: state.clone() };
+        (BackpressureProbe { sink, state }, watch)
+    }
+
+    /// Get a shared reference to the inner sink.
+    pub fn get_ref(&self) -> &Si {
+        &self.sink
+    }
+
+    /// Get a mutable reference to the inner sink.
+    pub fn get_mut(&mut self) -> &mut Si {
+        &mut self.sink
+    }
+
+    /// Get a pinned mutable reference to the inner sink.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut Si> {
+        self.sink()
+    }
+
+    /// Consumes this combinator, returning the underlying sink.
+    pub fn into_inner(self) -> Si {
+        self.sink
+    }
+}
+
+impl<Si: Sink<Item>, Item> Sink<Item> for BackpressureProbe<Si> {
+    type Error = Si::Error;
+
+    fn poll_ready(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        let poll = self.as_mut().sink().poll_ready(cx);
+        let mut state = self.state.lock().unwrap();
+        if poll.is_pending() {
+            if state.since.is_none() {
+                state.since = Some(Instant::now());
+            }
+        } else if let Some(since) = state.since.take() {
+            let now = Instant::now();
+            state.last_end = Some(now);
+            state.total += now - since;
+        }
+        poll
+    }
+
+    fn start_send(
+        self: Pin<&mut Self>,
+        item: Item,
+    ) -> Result<(), Self::Error> {
+        self.sink().start_send(item)
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.sink().poll_flush(cx)
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.sink().poll_close(cx)
+    }
+}
+
+impl PressureWatch {
+    /// Returns whether the sink is applying backpressure right now.
+    pub fn is_under_pressure(&self) -> bool {
+        self.state.lock().unwrap().since.is_some()
+    }
+
+    /// Returns for how long the sink has been applying backpressure, if it
+    /// is right now.
+    pub fn pressure_duration(&self) -> Option<Duration> {
+        self.state.lock().unwrap().since.map(|since| since.elapsed())
+    }
+
+    /// Returns whether the sink applied backpressure at any point during the
+    /// last `window`, including right now.
+    pub fn under_pressure_within(&self, window: Duration) -> bool {
+        let state = self.state.lock().unwrap();
+        if state.since.is_some() {
+            return true;
+        }
+        match state.last_end {
+            Some(end) => end.elapsed() <= window,
+            None => false,
+        }
+    }
+
+    /// Returns the total time the sink has spent applying backpressure.
+    pub fn total_pressure(&self) -> Duration {
+        let state = self.state.lock().unwrap();
+        match state.since {
+            Some(since) => state.total + since.elapsed(),
+            None => state.total,
+        }
+    }
+}
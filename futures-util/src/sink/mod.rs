@@ -32,12 +32,21 @@ pub use self::err_into::SinkErrInto;
 mod map_err;
 pub use self::map_err::SinkMapErr;
 
+mod or_else;
+pub use self::or_else::SinkOrElse;
+
+mod reconnecting;
+pub use self::reconnecting::{ReconnectingSink, ResumeState};
+
 mod send;
 pub use self::send::Send;
 
 mod send_all;
 pub use self::send_all::SendAll;
 
+mod unfold;
+pub use self::unfold::{unfold, Unfold};
+
 mod with;
 pub use self::with::With;
 
@@ -49,6 +58,23 @@ mod buffer;
 #[cfg(feature = "alloc")]
 pub use self::buffer::Buffer;
 
+#[cfg(feature = "alloc")]
+mod transactional;
+#[cfg(feature = "alloc")]
+pub use self::transactional::{Commit, Transactional};
+
+#[cfg(feature = "std")]
+mod backpressure_probe;
+#[cfg(feature = "std")]
+pub use self::backpressure_probe::{BackpressureProbe, PressureWatch};
+
+cfg_target_has_atomic! {
+    #[cfg(feature = "timer")]
+    mod registry;
+    #[cfg(feature = "timer")]
+    pub use self::registry::{Broadcast, Eviction, SinkRegistry};
+}
+
 impl<T: ?Sized, Item> SinkExt<Item> for T where T: Sink<Item> {}
 
 /// An extension trait for `Sink`s that provides a variety of convenient
@@ -138,6 +164,49 @@ pub trait SinkExt<Item>: Sink<Item> {
         SinkMapErr::new(self, f)
     }
 
+    /// Recovers from the errors of the sink.
+    ///
+    /// Whenever the sink fails, `f` is called with the error, and the future
+    /// it returns is polled to completion before the sink is used again. If
+    /// that future fails, its error is returned by the new sink. If it
+    /// succeeds, the error is swallowed: the operation which failed reports
+    /// success, and the item it was sending, if any, is dropped. This is
+    /// where errors which should not bring a publisher down are logged and
+    /// ignored; to replace a failed sink altogether, see
+    /// [`ReconnectingSink`].
+    ///
+    /// The recovery from an error of [`start_send`](Sink::start_send) runs
+    /// on the next call to `poll_ready`, `poll_flush` or `poll_close`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::channel::mpsc;
+    /// use futures::executor::block_on;
+    /// use futures::future;
+    /// use futures::sink::SinkExt;
+    ///
+    /// let (tx, rx) = mpsc::unbounded::<i32>();
+    /// drop(rx);
+    ///
+    /// let mut failures = 0;
+    /// let mut sink = tx.sink_or_else(|_| {
+    ///     failures += 1;
+    ///     future::ok::<(), ()>(())
+    /// });
+    /// // The channel is closed, so the item is dropped.
+    /// block_on(sink.send(1)).unwrap();
+    /// drop(sink);
+    /// assert_eq!(failures, 1);
+    /// ```
+    fn sink_or_else<E, F, Fut>(self, f: F) -> SinkOrElse<Self, F, Fut>
+        where F: FnMut(Self::Error) -> Fut,
+              Fut: Future<Output = Result<(), E>>,
+              Self: Sized,
+    {
+        SinkOrElse::new(self, f)
+    }
+
     /// Map this sink's error to a different error type using the `Into` trait.
     ///
     /// If wanting to map errors of a `Sink + Stream`, use `.sink_err_into().err_into()`.
@@ -168,6 +237,49 @@ pub trait SinkExt<Item>: Sink<Item> {
         Buffer::new(self, capacity)
     }
 
+    /// Stages the items sent to this sink until they are explicitly
+    /// committed.
+    ///
+    /// Items sent to the resulting sink are kept aside until
+    /// [`commit`](Transactional::commit) sends them all to the current sink
+    /// and flushes it, or [`rollback`](Transactional::rollback) discards them.
+    /// This aligns writes with transaction boundaries, so that the current
+    /// sink never sees half of a batch which was abandoned. Flushing the
+    /// resulting sink only flushes the current sink, without committing the
+    /// staged items. Likewise, closing it rolls back the staged items before
+    /// closing the current sink, so they must be committed first to be kept.
+    ///
+    /// Note that this function consumes the given sink, returning a wrapped
+    /// version, much like `Iterator::map`.
+    ///
+    /// This method is only available when the `std` or `alloc` feature of this
+    /// library is activated, and it is activated by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::sink::SinkExt;
+    ///
+    /// let mut sink = Vec::<i32>::new().transactional();
+    ///
+    /// block_on(sink.send(1))?;
+    /// block_on(sink.send(2))?;
+    /// block_on(sink.commit())?;
+    ///
+    /// block_on(sink.send(3))?;
+    /// sink.rollback();
+    ///
+    /// assert_eq!(sink.get_ref(), &vec![1, 2]);
+    /// # Ok::<(), futures::never::Never>(())
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn transactional(self) -> Transactional<Self, Item>
+        where Self: Sized,
+    {
+        Transactional::new(self)
+    }
+
     /// Close the sink.
     fn close(&mut self) -> Close<'_, Self, Item>
         where Self: Unpin,
@@ -187,6 +299,50 @@ pub trait SinkExt<Item>: Sink<Item> {
         Fanout::new(self, other)
     }
 
+    /// Wraps this sink so that the returned [`PressureWatch`] tells whether
+    /// it has been applying backpressure, and for how long.
+    ///
+    /// The sink applies backpressure while it is not ready to accept an
+    /// item, as with a full channel. A producer can check the watch to
+    /// adapt its rate, rather than guess from how long its sends take.
+    ///
+    /// This method is only available when the `std` feature of this
+    /// library is activated, and it is activated by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::channel::mpsc;
+    /// use futures::executor::block_on;
+    /// use futures::sink::{Sink, SinkExt};
+    /// use futures::stream::StreamExt;
+    /// use futures::task::{noop_waker_ref, Context};
+    /// use std::pin::Pin;
+    /// use std::time::Duration;
+    ///
+    /// let (tx, mut rx) = mpsc::channel::<i32>(0);
+    /// let (mut tx, watch) = tx.backpressure_probe();
+    /// let mut cx = Context::from_waker(noop_waker_ref());
+    ///
+    /// // The channel holds a single item, and is full until the receiver
+    /// // takes it.
+    /// assert!(Pin::new(&mut tx).poll_ready(&mut cx).is_ready());
+    /// Pin::new(&mut tx).start_send(1).unwrap();
+    /// assert!(Pin::new(&mut tx).poll_ready(&mut cx).is_pending());
+    /// assert!(watch.is_under_pressure());
+    ///
+    /// assert_eq!(block_on(rx.next()), Some(1));
+    /// assert!(Pin::new(&mut tx).poll_ready(&mut cx).is_ready());
+    /// assert!(!watch.is_under_pressure());
+    /// assert!(watch.under_pressure_within(Duration::from_secs(60)));
+    /// ```
+    #[cfg(feature = "std")]
+    fn backpressure_probe(self) -> (BackpressureProbe<Self>, PressureWatch)
+        where Self: Sized,
+    {
+        BackpressureProbe::new(self)
+    }
+
     /// Flush the sync, processing all pending items.
     ///
     /// This adapter is intended to be used when you want to stop sending to the sink
@@ -0,0 +1,151 @@
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// Sink for the [`sink_or_else`](super::SinkExt::sink_or_else) method.
+#[derive(Debug)]
+#[must_use = "sinks do nothing unless polled"]
+pub struct SinkOrElse<Si, F, Fut> {
+    sink: Si,
+    f: F,
+    recovering: Option<Fut>,
+    // Set when an error of `poll_ready` was recovered from, in which case the
+    // next item is dropped rather than sent to a sink which is not ready.
+    drop_next: bool,
+}
+
+impl<Si: Unpin, F, Fut: Unpin> Unpin for SinkOrElse<Si, F, Fut> {}
+
+impl<Si, F, Fut> SinkOrElse<Si, F, Fut> {
+    unsafe_pinned!(sink: Si);
+    unsafe_unpinned!(f: F);
+    unsafe_pinned!(recovering: Option<Fut>);
+    unsafe_unpinned!(drop_next: bool);
+
+    pub(super) fn new(sink: Si, f: F) -> SinkOrElse<Si, F, Fut> {
+        SinkOrElse { sink, f, recovering: None, drop_next: false }
+    }
+
+    /// Get a shared reference to the inner sink.
+    pub fn get_ref(&self) -> &Si {
+        &self.sink
+    }
+
+    /// Get a mutable reference to the inner sink.
+    pub fn get_mut(&mut self) -> &mut Si {
+        &mut self.sink
+    }
+
+    /// Get a pinned mutable reference to the inner sink.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut Si> {
+        self.sink()
+    }
+
+    /// Consumes this combinator, returning the underlying sink.
+    ///
+    /// Note that this may discard intermediate state of this combinator, so
+    /// care should be taken to avoid losing resources when this is called.
+    pub fn into_inner(self) -> Si {
+        self.sink
+    }
+}
+
+impl<Si, F, Fut, E> SinkOrElse<Si, F, Fut>
+    where Fut: Future<Output = Result<(), E>>,
+{
+    // Drives the recovery from the last error of the inner sink, if any.
+    fn poll_recovering(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), E>> {
+        if let Some(fut) = self.as_mut().recovering().as_pin_mut() {
+            let result = ready!(fut.poll(cx));
+            self.as_mut().recovering().set(None);
+            result?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn recover<Err>(mut self: Pin<&mut Self>, err: Err)
+        where F: FnMut(Err) -> Fut,
+    {
+        let fut = (self.as_mut().f())(err);
+        self.as_mut().recovering().set(Some(fut));
+    }
+}
+
+impl<Si, F, Fut, E, Item> Sink<Item> for SinkOrElse<Si, F, Fut>
+    where Si: Sink<Item>,
+          F: FnMut(Si::Error) -> Fut,
+          Fut: Future<Output = Result<(), E>>,
+{
+    type Error = E;
+
+    fn poll_ready(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_recovering(cx))?;
+        match ready!(self.as_mut().sink().poll_ready(cx)) {
+            // The next item can be sent after all.
+            Ok(()) => *self.as_mut().drop_next() = false,
+            Err(e) => {
+                self.as_mut().recover(e);
+                *self.as_mut().drop_next() = true;
+                ready!(self.as_mut().poll_recovering(cx))?;
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(
+        mut self: Pin<&mut Self>,
+        item: Item,
+    ) -> Result<(), Self::Error> {
+        if self.drop_next {
+            *self.as_mut().drop_next() = false;
+            return Ok(());
+        }
+        // The recovery is driven by the next call to any of the `poll_*`
+        // methods, which reports its error if it fails.
+        if let Err(e) = self.as_mut().sink().start_send(item) {
+            self.recover(e);
+        }
+        Ok(())
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_recovering(cx))?;
+        if let Err(e) = ready!(self.as_mut().sink().poll_flush(cx)) {
+            self.as_mut().recover(e);
+            ready!(self.as_mut().poll_recovering(cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_recovering(cx))?;
+        if let Err(e) = ready!(self.as_mut().sink().poll_close(cx)) {
+            self.as_mut().recover(e);
+            ready!(self.as_mut().poll_recovering(cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: Stream, F, Fut> Stream for SinkOrElse<S, F, Fut> {
+    type Item = S::Item;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<S::Item>> {
+        self.sink().poll_next(cx)
+    }
+}
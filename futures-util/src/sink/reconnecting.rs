@@ -0,0 +1,416 @@
+use core::fmt;
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+#[cfg_attr(
+    feature = "cfg-target-has-atomic",
+    cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+)]
+#[cfg(feature = "timer")]
+use crate::future::FutureExt;
+#[cfg_attr(
+    feature = "cfg-target-has-atomic",
+    cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+)]
+#[cfg(feature = "timer")]
+use crate::timer::{sleep, Sleep};
+#[cfg_attr(
+    feature = "cfg-target-has-atomic",
+    cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+)]
+#[cfg(feature = "timer")]
+use std::cmp;
+#[cfg_attr(
+    feature = "cfg-target-has-atomic",
+    cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+)]
+#[cfg(feature = "timer")]
+use std::time::Duration;
+
+/// A sink which replaces its underlying sink whenever it fails.
+///
+/// `ReconnectingSink` builds its underlying sink with a connection factory,
+/// a closure returning a future which resolves to a new sink. Whenever the
+/// underlying sink returns an error, it is discarded and the factory is
+/// invoked again to build a replacement, onto which the item that failed is
+/// sent again. This makes it possible for long-lived publishers to survive
+/// transient connection loss.
+///
+/// To guarantee that no item is lost when a connection fails, each item is
+/// flushed through the underlying sink before the next one is accepted, and
+/// items are cloned every time they are handed to the underlying sink.
+///
+/// Reconnections are bounded: once `max_retries` consecutive attempts to
+/// deliver an item have failed, the last error is returned and the item is
+/// dropped. Errors from the factory's futures count as failed attempts too.
+/// The count is reset every time an item is delivered.
+///
+/// By default a replacement is requested immediately. A delay growing
+/// exponentially between consecutive attempts can be configured with
+/// [`with_backoff`](ReconnectingSink::with_backoff).
+///
+/// The connection factory is handed the sink's [`ResumeState`], which records
+/// every delivered item, so that a replacement connection can pick up where
+/// the previous one left off. [`new`](ReconnectingSink::new) uses `()`, which
+/// records nothing; a custom state can be supplied with
+/// [`with_state`](ReconnectingSink::with_state).
+#[must_use = "sinks do nothing unless polled"]
+pub struct ReconnectingSink<Si, F, Fut, Item, R = ()> {
+    connect: F,
+    resume_state: R,
+    connecting: Option<Fut>,
+    sink: Option<Si>,
+    item: Option<Item>,
+    sent: bool,
+    max_retries: usize,
+    retries: usize,
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "timer")]
+    backoff: Backoff,
+}
+
+// The delay between consecutive attempts to connect.
+#[cfg_attr(
+    feature = "cfg-target-has-atomic",
+    cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+)]
+#[cfg(feature = "timer")]
+#[derive(Debug)]
+struct Backoff {
+    min: Duration,
+    max: Duration,
+    next: Duration,
+    delay: Option<Sleep>,
+}
+
+/// State carried across the connections of a reconnecting sink or stream.
+///
+/// [`ReconnectingSink`] records every item it has delivered, and
+/// [`ReconnectingStream`](crate::try_stream::ReconnectingStream) every item
+/// it has received, into its `ResumeState`. The state is then handed to the
+/// connection factory, which can use it to resume from the right offset or
+/// cursor rather than from scratch.
+///
+/// The unit type implements `ResumeState` by ignoring every item.
+pub trait ResumeState<Item> {
+    /// Records that `item` went through the current connection.
+    fn record(&mut self, item: &Item);
+}
+
+impl<Item> ResumeState<Item> for () {
+    fn record(&mut self, _item: &Item) {}
+}
+
+impl<Si: Unpin, F, Fut: Unpin, Item, R> Unpin for ReconnectingSink<Si, F, Fut, Item, R> {}
+
+impl<Si, F, Fut, Item, R> fmt::Debug for ReconnectingSink<Si, F, Fut, Item, R>
+where
+    Si: fmt::Debug,
+    Fut: fmt::Debug,
+    Item: fmt::Debug,
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("ReconnectingSink");
+        f
+            .field("resume_state", &self.resume_state)
+            .field("connecting", &self.connecting)
+            .field("sink", &self.sink)
+            .field("item", &self.item)
+            .field("sent", &self.sent)
+            .field("max_retries", &self.max_retries)
+            .field("retries", &self.retries);
+        #[cfg_attr(
+            feature = "cfg-target-has-atomic",
+            cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+        )]
+        #[cfg(feature = "timer")]
+        f.field("backoff", &self.backoff);
+        f.finish()
+    }
+}
+
+impl<Si, F, Fut, Item> ReconnectingSink<Si, F, Fut, Item>
+    where Si: Sink<Item>,
+          F: FnMut(&()) -> Fut,
+          Fut: Future<Output = Result<Si, Si::Error>>,
+          Item: Clone,
+{
+    /// Creates a new `ReconnectingSink` using `connect` to build its
+    /// underlying sink, retrying each item at most `max_retries` times.
+    ///
+    /// No connection is made until the sink is first used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::channel::mpsc;
+    /// use futures::executor::block_on;
+    /// use futures::future;
+    /// use futures::sink::{ReconnectingSink, SinkExt};
+    /// use futures::stream::StreamExt;
+    ///
+    /// let (tx, rx) = mpsc::unbounded();
+    /// let mut sink = ReconnectingSink::new(move |_| future::ok(tx.clone()), 3);
+    ///
+    /// block_on(sink.send(1)).unwrap();
+    /// block_on(sink.send(2)).unwrap();
+    /// drop(sink);
+    ///
+    /// assert_eq!(block_on(rx.collect::<Vec<_>>()), vec![1, 2]);
+    /// ```
+    pub fn new(connect: F, max_retries: usize) -> Self {
+        ReconnectingSink::with_state(connect, (), max_retries)
+    }
+}
+
+impl<Si, F, Fut, Item, R> ReconnectingSink<Si, F, Fut, Item, R>
+    where Si: Sink<Item>,
+          F: FnMut(&R) -> Fut,
+          Fut: Future<Output = Result<Si, Si::Error>>,
+          Item: Clone,
+          R: ResumeState<Item>,
+{
+    unsafe_unpinned!(resume_state: R);
+    unsafe_pinned!(connecting: Option<Fut>);
+    unsafe_pinned!(sink: Option<Si>);
+    unsafe_unpinned!(item: Option<Item>);
+    unsafe_unpinned!(sent: bool);
+    unsafe_unpinned!(retries: usize);
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "timer")]
+    unsafe_unpinned!(backoff: Backoff);
+
+    /// Creates a new `ReconnectingSink` like [`new`](ReconnectingSink::new),
+    /// recording delivered items into `state` and handing it to `connect`
+    /// on every connection.
+    pub fn with_state(connect: F, state: R, max_retries: usize) -> Self {
+        ReconnectingSink {
+            connect,
+            resume_state: state,
+            connecting: None,
+            sink: None,
+            item: None,
+            sent: false,
+            max_retries,
+            retries: 0,
+            #[cfg_attr(
+                feature = "cfg-target-has-atomic",
+                cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+            )]
+            #[cfg(feature = "timer")]
+            backoff: Backoff {
+                min: Duration::from_secs(0),
+                max: Duration::from_secs(0),
+                next: Duration::from_secs(0),
+                delay: None,
+            },
+        }
+    }
+
+    /// Waits before each attempt to replace the underlying sink, starting
+    /// with `min` and doubling the delay after each failed attempt, up to
+    /// `max`.
+    ///
+    /// This method is only available when the `timer` feature of this
+    /// library is activated.
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "timer")]
+    pub fn with_backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.backoff.min = min;
+        self.backoff.max = cmp::max(min, max);
+        self.backoff.next = min;
+        self
+    }
+
+    /// Get a shared reference to the current underlying sink, if connected.
+    pub fn get_ref(&self) -> Option<&Si> {
+        self.sink.as_ref()
+    }
+
+    /// Get a mutable reference to the current underlying sink, if connected.
+    pub fn get_mut(&mut self) -> Option<&mut Si> {
+        self.sink.as_mut()
+    }
+
+    /// Get a shared reference to the resumption state.
+    pub fn state(&self) -> &R {
+        &self.resume_state
+    }
+
+    fn reconnect(mut self: Pin<&mut Self>) {
+        self.as_mut().sink().set(None);
+        *self.as_mut().sent() = false;
+        // Safety: `connect` and `resume_state` are never pinned.
+        let this = unsafe { self.as_mut().get_unchecked_mut() };
+        let fut = (this.connect)(&this.resume_state);
+        self.as_mut().connecting().set(Some(fut));
+    }
+
+    fn fail(mut self: Pin<&mut Self>, err: Si::Error) -> Result<(), Si::Error> {
+        if self.retries >= self.max_retries {
+            self.as_mut().sink().set(None);
+            *self.as_mut().retries() = 0;
+            *self.as_mut().item() = None;
+            #[cfg_attr(
+                feature = "cfg-target-has-atomic",
+                cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+            )]
+            #[cfg(feature = "timer")]
+            {
+                let backoff = self.as_mut().backoff();
+                backoff.next = backoff.min;
+            }
+            return Err(err);
+        }
+        *self.as_mut().retries() += 1;
+        self.as_mut().sink().set(None);
+        *self.as_mut().sent() = false;
+        #[cfg_attr(
+            feature = "cfg-target-has-atomic",
+            cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+        )]
+        #[cfg(feature = "timer")]
+        {
+            let backoff = self.as_mut().backoff();
+            if backoff.next > Duration::from_secs(0) {
+                backoff.delay = Some(sleep(backoff.next));
+                backoff.next = cmp::min(backoff.next * 2, backoff.max);
+            }
+        }
+        Ok(())
+    }
+
+    // Drives the pending item, if any, until it has been flushed through the
+    // underlying sink, reconnecting as needed.
+    fn poll_deliver(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Si::Error>> {
+        loop {
+            #[cfg_attr(
+                feature = "cfg-target-has-atomic",
+                cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+            )]
+            #[cfg(feature = "timer")]
+            {
+                let backoff = self.as_mut().backoff();
+                if let Some(delay) = &mut backoff.delay {
+                    ready!(delay.poll_unpin(cx));
+                    backoff.delay = None;
+                }
+            }
+
+            if let Some(fut) = self.as_mut().connecting().as_pin_mut() {
+                let result = ready!(fut.poll(cx));
+                self.as_mut().connecting().set(None);
+                match result {
+                    Ok(sink) => self.as_mut().sink().set(Some(sink)),
+                    Err(e) => {
+                        self.as_mut().fail(e)?;
+                        continue;
+                    }
+                }
+            }
+
+            if self.item.is_none() {
+                return Poll::Ready(Ok(()));
+            }
+
+            if self.sink.is_none() {
+                self.as_mut().reconnect();
+                continue;
+            }
+
+            if !self.sent {
+                let sink = self.as_mut().sink().as_pin_mut().unwrap();
+                if let Err(e) = ready!(sink.poll_ready(cx)) {
+                    self.as_mut().fail(e)?;
+                    continue;
+                }
+                let item = self.item.clone().unwrap();
+                let sink = self.as_mut().sink().as_pin_mut().unwrap();
+                if let Err(e) = sink.start_send(item) {
+                    self.as_mut().fail(e)?;
+                    continue;
+                }
+                *self.as_mut().sent() = true;
+            }
+
+            let sink = self.as_mut().sink().as_pin_mut().unwrap();
+            if let Err(e) = ready!(sink.poll_flush(cx)) {
+                self.as_mut().fail(e)?;
+                continue;
+            }
+            if let Some(item) = self.as_mut().item().take() {
+                self.as_mut().resume_state().record(&item);
+            }
+            *self.as_mut().sent() = false;
+            *self.as_mut().retries() = 0;
+            #[cfg_attr(
+                feature = "cfg-target-has-atomic",
+                cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+            )]
+            #[cfg(feature = "timer")]
+            {
+                let backoff = self.as_mut().backoff();
+                backoff.next = backoff.min;
+            }
+        }
+    }
+}
+
+impl<Si, F, Fut, Item, R> Sink<Item> for ReconnectingSink<Si, F, Fut, Item, R>
+    where Si: Sink<Item>,
+          F: FnMut(&R) -> Fut,
+          Fut: Future<Output = Result<Si, Si::Error>>,
+          Item: Clone,
+          R: ResumeState<Item>,
+{
+    type Error = Si::Error;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.poll_deliver(cx)
+    }
+
+    fn start_send(
+        mut self: Pin<&mut Self>,
+        item: Item,
+    ) -> Result<(), Self::Error> {
+        debug_assert!(self.item.is_none());
+        *self.as_mut().item() = Some(item);
+        Ok(())
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.poll_deliver(cx)
+    }
+
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_deliver(cx))?;
+        match self.as_mut().sink().as_pin_mut() {
+            Some(sink) => sink.poll_close(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
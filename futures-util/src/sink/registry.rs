@@ -0,0 +1,268 @@
+use crate::future::FutureExt;
+use crate::timer::{sleep, Sleep};
+use core::fmt;
+use core::hash::Hash;
+use core::mem;
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Duration;
+
+/// A set of sinks, registered by key, to broadcast items to.
+///
+/// This is the fan-out half of a chat or publish/subscribe server: each
+/// subscriber registers the sink of its connection, and every published
+/// item is [`broadcast`](SinkRegistry::broadcast) to all of them.
+///
+/// Broadcasting applies backpressure: it resolves only once every sink has
+/// accepted and flushed the item. So that a single slow consumer cannot hold
+/// up everyone else forever, a registry created with
+/// [`with_timeout`](SinkRegistry::with_timeout) evicts the sinks which take
+/// longer than the timeout. Sinks which fail are evicted as well.
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::mpsc;
+/// use futures::executor::block_on;
+/// use futures::sink::SinkRegistry;
+/// use futures::stream::StreamExt;
+///
+/// let (tx1, rx1) = mpsc::unbounded();
+/// let (tx2, rx2) = mpsc::unbounded();
+/// let mut registry = SinkRegistry::new();
+/// registry.register("alice", tx1);
+/// registry.register("bob", tx2);
+///
+/// assert!(block_on(registry.broadcast("hello")).is_empty());
+/// registry.unregister(&"bob");
+/// assert!(block_on(registry.broadcast("bye")).is_empty());
+/// drop(registry);
+///
+/// assert_eq!(block_on(rx1.collect::<Vec<_>>()), vec!["hello", "bye"]);
+/// assert_eq!(block_on(rx2.collect::<Vec<_>>()), vec!["hello"]);
+/// ```
+pub struct SinkRegistry<K, Si> {
+    sinks: HashMap<K, Si>,
+    timeout: Option<Duration>,
+}
+
+impl<K, Si> fmt::Debug for SinkRegistry<K, Si>
+where
+    K: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SinkRegistry")
+            .field("keys", &self.sinks.keys().collect::<Vec<_>>())
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl<K: Hash + Eq, Si> Default for SinkRegistry<K, Si> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, Si> SinkRegistry<K, Si> {
+    /// Creates an empty registry, whose broadcasts wait for every sink
+    /// without limit.
+    pub fn new() -> Self {
+        SinkRegistry {
+            sinks: HashMap::new(),
+            timeout: None,
+        }
+    }
+
+    /// Creates an empty registry, whose broadcasts evict the sinks which
+    /// have not accepted and flushed the item within `timeout`.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        SinkRegistry {
+            sinks: HashMap::new(),
+            timeout: Some(timeout),
+        }
+    }
+
+    /// Registers `sink` under `key`, returning the sink it replaces, if any.
+    pub fn register(&mut self, key: K, sink: Si) -> Option<Si> {
+        self.sinks.insert(key, sink)
+    }
+
+    /// Unregisters the sink under `key`, returning it.
+    pub fn unregister(&mut self, key: &K) -> Option<Si> {
+        self.sinks.remove(key)
+    }
+
+    /// Returns whether a sink is registered under `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.sinks.contains_key(key)
+    }
+
+    /// Returns the number of registered sinks.
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    /// Returns `true` if no sink is registered.
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Sends a clone of `item` to every registered sink.
+    ///
+    /// The returned future resolves once every sink has accepted and flushed
+    /// the item, or was evicted. Sinks are evicted when they fail, or when
+    /// the registry has a timeout which elapses before they are done. The
+    /// future resolves to the keys of the evicted sinks, along with the
+    /// reason of their eviction; the evicted sinks themselves are dropped.
+    ///
+    /// Sinks registered while the returned future is alive do not receive
+    /// the item, since the future borrows the registry mutably.
+    pub fn broadcast<Item>(&mut self, item: Item) -> Broadcast<'_, K, Si, Item>
+        where K: Clone,
+              Si: Sink<Item> + Unpin,
+              Item: Clone,
+    {
+        Broadcast::new(self, item)
+    }
+}
+
+/// Reason for which a sink was evicted from a [`SinkRegistry`] during a
+/// broadcast.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Eviction<E> {
+    /// The sink failed with the given error.
+    Failed(E),
+    /// The sink did not accept and flush the item in time.
+    TimedOut,
+}
+
+impl<E: fmt::Display> fmt::Display for Eviction<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Eviction::Failed(e) => write!(f, "sink failed: {}", e),
+            Eviction::TimedOut => f.write_str("sink timed out"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for Eviction<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Eviction::Failed(e) => Some(e),
+            Eviction::TimedOut => None,
+        }
+    }
+}
+
+/// Future for the [`broadcast`](SinkRegistry::broadcast) method.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Broadcast<'a, K, Si: Sink<Item>, Item> {
+    registry: &'a mut SinkRegistry<K, Si>,
+    item: Item,
+    // Keys of the sinks which are not done yet, and whether they were sent
+    // the item already.
+    pending: Vec<(K, bool)>,
+    sleep: Option<Sleep>,
+    evicted: Vec<(K, Eviction<Si::Error>)>,
+}
+
+// Pinning is never projected to children
+impl<K, Si: Sink<Item>, Item> Unpin for Broadcast<'_, K, Si, Item> {}
+
+impl<K, Si, Item> fmt::Debug for Broadcast<'_, K, Si, Item>
+where
+    K: fmt::Debug,
+    Si: Sink<Item>,
+    Si::Error: fmt::Debug,
+    Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Broadcast")
+            .field("item", &self.item)
+            .field("pending", &self.pending)
+            .field("sleep", &self.sleep)
+            .field("evicted", &self.evicted)
+            .finish()
+    }
+}
+
+impl<'a, K, Si, Item> Broadcast<'a, K, Si, Item>
+    where K: Hash + Eq + Clone,
+          Si: Sink<Item> + Unpin,
+          Item: Clone,
+{
+    fn new(registry: &'a mut SinkRegistry<K, Si>, item: Item) -> Self {
+        let pending = registry.sinks.keys().map(|key| (key.clone(), false)).collect();
+        let sleep = registry.timeout.map(sleep);
+        Broadcast {
+            registry,
+            item,
+            pending,
+            sleep,
+            evicted: Vec::new(),
+        }
+    }
+}
+
+impl<K, Si, Item> Future for Broadcast<'_, K, Si, Item>
+    where K: Hash + Eq + Clone,
+          Si: Sink<Item> + Unpin,
+          Item: Clone,
+{
+    type Output = Vec<(K, Eviction<Si::Error>)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let mut i = 0;
+        while i < this.pending.len() {
+            let (key, sent) = &mut this.pending[i];
+            let sink = this.registry.sinks.get_mut(key).unwrap();
+            match poll_deliver(Pin::new(sink), &this.item, sent, cx) {
+                Poll::Ready(Ok(())) => {
+                    this.pending.swap_remove(i);
+                }
+                Poll::Ready(Err(e)) => {
+                    let (key, _) = this.pending.swap_remove(i);
+                    this.registry.sinks.remove(&key);
+                    this.evicted.push((key, Eviction::Failed(e)));
+                }
+                Poll::Pending => i += 1,
+            }
+        }
+
+        if !this.pending.is_empty() {
+            match &mut this.sleep {
+                Some(sleep) => ready!(sleep.poll_unpin(cx)),
+                None => return Poll::Pending,
+            }
+            for (key, _) in this.pending.drain(..) {
+                this.registry.sinks.remove(&key);
+                this.evicted.push((key, Eviction::TimedOut));
+            }
+        }
+        Poll::Ready(mem::replace(&mut this.evicted, Vec::new()))
+    }
+}
+
+// Sends `item` to `sink` unless it was `sent` already, then flushes it.
+fn poll_deliver<Si, Item>(
+    mut sink: Pin<&mut Si>,
+    item: &Item,
+    sent: &mut bool,
+    cx: &mut Context<'_>,
+) -> Poll<Result<(), Si::Error>>
+    where Si: Sink<Item> + Unpin,
+          Item: Clone,
+{
+    if !*sent {
+        ready!(sink.as_mut().poll_ready(cx))?;
+        sink.as_mut().start_send(item.clone())?;
+        *sent = true;
+    }
+    sink.poll_flush(cx)
+}
@@ -16,6 +16,8 @@ where
     sink: &'a mut Si,
     stream: Fuse<&'a mut St>,
     buffered: Option<St::Item>,
+    flush_interval: usize,
+    unflushed: usize,
 }
 
 // Pinning is never projected to any fields
@@ -38,9 +40,28 @@ where
             sink,
             stream: stream.fuse(),
             buffered: None,
+            flush_interval: 0,
+            unflushed: 0,
         }
     }
 
+    /// Flushes the sink every `items` items sent to it, on top of the final
+    /// flush once the stream is exhausted.
+    ///
+    /// By default, the sink is only flushed once the stream is exhausted, even
+    /// while waiting for the stream to yield another item. Items sent early in
+    /// a long transfer may then stay in the sink's buffers until the end.
+    /// Flushing regularly bounds the latency of these items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `items` is zero.
+    pub fn flush_every(mut self, items: usize) -> Self {
+        assert!(items > 0, "flush interval must be positive");
+        self.flush_interval = items;
+        self
+    }
+
     fn try_start_send(
         &mut self,
         cx: &mut Context<'_>,
@@ -49,7 +70,9 @@ where
         debug_assert!(self.buffered.is_none());
         match Pin::new(&mut self.sink).poll_ready(cx)? {
             Poll::Ready(()) => {
-                Poll::Ready(Pin::new(&mut self.sink).start_send(item))
+                Pin::new(&mut self.sink).start_send(item)?;
+                self.unflushed += 1;
+                Poll::Ready(Ok(()))
             }
             Poll::Pending => {
                 self.buffered = Some(item);
@@ -78,6 +101,11 @@ where
         }
 
         loop {
+            if this.flush_interval > 0 && this.unflushed >= this.flush_interval {
+                ready!(Pin::new(&mut this.sink).poll_flush(cx))?;
+                this.unflushed = 0;
+            }
+
             match this.stream.poll_next_unpin(cx) {
                 Poll::Ready(Some(item)) => {
                     ready!(this.try_start_send(cx, item))?
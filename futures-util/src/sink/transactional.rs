@@ -0,0 +1,140 @@
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use alloc::collections::VecDeque;
+
+/// Sink for the [`transactional`](super::SinkExt::transactional) method.
+#[derive(Debug)]
+#[must_use = "sinks do nothing unless polled"]
+pub struct Transactional<Si, Item> {
+    sink: Si,
+    staged: VecDeque<Item>,
+}
+
+impl<Si: Unpin, Item> Unpin for Transactional<Si, Item> {}
+
+impl<Si: Sink<Item>, Item> Transactional<Si, Item> {
+    unsafe_pinned!(sink: Si);
+    unsafe_unpinned!(staged: VecDeque<Item>);
+
+    pub(super) fn new(sink: Si) -> Self {
+        Transactional {
+            sink,
+            staged: VecDeque::new(),
+        }
+    }
+
+    /// Returns the number of items sent since the last commit or rollback.
+    pub fn staged_len(&self) -> usize {
+        self.staged.len()
+    }
+
+    /// Returns a future which sends the staged items to the underlying sink,
+    /// then flushes it.
+    ///
+    /// If the underlying sink fails, the future resolves to its error and the
+    /// items which were not sent yet stay staged, so that the commit can be
+    /// retried or rolled back.
+    pub fn commit(&mut self) -> Commit<'_, Si, Item>
+        where Si: Unpin,
+    {
+        Commit { transactional: self }
+    }
+
+    /// Discards the items sent since the last commit or rollback.
+    pub fn rollback(&mut self) {
+        self.staged.clear();
+    }
+
+    /// Get a shared reference to the inner sink.
+    pub fn get_ref(&self) -> &Si {
+        &self.sink
+    }
+
+    /// Get a mutable reference to the inner sink.
+    pub fn get_mut(&mut self) -> &mut Si {
+        &mut self.sink
+    }
+
+    /// Get a pinned mutable reference to the inner sink.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut Si> {
+        self.sink()
+    }
+
+    /// Consumes this combinator, returning the underlying sink.
+    ///
+    /// Note that the staged items are dropped.
+    pub fn into_inner(self) -> Si {
+        self.sink
+    }
+
+    fn poll_commit(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Si::Error>> {
+        while !self.staged.is_empty() {
+            ready!(self.as_mut().sink().poll_ready(cx))?;
+            let item = self.as_mut().staged().pop_front().unwrap();
+            self.as_mut().sink().start_send(item)?;
+        }
+        self.as_mut().sink().poll_flush(cx)
+    }
+}
+
+impl<Si: Sink<Item>, Item> Sink<Item> for Transactional<Si, Item> {
+    type Error = Si::Error;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(
+        mut self: Pin<&mut Self>,
+        item: Item,
+    ) -> Result<(), Self::Error> {
+        self.as_mut().staged().push_back(item);
+        Ok(())
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.sink().poll_flush(cx)
+    }
+
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        // Closing abandons the transaction in progress.
+        self.as_mut().staged().clear();
+        self.sink().poll_close(cx)
+    }
+}
+
+/// Future for the [`commit`](Transactional::commit) method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Commit<'a, Si, Item> {
+    transactional: &'a mut Transactional<Si, Item>,
+}
+
+// Pin is never projected to a field.
+impl<Si, Item> Unpin for Commit<'_, Si, Item> {}
+
+impl<Si: Sink<Item> + Unpin, Item> Future for Commit<'_, Si, Item> {
+    type Output = Result<(), Si::Error>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        Pin::new(&mut *self.transactional).poll_commit(cx)
+    }
+}
@@ -0,0 +1,122 @@
+use core::fmt;
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// Creates a `Sink` from a seed and a closure returning a `Future`.
+///
+/// This function is the dual of [`stream::unfold`](crate::stream::unfold):
+/// it makes it possible to build a simple sink without implementing the
+/// [`Sink`] trait by hand.
+///
+/// Each item sent into the sink is passed to `f` along with the current
+/// state, and the future returned by `f` is driven to completion before the
+/// sink is ready to accept the next item. When that future resolves to
+/// `Ok(state)`, the new state is used for the next item. When it resolves
+/// to an error, the error is returned by the sink, which must not be used
+/// afterwards.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::future;
+/// use futures::sink::{self, SinkExt};
+///
+/// let mut total = 0;
+/// {
+///     let sink = sink::unfold(&mut total, |total, x: i32| {
+///         *total += x;
+///         future::ready(Ok::<_, ()>(total))
+///     });
+///     futures::pin_mut!(sink);
+///     block_on(sink.send(5)).unwrap();
+///     block_on(sink.send(7)).unwrap();
+/// }
+/// assert_eq!(total, 12);
+/// ```
+pub fn unfold<T, F, Fut, Item, E>(init: T, f: F) -> Unfold<T, F, Fut>
+    where F: FnMut(T, Item) -> Fut,
+          Fut: Future<Output = Result<T, E>>,
+{
+    Unfold {
+        f,
+        state: Some(init),
+        fut: None,
+    }
+}
+
+/// Sink for the [`unfold`] function.
+#[must_use = "sinks do nothing unless polled"]
+pub struct Unfold<T, F, Fut> {
+    f: F,
+    state: Option<T>,
+    fut: Option<Fut>,
+}
+
+impl<T, F, Fut: Unpin> Unpin for Unfold<T, F, Fut> {}
+
+impl<T, F, Fut> fmt::Debug for Unfold<T, F, Fut>
+where
+    T: fmt::Debug,
+    Fut: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Unfold")
+            .field("state", &self.state)
+            .field("fut", &self.fut)
+            .finish()
+    }
+}
+
+impl<T, F, Fut> Unfold<T, F, Fut> {
+    unsafe_unpinned!(f: F);
+    unsafe_unpinned!(state: Option<T>);
+    unsafe_pinned!(fut: Option<Fut>);
+}
+
+impl<T, F, Fut, Item, E> Sink<Item> for Unfold<T, F, Fut>
+    where F: FnMut(T, Item) -> Fut,
+          Fut: Future<Output = Result<T, E>>,
+{
+    type Error = E;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+
+    fn start_send(
+        mut self: Pin<&mut Self>,
+        item: Item,
+    ) -> Result<(), Self::Error> {
+        let state = self.as_mut().state().take()
+            .expect("Unfold sink used after an error, or without waiting for poll_ready");
+        let fut = (self.as_mut().f())(state, item);
+        self.as_mut().fut().set(Some(fut));
+        Ok(())
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        if let Some(fut) = self.as_mut().fut().as_pin_mut() {
+            let result = ready!(fut.poll(cx));
+            self.as_mut().fut().set(None);
+            *self.as_mut().state() = Some(result?);
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
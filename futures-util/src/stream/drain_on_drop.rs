@@ -0,0 +1,129 @@
+use crate::stream::StreamExt;
+use crate::task::SpawnExt;
+use core::fmt;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll, Spawn};
+
+/// Stream for the [`drain_on_drop`](super::StreamExt::drain_on_drop) method.
+#[must_use = "streams do nothing unless polled"]
+pub struct DrainOnDrop<St, Sp, Fut, F>
+    where St: Stream + Unpin + Send + 'static,
+          Sp: Spawn,
+          F: FnMut(St::Item) -> Fut + Send + 'static,
+          Fut: Future<Output = ()> + Send + 'static,
+{
+    stream: Option<St>,
+    spawner: Sp,
+    limit: u64,
+    f: Option<F>,
+    _marker: PhantomData<fn() -> Fut>,
+}
+
+impl<St, Sp, Fut, F> Unpin for DrainOnDrop<St, Sp, Fut, F>
+    where St: Stream + Unpin + Send + 'static,
+          Sp: Spawn,
+          F: FnMut(St::Item) -> Fut + Send + 'static,
+          Fut: Future<Output = ()> + Send + 'static,
+{}
+
+impl<St, Sp, Fut, F> fmt::Debug for DrainOnDrop<St, Sp, Fut, F>
+    where St: Stream + Unpin + Send + fmt::Debug + 'static,
+          Sp: Spawn + fmt::Debug,
+          F: FnMut(St::Item) -> Fut + Send + 'static,
+          Fut: Future<Output = ()> + Send + 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DrainOnDrop")
+            .field("stream", &self.stream)
+            .field("spawner", &self.spawner)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+impl<St, Sp, Fut, F> DrainOnDrop<St, Sp, Fut, F>
+    where St: Stream + Unpin + Send + 'static,
+          Sp: Spawn,
+          F: FnMut(St::Item) -> Fut + Send + 'static,
+          Fut: Future<Output = ()> + Send + 'static,
+{
+    pub(super) fn new(stream: St, spawner: Sp, limit: u64, f: F) -> DrainOnDrop<St, Sp, Fut, F> {
+        DrainOnDrop {
+            stream: Some(stream),
+            spawner,
+            limit,
+            f: Some(f),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Acquires a reference to the underlying stream that this combinator is
+    /// pulling from, or `None` if it has already terminated.
+    pub fn get_ref(&self) -> Option<&St> {
+        self.stream.as_ref()
+    }
+
+    /// Acquires a mutable reference to the underlying stream that this
+    /// combinator is pulling from, or `None` if it has already terminated.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> Option<&mut St> {
+        self.stream.as_mut()
+    }
+}
+
+impl<St, Sp, Fut, F> FusedStream for DrainOnDrop<St, Sp, Fut, F>
+    where St: Stream + Unpin + Send + 'static,
+          Sp: Spawn,
+          F: FnMut(St::Item) -> Fut + Send + 'static,
+          Fut: Future<Output = ()> + Send + 'static,
+{
+    fn is_terminated(&self) -> bool {
+        self.stream.is_none()
+    }
+}
+
+impl<St, Sp, Fut, F> Stream for DrainOnDrop<St, Sp, Fut, F>
+    where St: Stream + Unpin + Send + 'static,
+          Sp: Spawn,
+          F: FnMut(St::Item) -> Fut + Send + 'static,
+          Fut: Future<Output = ()> + Send + 'static,
+{
+    type Item = St::Item;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<St::Item>> {
+        let item = match self.stream.as_mut() {
+            Some(stream) => ready!(stream.poll_next_unpin(cx)),
+            None => None,
+        };
+        if item.is_none() {
+            // Nothing is left to drain once the stream has terminated.
+            self.stream = None;
+        }
+        Poll::Ready(item)
+    }
+}
+
+impl<St, Sp, Fut, F> Drop for DrainOnDrop<St, Sp, Fut, F>
+    where St: Stream + Unpin + Send + 'static,
+          Sp: Spawn,
+          F: FnMut(St::Item) -> Fut + Send + 'static,
+          Fut: Future<Output = ()> + Send + 'static,
+{
+    fn drop(&mut self) {
+        if let (Some(stream), Some(f)) = (self.stream.take(), self.f.take()) {
+            if self.limit > 0 {
+                // If the executor has shut down there is nowhere left to run
+                // the drain, and the remaining items are dropped as usual.
+                let _ = self.spawner.spawn(stream.take(self.limit).for_each(f));
+            }
+        }
+    }
+}
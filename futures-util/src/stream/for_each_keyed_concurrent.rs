@@ -0,0 +1,198 @@
+use crate::stream::{FuturesUnordered, StreamExt};
+use core::fmt;
+use core::hash::Hash;
+use core::pin::Pin;
+use core::num::NonZeroUsize;
+use futures_core::future::{FusedFuture, Future};
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::collections::{HashMap, VecDeque};
+
+/// Future for the
+/// [`for_each_keyed_concurrent`](super::StreamExt::for_each_keyed_concurrent)
+/// method.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ForEachKeyedConcurrent<St: Stream, K, Fut, KF, F> {
+    stream: Option<St>,
+    key_fn: KF,
+    f: F,
+    futures: FuturesUnordered<Keyed<K, Fut>>,
+    // The keys with a future running, along with the items waiting for it.
+    active: HashMap<K, VecDeque<St::Item>>,
+    waiting: usize,
+    limit: Option<NonZeroUsize>,
+}
+
+impl<St, K, Fut, KF, F> Unpin for ForEachKeyedConcurrent<St, K, Fut, KF, F>
+where St: Stream + Unpin,
+      Fut: Unpin,
+{}
+
+impl<St, K, Fut, KF, F> fmt::Debug for ForEachKeyedConcurrent<St, K, Fut, KF, F>
+where
+    St: Stream + fmt::Debug,
+    K: fmt::Debug,
+    Fut: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForEachKeyedConcurrent")
+            .field("stream", &self.stream)
+            .field("futures", &self.futures)
+            .field("waiting", &self.waiting)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+impl<St, K, Fut, KF, F> ForEachKeyedConcurrent<St, K, Fut, KF, F>
+where St: Stream,
+      K: Hash + Eq + Clone,
+      KF: FnMut(&St::Item) -> K,
+      F: FnMut(St::Item) -> Fut,
+      Fut: Future<Output = ()>,
+{
+    unsafe_pinned!(stream: Option<St>);
+    unsafe_unpinned!(key_fn: KF);
+    unsafe_unpinned!(f: F);
+    unsafe_unpinned!(futures: FuturesUnordered<Keyed<K, Fut>>);
+    unsafe_unpinned!(active: HashMap<K, VecDeque<St::Item>>);
+    unsafe_unpinned!(waiting: usize);
+
+    pub(super) fn new(
+        stream: St,
+        limit: Option<usize>,
+        key_fn: KF,
+        f: F,
+    ) -> ForEachKeyedConcurrent<St, K, Fut, KF, F> {
+        ForEachKeyedConcurrent {
+            stream: Some(stream),
+            // Note: `limit` = 0 gets ignored.
+            limit: limit.and_then(NonZeroUsize::new),
+            key_fn,
+            f,
+            futures: FuturesUnordered::new(),
+            active: HashMap::new(),
+            waiting: 0,
+        }
+    }
+
+    // Starts processing `item`, whose key has no future running.
+    fn start(mut self: Pin<&mut Self>, key: K, item: St::Item) {
+        let future = (self.as_mut().f())(item);
+        self.as_mut().futures().push(Keyed { key: Some(key), future });
+    }
+}
+
+impl<St, K, Fut, KF, F> FusedFuture for ForEachKeyedConcurrent<St, K, Fut, KF, F>
+    where St: Stream,
+          K: Hash + Eq + Clone,
+          KF: FnMut(&St::Item) -> K,
+          F: FnMut(St::Item) -> Fut,
+          Fut: Future<Output = ()>,
+{
+    fn is_terminated(&self) -> bool {
+        self.stream.is_none() && self.futures.is_empty()
+    }
+}
+
+impl<St, K, Fut, KF, F> Future for ForEachKeyedConcurrent<St, K, Fut, KF, F>
+    where St: Stream,
+          K: Hash + Eq + Clone,
+          KF: FnMut(&St::Item) -> K,
+          F: FnMut(St::Item) -> Fut,
+          Fut: Future<Output = ()>,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        loop {
+            let mut made_progress_this_iter = false;
+
+            // Try and pull an item from the stream, as long as fewer than
+            // `limit` items are running or waiting for their key.
+            let current_len = self.futures.len() + self.waiting;
+            if self.limit.map(|limit| limit.get() > current_len).unwrap_or(true) {
+                let mut stream_completed = false;
+                let elem = if let Some(stream) = self.as_mut().stream().as_pin_mut() {
+                    match stream.poll_next(cx) {
+                        Poll::Ready(Some(elem)) => {
+                            made_progress_this_iter = true;
+                            Some(elem)
+                        },
+                        Poll::Ready(None) => {
+                            stream_completed = true;
+                            None
+                        }
+                        Poll::Pending => None,
+                    }
+                } else {
+                    None
+                };
+                if stream_completed {
+                    self.as_mut().stream().set(None);
+                }
+                if let Some(elem) = elem {
+                    let key = (self.as_mut().key_fn())(&elem);
+                    match self.as_mut().active().get_mut(&key) {
+                        Some(queue) => {
+                            queue.push_back(elem);
+                            *self.as_mut().waiting() += 1;
+                        }
+                        None => {
+                            self.as_mut().active().insert(key.clone(), VecDeque::new());
+                            self.as_mut().start(key, elem);
+                        }
+                    }
+                }
+            }
+
+            match self.as_mut().futures().poll_next_unpin(cx) {
+                Poll::Ready(Some(key)) => {
+                    made_progress_this_iter = true;
+                    // Hand the key over to the next item waiting for it.
+                    match self.as_mut().active().get_mut(&key).and_then(VecDeque::pop_front) {
+                        Some(elem) => {
+                            *self.as_mut().waiting() -= 1;
+                            self.as_mut().start(key, elem);
+                        }
+                        None => {
+                            self.as_mut().active().remove(&key);
+                        }
+                    }
+                }
+                Poll::Ready(None) => {
+                    if self.stream.is_none() {
+                        return Poll::Ready(())
+                    }
+                },
+                Poll::Pending => {}
+            }
+
+            if !made_progress_this_iter {
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+// A future run by `ForEachKeyedConcurrent`, which resolves to its key.
+#[derive(Debug)]
+struct Keyed<K, Fut> {
+    key: Option<K>,
+    future: Fut,
+}
+
+impl<K, Fut> Keyed<K, Fut> {
+    unsafe_pinned!(future: Fut);
+    unsafe_unpinned!(key: Option<K>);
+}
+
+impl<K, Fut: Future<Output = ()>> Future for Keyed<K, Fut> {
+    type Output = K;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<K> {
+        ready!(self.as_mut().future().poll(cx));
+        Poll::Ready(self.as_mut().key().take().expect("Keyed polled after completion"))
+    }
+}
@@ -15,6 +15,8 @@ pub struct Forward<St: TryStream, Si: Sink<St::Ok>> {
     sink: Option<Si>,
     stream: Fuse<St>,
     buffered_item: Option<St::Ok>,
+    flush_interval: usize,
+    unflushed: usize,
 }
 
 impl<St: TryStream + Unpin, Si: Sink<St::Ok> + Unpin> Unpin for Forward<St, Si> {}
@@ -27,15 +29,36 @@ where
     unsafe_pinned!(sink: Option<Si>);
     unsafe_pinned!(stream: Fuse<St>);
     unsafe_unpinned!(buffered_item: Option<St::Ok>);
+    unsafe_unpinned!(unflushed: usize);
 
     pub(super) fn new(stream: St, sink: Si) -> Self {
         Forward {
             sink: Some(sink),
             stream: stream.fuse(),
-                buffered_item: None,
+            buffered_item: None,
+            flush_interval: 0,
+            unflushed: 0,
         }
     }
 
+    /// Flushes the sink every `items` items, on top of whenever the stream
+    /// has no item ready.
+    ///
+    /// By default, the sink is only flushed when the stream is not ready to
+    /// yield another item. When forwarding a stream which always has items
+    /// ready, as can happen during long transfers, items may then stay in the
+    /// sink's buffers until the end. Flushing regularly bounds the latency of
+    /// these items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `items` is zero.
+    pub fn flush_every(mut self, items: usize) -> Self {
+        assert!(items > 0, "flush interval must be positive");
+        self.flush_interval = items;
+        self
+    }
+
     fn try_start_send(
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
@@ -45,7 +68,9 @@ where
         {
             let mut sink = self.as_mut().sink().as_pin_mut().unwrap();
             if sink.as_mut().poll_ready(cx)?.is_ready() {
-                return Poll::Ready(sink.start_send(item));
+                sink.start_send(item)?;
+                *self.as_mut().unflushed() += 1;
+                return Poll::Ready(Ok(()));
             }
         }
         *self.as_mut().buffered_item() = Some(item);
@@ -81,6 +106,11 @@ where
         }
 
         loop {
+            if self.flush_interval > 0 && self.unflushed >= self.flush_interval {
+                ready!(self.as_mut().sink().as_pin_mut().expect(INVALID_POLL).poll_flush(cx))?;
+                *self.as_mut().unflushed() = 0;
+            }
+
             match self.as_mut().stream().poll_next(cx)? {
                 Poll::Ready(Some(item)) =>
                    ready!(self.as_mut().try_start_send(cx, item))?,
@@ -91,6 +121,7 @@ where
                 }
                 Poll::Pending => {
                     ready!(self.as_mut().sink().as_pin_mut().expect(INVALID_POLL).poll_flush(cx))?;
+                    *self.as_mut().unflushed() = 0;
                     return Poll::Pending
                 }
             }
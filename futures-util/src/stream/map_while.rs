@@ -0,0 +1,120 @@
+use core::fmt;
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+#[cfg(feature = "sink")]
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// Stream for the [`map_while`](super::StreamExt::map_while) method.
+#[must_use = "streams do nothing unless polled"]
+pub struct MapWhile<St, F> {
+    stream: St,
+    f: F,
+    done_mapping: bool,
+}
+
+impl<St: Unpin, F> Unpin for MapWhile<St, F> {}
+
+impl<St, F> fmt::Debug for MapWhile<St, F>
+where
+    St: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapWhile")
+            .field("stream", &self.stream)
+            .field("done_mapping", &self.done_mapping)
+            .finish()
+    }
+}
+
+impl<St, T, F> MapWhile<St, F>
+    where St: Stream,
+          F: FnMut(St::Item) -> Option<T>,
+{
+    unsafe_pinned!(stream: St);
+    unsafe_unpinned!(f: F);
+    unsafe_unpinned!(done_mapping: bool);
+
+    pub(super) fn new(stream: St, f: F) -> MapWhile<St, F> {
+        MapWhile { stream, f, done_mapping: false }
+    }
+
+    /// Acquires a reference to the underlying stream that this combinator is
+    /// pulling from.
+    pub fn get_ref(&self) -> &St {
+        &self.stream
+    }
+
+    /// Acquires a mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> &mut St {
+        &mut self.stream
+    }
+
+    /// Acquires a pinned mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut St> {
+        self.stream()
+    }
+
+    /// Consumes this combinator, returning the underlying stream.
+    ///
+    /// Note that this may discard intermediate state of this combinator, so
+    /// care should be taken to avoid losing resources when this is called.
+    pub fn into_inner(self) -> St {
+        self.stream
+    }
+}
+
+impl<St, F, T> FusedStream for MapWhile<St, F>
+    where St: FusedStream,
+          F: FnMut(St::Item) -> Option<T>,
+{
+    fn is_terminated(&self) -> bool {
+        self.done_mapping || self.stream.is_terminated()
+    }
+}
+
+impl<St, F, T> Stream for MapWhile<St, F>
+    where St: Stream,
+          F: FnMut(St::Item) -> Option<T>,
+{
+    type Item = T;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<T>> {
+        if self.done_mapping {
+            return Poll::Ready(None);
+        }
+
+        let item = match ready!(self.as_mut().stream().poll_next(cx)) {
+            Some(e) => e,
+            None => return Poll::Ready(None),
+        };
+        let mapped = (self.as_mut().f())(item);
+        if mapped.is_none() {
+            *self.as_mut().done_mapping() = true;
+        }
+        Poll::Ready(mapped)
+    }
+}
+
+// Forwarding impl of Sink from the underlying stream
+#[cfg(feature = "sink")]
+impl<S, F, T, Item> Sink<Item> for MapWhile<S, F>
+    where S: Stream + Sink<Item>,
+          F: FnMut(S::Item) -> Option<T>,
+{
+    type Error = S::Error;
+
+    delegate_sink!(stream, Item);
+}
@@ -16,6 +16,20 @@ use futures_sink::Sink;
 use alloc::boxed::Box;
 #[cfg(feature = "alloc")]
 use futures_core::stream::{BoxStream, LocalBoxStream};
+#[cfg(feature = "alloc")]
+use futures_core::task::Spawn;
+#[cfg_attr(
+    feature = "cfg-target-has-atomic",
+    cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+)]
+#[cfg(feature = "timer")]
+use crate::timer::TimeoutTotal;
+#[cfg_attr(
+    feature = "cfg-target-has-atomic",
+    cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+)]
+#[cfg(feature = "timer")]
+use std::time::Duration;
 
 mod iter;
 pub use self::iter::{iter, Iter};
@@ -71,6 +85,9 @@ pub use self::inspect::Inspect;
 mod map;
 pub use self::map::Map;
 
+mod map_while;
+pub use self::map_while::MapWhile;
+
 mod next;
 pub use self::next::Next;
 
@@ -98,9 +115,18 @@ pub use self::skip::Skip;
 mod skip_while;
 pub use self::skip_while::SkipWhile;
 
+mod start_with;
+pub use self::start_with::StartWith;
+
+mod switch_map;
+pub use self::switch_map::SwitchMap;
+
 mod take;
 pub use self::take::Take;
 
+mod take_budget;
+pub use self::take_budget::TakeBudget;
+
 mod take_while;
 pub use self::take_while::TakeWhile;
 
@@ -118,6 +144,16 @@ mod chunks;
 #[cfg(feature = "alloc")]
 pub use self::chunks::Chunks;
 
+#[cfg(feature = "alloc")]
+mod drain_on_drop;
+#[cfg(feature = "alloc")]
+pub use self::drain_on_drop::DrainOnDrop;
+
+#[cfg(feature = "alloc")]
+mod stream_map;
+#[cfg(feature = "alloc")]
+pub use self::stream_map::StreamMap;
+
 cfg_target_has_atomic! {
     #[cfg(feature = "alloc")]
     mod buffer_unordered;
@@ -158,11 +194,23 @@ cfg_target_has_atomic! {
     pub use self::select_all::{select_all, SelectAll};
 }
 
+cfg_target_has_atomic! {
+    #[cfg(feature = "std")]
+    mod for_each_keyed_concurrent;
+    #[cfg(feature = "std")]
+    pub use self::for_each_keyed_concurrent::ForEachKeyedConcurrent;
+}
+
 #[cfg(feature = "std")]
 mod catch_unwind;
 #[cfg(feature = "std")]
 pub use self::catch_unwind::CatchUnwind;
 
+#[cfg(feature = "std")]
+mod stamp;
+#[cfg(feature = "std")]
+pub use self::stamp::{MeasureSinceStamp, Stamp, Stamped};
+
 impl<T: ?Sized> StreamExt for T where T: Stream {}
 
 /// An extension trait for `Stream`s that provides a variety of convenient
@@ -554,6 +602,42 @@ pub trait StreamExt: Stream {
         Flatten::new(self)
     }
 
+    /// Maps each item of this stream to a stream, yielding the items of the
+    /// stream of the latest item only.
+    ///
+    /// When a new item arrives, the stream created for the previous one is
+    /// dropped, cancelling whatever work it had in flight, and the stream of
+    /// the new item takes its place. This suits search-as-you-type, where
+    /// the results for a stale query are no longer wanted. Futures can be
+    /// mapped to with [`once`].
+    ///
+    /// The returned stream completes once this stream and the stream of its
+    /// last item have both completed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::future;
+    /// use futures::stream::{self, StreamExt};
+    ///
+    /// // The queries were all typed before the first search ran, so only
+    /// // the last one is searched.
+    /// let queries = stream::iter(vec!["r", "ru", "rus"]);
+    /// let results = queries.switch_map(|query| {
+    ///     stream::once(future::ready(format!("results for {}", query)))
+    /// });
+    ///
+    /// assert_eq!(block_on(results.collect::<Vec<_>>()), vec!["results for rus"]);
+    /// ```
+    fn switch_map<U, F>(self, f: F) -> SwitchMap<Self, U, F>
+        where F: FnMut(Self::Item) -> U,
+              U: Stream,
+              Self: Sized
+    {
+        SwitchMap::new(self, f)
+    }
+
     /// Skip elements on this stream while the provided asynchronous predicate
     /// resolves to `true`.
     ///
@@ -615,6 +699,36 @@ pub trait StreamExt: Stream {
         TakeWhile::new(self, f)
     }
 
+    /// Maps elements of this stream with the provided closure until it
+    /// returns [`None`].
+    ///
+    /// This function, like `Iterator::map_while`, yields the values returned
+    /// by `f` while it returns [`Some`], and ends the stream as soon as it
+    /// returns [`None`]. This suits protocols whose end is signaled by a
+    /// sentinel item rather than by the end of the underlying stream. The
+    /// closure is executed inline with calls to
+    /// [`poll_next`](Stream::poll_next).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::stream::{self, StreamExt};
+    ///
+    /// let frames = stream::iter(vec!["a", "b", "", "c"]);
+    /// let stream = frames.map_while(|frame| {
+    ///     if frame.is_empty() { None } else { Some(frame.to_uppercase()) }
+    /// });
+    ///
+    /// assert_eq!(vec!["A", "B"], block_on(stream.collect::<Vec<_>>()));
+    /// ```
+    fn map_while<T, F>(self, f: F) -> MapWhile<Self, F>
+        where F: FnMut(Self::Item) -> Option<T>,
+              Self: Sized
+    {
+        MapWhile::new(self, f)
+    }
+
     /// Runs this stream to completion, executing the provided asynchronous
     /// closure for each element on the stream.
     ///
@@ -720,6 +834,67 @@ pub trait StreamExt: Stream {
         ForEachConcurrent::new(self, limit.into(), f)
     }
 
+    /// Runs this stream to completion, executing the provided asynchronous
+    /// closure for each element on the stream concurrently, except for
+    /// elements sharing the same key, which are processed one at a time in
+    /// stream order.
+    ///
+    /// This is like
+    /// [`for_each_concurrent`](StreamExt::for_each_concurrent), with
+    /// `key_fn` computing a key for each element, e.g. the account or session
+    /// an event belongs to. The future for an element is only created once
+    /// the future for the previous element with the same key completed.
+    ///
+    /// The first argument is an optional limit on the number of elements
+    /// taken from the stream and not processed yet, whether their future is
+    /// running or waiting for their key. A limit of zero is interpreted as no
+    /// limit at all, and will have the same result as passing in `None`.
+    ///
+    /// This method is only available when the `std` feature of this library
+    /// is activated, and it is activated by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::future;
+    /// use futures::stream::{self, StreamExt};
+    /// use std::cell::RefCell;
+    ///
+    /// let log = RefCell::new(Vec::new());
+    /// let events = stream::iter(vec![("alice", 1), ("bob", 1), ("alice", 2)]);
+    /// block_on(events.for_each_keyed_concurrent(
+    ///     /* limit */ 10,
+    ///     |&(account, _)| account,
+    ///     |event| {
+    ///         log.borrow_mut().push(event);
+    ///         future::ready(())
+    ///     },
+    /// ));
+    ///
+    /// let alice: Vec<_> = log.borrow().iter().filter(|e| e.0 == "alice").cloned().collect();
+    /// assert_eq!(alice, vec![("alice", 1), ("alice", 2)]);
+    /// ```
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "std")]
+    fn for_each_keyed_concurrent<K, Fut, KF, F>(
+        self,
+        limit: impl Into<Option<usize>>,
+        key_fn: KF,
+        f: F,
+    ) -> ForEachKeyedConcurrent<Self, K, Fut, KF, F>
+        where K: std::hash::Hash + Eq + Clone,
+              KF: FnMut(&Self::Item) -> K,
+              F: FnMut(Self::Item) -> Fut,
+              Fut: Future<Output = ()>,
+              Self: Sized,
+    {
+        ForEachKeyedConcurrent::new(self, limit.into(), key_fn, f)
+    }
+
     /// Creates a new stream of at most `n` items of the underlying stream.
     ///
     /// Once `n` items have been yielded from this stream then it will always
@@ -743,6 +918,36 @@ pub trait StreamExt: Stream {
         Take::new(self, n)
     }
 
+    /// Creates a new stream which yields items of the underlying stream as
+    /// long as their cumulative size fits in `budget`.
+    ///
+    /// The size of each item is computed by the `size` closure, for example
+    /// the length of a chunk of bytes. The first item which does not fit in
+    /// the remaining budget is dropped and ends the stream, which can be told
+    /// apart from the end of the underlying stream with
+    /// [`TakeBudget::exceeded`]. This is useful to enforce size limits on
+    /// chunked bodies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::stream::{self, StreamExt};
+    ///
+    /// let chunks = stream::iter(vec!["abc", "de", "fghij", "k"]);
+    /// let mut limited = chunks.take_budget(6, |chunk| chunk.len() as u64);
+    ///
+    /// assert_eq!(block_on((&mut limited).collect::<Vec<_>>()), vec!["abc", "de"]);
+    /// assert!(limited.exceeded());
+    /// assert_eq!(limited.remaining(), 1);
+    /// ```
+    fn take_budget<F>(self, budget: u64, size: F) -> TakeBudget<Self, F>
+        where F: FnMut(&Self::Item) -> u64,
+              Self: Sized
+    {
+        TakeBudget::new(self, budget, size)
+    }
+
     /// Creates a new stream which skips `n` items of the underlying stream.
     ///
     /// Once `n` items have been skipped from this stream then it will always
@@ -891,6 +1096,94 @@ pub trait StreamExt: Stream {
         CatchUnwind::new(self)
     }
 
+    /// Stamps each item of this stream with the instant it was produced at.
+    ///
+    /// Together with [`measure_since_stamp`](StreamExt::measure_since_stamp),
+    /// this measures the time items spend going through a multi-stage
+    /// pipeline, queueing delays included. Intermediate stages can carry the
+    /// stamp along with [`Stamped::map`].
+    ///
+    /// This method is only available when the `std` feature of this
+    /// library is activated, and it is activated by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::stream::{self, StreamExt};
+    ///
+    /// let mut latencies = Vec::new();
+    /// let stream = stream::iter(1..=3)
+    ///     .stamp()
+    ///     .map(|stamped| stamped.map(|x| x * 2))
+    ///     .measure_since_stamp(|latency| latencies.push(latency));
+    ///
+    /// assert_eq!(block_on(stream.collect::<Vec<_>>()), vec![2, 4, 6]);
+    /// assert_eq!(latencies.len(), 3);
+    /// ```
+    #[cfg(feature = "std")]
+    fn stamp(self) -> Stamp<Self>
+        where Self: Sized
+    {
+        Stamp::new(self)
+    }
+
+    /// Records the time elapsed since each item of this stream was
+    /// [`stamp`](StreamExt::stamp)ed, yielding the items without their stamp.
+    ///
+    /// `record` is called with the latency of each item as it is yielded,
+    /// and would typically add it to a histogram.
+    ///
+    /// This method is only available when the `std` feature of this
+    /// library is activated, and it is activated by default.
+    #[cfg(feature = "std")]
+    fn measure_since_stamp<T, F>(self, record: F) -> MeasureSinceStamp<Self, F>
+        where Self: Stream<Item = Stamped<T>> + Sized,
+              F: FnMut(std::time::Duration),
+    {
+        MeasureSinceStamp::new(self, record)
+    }
+
+    /// Bounds the time taken by the whole stream to `dur`.
+    ///
+    /// The returned stream yields the items of this stream wrapped in `Ok`.
+    /// If this stream has not ended by the time `dur` has elapsed, it is
+    /// dropped and the returned stream yields a single `Err(TimedOut)`
+    /// before ending, even if items were still ready. Unlike a timeout on
+    /// each item, this bounds the entire transfer, such as a batch download.
+    /// If `dur` is too long for the deadline to be represented, the returned
+    /// stream never times out.
+    ///
+    /// This method is only available when the `timer` feature of this
+    /// library is activated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::stream::{self, StreamExt};
+    /// use futures::timer::TimedOut;
+    /// use std::time::Duration;
+    ///
+    /// let stream = stream::iter(vec![1, 2]).timeout_total(Duration::from_secs(1));
+    /// assert_eq!(block_on(stream.collect::<Vec<_>>()), vec![Ok(1), Ok(2)]);
+    ///
+    /// let stream = stream::iter(vec![1])
+    ///     .chain(stream::pending())
+    ///     .timeout_total(Duration::from_millis(10));
+    /// assert_eq!(block_on(stream.collect::<Vec<_>>()), vec![Ok(1), Err(TimedOut)]);
+    /// ```
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "timer")]
+    fn timeout_total(self, dur: Duration) -> TimeoutTotal<Self>
+        where Self: Sized
+    {
+        TimeoutTotal::new(self, dur)
+    }
+
     /// Wrap the stream in a Box, pinning it.
     ///
     /// This method is only available when the `std` or `alloc` feature of this
@@ -1048,6 +1341,28 @@ pub trait StreamExt: Stream {
         Chain::new(self, other)
     }
 
+    /// Adapter for yielding the given items before the items of this stream.
+    ///
+    /// This is typically used to hand new subscribers of a feed the current
+    /// state as a snapshot, followed by the live updates, through a single
+    /// stream.
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::stream::{self, StreamExt};
+    ///
+    /// let updates = stream::iter(vec![3, 4]);
+    /// let feed = updates.start_with(vec![1, 2]);
+    ///
+    /// assert_eq!(block_on(feed.collect::<Vec<_>>()), vec![1, 2, 3, 4]);
+    /// ```
+    fn start_with<I>(self, items: I) -> StartWith<Self, I::IntoIter>
+        where I: IntoIterator<Item = Self::Item>,
+              Self: Sized
+    {
+        StartWith::new(self, items.into_iter())
+    }
+
     /// Creates a new stream which exposes a `peek` method.
     ///
     /// Calling `peek` returns a reference to the next item in the stream.
@@ -1122,6 +1437,57 @@ pub trait StreamExt: Stream {
         split::split(self)
     }
 
+    /// Make sure the items left in this stream are processed rather than
+    /// silently discarded when the returned stream is dropped.
+    ///
+    /// The returned stream yields the same items as this one. If it is dropped
+    /// before this stream has terminated, a task draining at most `limit` of
+    /// the remaining items through `f` is spawned onto `spawner`, as with
+    /// [`for_each`](StreamExt::for_each). This is useful for streams whose
+    /// items hold on to resources, such as file handles or messages that need
+    /// to be acknowledged.
+    ///
+    /// No task is spawned if this stream has already terminated, or if
+    /// `limit` is zero. If spawning fails, the remaining items are dropped.
+    ///
+    /// This method is only available when the `std` or `alloc` feature of this
+    /// library is activated, and it is activated by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::channel::mpsc;
+    /// use futures::executor::{block_on, ThreadPool};
+    /// use futures::future;
+    /// use futures::stream::{self, StreamExt};
+    ///
+    /// let pool = ThreadPool::new().unwrap();
+    /// let (tx, rx) = mpsc::unbounded();
+    ///
+    /// let mut stream = stream::iter(1..=5).drain_on_drop(pool, 10, move |x| {
+    ///     tx.unbounded_send(x).unwrap();
+    ///     future::ready(())
+    /// });
+    /// assert_eq!(block_on(stream.next()), Some(1));
+    /// drop(stream);
+    ///
+    /// assert_eq!(block_on(rx.collect::<Vec<_>>()), vec![2, 3, 4, 5]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn drain_on_drop<Sp, Fut, F>(
+        self,
+        spawner: Sp,
+        limit: u64,
+        f: F,
+    ) -> DrainOnDrop<Self, Sp, Fut, F>
+        where Sp: Spawn,
+              F: FnMut(Self::Item) -> Fut + Send + 'static,
+              Fut: Future<Output = ()> + Send + 'static,
+              Self: Unpin + Send + Sized + 'static,
+    {
+        DrainOnDrop::new(self, spawner, limit, f)
+    }
+
     /// Do something with each item of this stream, afterwards passing it on.
     ///
     /// This is similar to the `Iterator::inspect` method in the standard
@@ -0,0 +1,241 @@
+use core::fmt;
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+#[cfg(feature = "sink")]
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::time::{Duration, Instant};
+
+/// An item along with the instant it was stamped at, as yielded by the
+/// [`stamp`](super::StreamExt::stamp) method.
+///
+/// The stamp is carried along with the item through the stages of a pipeline
+/// with [`map`](Stamped::map), so that the time the item spent in the whole
+/// pipeline can be measured at its end with
+/// [`measure_since_stamp`](super::StreamExt::measure_since_stamp).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stamped<T> {
+    item: T,
+    stamp: Instant,
+}
+
+impl<T> Stamped<T> {
+    /// Stamps `item` with the current instant.
+    pub fn new(item: T) -> Stamped<T> {
+        Stamped { item, stamp: Instant::now() }
+    }
+
+    /// Returns the instant this item was stamped at.
+    pub fn stamp(&self) -> Instant {
+        self.stamp
+    }
+
+    /// Returns the time elapsed since this item was stamped.
+    pub fn elapsed(&self) -> Duration {
+        self.stamp.elapsed()
+    }
+
+    /// Acquires a reference to the item.
+    pub fn get_ref(&self) -> &T {
+        &self.item
+    }
+
+    /// Acquires a mutable reference to the item.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.item
+    }
+
+    /// Maps the item to a different one, keeping the original stamp.
+    pub fn map<U, F>(self, f: F) -> Stamped<U>
+        where F: FnOnce(T) -> U,
+    {
+        Stamped { item: f(self.item), stamp: self.stamp }
+    }
+
+    /// Consumes the stamp, returning the item.
+    pub fn into_inner(self) -> T {
+        self.item
+    }
+}
+
+/// Stream for the [`stamp`](super::StreamExt::stamp) method.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Stamp<St> {
+    stream: St,
+}
+
+impl<St: Unpin> Unpin for Stamp<St> {}
+
+impl<St: Stream> Stamp<St> {
+    unsafe_pinned!(stream: St);
+
+    pub(super) fn new(stream: St) -> Stamp<St> {
+        Stamp { stream }
+    }
+
+    /// Acquires a reference to the underlying stream that this combinator is
+    /// pulling from.
+    pub fn get_ref(&self) -> &St {
+        &self.stream
+    }
+
+    /// Acquires a mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> &mut St {
+        &mut self.stream
+    }
+
+    /// Acquires a pinned mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut St> {
+        self.stream()
+    }
+
+    /// Consumes this combinator, returning the underlying stream.
+    ///
+    /// Note that this may discard intermediate state of this combinator, so
+    /// care should be taken to avoid losing resources when this is called.
+    pub fn into_inner(self) -> St {
+        self.stream
+    }
+}
+
+impl<St: FusedStream> FusedStream for Stamp<St> {
+    fn is_terminated(&self) -> bool {
+        self.stream.is_terminated()
+    }
+}
+
+impl<St: Stream> Stream for Stamp<St> {
+    type Item = Stamped<St::Item>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.stream()
+            .poll_next(cx)
+            .map(|opt| opt.map(Stamped::new))
+    }
+}
+
+// Forwarding impl of Sink from the underlying stream
+#[cfg(feature = "sink")]
+impl<S, Item> Sink<Item> for Stamp<S>
+    where S: Stream + Sink<Item>,
+{
+    type Error = S::Error;
+
+    delegate_sink!(stream, Item);
+}
+
+/// Stream for the [`measure_since_stamp`](super::StreamExt::measure_since_stamp)
+/// method.
+#[must_use = "streams do nothing unless polled"]
+pub struct MeasureSinceStamp<St, F> {
+    stream: St,
+    record: F,
+}
+
+impl<St: Unpin, F> Unpin for MeasureSinceStamp<St, F> {}
+
+impl<St, F> fmt::Debug for MeasureSinceStamp<St, F>
+where
+    St: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MeasureSinceStamp")
+            .field("stream", &self.stream)
+            .finish()
+    }
+}
+
+impl<St, T, F> MeasureSinceStamp<St, F>
+    where St: Stream<Item = Stamped<T>>,
+          F: FnMut(Duration),
+{
+    unsafe_pinned!(stream: St);
+    unsafe_unpinned!(record: F);
+
+    pub(super) fn new(stream: St, record: F) -> MeasureSinceStamp<St, F> {
+        MeasureSinceStamp { stream, record }
+    }
+
+    /// Acquires a reference to the underlying stream that this combinator is
+    /// pulling from.
+    pub fn get_ref(&self) -> &St {
+        &self.stream
+    }
+
+    /// Acquires a mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> &mut St {
+        &mut self.stream
+    }
+
+    /// Acquires a pinned mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut St> {
+        self.stream()
+    }
+
+    /// Consumes this combinator, returning the underlying stream.
+    ///
+    /// Note that this may discard intermediate state of this combinator, so
+    /// care should be taken to avoid losing resources when this is called.
+    pub fn into_inner(self) -> St {
+        self.stream
+    }
+}
+
+impl<St, T, F> FusedStream for MeasureSinceStamp<St, F>
+    where St: FusedStream<Item = Stamped<T>>,
+          F: FnMut(Duration),
+{
+    fn is_terminated(&self) -> bool {
+        self.stream.is_terminated()
+    }
+}
+
+impl<St, T, F> Stream for MeasureSinceStamp<St, F>
+    where St: Stream<Item = Stamped<T>>,
+          F: FnMut(Duration),
+{
+    type Item = T;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<T>> {
+        let stamped = ready!(self.as_mut().stream().poll_next(cx));
+        Poll::Ready(stamped.map(|stamped| {
+            (self.as_mut().record())(stamped.elapsed());
+            stamped.into_inner()
+        }))
+    }
+}
+
+// Forwarding impl of Sink from the underlying stream
+#[cfg(feature = "sink")]
+impl<S, F, T, Item> Sink<Item> for MeasureSinceStamp<S, F>
+    where S: Stream<Item = Stamped<T>> + Sink<Item>,
+          F: FnMut(Duration),
+{
+    type Error = S::Error;
+
+    delegate_sink!(stream, Item);
+}
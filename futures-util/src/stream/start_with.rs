@@ -0,0 +1,90 @@
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// Stream for the [`start_with`](super::StreamExt::start_with) method.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct StartWith<St, I> {
+    stream: St,
+    items: Option<I>,
+}
+
+impl<St: Unpin, I> Unpin for StartWith<St, I> {}
+
+impl<St, I> StartWith<St, I>
+where St: Stream,
+      I: Iterator<Item = St::Item>,
+{
+    unsafe_pinned!(stream: St);
+    unsafe_unpinned!(items: Option<I>);
+
+    pub(super) fn new(stream: St, items: I) -> StartWith<St, I> {
+        StartWith {
+            stream,
+            items: Some(items),
+        }
+    }
+
+    /// Acquires a reference to the underlying stream that this combinator is
+    /// pulling from.
+    pub fn get_ref(&self) -> &St {
+        &self.stream
+    }
+
+    /// Acquires a mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> &mut St {
+        &mut self.stream
+    }
+
+    /// Acquires a pinned mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut St> {
+        self.stream()
+    }
+
+    /// Consumes this combinator, returning the underlying stream.
+    ///
+    /// Note that this may discard intermediate state of this combinator, so
+    /// care should be taken to avoid losing resources when this is called.
+    pub fn into_inner(self) -> St {
+        self.stream
+    }
+}
+
+impl<St, I> FusedStream for StartWith<St, I>
+where St: FusedStream,
+      I: Iterator<Item = St::Item>,
+{
+    fn is_terminated(&self) -> bool {
+        self.items.is_none() && self.stream.is_terminated()
+    }
+}
+
+impl<St, I> Stream for StartWith<St, I>
+where St: Stream,
+      I: Iterator<Item = St::Item>,
+{
+    type Item = St::Item;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        if let Some(items) = self.as_mut().items() {
+            if let Some(item) = items.next() {
+                return Poll::Ready(Some(item));
+            }
+            *self.as_mut().items() = None;
+        }
+        self.as_mut().stream().poll_next(cx)
+    }
+}
@@ -0,0 +1,195 @@
+//! A map of keyed streams
+
+use core::fmt::{self, Debug};
+use core::iter::FromIterator;
+use core::pin::Pin;
+
+use futures_core::{Poll, Stream, FusedStream};
+use futures_core::task::Context;
+
+use alloc::vec::Vec;
+
+/// A map of streams, which yields the items of whichever stream is ready
+/// along with its key.
+///
+/// Unlike [`SelectAll`](super::SelectAll), streams keep their identity: each
+/// item comes with the key of the stream which produced it, streams can be
+/// looked up and removed by key while the map is running, and the end of
+/// each stream is reported.
+///
+/// The map yields `(key, Some(item))` for each item, and `(key, None)` once
+/// the stream of `key` ends, at which point the stream is removed from the
+/// map. Once the map is empty, it yields `None`; it can still be polled
+/// again after more streams were inserted.
+///
+/// Keys are looked up by linear search, and all the streams are polled
+/// whenever the map is, starting from a different one each time so that
+/// busy streams do not starve the others. This makes `StreamMap` best suited
+/// to tens of streams rather than thousands.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on_stream;
+/// use futures::stream::{self, StreamMap};
+///
+/// let mut topics = StreamMap::new();
+/// topics.insert("weather", stream::iter(vec!["sunny"]));
+/// topics.insert("news", stream::iter(vec![]));
+///
+/// let mut events: Vec<_> = block_on_stream(topics).collect();
+/// events.sort();
+/// assert_eq!(events, vec![
+///     ("news", None),
+///     ("weather", None),
+///     ("weather", Some("sunny")),
+/// ]);
+/// ```
+#[must_use = "streams do nothing unless polled"]
+pub struct StreamMap<K, St> {
+    entries: Vec<(K, St)>,
+    // The index of the stream to poll first next time.
+    next: usize,
+    is_terminated: bool,
+}
+
+impl<K: Debug, St> Debug for StreamMap<K, St> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamMap")
+            .field("keys", &self.entries.iter().map(|(k, _)| k).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<K, St> StreamMap<K, St> {
+    /// Constructs a new, empty `StreamMap`.
+    ///
+    /// The returned `StreamMap` does not contain any streams and, in this
+    /// state, `StreamMap::poll_next` will return `Poll::Ready(None)`.
+    pub fn new() -> StreamMap<K, St> {
+        StreamMap { entries: Vec::new(), next: 0, is_terminated: false }
+    }
+
+    /// Returns the number of streams contained in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map contains no streams.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the keys of the map.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+}
+
+impl<K: Eq, St> StreamMap<K, St> {
+    /// Inserts a stream into the map under `key`.
+    ///
+    /// If the map already had a stream under `key`, it is replaced and
+    /// returned. This function will not call `poll` on the inserted stream.
+    pub fn insert(&mut self, key: K, stream: St) -> Option<St> {
+        if let Some(existing) = self.get_mut(&key) {
+            return Some(core::mem::replace(existing, stream));
+        }
+        self.entries.push((key, stream));
+        self.is_terminated = false;
+        None
+    }
+
+    /// Removes the stream under `key` from the map, returning it.
+    ///
+    /// No end is reported for a removed stream.
+    pub fn remove(&mut self, key: &K) -> Option<St> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.swap_remove(index).1)
+    }
+
+    /// Returns `true` if the map contains a stream under `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entries.iter().any(|(k, _)| k == key)
+    }
+
+    /// Returns a reference to the stream under `key`.
+    pub fn get(&self, key: &K) -> Option<&St> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, s)| s)
+    }
+
+    /// Returns a mutable reference to the stream under `key`.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut St> {
+        self.entries.iter_mut().find(|(k, _)| k == key).map(|(_, s)| s)
+    }
+}
+
+impl<K, St> Default for StreamMap<K, St> {
+    fn default() -> StreamMap<K, St> {
+        StreamMap::new()
+    }
+}
+
+impl<K, St> Unpin for StreamMap<K, St> {}
+
+impl<K, St> Stream for StreamMap<K, St>
+    where K: Clone,
+          St: Stream + Unpin,
+{
+    type Item = (K, Option<St::Item>);
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let len = this.entries.len();
+        if len == 0 {
+            this.is_terminated = true;
+            return Poll::Ready(None);
+        }
+
+        let start = this.next % len;
+        this.next = start + 1;
+        for i in 0..len {
+            let index = (start + i) % len;
+            let (key, stream) = &mut this.entries[index];
+            match Pin::new(stream).poll_next(cx) {
+                Poll::Ready(Some(item)) => return Poll::Ready(Some((key.clone(), Some(item)))),
+                Poll::Ready(None) => {
+                    let (key, _) = this.entries.swap_remove(index);
+                    return Poll::Ready(Some((key, None)));
+                }
+                Poll::Pending => {}
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl<K, St> FusedStream for StreamMap<K, St>
+    where K: Clone,
+          St: Stream + Unpin,
+{
+    fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
+}
+
+impl<K: Eq, St> FromIterator<(K, St)> for StreamMap<K, St> {
+    fn from_iter<T: IntoIterator<Item = (K, St)>>(iter: T) -> Self {
+        let mut map = StreamMap::new();
+        for (key, stream) in iter {
+            map.insert(key, stream);
+        }
+        map
+    }
+}
+
+impl<K: Eq, St> Extend<(K, St)> for StreamMap<K, St> {
+    fn extend<T: IntoIterator<Item = (K, St)>>(&mut self, iter: T) {
+        for (key, stream) in iter {
+            self.insert(key, stream);
+        }
+    }
+}
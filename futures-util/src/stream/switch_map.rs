@@ -0,0 +1,147 @@
+use crate::stream::{Fuse, StreamExt};
+use core::fmt;
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+#[cfg(feature = "sink")]
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+// The most items taken from the underlying stream per call to `poll_next`,
+// after which the task is woken up to take the rest, so that a stream which
+// is always ready does not hog it.
+const SWITCH_BUDGET: usize = 32;
+
+/// Stream for the [`switch_map`](super::StreamExt::switch_map) method.
+#[must_use = "streams do nothing unless polled"]
+pub struct SwitchMap<St, U, F> {
+    stream: Fuse<St>,
+    inner: Option<U>,
+    f: F,
+}
+
+impl<St: Unpin, U: Unpin, F> Unpin for SwitchMap<St, U, F> {}
+
+impl<St, U, F> fmt::Debug for SwitchMap<St, U, F>
+where
+    St: fmt::Debug,
+    U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SwitchMap")
+            .field("stream", &self.stream)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<St, U, F> SwitchMap<St, U, F> {
+    unsafe_pinned!(stream: Fuse<St>);
+    unsafe_pinned!(inner: Option<U>);
+    unsafe_unpinned!(f: F);
+}
+
+impl<St, U, F> SwitchMap<St, U, F>
+    where St: Stream,
+          U: Stream,
+          F: FnMut(St::Item) -> U,
+{
+    pub(super) fn new(stream: St, f: F) -> SwitchMap<St, U, F> {
+        SwitchMap {
+            stream: stream.fuse(),
+            inner: None,
+            f,
+        }
+    }
+
+    /// Acquires a reference to the underlying stream that this combinator is
+    /// pulling from.
+    pub fn get_ref(&self) -> &St {
+        self.stream.get_ref()
+    }
+
+    /// Acquires a mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> &mut St {
+        self.stream.get_mut()
+    }
+
+    /// Acquires a pinned mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut St> {
+        self.stream().get_pin_mut()
+    }
+
+    /// Consumes this combinator, returning the underlying stream.
+    ///
+    /// Note that this may discard intermediate state of this combinator, so
+    /// care should be taken to avoid losing resources when this is called.
+    pub fn into_inner(self) -> St {
+        self.stream.into_inner()
+    }
+}
+
+impl<St, U, F> FusedStream for SwitchMap<St, U, F>
+    where St: Stream,
+          U: Stream,
+          F: FnMut(St::Item) -> U,
+{
+    fn is_terminated(&self) -> bool {
+        self.inner.is_none() && self.stream.is_terminated()
+    }
+}
+
+impl<St, U, F> Stream for SwitchMap<St, U, F>
+    where St: Stream,
+          U: Stream,
+          F: FnMut(St::Item) -> U,
+{
+    type Item = U::Item;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<U::Item>> {
+        // Only the stream of the latest item is kept: the previous one is
+        // dropped, cancelling its work, as soon as a new item arrives.
+        let mut budget = SWITCH_BUDGET;
+        while let Poll::Ready(Some(item)) = self.as_mut().stream().poll_next(cx) {
+            let inner = (self.as_mut().f())(item);
+            self.as_mut().inner().set(Some(inner));
+            budget -= 1;
+            if budget == 0 {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+        }
+
+        if let Some(inner) = self.as_mut().inner().as_pin_mut() {
+            match ready!(inner.poll_next(cx)) {
+                Some(item) => return Poll::Ready(Some(item)),
+                None => self.as_mut().inner().set(None),
+            }
+        }
+
+        if self.stream.is_done() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+// Forwarding impl of Sink from the underlying stream
+#[cfg(feature = "sink")]
+impl<S, U, F, Item> Sink<Item> for SwitchMap<S, U, F>
+    where S: Stream + Sink<Item>,
+{
+    type Error = S::Error;
+
+    delegate_sink!(stream, Item);
+}
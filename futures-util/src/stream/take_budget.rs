@@ -0,0 +1,133 @@
+use core::fmt;
+use core::pin::Pin;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+#[cfg(feature = "sink")]
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// Stream for the [`take_budget`](super::StreamExt::take_budget) method.
+#[must_use = "streams do nothing unless polled"]
+pub struct TakeBudget<St, F> {
+    stream: St,
+    size: F,
+    budget: u64,
+    budget_exceeded: bool,
+}
+
+impl<St: Unpin, F> Unpin for TakeBudget<St, F> {}
+
+impl<St, F> fmt::Debug for TakeBudget<St, F>
+where
+    St: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TakeBudget")
+            .field("stream", &self.stream)
+            .field("budget", &self.budget)
+            .field("budget_exceeded", &self.budget_exceeded)
+            .finish()
+    }
+}
+
+impl<St, F> TakeBudget<St, F>
+    where St: Stream,
+          F: FnMut(&St::Item) -> u64,
+{
+    unsafe_pinned!(stream: St);
+    unsafe_unpinned!(size: F);
+    unsafe_unpinned!(budget: u64);
+    unsafe_unpinned!(budget_exceeded: bool);
+
+    pub(super) fn new(stream: St, budget: u64, size: F) -> TakeBudget<St, F> {
+        TakeBudget {
+            stream,
+            size,
+            budget,
+            budget_exceeded: false,
+        }
+    }
+
+    /// Returns how much of the budget is left.
+    pub fn remaining(&self) -> u64 {
+        self.budget
+    }
+
+    /// Returns whether this stream ended because an item did not fit in the
+    /// remaining budget, rather than because the underlying stream ended.
+    pub fn exceeded(&self) -> bool {
+        self.budget_exceeded
+    }
+
+    /// Acquires a reference to the underlying stream that this combinator is
+    /// pulling from.
+    pub fn get_ref(&self) -> &St {
+        &self.stream
+    }
+
+    /// Acquires a mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> &mut St {
+        &mut self.stream
+    }
+
+    /// Acquires a pinned mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut St> {
+        self.stream()
+    }
+
+    /// Consumes this combinator, returning the underlying stream.
+    ///
+    /// Note that this may discard intermediate state of this combinator, so
+    /// care should be taken to avoid losing resources when this is called.
+    pub fn into_inner(self) -> St {
+        self.stream
+    }
+}
+
+impl<St, F> Stream for TakeBudget<St, F>
+    where St: Stream,
+          F: FnMut(&St::Item) -> u64,
+{
+    type Item = St::Item;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<St::Item>> {
+        if self.budget_exceeded {
+            return Poll::Ready(None);
+        }
+
+        let item = match ready!(self.as_mut().stream().poll_next(cx)) {
+            Some(item) => item,
+            None => return Poll::Ready(None),
+        };
+        let size = (self.as_mut().size())(&item);
+        if size > self.budget {
+            *self.as_mut().budget_exceeded() = true;
+            Poll::Ready(None)
+        } else {
+            *self.as_mut().budget() -= size;
+            Poll::Ready(Some(item))
+        }
+    }
+}
+
+// Forwarding impl of Sink from the underlying stream
+#[cfg(feature = "sink")]
+impl<S, F, Item> Sink<Item> for TakeBudget<S, F>
+    where S: Stream + Sink<Item>,
+          F: FnMut(&S::Item) -> u64,
+{
+    type Error = S::Error;
+
+    delegate_sink!(stream, Item);
+}
@@ -0,0 +1,356 @@
+use futures_core::future::{Future, FutureObj};
+use futures_core::task::{Context, Poll, Spawn, SpawnError};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+/// A spawner wrapper which bounds how many tasks of each category run at
+/// once.
+///
+/// Tasks are spawned with a category, such as the downstream service they
+/// call. At most the limit of a category run concurrently; the tasks spawned
+/// beyond it are queued, and spawned on the underlying spawner as running
+/// tasks of the same category complete. Queued tasks start in priority
+/// order, highest first, and in spawn order among equal priorities. The
+/// queue of a category can be bounded with
+/// [`set_queue_bound`](ConcurrencyLimiter::set_queue_bound), in which case
+/// spawning fails once it is full. Once the underlying spawner fails to spawn
+/// a queued task, the other tasks queued in its category are dropped.
+///
+/// This is a cheaply clonable handle: clones share their limits and queues.
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::oneshot;
+/// use futures::executor::ThreadPool;
+/// use futures::future::FutureExt;
+/// use futures::task::ConcurrencyLimiter;
+///
+/// let limiter = ConcurrencyLimiter::new(ThreadPool::new().unwrap(), 1);
+/// limiter.set_limit("search", 4);
+///
+/// // The second database task waits for the first one to complete.
+/// let (tx, rx) = oneshot::channel::<()>();
+/// limiter.spawn("db", rx.map(drop)).unwrap();
+/// limiter.spawn("db", async { /* ... */ }).unwrap();
+/// assert_eq!((limiter.running(&"db"), limiter.queued(&"db")), (1, 1));
+/// tx.send(()).unwrap();
+/// ```
+pub struct ConcurrencyLimiter<K, Sp> {
+    inner: Arc<Inner<K, Sp>>,
+}
+
+struct Inner<K, Sp> {
+    spawner: Mutex<Sp>,
+    state: Mutex<State<K>>,
+}
+
+struct State<K> {
+    default_limit: usize,
+    categories: HashMap<K, Category>,
+    // Orders the queued tasks which have the same priority.
+    next_seq: u64,
+}
+
+struct Category {
+    limit: Option<usize>,
+    queue_bound: Option<usize>,
+    running: usize,
+    queue: BinaryHeap<Queued>,
+    // Set while queued tasks are being started.
+    starting: bool,
+}
+
+struct Queued {
+    priority: i32,
+    seq: u64,
+    future: FutureObj<'static, ()>,
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Highest priority first, then first in, first out.
+        self.priority.cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl Category {
+    fn new() -> Category {
+        Category {
+            limit: None,
+            queue_bound: None,
+            running: 0,
+            queue: BinaryHeap::new(),
+            starting: false,
+        }
+    }
+}
+
+/// The error returned when a [`ConcurrencyLimiter`] fails to spawn a task.
+#[derive(Debug)]
+pub enum ConcurrencyLimitError {
+    /// The queue of the category is full.
+    QueueFull,
+    /// The underlying spawner failed to spawn the task.
+    Spawn(SpawnError),
+}
+
+impl fmt::Display for ConcurrencyLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConcurrencyLimitError::QueueFull => write!(f, "the queue of the category is full"),
+            ConcurrencyLimitError::Spawn(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ConcurrencyLimitError {}
+
+impl<K, Sp> Clone for ConcurrencyLimiter<K, Sp> {
+    fn clone(&self) -> Self {
+        ConcurrencyLimiter { inner: self.inner.clone() }
+    }
+}
+
+impl<K, Sp> fmt::Debug for ConcurrencyLimiter<K, Sp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.inner.state.lock().unwrap();
+        f.debug_struct("ConcurrencyLimiter")
+            .field("default_limit", &state.default_limit)
+            .field("categories", &state.categories.len())
+            .finish()
+    }
+}
+
+impl<K, Sp> ConcurrencyLimiter<K, Sp>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    Sp: Spawn + Clone + Send + 'static,
+{
+    /// Creates a limiter spawning tasks on `spawner`, with at most
+    /// `default_limit` concurrent tasks in each category unless set
+    /// otherwise with [`set_limit`](ConcurrencyLimiter::set_limit).
+    pub fn new(spawner: Sp, default_limit: usize) -> Self {
+        ConcurrencyLimiter {
+            inner: Arc::new(Inner {
+                spawner: Mutex::new(spawner),
+                state: Mutex::new(State {
+                    default_limit,
+                    categories: HashMap::new(),
+                    next_seq: 0,
+                }),
+            }),
+        }
+    }
+
+    /// Sets the maximum number of concurrent tasks of `category`.
+    ///
+    /// Raising the limit starts queued tasks right away; lowering it lets
+    /// the running tasks complete.
+    pub fn set_limit(&self, category: K, limit: usize) {
+        self.inner.state.lock().unwrap()
+            .categories.entry(category.clone()).or_insert_with(Category::new)
+            .limit = Some(limit);
+        self.inner.start_queued(&category);
+    }
+
+    /// Bounds the number of queued tasks of `category`, or lifts the bound
+    /// with `None`, which is the default.
+    pub fn set_queue_bound(&self, category: K, bound: Option<usize>) {
+        self.inner.state.lock().unwrap()
+            .categories.entry(category).or_insert_with(Category::new)
+            .queue_bound = bound;
+    }
+
+    /// Returns the number of running tasks of `category`.
+    pub fn running(&self, category: &K) -> usize {
+        let state = self.inner.state.lock().unwrap();
+        state.categories.get(category).map_or(0, |c| c.running)
+    }
+
+    /// Returns the number of queued tasks of `category`.
+    pub fn queued(&self, category: &K) -> usize {
+        let state = self.inner.state.lock().unwrap();
+        state.categories.get(category).map_or(0, |c| c.queue.len())
+    }
+
+    /// Spawns `future` as a task of `category`, or queues it if the category
+    /// is at its limit.
+    pub fn spawn<Fut>(&self, category: K, future: Fut) -> Result<(), ConcurrencyLimitError>
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_with_priority(category, 0, future)
+    }
+
+    /// Spawns `future` as a task of `category`, or queues it with the given
+    /// priority if the category is at its limit.
+    ///
+    /// Queued tasks with a higher priority start first.
+    pub fn spawn_with_priority<Fut>(
+        &self,
+        category: K,
+        priority: i32,
+        future: Fut,
+    ) -> Result<(), ConcurrencyLimitError>
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let future = FutureObj::new(Box::new(future));
+        {
+            let mut state = self.inner.state.lock().unwrap();
+            let default_limit = state.default_limit;
+            let seq = state.next_seq;
+            let entry = state.categories.entry(category.clone()).or_insert_with(Category::new);
+            if entry.running >= entry.limit.unwrap_or(default_limit) {
+                if let Some(bound) = entry.queue_bound {
+                    if entry.queue.len() >= bound {
+                        return Err(ConcurrencyLimitError::QueueFull);
+                    }
+                }
+                entry.queue.push(Queued { priority, seq, future });
+                state.next_seq += 1;
+                return Ok(());
+            }
+            entry.running += 1;
+        }
+        self.inner.spawn(category, future).map_err(ConcurrencyLimitError::Spawn)
+    }
+}
+
+impl<K, Sp> Inner<K, Sp>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    Sp: Spawn + Clone + Send + 'static,
+{
+    // Spawns a task of `category`, which was counted as running already.
+    fn spawn(self: &Arc<Self>, category: K, future: FutureObj<'static, ()>) -> Result<(), SpawnError> {
+        let task = Limited {
+            future,
+            _release: Release { inner: self.clone(), category: Some(category) },
+        };
+        // The release of the task runs if spawning fails, which uncounts it
+        // and may spawn again, so the spawner must not stay locked.
+        let mut spawner = self.spawner.lock().unwrap().clone();
+        spawner.spawn_obj(FutureObj::new(Box::new(task)))
+    }
+
+    // Starts queued tasks of `category` for as long as it is under its limit.
+    //
+    // Only one call starts the tasks of a category at a time, the others
+    // leaving it to that one, as the release of a task which fails to spawn
+    // calls back into here.
+    fn start_queued(self: &Arc<Self>, category: &K) {
+        match self.state.lock().unwrap().categories.get_mut(category) {
+            Some(entry) if !entry.starting => entry.starting = true,
+            _ => return,
+        }
+        loop {
+            let future = {
+                let mut state = self.state.lock().unwrap();
+                let default_limit = state.default_limit;
+                let entry = state.categories.get_mut(category).unwrap();
+                let queued = if entry.running < entry.limit.unwrap_or(default_limit) {
+                    entry.queue.pop()
+                } else {
+                    None
+                };
+                match queued {
+                    Some(queued) => {
+                        entry.running += 1;
+                        queued.future
+                    }
+                    None => {
+                        entry.starting = false;
+                        return;
+                    }
+                }
+            };
+            if self.spawn(category.clone(), future).is_err() {
+                // The spawner cannot take tasks any more, so the queued ones
+                // would never start. They are dropped outside of the lock.
+                let queue = {
+                    let mut state = self.state.lock().unwrap();
+                    let entry = state.categories.get_mut(category).unwrap();
+                    entry.starting = false;
+                    mem::replace(&mut entry.queue, BinaryHeap::new())
+                };
+                drop(queue);
+                return;
+            }
+        }
+    }
+}
+
+// A task spawned by a `ConcurrencyLimiter`.
+struct Limited<K, Sp>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    Sp: Spawn + Clone + Send + 'static,
+{
+    future: FutureObj<'static, ()>,
+    _release: Release<K, Sp>,
+}
+
+impl<K, Sp> Unpin for Limited<K, Sp>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    Sp: Spawn + Clone + Send + 'static,
+{}
+
+impl<K, Sp> Future for Limited<K, Sp>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    Sp: Spawn + Clone + Send + 'static,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        Pin::new(&mut self.future).poll(cx)
+    }
+}
+
+// Frees the slot of a task once it completes or is dropped, letting the next
+// queued task of its category start.
+struct Release<K, Sp>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    Sp: Spawn + Clone + Send + 'static,
+{
+    inner: Arc<Inner<K, Sp>>,
+    category: Option<K>,
+}
+
+impl<K, Sp> Drop for Release<K, Sp>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    Sp: Spawn + Clone + Send + 'static,
+{
+    fn drop(&mut self) {
+        let category = self.category.take().unwrap();
+        if let Some(entry) = self.inner.state.lock().unwrap().categories.get_mut(&category) {
+            entry.running -= 1;
+        }
+        self.inner.start_queued(&category);
+    }
+}
@@ -31,6 +31,11 @@ cfg_target_has_atomic! {
     pub use self::waker_ref::{waker_ref, WakerRef};
 
     pub use futures_core::task::__internal::AtomicWaker;
+
+    #[cfg(feature = "std")]
+    mod concurrency_limiter;
+    #[cfg(feature = "std")]
+    pub use self::concurrency_limiter::{ConcurrencyLimiter, ConcurrencyLimitError};
 }
 
 mod noop_waker;
@@ -0,0 +1,261 @@
+use super::Sleep;
+use crate::future::FutureExt;
+use crate::task::AtomicWaker;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::Hash;
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Stream of the keys which have been idle for too long, such as the
+/// connections a server should close.
+///
+/// Activity is recorded per key through a [`ReaperHandle`], typically from
+/// the tasks doing the I/O of each connection: every call to
+/// [`touch`](ReaperHandle::touch) pushes the expiry of the key back to
+/// `idle_timeout` from now. Once a key goes `idle_timeout` without being
+/// touched, the stream yields it and forgets about it, unless it was
+/// [`remove`](ReaperHandle::remove)d first.
+///
+/// The stream never ends; while no key is tracked, it waits for one to be
+/// touched.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::stream::StreamExt;
+/// use futures::timer::ConnectionReaper;
+/// use std::time::Duration;
+///
+/// let mut reaper = ConnectionReaper::new(Duration::from_millis(10));
+/// let handle = reaper.handle();
+/// handle.touch("alice");
+/// handle.touch("bob");
+/// handle.remove(&"bob");
+///
+/// assert_eq!(block_on(reaper.next()), Some("alice"));
+/// assert!(handle.is_empty());
+/// ```
+#[must_use = "streams do nothing unless polled"]
+pub struct ConnectionReaper<K> {
+    shared: Arc<Shared<K>>,
+    sleep: Option<Sleep>,
+}
+
+/// A handle to record the activity of the keys of a [`ConnectionReaper`].
+pub struct ReaperHandle<K> {
+    shared: Arc<Shared<K>>,
+}
+
+// State shared between a reaper and its handles.
+struct Shared<K> {
+    idle_timeout: Duration,
+    waker: AtomicWaker,
+    state: Mutex<State<K>>,
+}
+
+struct State<K> {
+    // The expiry of each key, along with the generation of its entry in the
+    // queue. Keys whose expiry is too far away to be represented never
+    // expire, and have no entry.
+    expiries: HashMap<K, (Option<Instant>, u64)>,
+    // One entry per tracked key, which may be older than its expiry, along
+    // with stale entries of removed keys.
+    queue: BinaryHeap<Entry<K>>,
+    next_gen: u64,
+}
+
+struct Entry<K> {
+    expiry: Instant,
+    gen: u64,
+    key: K,
+}
+
+impl<K> PartialEq for Entry<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K> Eq for Entry<K> {}
+
+impl<K> PartialOrd for Entry<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K> Ord for Entry<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Earliest expiry first.
+        other.expiry.cmp(&self.expiry)
+    }
+}
+
+impl<K: Hash + Eq + Clone> ConnectionReaper<K> {
+    /// Creates a reaper yielding the keys which go `idle_timeout` without
+    /// activity.
+    pub fn new(idle_timeout: Duration) -> Self {
+        ConnectionReaper {
+            shared: Arc::new(Shared {
+                idle_timeout,
+                waker: AtomicWaker::new(),
+                state: Mutex::new(State {
+                    expiries: HashMap::new(),
+                    queue: BinaryHeap::new(),
+                    next_gen: 0,
+                }),
+            }),
+            sleep: None,
+        }
+    }
+
+    /// Returns a handle through which activity is recorded.
+    pub fn handle(&self) -> ReaperHandle<K> {
+        ReaperHandle { shared: self.shared.clone() }
+    }
+}
+
+impl<K> fmt::Debug for ConnectionReaper<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectionReaper")
+            .field("idle_timeout", &self.shared.idle_timeout)
+            .field("sleep", &self.sleep)
+            .finish()
+    }
+}
+
+impl<K> Unpin for ConnectionReaper<K> {}
+
+impl<K: Hash + Eq + Clone> Stream for ConnectionReaper<K> {
+    type Item = K;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<K>> {
+        let this = &mut *self;
+        this.shared.waker.register(cx.waker());
+        loop {
+            let next_expiry = {
+                let mut state = this.shared.state.lock().unwrap();
+                if let Some(key) = state.pop_expired(Instant::now()) {
+                    return Poll::Ready(Some(key));
+                }
+                match state.queue.peek() {
+                    Some(entry) => entry.expiry,
+                    None => {
+                        this.sleep = None;
+                        return Poll::Pending;
+                    }
+                }
+            };
+            match &mut this.sleep {
+                Some(sleep) if sleep.deadline() == Some(next_expiry) => {}
+                Some(sleep) => sleep.reset(next_expiry),
+                None => this.sleep = Some(super::sleep_until(next_expiry)),
+            }
+            ready!(this.sleep.as_mut().unwrap().poll_unpin(cx));
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone> FusedStream for ConnectionReaper<K> {
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
+
+impl<K: Hash + Eq + Clone> State<K> {
+    // Removes and returns a key which expired by `now`, if any.
+    fn pop_expired(&mut self, now: Instant) -> Option<K> {
+        loop {
+            match self.queue.peek() {
+                Some(entry) if entry.expiry <= now => {}
+                _ => return None,
+            }
+            let entry = self.queue.pop().unwrap();
+            // Entries of keys removed since they were queued are dropped.
+            if let Some(&(expiry, gen)) = self.expiries.get(&entry.key) {
+                if gen != entry.gen {
+                    continue;
+                }
+                match expiry {
+                    Some(expiry) if expiry <= now => {
+                        self.expiries.remove(&entry.key);
+                        return Some(entry.key);
+                    }
+                    // Touched since it was queued.
+                    Some(expiry) => self.queue.push(Entry { expiry, ..entry }),
+                    // Touched with an expiry which is never reached.
+                    None => {}
+                }
+            }
+        }
+    }
+}
+
+impl<K> Clone for ReaperHandle<K> {
+    fn clone(&self) -> Self {
+        ReaperHandle { shared: self.shared.clone() }
+    }
+}
+
+impl<K> fmt::Debug for ReaperHandle<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReaperHandle")
+            .field("idle_timeout", &self.shared.idle_timeout)
+            .finish()
+    }
+}
+
+impl<K: Hash + Eq + Clone> ReaperHandle<K> {
+    /// Records activity on `key`, which expires `idle_timeout` from now,
+    /// starting to track it if it was not.
+    ///
+    /// If `idle_timeout` is too long for the expiry to be represented, the
+    /// key never expires.
+    pub fn touch(&self, key: K) {
+        let expiry = Instant::now().checked_add(self.shared.idle_timeout);
+        let mut state = self.shared.state.lock().unwrap();
+        if let Some(entry) = state.expiries.get_mut(&key) {
+            // A key which never expired has no entry in the queue, so it is
+            // queued again below under a new generation.
+            if entry.0.is_some() || expiry.is_none() {
+                entry.0 = expiry;
+                return;
+            }
+        }
+        let gen = state.next_gen;
+        state.next_gen += 1;
+        state.expiries.insert(key.clone(), (expiry, gen));
+        if let Some(expiry) = expiry {
+            state.queue.push(Entry { expiry, gen, key });
+            drop(state);
+            self.shared.waker.wake();
+        }
+    }
+
+    /// Stops tracking `key`, e.g. because its connection was closed by its
+    /// peer. Returns whether it was tracked.
+    pub fn remove(&self, key: &K) -> bool {
+        self.shared.state.lock().unwrap().expiries.remove(key).is_some()
+    }
+
+    /// Returns whether `key` is tracked.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.shared.state.lock().unwrap().expiries.contains_key(key)
+    }
+
+    /// Returns the number of tracked keys.
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().unwrap().expiries.len()
+    }
+
+    /// Returns `true` if no key is tracked.
+    pub fn is_empty(&self) -> bool {
+        self.shared.state.lock().unwrap().expiries.is_empty()
+    }
+}
@@ -0,0 +1,185 @@
+use crate::task::AtomicWaker;
+use futures_core::task::Waker;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ptr;
+use std::sync::atomic::{self, AtomicBool, AtomicPtr, AtomicUsize};
+use std::sync::{Arc, Condvar, Mutex, Once, Weak};
+use std::thread;
+use std::time::Instant;
+
+/// The part of a timer that is shared with the timer thread.
+///
+/// The timer thread only holds a weak reference to each node, so dropping the
+/// owning future is enough to cancel the timer.
+#[derive(Debug)]
+pub(super) struct Node {
+    waker: AtomicWaker,
+    fired: AtomicBool,
+}
+
+impl Node {
+    pub(super) fn register_waker(&self, waker: &Waker) {
+        self.waker.register(waker);
+    }
+
+    pub(super) fn is_fired(&self) -> bool {
+        self.fired.load(atomic::Ordering::SeqCst)
+    }
+
+    fn fire(&self) {
+        self.fired.store(true, atomic::Ordering::SeqCst);
+        self.waker.wake();
+    }
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        // The entry of a timer canceled before firing lingers in the heap
+        // until it is compacted.
+        if !self.is_fired() {
+            Timer::get().canceled.fetch_add(1, atomic::Ordering::SeqCst);
+        }
+    }
+}
+
+struct Entry {
+    deadline: Instant,
+    seq: u64,
+    node: Weak<Node>,
+}
+
+// `BinaryHeap` is a max-heap, so entries are ordered in reverse to pop the
+// earliest deadline first. `seq` breaks ties in registration order.
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.deadline, other.seq).cmp(&(self.deadline, self.seq))
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.seq == other.seq
+    }
+}
+
+impl Eq for Entry {}
+
+struct State {
+    heap: BinaryHeap<Entry>,
+    next_seq: u64,
+}
+
+struct Timer {
+    state: Mutex<State>,
+    condvar: Condvar,
+    // An estimate of the number of entries of canceled timers in the heap.
+    canceled: AtomicUsize,
+}
+
+impl Timer {
+    fn get() -> &'static Timer {
+        static INIT: Once = Once::new();
+        static TIMER: AtomicPtr<Timer> = AtomicPtr::new(ptr::null_mut());
+
+        INIT.call_once(|| {
+            let timer: &'static Timer = Box::leak(Box::new(Timer {
+                state: Mutex::new(State {
+                    heap: BinaryHeap::new(),
+                    next_seq: 0,
+                }),
+                condvar: Condvar::new(),
+                canceled: AtomicUsize::new(0),
+            }));
+            thread::Builder::new()
+                .name("futures-timer".to_string())
+                .spawn(move || timer.run())
+                .expect("failed to spawn the timer thread");
+            TIMER.store(timer as *const Timer as *mut Timer, atomic::Ordering::SeqCst);
+        });
+
+        unsafe { &*TIMER.load(atomic::Ordering::SeqCst) }
+    }
+
+    fn run(&self) {
+        let mut expired = Vec::new();
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            while let Some(entry) = state.heap.peek() {
+                if entry.deadline > now {
+                    break;
+                }
+                expired.push(state.heap.pop().unwrap().node);
+            }
+
+            // Wake tasks without holding the lock, as a waker may register a
+            // new timer on this thread.
+            if !expired.is_empty() {
+                drop(state);
+                for node in expired.drain(..) {
+                    if let Some(node) = node.upgrade() {
+                        node.fire();
+                    }
+                }
+                state = self.state.lock().unwrap();
+                continue;
+            }
+
+            state = match state.heap.peek() {
+                Some(entry) => {
+                    let timeout = entry.deadline - now;
+                    self.condvar.wait_timeout(state, timeout).unwrap().0
+                }
+                None => self.condvar.wait(state).unwrap(),
+            };
+        }
+    }
+}
+
+/// Registers a timer firing at `deadline` with the timer thread.
+pub(super) fn register(deadline: Instant) -> Arc<Node> {
+    let node = Arc::new(Node {
+        waker: AtomicWaker::new(),
+        fired: AtomicBool::new(false),
+    });
+
+    let timer = Timer::get();
+    let mut state = timer.state.lock().unwrap();
+    // Without compaction, timers canceled long before their deadline, such
+    // as the timeouts of operations which completed, would pile up in the
+    // heap. Compacting once they make up half of it keeps this amortized
+    // constant time.
+    if timer.canceled.load(atomic::Ordering::SeqCst) * 2 > state.heap.len() {
+        timer.canceled.store(0, atomic::Ordering::SeqCst);
+        let live: Vec<_> = state.heap.drain()
+            .filter(|entry| entry.node.upgrade().is_some())
+            .collect();
+        state.heap = BinaryHeap::from(live);
+    }
+    let earliest = match state.heap.peek() {
+        Some(entry) => deadline < entry.deadline,
+        None => true,
+    };
+    let seq = state.next_seq;
+    state.next_seq += 1;
+    state.heap.push(Entry {
+        deadline,
+        seq,
+        node: Arc::downgrade(&node),
+    });
+    drop(state);
+
+    // Only a new earliest deadline changes how long the thread should sleep.
+    if earliest {
+        timer.condvar.notify_one();
+    }
+
+    node
+}
@@ -0,0 +1,186 @@
+use super::Sleep;
+use crate::future::FutureExt;
+use crate::task::AtomicWaker;
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Creates a stream yielding a tick every `period`, the first tick happening
+/// one `period` from now.
+///
+/// Ticks are spaced on the monotonic clock and do not drift. When the stream
+/// falls behind by more than a period, the missed ticks are skipped rather
+/// than yielded in a burst. The stream can be paused, resumed, or given
+/// another period at runtime through an [`IntervalHandle`].
+///
+/// # Panics
+///
+/// This function will panic if `period` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::stream::StreamExt;
+/// use futures::timer::interval;
+/// use std::time::Duration;
+///
+/// let mut ticks = interval(Duration::from_millis(10));
+/// let handle = ticks.handle();
+/// block_on(ticks.next());
+///
+/// // Poll less often from now on.
+/// handle.set_period(Duration::from_millis(20));
+/// block_on(ticks.next());
+/// assert_eq!(handle.period(), Duration::from_millis(20));
+/// ```
+pub fn interval(period: Duration) -> Interval {
+    assert!(period > Duration::from_millis(0), "period must be positive");
+    let now = Instant::now();
+    Interval {
+        shared: Arc::new(Shared {
+            waker: AtomicWaker::new(),
+            control: Mutex::new(Control {
+                period,
+                paused: false,
+                changed: false,
+            }),
+        }),
+        last: now,
+        sleep: Sleep::new(now + period),
+        paused: false,
+    }
+}
+
+/// Stream for the [`interval`] function.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Interval {
+    shared: Arc<Shared>,
+    // The time of the last tick, or of the start of the stream or of its
+    // last resumption, from which the next tick is scheduled.
+    last: Instant,
+    sleep: Sleep,
+    // Whether the stream was seen paused the last time it was polled.
+    paused: bool,
+}
+
+/// A handle to control an [`Interval`] stream while it runs.
+#[derive(Debug, Clone)]
+pub struct IntervalHandle {
+    shared: Arc<Shared>,
+}
+
+// State shared between an interval and its handles.
+#[derive(Debug)]
+struct Shared {
+    waker: AtomicWaker,
+    control: Mutex<Control>,
+}
+
+#[derive(Debug)]
+struct Control {
+    period: Duration,
+    paused: bool,
+    // Whether the period or pause state changed since the stream last saw
+    // it.
+    changed: bool,
+}
+
+impl Interval {
+    /// Returns a handle through which this stream can be paused, resumed,
+    /// or given another period.
+    pub fn handle(&self) -> IntervalHandle {
+        IntervalHandle { shared: self.shared.clone() }
+    }
+}
+
+impl Stream for Interval {
+    type Item = ();
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        let this = &mut *self;
+        this.shared.waker.register(cx.waker());
+        let (period, paused, changed) = {
+            let mut control = this.shared.control.lock().unwrap();
+            let changed = control.changed;
+            control.changed = false;
+            (control.period, control.paused, changed)
+        };
+
+        if paused {
+            this.paused = true;
+            return Poll::Pending;
+        }
+        if this.paused {
+            // Start over a full period after being resumed.
+            this.paused = false;
+            this.last = Instant::now();
+            this.sleep.reset(this.last + period);
+        } else if changed {
+            this.sleep.reset(this.last + period);
+        }
+
+        ready!(this.sleep.poll_unpin(cx));
+        let now = Instant::now();
+        this.last += period;
+        if this.last + period < now {
+            this.last = now;
+        }
+        this.sleep.reset(this.last + period);
+        Poll::Ready(Some(()))
+    }
+}
+
+impl FusedStream for Interval {
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
+
+impl IntervalHandle {
+    /// Stops the stream from yielding ticks until it is resumed.
+    pub fn pause(&self) {
+        self.update(|control| control.paused = true);
+    }
+
+    /// Resumes a paused stream, whose next tick happens one period from
+    /// now.
+    pub fn resume(&self) {
+        self.update(|control| control.paused = false);
+    }
+
+    /// Returns whether the stream is paused.
+    pub fn is_paused(&self) -> bool {
+        self.shared.control.lock().unwrap().paused
+    }
+
+    /// Changes the period of the stream.
+    ///
+    /// The next tick is rescheduled to one new period after the last tick,
+    /// and happens right away if that time has passed already.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `period` is zero.
+    pub fn set_period(&self, period: Duration) {
+        assert!(period > Duration::from_millis(0), "period must be positive");
+        self.update(|control| control.period = period);
+    }
+
+    /// Returns the period of the stream.
+    pub fn period(&self) -> Duration {
+        self.shared.control.lock().unwrap().period
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Control)) {
+        {
+            let mut control = self.shared.control.lock().unwrap();
+            f(&mut control);
+            control.changed = true;
+        }
+        self.shared.waker.wake();
+    }
+}
@@ -0,0 +1,47 @@
+//! Timers
+//!
+//! This module contains futures for waiting on the passage of time, such as
+//! [`sleep`] and the [`timeout`](crate::future::FutureExt::timeout)
+//! combinator.
+//!
+//! All timers in a process are driven by a single background thread, which is
+//! started lazily the first time a timer is registered. Timers never block the
+//! task polling them; the background thread only wakes tasks up once their
+//! deadline has passed.
+//!
+//! This module is only available when the `timer` feature of this
+//! library is activated.
+
+mod global;
+
+mod sleep;
+pub use self::sleep::{sleep, sleep_until, Sleep};
+
+mod system_time;
+pub use self::system_time::{sleep_until_system_time, SleepUntilSystemTime};
+
+mod connection_reaper;
+pub use self::connection_reaper::{ConnectionReaper, ReaperHandle};
+
+mod interval;
+pub use self::interval::{interval, Interval, IntervalHandle};
+
+mod poll_with_strategy;
+pub use self::poll_with_strategy::{poll_with_strategy, PollWithStrategy};
+
+#[cfg(feature = "sink")]
+mod record;
+#[cfg(feature = "sink")]
+pub use self::record::{record, Record};
+
+mod replay;
+pub use self::replay::{replay, RecordedEvent, Replay};
+
+mod schedule;
+pub use self::schedule::{schedule, Schedule, ScheduleSpec};
+
+mod timeout;
+pub use self::timeout::{Timeout, TimedOut};
+
+mod timeout_total;
+pub use self::timeout_total::TimeoutTotal;
@@ -0,0 +1,122 @@
+use super::Sleep;
+use crate::future::FutureExt;
+use core::fmt;
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::time::Duration;
+
+/// Creates a stream which repeatedly fetches values with `fetch`, waiting
+/// between fetches for a delay chosen by `strategy`.
+///
+/// The first fetch starts right away. Each value returned by `fetch` is
+/// shown to `strategy`, which returns how long to wait before the next fetch,
+/// and then yielded by the stream. This generalizes long polling: the delay
+/// can be shortened while there is work, and lengthened while the source is
+/// idle. The stream never ends; fallible fetches can yield `Result`s, for
+/// `strategy` to back off on errors. A delay too long to be represented is
+/// never over, so no further fetch is made.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::future;
+/// use futures::stream::StreamExt;
+/// use futures::timer::poll_with_strategy;
+/// use std::time::Duration;
+///
+/// let mut batches = vec![vec![], vec![1, 2], vec![3]].into_iter();
+/// let mut delay = Duration::from_millis(1);
+/// let stream = poll_with_strategy(
+///     move || future::ready(batches.next().unwrap_or_default()),
+///     move |batch: &Vec<i32>| {
+///         // Poll again soon while there are items, and back off when idle.
+///         delay = if batch.is_empty() { delay * 2 } else { Duration::from_millis(1) };
+///         delay
+///     },
+/// );
+///
+/// let batches = block_on(stream.take(3).collect::<Vec<_>>());
+/// assert_eq!(batches, vec![vec![], vec![1, 2], vec![3]]);
+/// ```
+pub fn poll_with_strategy<F, Fut, S>(fetch: F, strategy: S) -> PollWithStrategy<F, Fut, S>
+    where F: FnMut() -> Fut,
+          Fut: Future,
+          S: FnMut(&Fut::Output) -> Duration,
+{
+    PollWithStrategy {
+        fetch,
+        strategy,
+        future: None,
+        sleep: None,
+    }
+}
+
+/// Stream for the [`poll_with_strategy`] function.
+#[must_use = "streams do nothing unless polled"]
+pub struct PollWithStrategy<F, Fut, S> {
+    fetch: F,
+    strategy: S,
+    future: Option<Fut>,
+    sleep: Option<Sleep>,
+}
+
+impl<F, Fut: Unpin, S> Unpin for PollWithStrategy<F, Fut, S> {}
+
+impl<F, Fut, S> fmt::Debug for PollWithStrategy<F, Fut, S>
+where
+    Fut: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PollWithStrategy")
+            .field("future", &self.future)
+            .field("sleep", &self.sleep)
+            .finish()
+    }
+}
+
+impl<F, Fut, S> PollWithStrategy<F, Fut, S> {
+    unsafe_unpinned!(fetch: F);
+    unsafe_unpinned!(strategy: S);
+    unsafe_pinned!(future: Option<Fut>);
+    unsafe_unpinned!(sleep: Option<Sleep>);
+}
+
+impl<F, Fut, S> Stream for PollWithStrategy<F, Fut, S>
+    where F: FnMut() -> Fut,
+          Fut: Future,
+          S: FnMut(&Fut::Output) -> Duration,
+{
+    type Item = Fut::Output;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(sleep) = self.as_mut().sleep() {
+            ready!(sleep.poll_unpin(cx));
+            *self.as_mut().sleep() = None;
+        }
+
+        if self.future.is_none() {
+            let future = (self.as_mut().fetch())();
+            self.as_mut().future().set(Some(future));
+        }
+
+        let value = ready!(self.as_mut().future().as_pin_mut().unwrap().poll(cx));
+        self.as_mut().future().set(None);
+        let delay = (self.as_mut().strategy())(&value);
+        *self.as_mut().sleep() = Some(Sleep::after(delay));
+        Poll::Ready(Some(value))
+    }
+}
+
+impl<F, Fut, S> FusedStream for PollWithStrategy<F, Fut, S>
+    where F: FnMut() -> Fut,
+          Fut: Future,
+          S: FnMut(&Fut::Output) -> Duration,
+{
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,157 @@
+use super::RecordedEvent;
+use core::fmt;
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::time::Instant;
+
+/// Creates a stream which yields the items of `stream`, and records each of
+/// them into `events` along with its timing.
+///
+/// Timings are relative to the creation of the returned stream. The events
+/// are sent to `events` as the items go through, and `events` is closed once
+/// `stream` ends. A failure of `events` stops the recording without affecting
+/// the items, so that traffic can be recorded on a live stream.
+///
+/// This function is only available when the `sink` feature of this library
+/// is activated.
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::mpsc;
+/// use futures::executor::block_on;
+/// use futures::stream::{self, StreamExt};
+/// use futures::timer::{record, replay, sleep_until};
+///
+/// let (tx, rx) = mpsc::unbounded();
+/// let items = block_on(record(stream::iter(1..=3), tx).collect::<Vec<_>>());
+/// assert_eq!(items, vec![1, 2, 3]);
+///
+/// // The event log can be stored, and replayed in a later test run.
+/// let events = block_on(rx.collect::<Vec<_>>());
+/// let replayed = block_on(replay(events, sleep_until).collect::<Vec<_>>());
+/// assert_eq!(replayed, vec![1, 2, 3]);
+/// ```
+pub fn record<St, Si>(stream: St, events: Si) -> Record<St, Si>
+    where St: Stream,
+          St::Item: Clone,
+          Si: Sink<RecordedEvent<St::Item>>,
+{
+    Record {
+        stream,
+        events: Some(events),
+        start: Instant::now(),
+        pending: None,
+    }
+}
+
+/// Stream for the [`record`] function.
+#[must_use = "streams do nothing unless polled"]
+pub struct Record<St: Stream, Si> {
+    stream: St,
+    events: Option<Si>,
+    start: Instant,
+    // An event waiting for `events` to be ready, which holds the item back.
+    pending: Option<RecordedEvent<St::Item>>,
+}
+
+impl<St: Stream + Unpin, Si: Unpin> Unpin for Record<St, Si> {}
+
+impl<St, Si> fmt::Debug for Record<St, Si>
+where
+    St: Stream + fmt::Debug,
+    St::Item: fmt::Debug,
+    Si: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Record")
+            .field("stream", &self.stream)
+            .field("events", &self.events)
+            .field("start", &self.start)
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
+
+impl<St: Stream, Si> Record<St, Si> {
+    unsafe_pinned!(stream: St);
+    unsafe_pinned!(events: Option<Si>);
+    unsafe_unpinned!(pending: Option<RecordedEvent<St::Item>>);
+}
+
+impl<St, Si> Record<St, Si>
+    where St: Stream,
+          St::Item: Clone,
+          Si: Sink<RecordedEvent<St::Item>>,
+{
+    // Sends the pending event, returning the item it holds back. The event
+    // is dropped if `events` fails.
+    fn poll_send_pending(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<St::Item> {
+        let event = self.as_mut().pending().take().unwrap();
+        let item = event.item.clone();
+        if let Some(mut events) = self.as_mut().events().as_pin_mut() {
+            match events.as_mut().poll_ready(cx) {
+                Poll::Pending => {
+                    *self.as_mut().pending() = Some(event);
+                    return Poll::Pending;
+                }
+                Poll::Ready(Ok(())) => {
+                    if events.start_send(event).is_err() {
+                        self.as_mut().events().set(None);
+                    }
+                }
+                Poll::Ready(Err(_)) => self.as_mut().events().set(None),
+            }
+        }
+        Poll::Ready(item)
+    }
+}
+
+impl<St, Si> FusedStream for Record<St, Si>
+    where St: FusedStream,
+          St::Item: Clone,
+          Si: Sink<RecordedEvent<St::Item>>,
+{
+    fn is_terminated(&self) -> bool {
+        self.pending.is_none() && self.events.is_none() && self.stream.is_terminated()
+    }
+}
+
+impl<St, Si> Stream for Record<St, Si>
+    where St: Stream,
+          St::Item: Clone,
+          Si: Sink<RecordedEvent<St::Item>>,
+{
+    type Item = St::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<St::Item>> {
+        if self.pending.is_none() {
+            match self.as_mut().stream().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    let at = self.start.elapsed();
+                    *self.as_mut().pending() = Some(RecordedEvent { at, item });
+                }
+                Poll::Ready(None) => {
+                    // Close the log, so that the events are all written out.
+                    if let Some(events) = self.as_mut().events().as_pin_mut() {
+                        let _ = ready!(events.poll_close(cx));
+                        self.as_mut().events().set(None);
+                    }
+                    return Poll::Ready(None);
+                }
+                Poll::Pending => {
+                    if let Some(events) = self.as_mut().events().as_pin_mut() {
+                        if let Poll::Ready(Err(_)) = events.poll_flush(cx) {
+                            self.as_mut().events().set(None);
+                        }
+                    }
+                    return Poll::Pending;
+                }
+            }
+        }
+        self.poll_send_pending(cx).map(Some)
+    }
+}
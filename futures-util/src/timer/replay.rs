@@ -0,0 +1,140 @@
+use core::fmt;
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::time::{Duration, Instant};
+
+/// An item of a stream, along with when it was yielded relative to the start
+/// of the recording, as produced by [`record`](super::record) and consumed by
+/// [`replay`].
+///
+/// Event logs are plain lists of these, which can be stored in any format and
+/// turned back into a stream with the same timing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecordedEvent<T> {
+    /// The time from the start of the recording to the item.
+    pub at: Duration,
+    /// The item.
+    pub item: T,
+}
+
+/// Creates a stream which yields the items of `events`, each at the time it
+/// was [`record`](super::record)ed at relative to the first poll of the stream.
+///
+/// The stream waits for each event with the future returned by `timer` for
+/// the instant it is due at. This is usually
+/// [`sleep_until`](super::sleep_until), but a test can pass its own timer,
+/// for example one which completes right away, to replay the order of the
+/// events without waiting for them.
+///
+/// The timing can be compressed with [`accelerate`](Replay::accelerate), to
+/// drive regression tests with the shape of recorded traffic without waiting
+/// for as long as it took.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::stream::StreamExt;
+/// use futures::timer::{replay, sleep_until, RecordedEvent};
+/// use std::time::{Duration, Instant};
+///
+/// let events = vec![
+///     RecordedEvent { at: Duration::from_millis(20), item: "a" },
+///     RecordedEvent { at: Duration::from_millis(40), item: "b" },
+/// ];
+///
+/// let start = Instant::now();
+/// let replayed = block_on(replay(events, sleep_until).accelerate(2).collect::<Vec<_>>());
+/// assert_eq!(replayed, vec!["a", "b"]);
+/// assert!(start.elapsed() >= Duration::from_millis(20));
+/// ```
+pub fn replay<I, T, F, Fut>(events: I, timer: F) -> Replay<I::IntoIter, F, Fut>
+    where I: IntoIterator<Item = RecordedEvent<T>>,
+          F: FnMut(Instant) -> Fut,
+          Fut: Future<Output = ()>,
+{
+    Replay {
+        events: events.into_iter(),
+        timer,
+        factor: 1,
+        start: None,
+        next: None,
+        delay: None,
+    }
+}
+
+/// Stream for the [`replay`] function.
+#[must_use = "streams do nothing unless polled"]
+pub struct Replay<I: Iterator, F, Fut> {
+    events: I,
+    timer: F,
+    factor: u32,
+    start: Option<Instant>,
+    next: Option<I::Item>,
+    delay: Option<Fut>,
+}
+
+impl<I: Iterator, F, Fut: Unpin> Unpin for Replay<I, F, Fut> {}
+
+impl<I, F, Fut> fmt::Debug for Replay<I, F, Fut>
+where
+    I: Iterator + fmt::Debug,
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replay")
+            .field("events", &self.events)
+            .field("factor", &self.factor)
+            .field("start", &self.start)
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+impl<I: Iterator, F, Fut> Replay<I, F, Fut> {
+    unsafe_unpinned!(events: I);
+    unsafe_unpinned!(timer: F);
+    unsafe_unpinned!(start: Option<Instant>);
+    unsafe_unpinned!(next: Option<I::Item>);
+    unsafe_pinned!(delay: Option<Fut>);
+
+    /// Replays the events `factor` times faster than they were recorded.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `factor` is zero.
+    pub fn accelerate(mut self, factor: u32) -> Self {
+        assert!(factor > 0, "acceleration factor must be positive");
+        self.factor = factor;
+        self
+    }
+}
+
+impl<I, T, F, Fut> Stream for Replay<I, F, Fut>
+    where I: Iterator<Item = RecordedEvent<T>>,
+          F: FnMut(Instant) -> Fut,
+          Fut: Future<Output = ()>,
+{
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let start = *self.as_mut().start().get_or_insert_with(Instant::now);
+        if self.next.is_none() {
+            let event = match self.as_mut().events().next() {
+                Some(event) => event,
+                None => return Poll::Ready(None),
+            };
+            let at = event.at / self.factor;
+            *self.as_mut().next() = Some(event);
+            let delay = (self.as_mut().timer())(start + at);
+            self.as_mut().delay().set(Some(delay));
+        }
+
+        ready!(self.as_mut().delay().as_pin_mut().unwrap().poll(cx));
+        self.as_mut().delay().set(None);
+        Poll::Ready(self.as_mut().next().take().map(|event| event.item))
+    }
+}
@@ -0,0 +1,239 @@
+use super::{sleep_until_system_time, Sleep, SleepUntilSystemTime};
+use crate::future::FutureExt;
+use core::fmt;
+use core::hash::{BuildHasher, Hasher};
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+use std::collections::hash_map::RandomState;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// When the ticks of a [`schedule`] stream happen.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScheduleSpec {
+    kind: Kind,
+    jitter: Duration,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Kind {
+    // A fixed period on the monotonic clock.
+    Every(Duration),
+    // Times of the system clock that are `offset` past a multiple of
+    // `period`, both in seconds since the Unix epoch.
+    Calendar { period: u64, offset: u64 },
+}
+
+impl ScheduleSpec {
+    /// Ticks every `period`, the first tick happening one `period` from
+    /// when the stream is created.
+    ///
+    /// Ticks are spaced on the monotonic clock and do not drift. When the
+    /// stream falls behind by more than a period, the missed ticks are
+    /// skipped rather than yielded in a burst.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `period` is zero.
+    pub fn every(period: Duration) -> Self {
+        assert!(period > Duration::from_millis(0), "period must be positive");
+        ScheduleSpec { kind: Kind::Every(period), jitter: Duration::from_millis(0) }
+    }
+
+    /// Ticks every day when the system clock reads `hour:minute:second`,
+    /// in UTC.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `hour`, `minute` or `second` is out of
+    /// range.
+    pub fn daily_at(hour: u32, minute: u32, second: u32) -> Self {
+        assert!(hour < 24, "hour out of range");
+        assert!(minute < 60 && second < 60, "minute or second out of range");
+        let offset = u64::from(hour * 3600 + minute * 60 + second);
+        ScheduleSpec {
+            kind: Kind::Calendar { period: 24 * 3600, offset },
+            jitter: Duration::from_millis(0),
+        }
+    }
+
+    /// Ticks every hour when the system clock reads `minute:second` past
+    /// the hour.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `minute` or `second` is out of range.
+    pub fn hourly_at(minute: u32, second: u32) -> Self {
+        assert!(minute < 60 && second < 60, "minute or second out of range");
+        let offset = u64::from(minute * 60 + second);
+        ScheduleSpec {
+            kind: Kind::Calendar { period: 3600, offset },
+            jitter: Duration::from_millis(0),
+        }
+    }
+
+    /// Delays each tick by a random duration of up to `jitter`.
+    ///
+    /// This spreads out the ticks of the many processes running the same
+    /// schedule, so that they do not all hit a shared resource at once.
+    /// Jitter only ever delays a tick, and does not accumulate over ticks.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+}
+
+/// Creates a stream yielding a tick at each time scheduled by `spec`.
+///
+/// Schedules on the monotonic clock, made with
+/// [`ScheduleSpec::every`], are not affected by adjustments of the system
+/// clock. Calendar schedules, made with [`ScheduleSpec::daily_at`] or
+/// [`ScheduleSpec::hourly_at`], follow the system clock like
+/// [`sleep_until_system_time`](super::sleep_until_system_time).
+///
+/// The stream never ends. Ticks are only scheduled while the stream is
+/// polled, so a tick which is due while the previous one is being handled
+/// is yielded as soon as the stream is polled again.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::stream::StreamExt;
+/// use futures::timer::{schedule, ScheduleSpec};
+/// use std::time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// let spec = ScheduleSpec::every(Duration::from_millis(10))
+///     .with_jitter(Duration::from_millis(5));
+/// let ticks = block_on(schedule(spec).take(3).collect::<Vec<()>>());
+/// assert_eq!(ticks.len(), 3);
+/// assert!(start.elapsed() >= Duration::from_millis(30));
+/// ```
+pub fn schedule(spec: ScheduleSpec) -> Schedule {
+    let base = match spec.kind {
+        Kind::Every(period) => Base::Monotonic(Instant::now() + period),
+        Kind::Calendar { period, offset } => {
+            Base::WallClock(next_calendar_time(SystemTime::now(), period, offset))
+        }
+    };
+    Schedule {
+        spec,
+        base,
+        random: RandomState::new(),
+        ticks: 0,
+        next: None,
+    }
+}
+
+/// Stream for the [`schedule`] function.
+#[must_use = "streams do nothing unless polled"]
+pub struct Schedule {
+    spec: ScheduleSpec,
+    // The time of the next tick, before jitter.
+    base: Base,
+    random: RandomState,
+    ticks: u64,
+    next: Option<Next>,
+}
+
+impl fmt::Debug for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Schedule")
+            .field("spec", &self.spec)
+            .field("ticks", &self.ticks)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+enum Base {
+    Monotonic(Instant),
+    WallClock(SystemTime),
+}
+
+#[derive(Debug)]
+enum Next {
+    Monotonic(Sleep),
+    WallClock(SleepUntilSystemTime),
+}
+
+impl Schedule {
+    /// Returns the specification of this schedule.
+    pub fn spec(&self) -> &ScheduleSpec {
+        &self.spec
+    }
+
+    // Picks the jitter of the next tick.
+    fn jitter(&self) -> Duration {
+        let max = self.spec.jitter;
+        let nanos = max.as_secs() * 1_000_000_000 + u64::from(max.subsec_nanos());
+        if nanos == 0 {
+            return max;
+        }
+        let mut hasher = self.random.build_hasher();
+        hasher.write_u64(self.ticks);
+        Duration::from_nanos(hasher.finish() % nanos)
+    }
+
+    // Moves the base on to the tick following the current one.
+    fn advance(&mut self) {
+        self.ticks += 1;
+        self.base = match (&self.spec.kind, &self.base) {
+            (Kind::Every(period), Base::Monotonic(base)) => {
+                let now = Instant::now();
+                let next = *base + *period;
+                Base::Monotonic(if next + *period < now { now + *period } else { next })
+            }
+            (Kind::Calendar { period, offset }, Base::WallClock(base)) => {
+                // Calendar ticks missed while the stream was not polled are
+                // skipped as well.
+                let now = SystemTime::now();
+                let after = if now > *base { now } else { *base };
+                Base::WallClock(next_calendar_time(after, *period, *offset))
+            }
+            _ => unreachable!(),
+        };
+    }
+}
+
+impl Stream for Schedule {
+    type Item = ();
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        if self.next.is_none() {
+            let jitter = self.jitter();
+            let next = match self.base {
+                Base::Monotonic(base) => Next::Monotonic(Sleep::new(base + jitter)),
+                Base::WallClock(base) => Next::WallClock(sleep_until_system_time(base + jitter)),
+            };
+            self.next = Some(next);
+        }
+
+        match self.next.as_mut().unwrap() {
+            Next::Monotonic(sleep) => ready!(sleep.poll_unpin(cx)),
+            Next::WallClock(sleep) => ready!(sleep.poll_unpin(cx)),
+        }
+        self.next = None;
+        self.advance();
+        Poll::Ready(Some(()))
+    }
+}
+
+impl FusedStream for Schedule {
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
+
+// Returns the first time after `after` which is `offset` seconds past a
+// multiple of `period` seconds since the Unix epoch.
+fn next_calendar_time(after: SystemTime, period: u64, offset: u64) -> SystemTime {
+    let secs = after.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let next = if secs < offset {
+        offset
+    } else {
+        ((secs - offset) / period + 1) * period + offset
+    };
+    UNIX_EPOCH + Duration::from_secs(next)
+}
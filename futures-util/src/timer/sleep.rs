@@ -0,0 +1,120 @@
+use super::global::{self, Node};
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::task::{Context, Poll};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Future for the [`sleep`] function.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Sleep {
+    // `None` if the deadline is too far away to be represented, in which case
+    // the future never completes.
+    deadline: Option<Instant>,
+    node: Option<Arc<Node>>,
+}
+
+/// Creates a future which completes once `dur` has elapsed.
+///
+/// If `dur` is so long that the deadline cannot be represented, the future
+/// never completes.
+///
+/// The timer is registered with the timer thread the first time the future is
+/// polled, and is canceled when the future is dropped. As the returned future
+/// is [`Unpin`], it can be raced against other futures with
+/// [`select`](crate::future::select) to wait on an explicit deadline.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::future::{self, Either};
+/// use futures::timer::sleep;
+/// use std::time::Duration;
+///
+/// let never = future::pending::<()>();
+/// match block_on(future::select(never, sleep(Duration::from_millis(10)))) {
+///     Either::Left(_) => unreachable!(),
+///     Either::Right(((), _never)) => {}
+/// }
+/// ```
+pub fn sleep(dur: Duration) -> Sleep {
+    Sleep::after(dur)
+}
+
+/// Creates a future which completes once `deadline` is reached.
+///
+/// Like [`sleep`], this waits on the monotonic clock, so adjustments of the
+/// system clock do not affect when the future completes. Use
+/// [`sleep_until_system_time`](super::sleep_until_system_time) to wait for a
+/// wall-clock time instead.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::timer::sleep_until;
+/// use std::time::{Duration, Instant};
+///
+/// let deadline = Instant::now() + Duration::from_millis(10);
+/// block_on(sleep_until(deadline));
+/// assert!(Instant::now() >= deadline);
+/// ```
+pub fn sleep_until(deadline: Instant) -> Sleep {
+    Sleep::new(deadline)
+}
+
+impl Sleep {
+    pub(super) fn new(deadline: Instant) -> Sleep {
+        Sleep {
+            deadline: Some(deadline),
+            node: None,
+        }
+    }
+
+    pub(super) fn after(dur: Duration) -> Sleep {
+        Sleep {
+            deadline: Instant::now().checked_add(dur),
+            node: None,
+        }
+    }
+
+    /// Returns the instant at which this future completes, or `None` if it
+    /// never does.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Changes the instant at which this future completes.
+    ///
+    /// This may be called both before and after the future has completed;
+    /// a completed future will be pending again until the new deadline.
+    pub fn reset(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+        self.node = None;
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let deadline = match self.deadline {
+            Some(deadline) => deadline,
+            None => return Poll::Pending,
+        };
+        if Instant::now() >= deadline {
+            self.node = None;
+            return Poll::Ready(());
+        }
+
+        let node = self.node.get_or_insert_with(|| global::register(deadline));
+        node.register_waker(cx.waker());
+        if node.is_fired() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use super::Sleep;
+use crate::future::FutureExt;
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime};
+
+// Longest time the system clock is left unchecked, bounding how late the
+// future completes after the clock was moved forward.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Future for the [`sleep_until_system_time`] function.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SleepUntilSystemTime {
+    time: SystemTime,
+    sleep: Option<Sleep>,
+}
+
+/// Creates a future which completes once the system clock reaches `time`.
+///
+/// Unlike [`sleep_until`](super::sleep_until), this follows adjustments of
+/// the system clock: the timer thread runs on the monotonic clock, so the
+/// future re-arms itself against the system clock at least every second,
+/// and completes within about a second of the clock being moved past
+/// `time`. Moving the clock back delays the future accordingly.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::timer::sleep_until_system_time;
+/// use std::time::{Duration, SystemTime};
+///
+/// let time = SystemTime::now() + Duration::from_millis(10);
+/// block_on(sleep_until_system_time(time));
+/// assert!(SystemTime::now() >= time);
+/// ```
+pub fn sleep_until_system_time(time: SystemTime) -> SleepUntilSystemTime {
+    SleepUntilSystemTime { time, sleep: None }
+}
+
+impl SleepUntilSystemTime {
+    /// Returns the system time at which this future completes.
+    pub fn deadline(&self) -> SystemTime {
+        self.time
+    }
+
+    /// Changes the system time at which this future completes.
+    ///
+    /// This may be called both before and after the future has completed;
+    /// a completed future will be pending again until the new time.
+    pub fn reset(&mut self, time: SystemTime) {
+        self.time = time;
+        self.sleep = None;
+    }
+}
+
+impl Future for SleepUntilSystemTime {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        loop {
+            if let Some(sleep) = &mut self.sleep {
+                ready!(sleep.poll_unpin(cx));
+            }
+
+            // An error means that `time` has passed.
+            let remaining = match self.time.duration_since(SystemTime::now()) {
+                Ok(remaining) if remaining > Duration::from_millis(0) => remaining,
+                _ => {
+                    self.sleep = None;
+                    return Poll::Ready(());
+                }
+            };
+            let wait = if remaining < RECHECK_INTERVAL { remaining } else { RECHECK_INTERVAL };
+            self.sleep = Some(Sleep::new(Instant::now() + wait));
+        }
+    }
+}
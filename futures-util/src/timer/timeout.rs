@@ -0,0 +1,68 @@
+use super::Sleep;
+use crate::future::FutureExt;
+use core::fmt;
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::task::{Context, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+/// Future for the [`timeout`](crate::future::FutureExt::timeout) method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Timeout<Fut> {
+    future: Option<Fut>,
+    sleep: Sleep,
+}
+
+impl<Fut: Unpin> Unpin for Timeout<Fut> {}
+
+impl<Fut: Future> Timeout<Fut> {
+    unsafe_pinned!(future: Option<Fut>);
+    unsafe_unpinned!(sleep: Sleep);
+
+    pub(crate) fn new(future: Fut, dur: Duration) -> Timeout<Fut> {
+        Timeout {
+            future: Some(future),
+            sleep: Sleep::after(dur),
+        }
+    }
+
+    /// Returns the instant at which this future times out, or `None` if the
+    /// timeout is too long for it to ever do so.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.sleep.deadline()
+    }
+}
+
+impl<Fut: Future> Future for Timeout<Fut> {
+    type Output = Result<Fut::Output, TimedOut>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(future) = self.as_mut().future().as_pin_mut() {
+            if let Poll::Ready(output) = future.poll(cx) {
+                return Poll::Ready(Ok(output));
+            }
+        }
+
+        ready!(self.as_mut().sleep().poll_unpin(cx));
+        // Cancel the inner future right away rather than when `Timeout` is
+        // eventually dropped.
+        self.as_mut().future().set(None);
+        Poll::Ready(Err(TimedOut))
+    }
+}
+
+/// Error returned by [`Timeout`] when the deadline passed before the inner
+/// future completed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TimedOut;
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "future timed out")
+    }
+}
+
+impl Error for TimedOut {}
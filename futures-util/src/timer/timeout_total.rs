@@ -0,0 +1,65 @@
+use super::{Sleep, TimedOut};
+use crate::future::FutureExt;
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::time::{Duration, Instant};
+
+/// Stream for the [`timeout_total`](crate::stream::StreamExt::timeout_total)
+/// method.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct TimeoutTotal<St> {
+    stream: Option<St>,
+    sleep: Sleep,
+}
+
+impl<St: Unpin> Unpin for TimeoutTotal<St> {}
+
+impl<St: Stream> TimeoutTotal<St> {
+    unsafe_pinned!(stream: Option<St>);
+    unsafe_unpinned!(sleep: Sleep);
+
+    pub(crate) fn new(stream: St, dur: Duration) -> TimeoutTotal<St> {
+        TimeoutTotal {
+            stream: Some(stream),
+            sleep: Sleep::after(dur),
+        }
+    }
+
+    /// Returns the instant at which this stream times out, or `None` if the
+    /// timeout is too long for it to ever do so.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.sleep.deadline()
+    }
+}
+
+impl<St: Stream> Stream for TimeoutTotal<St> {
+    type Item = Result<St::Item, TimedOut>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.stream.is_none() {
+            return Poll::Ready(None);
+        }
+
+        // The deadline is checked first, so that a stream which always has
+        // an item ready still times out.
+        if self.as_mut().sleep().poll_unpin(cx).is_ready() {
+            self.as_mut().stream().set(None);
+            return Poll::Ready(Some(Err(TimedOut)));
+        }
+
+        let item = ready!(self.as_mut().stream().as_pin_mut().unwrap().poll_next(cx));
+        if item.is_none() {
+            self.as_mut().stream().set(None);
+        }
+        Poll::Ready(item.map(Ok))
+    }
+}
+
+impl<St: Stream> FusedStream for TimeoutTotal<St> {
+    fn is_terminated(&self) -> bool {
+        self.stream.is_none()
+    }
+}
@@ -0,0 +1,178 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::unsafe_pinned;
+
+/// The length in bytes of the trailer appended to each frame by
+/// [`checksum_trailer`](super::TransportExt::checksum_trailer).
+pub const TRAILER_LEN: usize = 4;
+
+/// A checksum to verify the integrity of frames with.
+pub trait Checksum {
+    /// Computes the checksum of `data`.
+    fn checksum(&self, data: &[u8]) -> u32;
+}
+
+/// The CRC-32 checksum of IEEE 802.3, as used by zlib, PNG, and many storage
+/// formats.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Crc32;
+
+impl Checksum for Crc32 {
+    fn checksum(&self, data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in data {
+            crc ^= u32::from(byte);
+            for _ in 0..8 {
+                let mask = (crc & 1).wrapping_neg();
+                crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+        !crc
+    }
+}
+
+/// Error yielded for an inbound frame which failed verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumError {
+    /// The frame is too short to hold a trailer.
+    Truncated {
+        /// The length of the frame.
+        len: usize,
+    },
+    /// The checksum in the trailer does not match the one computed from the
+    /// frame.
+    Mismatch {
+        /// The checksum in the trailer.
+        expected: u32,
+        /// The checksum computed from the frame.
+        actual: u32,
+    },
+}
+
+impl fmt::Display for ChecksumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChecksumError::Truncated { len } => {
+                write!(f, "frame of {} bytes is too short for a checksum trailer", len)
+            }
+            ChecksumError::Mismatch { expected, actual } => {
+                write!(f, "checksum mismatch: expected {:#010x}, got {:#010x}", expected, actual)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChecksumError {}
+
+/// Transport for the
+/// [`checksum_trailer`](super::TransportExt::checksum_trailer) and
+/// [`checksum_trailer_with`](super::TransportExt::checksum_trailer_with)
+/// methods.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct ChecksumTrailer<T, C> {
+    transport: T,
+    checksum: C,
+}
+
+impl<T: Unpin, C> Unpin for ChecksumTrailer<T, C> {}
+
+impl<T, C> ChecksumTrailer<T, C> {
+    unsafe_pinned!(transport: T);
+
+    pub(super) fn new(transport: T, checksum: C) -> Self {
+        ChecksumTrailer { transport, checksum }
+    }
+
+    /// Acquires a reference to the underlying transport.
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    /// Acquires a mutable reference to the underlying transport.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// transport which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Acquires a pinned mutable reference to the underlying transport.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// transport which may otherwise confuse this combinator.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.transport()
+    }
+
+    /// Consumes this combinator, returning the underlying transport.
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+}
+
+impl<T, C> Stream for ChecksumTrailer<T, C>
+    where T: Stream<Item = Vec<u8>>,
+          C: Checksum,
+{
+    type Item = Result<Vec<u8>, ChecksumError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut frame = match ready!(self.as_mut().transport().poll_next(cx)) {
+            Some(frame) => frame,
+            None => return Poll::Ready(None),
+        };
+        if frame.len() < TRAILER_LEN {
+            return Poll::Ready(Some(Err(ChecksumError::Truncated { len: frame.len() })));
+        }
+        let body_len = frame.len() - TRAILER_LEN;
+        let mut trailer = [0; TRAILER_LEN];
+        trailer.copy_from_slice(&frame[body_len..]);
+        let expected = u32::from_be_bytes(trailer);
+        frame.truncate(body_len);
+        let actual = self.checksum.checksum(&frame);
+        if actual != expected {
+            return Poll::Ready(Some(Err(ChecksumError::Mismatch { expected, actual })));
+        }
+        Poll::Ready(Some(Ok(frame)))
+    }
+}
+
+impl<T, C> FusedStream for ChecksumTrailer<T, C>
+    where T: FusedStream<Item = Vec<u8>>,
+          C: Checksum,
+{
+    fn is_terminated(&self) -> bool {
+        self.transport.is_terminated()
+    }
+}
+
+impl<T, C> Sink<Vec<u8>> for ChecksumTrailer<T, C>
+    where T: Sink<Vec<u8>>,
+          C: Checksum,
+{
+    type Error = T::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.transport().poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, mut frame: Vec<u8>) -> Result<(), Self::Error> {
+        let checksum = self.checksum.checksum(&frame);
+        frame.extend_from_slice(&checksum.to_be_bytes());
+        self.as_mut().transport().start_send(frame)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.transport().poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.transport().poll_close(cx)
+    }
+}
@@ -0,0 +1,69 @@
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::unsafe_pinned;
+
+/// Transport for the [`duplex`] function.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Duplex<St, Si> {
+    stream: St,
+    sink: Si,
+}
+
+impl<St: Unpin, Si: Unpin> Unpin for Duplex<St, Si> {}
+
+/// Joins a stream of inbound frames and a sink of outbound frames into a
+/// single transport.
+///
+/// This is the opposite of [`split`](crate::stream::StreamExt::split), and
+/// is useful when the two halves of a connection come from different places,
+/// such as a pair of channels in tests.
+pub fn duplex<St, Si, Out>(stream: St, sink: Si) -> Duplex<St, Si>
+    where St: Stream,
+          Si: Sink<Out>,
+{
+    Duplex { stream, sink }
+}
+
+impl<St, Si> Duplex<St, Si> {
+    unsafe_pinned!(stream: St);
+    unsafe_pinned!(sink: Si);
+
+    /// Acquires a reference to the inbound stream.
+    pub fn stream_ref(&self) -> &St {
+        &self.stream
+    }
+
+    /// Acquires a reference to the outbound sink.
+    pub fn sink_ref(&self) -> &Si {
+        &self.sink
+    }
+
+    /// Consumes this transport, returning the stream and the sink it was
+    /// made of.
+    pub fn into_inner(self) -> (St, Si) {
+        (self.stream, self.sink)
+    }
+}
+
+impl<St: Stream, Si> Stream for Duplex<St, Si> {
+    type Item = St::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<St::Item>> {
+        self.stream().poll_next(cx)
+    }
+}
+
+impl<St: FusedStream, Si> FusedStream for Duplex<St, Si> {
+    fn is_terminated(&self) -> bool {
+        self.stream.is_terminated()
+    }
+}
+
+impl<St, Si: Sink<Item>, Item> Sink<Item> for Duplex<St, Si> {
+    type Error = Si::Error;
+
+    delegate_sink!(sink, Item);
+}
@@ -0,0 +1,196 @@
+use crate::future::FutureExt;
+use crate::timer::{sleep, Sleep};
+use core::fmt;
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Beat {
+    Idle,
+    Send,
+    Flush,
+}
+
+/// Transport for the [`heartbeat`](super::TransportExt::heartbeat) method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Heartbeat<T, F> {
+    transport: T,
+    beat: F,
+    interval: Duration,
+    sleep: Sleep,
+    last_sent: Instant,
+    state: Beat,
+    // Set while the user holds the slot reserved by a successful `poll_ready`,
+    // which a heartbeat must not take.
+    reserved: bool,
+}
+
+impl<T: Unpin, F> Unpin for Heartbeat<T, F> {}
+
+impl<T, F> fmt::Debug for Heartbeat<T, F>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Heartbeat")
+            .field("transport", &self.transport)
+            .field("interval", &self.interval)
+            .field("last_sent", &self.last_sent)
+            .field("state", &self.state)
+            .field("reserved", &self.reserved)
+            .finish()
+    }
+}
+
+impl<T, F> Heartbeat<T, F> {
+    unsafe_pinned!(transport: T);
+    unsafe_unpinned!(beat: F);
+    unsafe_unpinned!(sleep: Sleep);
+    unsafe_unpinned!(last_sent: Instant);
+    unsafe_unpinned!(state: Beat);
+    unsafe_unpinned!(reserved: bool);
+
+    pub(super) fn new(transport: T, interval: Duration, beat: F) -> Self {
+        Heartbeat {
+            transport,
+            beat,
+            interval,
+            sleep: sleep(interval),
+            last_sent: Instant::now(),
+            state: Beat::Idle,
+            reserved: false,
+        }
+    }
+
+    /// Acquires a reference to the underlying transport.
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    /// Acquires a mutable reference to the underlying transport.
+    ///
+    /// Note that frames sent directly to the underlying transport are not
+    /// taken into account to delay heartbeats.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Acquires a pinned mutable reference to the underlying transport.
+    ///
+    /// Note that frames sent directly to the underlying transport are not
+    /// taken into account to delay heartbeats.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.transport()
+    }
+
+    /// Consumes this combinator, returning the underlying transport.
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    // Schedules a heartbeat if nothing was sent for a whole interval.
+    fn poll_interval(mut self: Pin<&mut Self>, cx: &mut Context<'_>) {
+        while self.as_mut().sleep().poll_unpin(cx).is_ready() {
+            let next = self.last_sent + self.interval;
+            let now = Instant::now();
+            if now >= next {
+                *self.as_mut().state() = Beat::Send;
+                *self.as_mut().last_sent() = now;
+                let interval = self.interval;
+                self.as_mut().sleep().reset(now + interval);
+            } else {
+                self.as_mut().sleep().reset(next);
+            }
+        }
+    }
+}
+
+impl<T, F, Out> Heartbeat<T, F>
+    where T: Sink<Out>,
+          F: FnMut() -> Out,
+{
+    // Moves the pending heartbeat along, if any. Errors are dropped, to be
+    // reported by the next operation on the sink instead.
+    //
+    // The heartbeat is held back while the user has a slot reserved, as what
+    // the user sends in it makes the heartbeat unnecessary anyway.
+    fn poll_beat(mut self: Pin<&mut Self>, cx: &mut Context<'_>) {
+        if self.state == Beat::Send && !self.reserved {
+            match self.as_mut().transport().poll_ready(cx) {
+                Poll::Ready(Ok(())) => {
+                    let frame = (self.as_mut().beat())();
+                    *self.as_mut().state() = match self.as_mut().transport().start_send(frame) {
+                        Ok(()) => Beat::Flush,
+                        Err(_) => Beat::Idle,
+                    };
+                }
+                Poll::Ready(Err(_)) => *self.as_mut().state() = Beat::Idle,
+                Poll::Pending => return,
+            }
+        }
+        if self.state == Beat::Flush && self.as_mut().transport().poll_flush(cx).is_ready() {
+            *self.as_mut().state() = Beat::Idle;
+        }
+    }
+}
+
+impl<T, F, Out> Stream for Heartbeat<T, F>
+    where T: Stream + Sink<Out>,
+          F: FnMut() -> Out,
+{
+    type Item = T::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T::Item>> {
+        self.as_mut().poll_interval(cx);
+        self.as_mut().poll_beat(cx);
+        self.transport().poll_next(cx)
+    }
+}
+
+impl<T, F, Out> FusedStream for Heartbeat<T, F>
+    where T: FusedStream + Sink<Out>,
+          F: FnMut() -> Out,
+{
+    fn is_terminated(&self) -> bool {
+        self.transport.is_terminated()
+    }
+}
+
+impl<T, F, Out> Sink<Out> for Heartbeat<T, F>
+    where T: Sink<Out>,
+          F: FnMut() -> Out,
+{
+    type Error = T::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().transport().poll_ready(cx))?;
+        *self.as_mut().reserved() = true;
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Out) -> Result<(), Self::Error> {
+        *self.as_mut().reserved() = false;
+        *self.as_mut().last_sent() = Instant::now();
+        // Anything sent makes a pending heartbeat unnecessary.
+        if self.state == Beat::Send {
+            *self.as_mut().state() = Beat::Idle;
+        }
+        self.transport().start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().transport().poll_flush(cx))?;
+        if self.state == Beat::Flush {
+            *self.as_mut().state() = Beat::Idle;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.transport().poll_close(cx)
+    }
+}
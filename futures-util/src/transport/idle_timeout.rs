@@ -0,0 +1,105 @@
+use crate::future::FutureExt;
+use crate::timer::{sleep, Sleep, TimedOut};
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream};
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::time::{Duration, Instant};
+
+/// Transport for the [`idle_timeout`](super::TransportExt::idle_timeout)
+/// method.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct IdleTimeout<T> {
+    transport: T,
+    timeout: Duration,
+    sleep: Sleep,
+    last_received: Instant,
+    timed_out: bool,
+}
+
+impl<T: Unpin> Unpin for IdleTimeout<T> {}
+
+impl<T> IdleTimeout<T> {
+    unsafe_pinned!(transport: T);
+    unsafe_unpinned!(sleep: Sleep);
+    unsafe_unpinned!(last_received: Instant);
+    unsafe_unpinned!(timed_out: bool);
+
+    pub(super) fn new(transport: T, timeout: Duration) -> Self {
+        IdleTimeout {
+            transport,
+            timeout,
+            sleep: sleep(timeout),
+            last_received: Instant::now(),
+            timed_out: false,
+        }
+    }
+
+    /// Acquires a reference to the underlying transport.
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    /// Acquires a mutable reference to the underlying transport.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// transport which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Acquires a pinned mutable reference to the underlying transport.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// transport which may otherwise confuse this combinator.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.transport()
+    }
+
+    /// Consumes this combinator, returning the underlying transport.
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+}
+
+impl<T: Stream> Stream for IdleTimeout<T> {
+    type Item = Result<T::Item, TimedOut>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.timed_out {
+            return Poll::Ready(None);
+        }
+
+        if let Poll::Ready(item) = self.as_mut().transport().poll_next(cx) {
+            if item.is_some() {
+                *self.as_mut().last_received() = Instant::now();
+            }
+            return Poll::Ready(item.map(Ok));
+        }
+
+        // The timer is only reset when it fires, rather than on every frame.
+        while self.as_mut().sleep().poll_unpin(cx).is_ready() {
+            let deadline = self.last_received + self.timeout;
+            if Instant::now() >= deadline {
+                *self.as_mut().timed_out() = true;
+                return Poll::Ready(Some(Err(TimedOut)));
+            }
+            self.as_mut().sleep().reset(deadline);
+        }
+        Poll::Pending
+    }
+}
+
+impl<T: FusedStream> FusedStream for IdleTimeout<T> {
+    fn is_terminated(&self) -> bool {
+        self.timed_out || self.transport.is_terminated()
+    }
+}
+
+impl<T: Sink<Item>, Item> Sink<Item> for IdleTimeout<T> {
+    type Error = T::Error;
+
+    delegate_sink!(transport, Item);
+}
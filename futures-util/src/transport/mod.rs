@@ -0,0 +1,242 @@
+//! Transports
+//!
+//! A transport is a duplex connection carrying frames: a [`Stream`] of
+//! inbound frames which is also a [`Sink`] of outbound frames, such as a
+//! socket wrapped in a codec. The [`Transport`] trait names this combination,
+//! so that protocol middleware can be written once against it rather than for
+//! each concrete connection type.
+//!
+//! Middleware layers are combinators which wrap a transport and are
+//! transports themselves. Every adapter of [`StreamExt`](crate::stream::StreamExt)
+//! forwards the [`Sink`] half of the value it wraps, and every adapter of
+//! [`SinkExt`](crate::sink::SinkExt) forwards the [`Stream`] half, so they
+//! already act as layers: for instance, logging can be added with
+//! [`inspect`](crate::stream::StreamExt::inspect) for inbound frames and
+//! [`with`](crate::sink::SinkExt::with) for outbound ones. The
+//! [`TransportExt`] trait adds layers which need both halves, such as
+//! heartbeats and idle timeouts, and [`duplex`] joins a separate stream and
+//! sink into a transport.
+//!
+//! The [`multiplex`] and [`pipeline`] modules build request/response
+//! protocols on top of a transport, and the [`streaming`] module adds
+//! messages with streaming bodies to them. On the server side, [`serve`]
+//! runs the accept loop, handling each connection in a task of its own.
+//!
+//! This module is only available when the `sink` feature of this library is
+//! activated, and it is activated by default.
+
+use futures_core::stream::Stream;
+use futures_sink::Sink;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg_attr(
+    feature = "cfg-target-has-atomic",
+    cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+)]
+#[cfg(feature = "timer")]
+use std::time::Duration;
+
+#[cfg(feature = "alloc")]
+mod checksum;
+#[cfg(feature = "alloc")]
+pub use self::checksum::{Checksum, ChecksumError, ChecksumTrailer, Crc32, TRAILER_LEN};
+
+mod duplex;
+pub use self::duplex::{duplex, Duplex};
+
+cfg_target_has_atomic! {
+    #[cfg(feature = "timer")]
+    mod heartbeat;
+    #[cfg(feature = "timer")]
+    pub use self::heartbeat::Heartbeat;
+
+    #[cfg(feature = "timer")]
+    mod idle_timeout;
+    #[cfg(feature = "timer")]
+    pub use self::idle_timeout::IdleTimeout;
+
+    #[cfg(feature = "channel")]
+    mod outbound;
+
+    #[cfg(all(feature = "channel", feature = "timer"))]
+    pub mod multiplex;
+
+    #[cfg(feature = "channel")]
+    pub mod pipeline;
+
+    #[cfg(feature = "channel")]
+    pub mod streaming;
+
+    #[cfg(all(feature = "channel", feature = "timer"))]
+    mod serve;
+    #[cfg(all(feature = "channel", feature = "timer"))]
+    pub use self::serve::{serve, Serve, ShutdownHandle};
+}
+
+/// A duplex connection: a [`Stream`] of inbound frames which is also a
+/// [`Sink`] of `Out` frames.
+///
+/// This trait is implemented for every type which is both, and is only meant
+/// to shorten bounds.
+pub trait Transport<Out>: Stream + Sink<Out> {}
+
+impl<T, Out> Transport<Out> for T where T: Stream + Sink<Out> + ?Sized {}
+
+impl<T: ?Sized> TransportExt for T where T: Stream {}
+
+/// An extension trait for transports which provides middleware layers.
+pub trait TransportExt: Stream {
+    /// Sends a heartbeat frame whenever nothing was sent on this transport
+    /// for `interval`.
+    ///
+    /// The heartbeat frames are created by calling `beat`. Outbound activity
+    /// is tracked through the [`Sink`] half of the returned transport, and
+    /// heartbeats are sent while its [`Stream`] half is being polled, which
+    /// is usually the case for the whole life of a connection.
+    ///
+    /// Errors of the underlying sink while sending a heartbeat are not
+    /// reported right away: a failed sink is expected to fail the next frame
+    /// sent through the returned transport as well.
+    ///
+    /// This method is only available when the `timer` feature of this
+    /// library is activated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::channel::mpsc;
+    /// use futures::executor::block_on;
+    /// use futures::future::{self, Either};
+    /// use futures::stream::StreamExt;
+    /// use futures::transport::{self, TransportExt};
+    /// use std::time::Duration;
+    ///
+    /// let (_inbound_tx, inbound_rx) = mpsc::unbounded::<&str>();
+    /// let (outbound_tx, mut outbound_rx) = mpsc::unbounded();
+    /// let mut transport = transport::duplex(inbound_rx, outbound_tx)
+    ///     .heartbeat(Duration::from_millis(10), || "PING");
+    ///
+    /// // Nothing is sent while waiting for an inbound frame, so a heartbeat
+    /// // goes out.
+    /// match block_on(future::select(transport.next(), outbound_rx.next())) {
+    ///     Either::Right((frame, _)) => assert_eq!(frame, Some("PING")),
+    ///     Either::Left(_) => unreachable!(),
+    /// }
+    /// ```
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "timer")]
+    fn heartbeat<F, Out>(self, interval: Duration, beat: F) -> Heartbeat<Self, F>
+        where F: FnMut() -> Out,
+              Self: Sink<Out> + Sized,
+    {
+        Heartbeat::new(self, interval, beat)
+    }
+
+    /// Ends the inbound half of this transport with an error when no frame
+    /// was received for `timeout`.
+    ///
+    /// The returned transport yields inbound frames wrapped in `Ok`. Once
+    /// `timeout` elapses without a frame, it yields a single
+    /// `Err(TimedOut)` and then ends. Its [`Sink`] half keeps working, for
+    /// example to send a goodbye frame before closing.
+    ///
+    /// This method is only available when the `timer` feature of this
+    /// library is activated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::channel::mpsc;
+    /// use futures::executor::block_on;
+    /// use futures::stream::StreamExt;
+    /// use futures::timer::TimedOut;
+    /// use futures::transport::{self, TransportExt};
+    /// use std::time::Duration;
+    ///
+    /// let (inbound_tx, inbound_rx) = mpsc::unbounded();
+    /// let (outbound_tx, _outbound_rx) = mpsc::unbounded::<()>();
+    /// let mut transport = transport::duplex(inbound_rx, outbound_tx)
+    ///     .idle_timeout(Duration::from_millis(10));
+    ///
+    /// inbound_tx.unbounded_send(1).unwrap();
+    /// assert_eq!(block_on(transport.next()), Some(Ok(1)));
+    /// assert_eq!(block_on(transport.next()), Some(Err(TimedOut)));
+    /// assert_eq!(block_on(transport.next()), None);
+    /// ```
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "timer")]
+    fn idle_timeout(self, timeout: Duration) -> IdleTimeout<Self>
+        where Self: Sized,
+    {
+        IdleTimeout::new(self, timeout)
+    }
+
+    /// Appends a CRC-32 trailer to each outbound frame, and verifies and
+    /// strips the trailer of each inbound frame.
+    ///
+    /// Frames are byte vectors. The trailer is the checksum of the frame, in
+    /// big-endian order, over [`TRAILER_LEN`] bytes. The returned transport
+    /// yields inbound frames wrapped in `Ok`, and a [`ChecksumError`] for
+    /// each frame which is corrupted or too short, after which it goes on
+    /// with the next frame. Use
+    /// [`checksum_trailer_with`](TransportExt::checksum_trailer_with) for
+    /// another checksum.
+    ///
+    /// This method is only available when the `std` or `alloc` feature of this
+    /// library is activated, and it is activated by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::channel::mpsc;
+    /// use futures::executor::block_on;
+    /// use futures::sink::SinkExt;
+    /// use futures::stream::StreamExt;
+    /// use futures::transport::{self, ChecksumError, TransportExt};
+    ///
+    /// let (inbound_tx, inbound_rx) = mpsc::unbounded();
+    /// let (outbound_tx, mut outbound_rx) = mpsc::unbounded();
+    /// let mut transport = transport::duplex(inbound_rx, outbound_tx).checksum_trailer();
+    ///
+    /// block_on(transport.send(b"hello".to_vec())).unwrap();
+    /// let mut frame = block_on(outbound_rx.next()).unwrap();
+    /// assert_eq!(frame.len(), 5 + transport::TRAILER_LEN);
+    ///
+    /// // The frame comes back intact once, then corrupted.
+    /// inbound_tx.unbounded_send(frame.clone()).unwrap();
+    /// frame[0] = b'j';
+    /// inbound_tx.unbounded_send(frame).unwrap();
+    /// assert_eq!(block_on(transport.next()), Some(Ok(b"hello".to_vec())));
+    /// match block_on(transport.next()) {
+    ///     Some(Err(ChecksumError::Mismatch { .. })) => {}
+    ///     other => panic!("unexpected frame: {:?}", other),
+    /// }
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn checksum_trailer(self) -> ChecksumTrailer<Self, Crc32>
+        where Self: Stream<Item = Vec<u8>> + Sink<Vec<u8>> + Sized,
+    {
+        ChecksumTrailer::new(self, Crc32)
+    }
+
+    /// Appends a trailer computed by `checksum` to each outbound frame, and
+    /// verifies and strips the trailer of each inbound frame.
+    ///
+    /// See [`checksum_trailer`](TransportExt::checksum_trailer) for details.
+    ///
+    /// This method is only available when the `std` or `alloc` feature of this
+    /// library is activated, and it is activated by default.
+    #[cfg(feature = "alloc")]
+    fn checksum_trailer_with<C>(self, checksum: C) -> ChecksumTrailer<Self, C>
+        where C: Checksum,
+              Self: Stream<Item = Vec<u8>> + Sink<Vec<u8>> + Sized,
+    {
+        ChecksumTrailer::new(self, checksum)
+    }
+}
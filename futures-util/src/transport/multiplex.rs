@@ -0,0 +1,434 @@
+//! Request/response multiplexing over a transport.
+//!
+//! In a multiplexed protocol, every request carries an id which the matching
+//! response repeats, so that many requests can be in flight on a single
+//! connection and responses can come back in any order. The helpers in this
+//! module implement both ends of such a protocol on top of a transport of
+//! `(id, frame)` pairs:
+//!
+//! - [`client`] splits a transport into a clonable [`Client`] handle, whose
+//!   [`call`](Client::call) method sends a request and returns a future of
+//!   its response, and a [`ClientTask`] future which drives the connection.
+//! - [`server`] turns a transport into a stream of requests, each paired with
+//!   a [`Responder`] to send its response with.
+//!
+//! Inbound frames are read as a [`TryStream`], so that errors of the
+//! connection, which must be the same as those of its sink, end the helpers.
+//! Adapting a transport to the `(id, frame)` shape is left to stream and sink
+//! adapters such as [`map_ok`](crate::try_stream::TryStreamExt::map_ok) and
+//! [`with`](crate::sink::SinkExt::with).
+
+use super::outbound::{Outbound, Sending};
+use crate::future::{CorrelatedResponse, Correlator, FutureExt};
+use crate::stream::StreamExt;
+use core::fmt;
+use core::pin::Pin;
+use futures_channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures_core::future::{FusedFuture, Future};
+use futures_core::stream::{Stream, TryStream};
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+pub use futures_channel::oneshot::Canceled;
+
+/// Id of a request in a multiplexed protocol.
+pub type RequestId = u64;
+
+/// Splits `transport` into the two halves of a multiplexing client.
+///
+/// The returned [`ClientTask`] must be polled, usually by spawning it, for
+/// requests to be sent and responses received. Ids are assigned to requests
+/// in increasing order, starting from 0.
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::mpsc;
+/// use futures::executor::block_on;
+/// use futures::future::{self, FutureExt};
+/// use futures::stream::StreamExt;
+/// use futures::transport::{self, multiplex};
+///
+/// let (requests_tx, requests_rx) = mpsc::unbounded::<(u64, &str)>();
+/// let (responses_tx, responses_rx) = mpsc::unbounded();
+/// let (client, task) = multiplex::client(transport::duplex(responses_rx.map(Ok), requests_tx));
+///
+/// // A server answering the requests in reverse order.
+/// let server = requests_rx.take(2).collect::<Vec<_>>().map(move |mut requests| {
+///     while let Some((id, request)) = requests.pop() {
+///         responses_tx.unbounded_send((id, request.len())).unwrap();
+///     }
+/// });
+///
+/// let calls = future::join(client.call("a"), client.call("bcd"));
+/// let (responses, _, _) = block_on(future::join3(calls, task, server));
+/// assert_eq!(responses, (Ok(1), Ok(3)));
+/// ```
+pub fn client<T, Req, Resp>(transport: T) -> (Client<Req, Resp>, ClientTask<T, Req, Resp>)
+    where T: TryStream<Ok = (RequestId, Resp)> + Sink<(RequestId, Req), Error = <T as TryStream>::Error>,
+{
+    let (tx, rx) = mpsc::unbounded();
+    let correlator = Correlator::new();
+    let client = Client {
+        requests: tx,
+        correlator: correlator.clone(),
+        next_id: Arc::new(AtomicUsize::new(0)),
+    };
+    let task = ClientTask {
+        transport,
+        requests: rx,
+        outbound: Outbound::new(),
+        correlator,
+        done: false,
+    };
+    (client, task)
+}
+
+/// A handle to send requests on a multiplexed connection.
+///
+/// This is created by the [`client`] function. It can be cloned to send
+/// requests from several tasks.
+pub struct Client<Req, Resp> {
+    requests: UnboundedSender<(RequestId, Req)>,
+    correlator: Correlator<RequestId, Resp, ()>,
+    next_id: Arc<AtomicUsize>,
+}
+
+impl<Req, Resp> Clone for Client<Req, Resp> {
+    fn clone(&self) -> Self {
+        Client {
+            requests: self.requests.clone(),
+            correlator: self.correlator.clone(),
+            next_id: self.next_id.clone(),
+        }
+    }
+}
+
+impl<Req, Resp> fmt::Debug for Client<Req, Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("pending", &self.correlator.len())
+            .finish()
+    }
+}
+
+impl<Req, Resp> Client<Req, Resp> {
+    /// Sends `request`, returning a future of its response.
+    ///
+    /// The future resolves to [`Canceled`] if the connection ends before the
+    /// response is received. Dropping the future does not take back the
+    /// request, but its response is then ignored.
+    pub fn call(&self, request: Req) -> Call<Resp> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) as RequestId;
+        // Registered before the request is sent, so that its response cannot
+        // arrive first.
+        let response = self.correlator.register(id);
+        match self.requests.unbounded_send((id, request)) {
+            Ok(()) => Call { response: Some(response) },
+            Err(_) => {
+                // Unregisters the request, which will never be answered.
+                drop(response);
+                Call { response: None }
+            }
+        }
+    }
+}
+
+/// Future for the [`call`](Client::call) method.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Call<Resp> {
+    response: Option<CorrelatedResponse<RequestId, Resp, ()>>,
+}
+
+impl<Resp> Unpin for Call<Resp> {}
+
+impl<Resp> fmt::Debug for Call<Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Call")
+            .field("id", &self.response.as_ref().map(|r| *r.id()))
+            .finish()
+    }
+}
+
+impl<Resp> Future for Call<Resp> {
+    type Output = Result<Resp, Canceled>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = match &mut self.response {
+            Some(response) => ready!(response.poll_unpin(cx)).map_err(|_| Canceled),
+            None => Err(Canceled),
+        };
+        self.response = None;
+        Poll::Ready(result)
+    }
+}
+
+/// Future driving the connection of a multiplexing client.
+///
+/// This is created by the [`client`] function. It sends the requests made
+/// through the [`Client`] handles and hands the responses over to their
+/// callers. It resolves once the inbound stream ends or fails, or once every
+/// `Client` is dropped and no call is pending anymore, in which case the
+/// transport is closed. Calls which are still pending then resolve to
+/// [`Canceled`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ClientTask<T, Req, Resp> {
+    transport: T,
+    requests: UnboundedReceiver<(RequestId, Req)>,
+    outbound: Outbound<(RequestId, Req)>,
+    correlator: Correlator<RequestId, Resp, ()>,
+    done: bool,
+}
+
+impl<T: Unpin, Req, Resp> Unpin for ClientTask<T, Req, Resp> {}
+
+impl<T: fmt::Debug, Req, Resp> fmt::Debug for ClientTask<T, Req, Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientTask")
+            .field("transport", &self.transport)
+            .field("pending", &self.correlator.len())
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<T, Req, Resp> ClientTask<T, Req, Resp> {
+    unsafe_pinned!(transport: T);
+    unsafe_unpinned!(requests: UnboundedReceiver<(RequestId, Req)>);
+    unsafe_unpinned!(outbound: Outbound<(RequestId, Req)>);
+    unsafe_unpinned!(done: bool);
+
+    fn finish(mut self: Pin<&mut Self>) {
+        *self.as_mut().done() = true;
+        self.as_mut().requests().close();
+        self.as_mut().outbound().clear();
+        self.correlator.fail_all(());
+    }
+}
+
+impl<T, Req, Resp> Sending<(RequestId, Req)> for ClientTask<T, Req, Resp>
+    where T: Sink<(RequestId, Req)>,
+{
+    type Sink = T;
+
+    fn outbound(self: Pin<&mut Self>) -> &mut Outbound<(RequestId, Req)> {
+        self.outbound()
+    }
+
+    fn sink(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.transport()
+    }
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<(RequestId, Req)>> {
+        self.requests().poll_next_unpin(cx)
+    }
+}
+
+impl<T, Req, Resp> FusedFuture for ClientTask<T, Req, Resp>
+    where T: TryStream<Ok = (RequestId, Resp)> + Sink<(RequestId, Req), Error = <T as TryStream>::Error>,
+{
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+impl<T, Req, Resp> Future for ClientTask<T, Req, Resp>
+    where T: TryStream<Ok = (RequestId, Resp)> + Sink<(RequestId, Req), Error = <T as TryStream>::Error>,
+{
+    type Output = Result<(), <T as TryStream>::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Err(e) = Outbound::poll_send(self.as_mut(), cx) {
+            self.finish();
+            return Poll::Ready(Err(e));
+        }
+
+        loop {
+            match self.as_mut().transport().try_poll_next(cx) {
+                Poll::Ready(Some(Ok((id, response)))) => {
+                    // The caller may have given up on the response already.
+                    let _ = self.correlator.complete(&id, response);
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    self.finish();
+                    return Poll::Ready(Err(e));
+                }
+                Poll::Ready(None) => {
+                    self.finish();
+                    return Poll::Ready(Ok(()));
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        if self.outbound.is_finished() && self.correlator.is_empty() {
+            let res = ready!(self.as_mut().transport().poll_close(cx));
+            self.finish();
+            return Poll::Ready(res);
+        }
+        Poll::Pending
+    }
+}
+
+impl<T, Req, Resp> Drop for ClientTask<T, Req, Resp> {
+    fn drop(&mut self) {
+        self.correlator.fail_all(());
+    }
+}
+
+/// Turns `transport` into a stream of the requests received by a
+/// multiplexing server.
+///
+/// Each request is paired with a [`Responder`], which sends its response
+/// with the request's id. Responses are sent while the returned stream is
+/// polled. Once the inbound stream ends, the returned stream keeps sending
+/// responses until every `Responder` is used or dropped, and only then ends.
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::mpsc;
+/// use futures::executor::block_on;
+/// use futures::stream::StreamExt;
+/// use futures::transport::{self, multiplex};
+///
+/// let (requests_tx, requests_rx) = mpsc::unbounded::<(u64, &str)>();
+/// let (responses_tx, responses_rx) = mpsc::unbounded();
+/// let server = multiplex::server(transport::duplex(requests_rx.map(Ok), responses_tx));
+///
+/// requests_tx.unbounded_send((7, "ping")).unwrap();
+/// drop(requests_tx);
+///
+/// let served = server.for_each(|request| {
+///     let (request, responder) = request.unwrap();
+///     assert_eq!(request, "ping");
+///     responder.respond("pong").unwrap();
+///     futures::future::ready(())
+/// });
+/// block_on(served);
+/// assert_eq!(block_on(responses_rx.collect::<Vec<_>>()), vec![(7, "pong")]);
+/// ```
+pub fn server<T, Req, Resp>(transport: T) -> Server<T, Resp>
+    where T: TryStream<Ok = (RequestId, Req)> + Sink<(RequestId, Resp), Error = <T as TryStream>::Error>,
+{
+    let (tx, rx) = mpsc::unbounded();
+    Server {
+        transport,
+        responses: Some(tx),
+        queued: rx,
+        outbound: Outbound::new(),
+    }
+}
+
+/// Stream for the [`server`] function.
+#[must_use = "streams do nothing unless polled"]
+pub struct Server<T, Resp> {
+    transport: T,
+    // Dropped once the inbound stream ends, so that the outbound queue ends
+    // with the last `Responder`.
+    responses: Option<UnboundedSender<(RequestId, Resp)>>,
+    queued: UnboundedReceiver<(RequestId, Resp)>,
+    outbound: Outbound<(RequestId, Resp)>,
+}
+
+impl<T: Unpin, Resp> Unpin for Server<T, Resp> {}
+
+impl<T: fmt::Debug, Resp> fmt::Debug for Server<T, Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Server")
+            .field("transport", &self.transport)
+            .finish()
+    }
+}
+
+impl<T, Resp> Server<T, Resp> {
+    unsafe_pinned!(transport: T);
+    unsafe_unpinned!(responses: Option<UnboundedSender<(RequestId, Resp)>>);
+    unsafe_unpinned!(queued: UnboundedReceiver<(RequestId, Resp)>);
+    unsafe_unpinned!(outbound: Outbound<(RequestId, Resp)>);
+}
+
+impl<T, Resp> Sending<(RequestId, Resp)> for Server<T, Resp>
+    where T: Sink<(RequestId, Resp)>,
+{
+    type Sink = T;
+
+    fn outbound(self: Pin<&mut Self>) -> &mut Outbound<(RequestId, Resp)> {
+        self.outbound()
+    }
+
+    fn sink(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.transport()
+    }
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<(RequestId, Resp)>> {
+        self.queued().poll_next_unpin(cx)
+    }
+}
+
+impl<T, Req, Resp> Stream for Server<T, Resp>
+    where T: TryStream<Ok = (RequestId, Req)> + Sink<(RequestId, Resp), Error = <T as TryStream>::Error>,
+{
+    type Item = Result<(Req, Responder<Resp>), <T as TryStream>::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Err(e) = Outbound::poll_send(self.as_mut(), cx) {
+                return Poll::Ready(Some(Err(e)));
+            }
+
+            if self.responses.is_none() {
+                if self.outbound.is_finished() {
+                    return Poll::Ready(None);
+                }
+                return Poll::Pending;
+            }
+            match ready!(self.as_mut().transport().try_poll_next(cx)) {
+                Some(Ok((id, request))) => {
+                    let responses = self.responses.as_ref().unwrap().clone();
+                    let responder = Responder { id, responses };
+                    return Poll::Ready(Some(Ok((request, responder))));
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                // Go around to notice when the outbound queue ends.
+                None => *self.as_mut().responses() = None,
+            }
+        }
+    }
+}
+
+/// A means of sending the response to a request received by a multiplexing
+/// server.
+///
+/// This is created by the [`Server`] stream.
+pub struct Responder<Resp> {
+    id: RequestId,
+    responses: UnboundedSender<(RequestId, Resp)>,
+}
+
+impl<Resp> fmt::Debug for Responder<Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Responder")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl<Resp> Responder<Resp> {
+    /// Returns the id of the request to respond to.
+    pub fn id(&self) -> RequestId {
+        self.id
+    }
+
+    /// Queues `response` to be sent by the [`Server`] stream.
+    ///
+    /// Returns the response back if the server was dropped.
+    pub fn respond(self, response: Resp) -> Result<(), Resp> {
+        self.responses
+            .unbounded_send((self.id, response))
+            .map_err(|e| e.into_inner().1)
+    }
+}
@@ -0,0 +1,86 @@
+use core::pin::Pin;
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+
+// State of the frames waiting to be sent on the sink half of a transport,
+// shared by the protocol helpers.
+#[derive(Debug)]
+pub(super) struct Outbound<Item> {
+    buffered: Option<Item>,
+    done: bool,
+    unflushed: bool,
+}
+
+// A protocol helper sending frames with an `Outbound`, whose parts are
+// reached through pin projections.
+pub(super) trait Sending<Item> {
+    type Sink: Sink<Item> + ?Sized;
+
+    fn outbound(self: Pin<&mut Self>) -> &mut Outbound<Item>;
+
+    fn sink(self: Pin<&mut Self>) -> Pin<&mut Self::Sink>;
+
+    // Takes the next frame to send, or `None` once there are no more.
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Item>>;
+}
+
+impl<Item> Outbound<Item> {
+    pub(super) fn new() -> Self {
+        Outbound { buffered: None, done: false, unflushed: false }
+    }
+
+    // Sends the frames of `helper` to its sink until either side is not
+    // ready, then flushes it.
+    pub(super) fn poll_send<H>(
+        mut helper: Pin<&mut H>,
+        cx: &mut Context<'_>,
+    ) -> Result<(), <H::Sink as Sink<Item>>::Error>
+        where H: Sending<Item> + ?Sized,
+    {
+        loop {
+            if helper.as_mut().outbound().buffered.is_some() {
+                match helper.as_mut().sink().poll_ready(cx) {
+                    Poll::Ready(Ok(())) => {
+                        let item = helper.as_mut().outbound().buffered.take().unwrap();
+                        helper.as_mut().sink().start_send(item)?;
+                        helper.as_mut().outbound().unflushed = true;
+                    }
+                    Poll::Ready(Err(e)) => return Err(e),
+                    Poll::Pending => break,
+                }
+            }
+            if helper.as_mut().outbound().done {
+                break;
+            }
+            match helper.as_mut().poll_frame(cx) {
+                Poll::Ready(Some(item)) => helper.as_mut().outbound().buffered = Some(item),
+                Poll::Ready(None) => helper.as_mut().outbound().done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        if helper.as_mut().outbound().unflushed {
+            match helper.as_mut().sink().poll_flush(cx) {
+                Poll::Ready(Ok(())) => helper.as_mut().outbound().unflushed = false,
+                Poll::Ready(Err(e)) => return Err(e),
+                Poll::Pending => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Whether there are no more frames and the last one was flushed.
+    pub(super) fn is_finished(&self) -> bool {
+        self.done && self.buffered.is_none() && !self.unflushed
+    }
+
+    // Whether a frame taken from the helper is waiting to be sent.
+    pub(super) fn has_buffered(&self) -> bool {
+        self.buffered.is_some()
+    }
+
+    // Drops the frame waiting to be sent, if any.
+    pub(super) fn clear(&mut self) {
+        self.buffered = None;
+    }
+}
@@ -0,0 +1,436 @@
+//! In-order request/response pipelining over a transport.
+//!
+//! In a pipelined protocol, such as HTTP/1.1 or Redis, several requests can
+//! be in flight on a single connection, but frames carry no id: responses are
+//! sent in the order of the requests they answer. The helpers in this module
+//! implement both ends of such a protocol on top of a transport, and bound
+//! the number of requests in flight to a given depth:
+//!
+//! - [`client`] splits a transport into a clonable [`Client`] handle, whose
+//!   [`call`](Client::call) method sends a request and returns a future of
+//!   its response, and a [`ClientTask`] future which drives the connection.
+//! - [`server`] turns a transport into a stream of requests, each paired with
+//!   a [`Responder`] to send its response with. Responses are sent in request
+//!   order, whatever the order in which they are given.
+//!
+//! Inbound frames are read as a [`TryStream`], so that errors of the
+//! connection, which must be the same as those of its sink, end the helpers.
+//! Protocols which tag requests with ids are better served by the
+//! [`multiplex`](super::multiplex) module.
+
+use super::outbound::{Outbound, Sending};
+use crate::future::FutureExt;
+use crate::stream::StreamExt;
+use core::fmt;
+use core::pin::Pin;
+use futures_channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures_channel::oneshot::{self, Receiver, Sender};
+use futures_core::future::{FusedFuture, Future};
+use futures_core::stream::{Stream, TryStream};
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::collections::VecDeque;
+
+pub use futures_channel::oneshot::Canceled;
+
+/// Splits `transport` into the two halves of a pipelining client, which
+/// keeps at most `depth` requests in flight.
+///
+/// The returned [`ClientTask`] must be polled, usually by spawning it, for
+/// requests to be sent and responses received. Further requests are queued
+/// until responses come back for earlier ones. Responses received while no
+/// request is in flight are dropped.
+///
+/// # Panics
+///
+/// This function will panic if `depth` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::mpsc;
+/// use futures::executor::block_on;
+/// use futures::future;
+/// use futures::stream::StreamExt;
+/// use futures::transport::{self, pipeline};
+///
+/// let (requests_tx, requests_rx) = mpsc::unbounded::<&str>();
+/// let (responses_tx, responses_rx) = mpsc::unbounded();
+/// let (client, task) = pipeline::client(transport::duplex(responses_rx.map(Ok), requests_tx), 2);
+///
+/// // A server answering the requests in order.
+/// let server = requests_rx.take(2).for_each(move |request| {
+///     responses_tx.unbounded_send(request.len()).unwrap();
+///     future::ready(())
+/// });
+///
+/// let calls = future::join(client.call("a"), client.call("bcd"));
+/// drop(client);
+/// let (responses, res, ()) = block_on(future::join3(calls, task, server));
+/// assert_eq!(responses, (Ok(1), Ok(3)));
+/// assert_eq!(res, Ok(()));
+/// ```
+pub fn client<T, Req, Resp>(transport: T, depth: usize) -> (Client<Req, Resp>, ClientTask<T, Req, Resp>)
+    where T: TryStream<Ok = Resp> + Sink<Req, Error = <T as TryStream>::Error>,
+{
+    assert!(depth > 0);
+
+    let (tx, rx) = mpsc::unbounded();
+    let task = ClientTask {
+        transport,
+        calls: rx,
+        outbound: Outbound::new(),
+        in_flight: VecDeque::with_capacity(depth),
+        depth,
+        done: false,
+    };
+    (Client { calls: tx }, task)
+}
+
+/// A handle to send requests on a pipelined connection.
+///
+/// This is created by the [`client`] function. It can be cloned to send
+/// requests from several tasks, in which case requests are sent in the order
+/// in which [`call`](Client::call) is called.
+pub struct Client<Req, Resp> {
+    calls: UnboundedSender<(Req, Sender<Resp>)>,
+}
+
+impl<Req, Resp> Clone for Client<Req, Resp> {
+    fn clone(&self) -> Self {
+        Client { calls: self.calls.clone() }
+    }
+}
+
+impl<Req, Resp> fmt::Debug for Client<Req, Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client").finish()
+    }
+}
+
+impl<Req, Resp> Client<Req, Resp> {
+    /// Queues `request` to be sent, returning a future of its response.
+    ///
+    /// The future resolves to [`Canceled`] if the connection ends before the
+    /// response is received. Dropping the future does not take back the
+    /// request, but its response is then ignored.
+    pub fn call(&self, request: Req) -> Call<Resp> {
+        let (tx, rx) = oneshot::channel();
+        // On failure, `tx` is dropped along with the request, which cancels
+        // `rx`.
+        let _ = self.calls.unbounded_send((request, tx));
+        Call { rx }
+    }
+}
+
+/// Future for the [`call`](Client::call) method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Call<Resp> {
+    rx: Receiver<Resp>,
+}
+
+impl<Resp> Unpin for Call<Resp> {}
+
+impl<Resp> Future for Call<Resp> {
+    type Output = Result<Resp, Canceled>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.rx.poll_unpin(cx)
+    }
+}
+
+/// Future driving the connection of a pipelining client.
+///
+/// This is created by the [`client`] function. It sends the requests made
+/// through the [`Client`] handles and hands the responses over to their
+/// callers. It resolves once the inbound stream ends or fails, or once every
+/// `Client` is dropped and no call is in flight anymore, in which case the
+/// transport is closed. Calls which are still pending then resolve to
+/// [`Canceled`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ClientTask<T, Req, Resp> {
+    transport: T,
+    calls: UnboundedReceiver<(Req, Sender<Resp>)>,
+    outbound: Outbound<Req>,
+    in_flight: VecDeque<Sender<Resp>>,
+    depth: usize,
+    done: bool,
+}
+
+impl<T: Unpin, Req, Resp> Unpin for ClientTask<T, Req, Resp> {}
+
+impl<T: fmt::Debug, Req, Resp> fmt::Debug for ClientTask<T, Req, Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientTask")
+            .field("transport", &self.transport)
+            .field("in_flight", &self.in_flight.len())
+            .field("depth", &self.depth)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<T, Req, Resp> ClientTask<T, Req, Resp> {
+    unsafe_pinned!(transport: T);
+    unsafe_unpinned!(calls: UnboundedReceiver<(Req, Sender<Resp>)>);
+    unsafe_unpinned!(outbound: Outbound<Req>);
+    unsafe_unpinned!(in_flight: VecDeque<Sender<Resp>>);
+    unsafe_unpinned!(done: bool);
+
+    fn finish(mut self: Pin<&mut Self>) {
+        *self.as_mut().done() = true;
+        self.as_mut().calls().close();
+        self.as_mut().outbound().clear();
+        self.in_flight().clear();
+    }
+}
+
+impl<T, Req, Resp> Sending<Req> for ClientTask<T, Req, Resp>
+    where T: Sink<Req>,
+{
+    type Sink = T;
+
+    fn outbound(self: Pin<&mut Self>) -> &mut Outbound<Req> {
+        self.outbound()
+    }
+
+    fn sink(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.transport()
+    }
+
+    // Takes the next request, unless the pipeline is full.
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Req>> {
+        if self.in_flight.len() == self.depth {
+            return Poll::Pending;
+        }
+        match ready!(self.as_mut().calls().poll_next_unpin(cx)) {
+            Some((request, tx)) => {
+                self.as_mut().in_flight().push_back(tx);
+                Poll::Ready(Some(request))
+            }
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+impl<T, Req, Resp> FusedFuture for ClientTask<T, Req, Resp>
+    where T: TryStream<Ok = Resp> + Sink<Req, Error = <T as TryStream>::Error>,
+{
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+impl<T, Req, Resp> Future for ClientTask<T, Req, Resp>
+    where T: TryStream<Ok = Resp> + Sink<Req, Error = <T as TryStream>::Error>,
+{
+    type Output = Result<(), <T as TryStream>::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            if let Err(e) = Outbound::poll_send(self.as_mut(), cx) {
+                self.finish();
+                return Poll::Ready(Err(e));
+            }
+
+            match self.as_mut().transport().try_poll_next(cx) {
+                Poll::Ready(Some(Ok(response))) => {
+                    // The caller may have given up on the response already.
+                    if let Some(tx) = self.as_mut().in_flight().pop_front() {
+                        let _ = tx.send(response);
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    self.finish();
+                    return Poll::Ready(Err(e));
+                }
+                Poll::Ready(None) => {
+                    self.finish();
+                    return Poll::Ready(Ok(()));
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        if self.outbound.is_finished() && self.in_flight.is_empty() {
+            let res = ready!(self.as_mut().transport().poll_close(cx));
+            self.finish();
+            return Poll::Ready(res);
+        }
+        Poll::Pending
+    }
+}
+
+/// Turns `transport` into a stream of the requests received by a pipelining
+/// server, which keeps at most `depth` requests in flight.
+///
+/// Each request is paired with a [`Responder`]. Responses are sent in the
+/// order of the requests while the returned stream is polled, so a response
+/// given early waits for those of earlier requests. No further request is
+/// read while `depth` of them wait for their response to be sent. Once the
+/// inbound stream ends, the returned stream keeps sending responses until
+/// every `Responder` is used or dropped, and only then ends.
+///
+/// A request whose `Responder` is dropped gets no response, which most
+/// pipelined protocols cannot recover from: respond with an error frame
+/// instead.
+///
+/// # Panics
+///
+/// This function will panic if `depth` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::mpsc;
+/// use futures::executor::block_on;
+/// use futures::stream::StreamExt;
+/// use futures::transport::{self, pipeline};
+///
+/// let (requests_tx, requests_rx) = mpsc::unbounded::<&str>();
+/// let (responses_tx, responses_rx) = mpsc::unbounded();
+/// let mut server = pipeline::server(transport::duplex(requests_rx.map(Ok), responses_tx), 2);
+///
+/// requests_tx.unbounded_send("first").unwrap();
+/// requests_tx.unbounded_send("second").unwrap();
+/// drop(requests_tx);
+///
+/// let (_, first) = block_on(server.next()).unwrap().unwrap();
+/// let (_, second) = block_on(server.next()).unwrap().unwrap();
+/// second.respond(2).unwrap();
+/// first.respond(1).unwrap();
+/// assert!(block_on(server.next()).is_none());
+///
+/// drop(server);
+/// assert_eq!(block_on(responses_rx.collect::<Vec<_>>()), vec![1, 2]);
+/// ```
+pub fn server<T, Req, Resp>(transport: T, depth: usize) -> Server<T, Resp>
+    where T: TryStream<Ok = Req> + Sink<Resp, Error = <T as TryStream>::Error>,
+{
+    assert!(depth > 0);
+
+    Server {
+        transport,
+        pending: VecDeque::with_capacity(depth),
+        outbound: Outbound::new(),
+        depth,
+        inbound_done: false,
+    }
+}
+
+/// Stream for the [`server`] function.
+#[must_use = "streams do nothing unless polled"]
+pub struct Server<T, Resp> {
+    transport: T,
+    pending: VecDeque<Receiver<Resp>>,
+    outbound: Outbound<Resp>,
+    depth: usize,
+    inbound_done: bool,
+}
+
+impl<T: Unpin, Resp> Unpin for Server<T, Resp> {}
+
+impl<T: fmt::Debug, Resp> fmt::Debug for Server<T, Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Server")
+            .field("transport", &self.transport)
+            .field("in_flight", &self.pending.len())
+            .field("depth", &self.depth)
+            .finish()
+    }
+}
+
+impl<T, Resp> Server<T, Resp> {
+    unsafe_pinned!(transport: T);
+    unsafe_unpinned!(pending: VecDeque<Receiver<Resp>>);
+    unsafe_unpinned!(outbound: Outbound<Resp>);
+    unsafe_unpinned!(inbound_done: bool);
+}
+
+impl<T, Resp> Sending<Resp> for Server<T, Resp>
+    where T: Sink<Resp>,
+{
+    type Sink = T;
+
+    fn outbound(self: Pin<&mut Self>) -> &mut Outbound<Resp> {
+        self.outbound()
+    }
+
+    fn sink(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.transport()
+    }
+
+    // Takes the response to the earliest request, once it is given. There are
+    // no more responses once the inbound stream ended and every request was
+    // answered.
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Resp>> {
+        while let Some(rx) = self.as_mut().pending().front_mut() {
+            let res = ready!(rx.poll_unpin(cx));
+            self.as_mut().pending().pop_front();
+            if let Ok(response) = res {
+                return Poll::Ready(Some(response));
+            }
+        }
+        if self.inbound_done {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<T, Req, Resp> Stream for Server<T, Resp>
+    where T: TryStream<Ok = Req> + Sink<Resp, Error = <T as TryStream>::Error>,
+{
+    type Item = Result<(Req, Responder<Resp>), <T as TryStream>::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Err(e) = Outbound::poll_send(self.as_mut(), cx) {
+                return Poll::Ready(Some(Err(e)));
+            }
+
+            if self.inbound_done {
+                if self.outbound.is_finished() {
+                    return Poll::Ready(None);
+                }
+                return Poll::Pending;
+            }
+            // A response waiting to be sent still counts against the depth.
+            if self.pending.len() + self.outbound.has_buffered() as usize == self.depth {
+                return Poll::Pending;
+            }
+            match ready!(self.as_mut().transport().try_poll_next(cx)) {
+                Some(Ok(request)) => {
+                    let (tx, rx) = oneshot::channel();
+                    self.as_mut().pending().push_back(rx);
+                    return Poll::Ready(Some(Ok((request, Responder { tx }))));
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                // Go around to notice when the last response is sent.
+                None => *self.as_mut().inbound_done() = true,
+            }
+        }
+    }
+}
+
+/// A means of sending the response to a request received by a pipelining
+/// server.
+///
+/// This is created by the [`Server`] stream.
+#[derive(Debug)]
+pub struct Responder<Resp> {
+    tx: Sender<Resp>,
+}
+
+impl<Resp> Responder<Resp> {
+    /// Queues `response` to be sent by the [`Server`] stream once the
+    /// responses to earlier requests are sent.
+    ///
+    /// Returns the response back if the server was dropped.
+    pub fn respond(self, response: Resp) -> Result<(), Resp> {
+        self.tx.send(response)
+    }
+}
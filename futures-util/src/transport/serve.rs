@@ -0,0 +1,284 @@
+use crate::future::{FutureExt, RemoteHandle};
+use crate::stream::{FuturesUnordered, StreamExt};
+use crate::task::{AtomicWaker, SpawnExt};
+use crate::timer::{sleep, Sleep};
+use core::fmt;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use futures_core::future::{FusedFuture, Future};
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll, Spawn, SpawnError};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Serves the connections of `incoming`, spawning a task on `spawner` to
+/// handle each of them with `handler`.
+///
+/// The returned future accepts connections until `incoming` ends or a
+/// shutdown is requested through a [`ShutdownHandle`]. It then waits for the
+/// connections in flight to be handled before resolving. The number of
+/// connections handled at once can be bounded with
+/// [`max_connections`](Serve::max_connections), and the wait for the last
+/// ones with [`drain_timeout`](Serve::drain_timeout).
+///
+/// A panic in `handler` or the future it returns only ends the task of its
+/// connection. The returned future resolves to an error if a task cannot be
+/// spawned, in which case the connections in flight are canceled.
+///
+/// This function is only available when the `std`, `channel` and `timer`
+/// features of this library are activated, and they are activated by default.
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::mpsc;
+/// use futures::executor::{block_on, ThreadPool};
+/// use futures::future;
+/// use futures::stream::{self, StreamExt};
+/// use futures::transport;
+///
+/// let pool = ThreadPool::new().unwrap();
+/// let (tx, rx) = mpsc::unbounded();
+///
+/// // Connections are plain numbers here, and handling one echoes it back.
+/// let incoming = stream::iter(1..=3);
+/// let server = transport::serve(incoming, pool, move |conn| {
+///     tx.unbounded_send(conn).unwrap();
+///     future::ready(())
+/// })
+/// .max_connections(2);
+///
+/// block_on(server).unwrap();
+/// let mut handled = block_on(rx.collect::<Vec<_>>());
+/// handled.sort();
+/// assert_eq!(handled, vec![1, 2, 3]);
+/// ```
+pub fn serve<St, Sp, F, Fut>(incoming: St, spawner: Sp, handler: F) -> Serve<St, Sp, F, Fut>
+    where St: Stream,
+          Sp: Spawn,
+          F: FnMut(St::Item) -> Fut,
+          Fut: Future<Output = ()> + Send + 'static,
+{
+    Serve {
+        incoming: Some(incoming),
+        spawner,
+        handler,
+        in_flight: FuturesUnordered::new(),
+        max_connections: usize::max_value(),
+        drain_timeout: None,
+        drain: None,
+        shutdown: Arc::new(ShutdownInner {
+            waker: AtomicWaker::new(),
+            shutdown: AtomicBool::new(false),
+        }),
+        done: false,
+        _handled: PhantomData,
+    }
+}
+
+/// Future for the [`serve`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Serve<St, Sp, F, Fut> {
+    incoming: Option<St>,
+    spawner: Sp,
+    handler: F,
+    in_flight: FuturesUnordered<RemoteHandle<()>>,
+    max_connections: usize,
+    drain_timeout: Option<Duration>,
+    drain: Option<Sleep>,
+    shutdown: Arc<ShutdownInner>,
+    done: bool,
+    _handled: PhantomData<fn() -> Fut>,
+}
+
+impl<St: Unpin, Sp, F, Fut> Unpin for Serve<St, Sp, F, Fut> {}
+
+impl<St, Sp, F, Fut> fmt::Debug for Serve<St, Sp, F, Fut>
+where
+    St: fmt::Debug,
+    Sp: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Serve")
+            .field("incoming", &self.incoming)
+            .field("spawner", &self.spawner)
+            .field("in_flight", &self.in_flight.len())
+            .field("max_connections", &self.max_connections)
+            .field("drain_timeout", &self.drain_timeout)
+            .finish()
+    }
+}
+
+impl<St, Sp, F, Fut> Serve<St, Sp, F, Fut> {
+    unsafe_pinned!(incoming: Option<St>);
+    unsafe_unpinned!(spawner: Sp);
+    unsafe_unpinned!(handler: F);
+    unsafe_unpinned!(in_flight: FuturesUnordered<RemoteHandle<()>>);
+    unsafe_unpinned!(drain: Option<Sleep>);
+    unsafe_unpinned!(done: bool);
+
+    /// Handles at most `max` connections at once.
+    ///
+    /// While `max` connections are in flight, no further connection is
+    /// accepted from the incoming stream, leaving it to apply backpressure,
+    /// for example through the listen backlog of a socket.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `max` is zero.
+    pub fn max_connections(mut self, max: usize) -> Self {
+        assert!(max > 0);
+        self.max_connections = max;
+        self
+    }
+
+    /// Waits at most `timeout` for the connections in flight once no more
+    /// connection is accepted.
+    ///
+    /// The tasks of the connections still in flight when `timeout` elapses
+    /// are canceled. By default, they are waited for without limit.
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns a handle through which a graceful shutdown of this server can
+    /// be requested.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle { inner: self.shutdown.clone() }
+    }
+
+    /// Returns the number of connections in flight.
+    pub fn connections(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Returns whether connections are still being accepted.
+    pub fn is_accepting(&self) -> bool {
+        self.incoming.is_some()
+    }
+}
+
+impl<St, Sp, F, Fut> Serve<St, Sp, F, Fut>
+    where St: Stream,
+          Sp: Spawn,
+          F: FnMut(St::Item) -> Fut,
+          Fut: Future<Output = ()> + Send + 'static,
+{
+    // Accepts connections until the limit is reached or `incoming` is not
+    // ready, spawning a task for each of them.
+    fn poll_accept(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Result<(), SpawnError> {
+        while self.in_flight.len() < self.max_connections {
+            let incoming = match self.as_mut().incoming().as_pin_mut() {
+                Some(incoming) => incoming,
+                None => break,
+            };
+            match incoming.poll_next(cx) {
+                Poll::Ready(Some(conn)) => {
+                    let fut = (self.as_mut().handler())(conn);
+                    // Panics are caught so that they end the connection
+                    // rather than the server.
+                    let fut = AssertUnwindSafe(fut).catch_unwind().map(drop);
+                    let handle = self.as_mut().spawner().spawn_with_handle(fut)?;
+                    self.as_mut().in_flight().push(handle);
+                }
+                Poll::Ready(None) => self.as_mut().incoming().set(None),
+                Poll::Pending => break,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<St, Sp, F, Fut> FusedFuture for Serve<St, Sp, F, Fut>
+    where St: Stream,
+          Sp: Spawn,
+          F: FnMut(St::Item) -> Fut,
+          Fut: Future<Output = ()> + Send + 'static,
+{
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+impl<St, Sp, F, Fut> Future for Serve<St, Sp, F, Fut>
+    where St: Stream,
+          Sp: Spawn,
+          F: FnMut(St::Item) -> Fut,
+          Fut: Future<Output = ()> + Send + 'static,
+{
+    type Output = Result<(), SpawnError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.incoming.is_some() {
+            self.shutdown.waker.register(cx.waker());
+            if self.shutdown.shutdown.load(Ordering::Acquire) {
+                self.as_mut().incoming().set(None);
+            }
+        }
+
+        loop {
+            if let Err(e) = self.as_mut().poll_accept(cx) {
+                // Dropping the handles cancels their tasks.
+                *self.as_mut().in_flight() = FuturesUnordered::new();
+                *self.as_mut().done() = true;
+                return Poll::Ready(Err(e));
+            }
+            // Go around when a connection ends, as it makes room for another.
+            match self.as_mut().in_flight().poll_next_unpin(cx) {
+                Poll::Ready(Some(())) => {}
+                Poll::Ready(None) | Poll::Pending => break,
+            }
+        }
+
+        if self.incoming.is_none() {
+            if self.in_flight.is_empty() {
+                *self.as_mut().done() = true;
+                return Poll::Ready(Ok(()));
+            }
+            if let Some(timeout) = self.drain_timeout {
+                let drain = self.as_mut().drain().get_or_insert_with(|| sleep(timeout));
+                if drain.poll_unpin(cx).is_ready() {
+                    *self.as_mut().in_flight() = FuturesUnordered::new();
+                    *self.as_mut().done() = true;
+                    return Poll::Ready(Ok(()));
+                }
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// A handle to request a graceful shutdown of a [`Serve`] future.
+///
+/// Once a shutdown is requested, the server stops accepting connections and
+/// resolves when the connections in flight are done, or when its
+/// [`drain_timeout`](Serve::drain_timeout) elapses.
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    inner: Arc<ShutdownInner>,
+}
+
+// Inner type storing the waker of the server and whether it should shut
+// down.
+#[derive(Debug)]
+struct ShutdownInner {
+    waker: AtomicWaker,
+    shutdown: AtomicBool,
+}
+
+impl ShutdownHandle {
+    /// Requests the server to shut down.
+    pub fn shutdown(&self) {
+        self.inner.shutdown.store(true, Ordering::Release);
+        self.inner.waker.wake();
+    }
+
+    /// Returns whether a shutdown was requested.
+    pub fn is_shutdown(&self) -> bool {
+        self.inner.shutdown.load(Ordering::Acquire)
+    }
+}
@@ -0,0 +1,353 @@
+//! Messages with streaming bodies.
+//!
+//! Large payloads cannot be buffered into a single frame. Protocols carrying
+//! them instead send each message as a head frame, followed by any number of
+//! body chunks, and ended by a trailer frame, as described by [`Frame`].
+//!
+//! The [`messages`] function turns a transport of such frames into a
+//! transport of messages: inbound messages are yielded as soon as their head
+//! arrives, along with a [`Body`] stream of what follows, and outbound
+//! messages are a head and a `Body`, whose frames are sent as they are
+//! produced. Being a transport itself, the result can be handed over to the
+//! [`pipeline`](super::pipeline) and [`multiplex`](super::multiplex) helpers
+//! to make requests and responses with streaming bodies. Bodies are not
+//! interleaved on the wire, so with the `multiplex` helpers the request id is
+//! carried in the head.
+
+use crate::stream::StreamExt;
+use core::fmt;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use futures_channel::mpsc::{self, Receiver, Sender};
+use futures_core::stream::{FusedStream, Stream, TryStream};
+use futures_core::task::{Context, Poll};
+use futures_sink::Sink;
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// A frame of a message with a streaming body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Frame<H, C, T> {
+    /// The head of a message, starting it.
+    Head(H),
+    /// A chunk of the body of the current message.
+    Chunk(C),
+    /// The trailer of the current message, ending it.
+    Trailer(T),
+}
+
+/// A frame of a message body: the [`Frame`]s which follow a head.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BodyFrame<C, T> {
+    /// A chunk of the body.
+    Chunk(C),
+    /// The trailer, ending the body.
+    Trailer(T),
+}
+
+impl<H, C, T> From<BodyFrame<C, T>> for Frame<H, C, T> {
+    fn from(frame: BodyFrame<C, T>) -> Self {
+        match frame {
+            BodyFrame::Chunk(chunk) => Frame::Chunk(chunk),
+            BodyFrame::Trailer(trailer) => Frame::Trailer(trailer),
+        }
+    }
+}
+
+/// Creates a body fed through a channel, which buffers up to `buffer`
+/// frames.
+///
+/// The returned sender is a [`Sink`] of [`BodyFrame`]s. The body ends after
+/// the trailer sent through it, or when it is dropped, in which case the
+/// body is truncated.
+pub fn body_channel<C, T>(buffer: usize) -> (Sender<BodyFrame<C, T>>, Body<C, T>) {
+    let (tx, rx) = mpsc::channel(buffer);
+    (tx, Body { rx: Some(rx) })
+}
+
+/// The streaming body of a message.
+///
+/// This stream yields the chunks of the body, then its trailer, and ends. It
+/// ends without a trailer if the body was truncated, for example because the
+/// connection was lost.
+///
+/// Bodies of inbound messages are created by the [`Messages`] transport, and
+/// bodies of outbound messages by the [`body_channel`] function or the
+/// [`Body::empty`] constructor.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Body<C, T> {
+    rx: Option<Receiver<BodyFrame<C, T>>>,
+}
+
+impl<C, T> Unpin for Body<C, T> {}
+
+impl<C, T> Body<C, T> {
+    /// Creates a body with no chunk, ended by `trailer`.
+    pub fn empty(trailer: T) -> Self {
+        let (mut tx, body) = body_channel(0);
+        // Every sender has a slot of its own, so this cannot fail.
+        tx.try_send(BodyFrame::Trailer(trailer)).unwrap();
+        body
+    }
+}
+
+impl<C, T> Stream for Body<C, T> {
+    type Item = BodyFrame<C, T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let frame = match &mut self.rx {
+            Some(rx) => ready!(rx.poll_next_unpin(cx)),
+            None => return Poll::Ready(None),
+        };
+        if let Some(BodyFrame::Chunk(_)) = frame {
+            return Poll::Ready(frame);
+        }
+        self.rx = None;
+        Poll::Ready(frame)
+    }
+}
+
+impl<C, T> FusedStream for Body<C, T> {
+    fn is_terminated(&self) -> bool {
+        self.rx.is_none()
+    }
+}
+
+/// Turns a transport of [`Frame`]s into a transport of messages with
+/// streaming bodies.
+///
+/// The returned transport yields each inbound message as its head and a
+/// [`Body`], through which the rest of the message is received. The body
+/// buffers up to `buffer` frames, after which no more inbound frame is read
+/// until the body is polled: the whole body needs to be read, or the body
+/// dropped, before the next message can be received. Frames of a dropped
+/// body are discarded, as are body frames received outside of a message.
+///
+/// Outbound messages are a head and a `Body`. The frames of the body are sent
+/// as they are produced, up to its trailer, and the next message is only
+/// accepted once the body is done.
+/// Flushing the returned transport flushes the frames sent so far and waits
+/// for the body to be done. Chunks and trailers have the same types in both
+/// directions, while heads may differ.
+///
+/// # Examples
+///
+/// ```
+/// use futures::channel::mpsc;
+/// use futures::executor::block_on;
+/// use futures::future;
+/// use futures::sink::SinkExt;
+/// use futures::stream::StreamExt;
+/// use futures::transport::{self, streaming::{self, Body, BodyFrame, Frame}};
+///
+/// let (inbound_tx, inbound_rx) = mpsc::unbounded();
+/// let (outbound_tx, outbound_rx) = mpsc::unbounded();
+/// let inbound = inbound_rx.map(Ok::<_, mpsc::SendError>);
+/// let mut messages = streaming::messages(transport::duplex(inbound, outbound_tx), 4);
+///
+/// inbound_tx.unbounded_send(Frame::Head("GET")).unwrap();
+/// inbound_tx.unbounded_send(Frame::Chunk(1)).unwrap();
+/// inbound_tx.unbounded_send(Frame::Trailer(())).unwrap();
+/// drop(inbound_tx);
+///
+/// let (head, body) = block_on(messages.next()).unwrap().unwrap();
+/// assert_eq!(head, "GET");
+/// // The body is fed while the transport is polled.
+/// let (body, next) = block_on(future::join(body.collect::<Vec<_>>(), messages.next()));
+/// assert_eq!(body, vec![BodyFrame::Chunk(1), BodyFrame::Trailer(())]);
+/// assert!(next.is_none());
+///
+/// block_on(messages.send(("OK", Body::empty(())))).unwrap();
+/// drop(messages);
+/// assert_eq!(
+///     block_on(outbound_rx.collect::<Vec<_>>()),
+///     vec![Frame::Head("OK"), Frame::Trailer(())],
+/// );
+/// ```
+pub fn messages<T, H, C, Tr>(transport: T, buffer: usize) -> Messages<T, H, C, Tr>
+    where T: TryStream<Ok = Frame<H, C, Tr>>,
+{
+    Messages {
+        transport,
+        buffer,
+        inbound: None,
+        pending: None,
+        outbound: None,
+        _head: PhantomData,
+    }
+}
+
+/// Transport for the [`messages`] function.
+#[must_use = "streams do nothing unless polled"]
+pub struct Messages<T, H, C, Tr> {
+    transport: T,
+    buffer: usize,
+    // Sender of the body of the inbound message being received.
+    inbound: Option<Sender<BodyFrame<C, Tr>>>,
+    // Inbound body frame waiting for room in the body.
+    pending: Option<BodyFrame<C, Tr>>,
+    // Body of the outbound message being sent.
+    outbound: Option<Body<C, Tr>>,
+    _head: PhantomData<fn() -> H>,
+}
+
+impl<T: Unpin, H, C, Tr> Unpin for Messages<T, H, C, Tr> {}
+
+impl<T, H, C, Tr> fmt::Debug for Messages<T, H, C, Tr>
+    where T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Messages")
+            .field("transport", &self.transport)
+            .field("buffer", &self.buffer)
+            .field("receiving", &self.inbound.is_some())
+            .field("sending", &self.outbound.is_some())
+            .finish()
+    }
+}
+
+impl<T, H, C, Tr> Messages<T, H, C, Tr> {
+    unsafe_pinned!(transport: T);
+    unsafe_unpinned!(inbound: Option<Sender<BodyFrame<C, Tr>>>);
+    unsafe_unpinned!(pending: Option<BodyFrame<C, Tr>>);
+    unsafe_unpinned!(outbound: Option<Body<C, Tr>>);
+
+    /// Acquires a reference to the underlying transport.
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    /// Acquires a mutable reference to the underlying transport.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// transport which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Acquires a pinned mutable reference to the underlying transport.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// transport which may otherwise confuse this combinator.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.transport()
+    }
+
+    /// Consumes this combinator, returning the underlying transport.
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    // Hands the pending frame over to the body being received. Frames are
+    // dropped if there is no such body or it was dropped.
+    fn poll_deliver(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let frame = match self.as_mut().pending().take() {
+            Some(frame) => frame,
+            None => return Poll::Ready(()),
+        };
+        let end = match frame {
+            BodyFrame::Trailer(_) => true,
+            _ => false,
+        };
+        if let Some(tx) = self.as_mut().inbound() {
+            match tx.poll_ready(cx) {
+                Poll::Ready(Ok(())) => {
+                    let _ = tx.start_send(frame);
+                }
+                Poll::Ready(Err(_)) => {}
+                Poll::Pending => {
+                    *self.as_mut().pending() = Some(frame);
+                    return Poll::Pending;
+                }
+            }
+        }
+        if end {
+            *self.inbound() = None;
+        }
+        Poll::Ready(())
+    }
+}
+
+impl<T, H, C, Tr> Stream for Messages<T, H, C, Tr>
+    where T: TryStream<Ok = Frame<H, C, Tr>>,
+{
+    type Item = Result<(H, Body<C, Tr>), T::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            ready!(self.as_mut().poll_deliver(cx));
+
+            match ready!(self.as_mut().transport().try_poll_next(cx)) {
+                Some(Ok(Frame::Head(head))) => {
+                    // Dropping the sender of an unfinished body truncates it.
+                    let (tx, body) = body_channel(self.buffer);
+                    *self.as_mut().inbound() = Some(tx);
+                    return Poll::Ready(Some(Ok((head, body))));
+                }
+                Some(Ok(Frame::Chunk(chunk))) => {
+                    *self.as_mut().pending() = Some(BodyFrame::Chunk(chunk));
+                }
+                Some(Ok(Frame::Trailer(trailer))) => {
+                    *self.as_mut().pending() = Some(BodyFrame::Trailer(trailer));
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => {
+                    *self.as_mut().inbound() = None;
+                    return Poll::Ready(None);
+                }
+            }
+        }
+    }
+}
+
+impl<T, H, C, Tr> Messages<T, H, C, Tr> {
+    // Sends the frames of the body being sent, if any, until it is done.
+    fn poll_body<Out>(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), T::Error>>
+        where T: Sink<Frame<Out, C, Tr>>,
+    {
+        while self.outbound.is_some() {
+            ready!(self.as_mut().transport().poll_ready(cx))?;
+            let body = self.as_mut().outbound().as_mut().unwrap();
+            match ready!(body.poll_next_unpin(cx)) {
+                Some(frame) => {
+                    if let BodyFrame::Trailer(_) = frame {
+                        *self.as_mut().outbound() = None;
+                    }
+                    self.as_mut().transport().start_send(frame.into())?;
+                }
+                None => *self.as_mut().outbound() = None,
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T, H, C, Tr, Out> Sink<(Out, Body<C, Tr>)> for Messages<T, H, C, Tr>
+    where T: Sink<Frame<Out, C, Tr>>,
+{
+    type Error = T::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_body(cx))?;
+        self.transport().poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, (head, body): (Out, Body<C, Tr>)) -> Result<(), Self::Error> {
+        self.as_mut().transport().start_send(Frame::Head(head))?;
+        *self.outbound() = Some(body);
+        Ok(())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Frames already sent are flushed even if the body is not done, so
+        // that they are not held back by a slow body.
+        let body = self.as_mut().poll_body(cx)?;
+        ready!(self.transport().poll_flush(cx))?;
+        body.map(Ok)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_body(cx))?;
+        self.transport().poll_close(cx)
+    }
+}
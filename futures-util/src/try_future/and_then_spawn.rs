@@ -0,0 +1,56 @@
+use super::{TryChain, TryChainAction};
+use core::pin::Pin;
+use futures_core::future::{FusedFuture, Future, TryFuture};
+use futures_core::task::{Context, Poll, Spawn};
+use pin_utils::unsafe_pinned;
+
+/// Future for the [`and_then_spawn`](super::TryFutureExt::and_then_spawn)
+/// method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct AndThenSpawn<Fut1, Fut2, F, Sp> {
+    try_chain: TryChain<Fut1, Fut2, (F, Sp)>,
+}
+
+impl<Fut1, Fut2, F, Sp> AndThenSpawn<Fut1, Fut2, F, Sp>
+    where Fut1: TryFuture,
+          Fut2: TryFuture,
+{
+    unsafe_pinned!(try_chain: TryChain<Fut1, Fut2, (F, Sp)>);
+
+    /// Creates a new `AndThenSpawn`.
+    pub(super) fn new(future: Fut1, spawner: Sp, f: F) -> AndThenSpawn<Fut1, Fut2, F, Sp> {
+        AndThenSpawn {
+            try_chain: TryChain::new(future, (f, spawner)),
+        }
+    }
+}
+
+impl<Fut1, Fut2, F, Sp> FusedFuture for AndThenSpawn<Fut1, Fut2, F, Sp>
+    where Fut1: TryFuture,
+          Fut2: TryFuture<Error = Fut1::Error>,
+          F: FnOnce(Fut1::Ok, &mut Sp) -> Fut2,
+          Sp: Spawn,
+{
+    fn is_terminated(&self) -> bool {
+        self.try_chain.is_terminated()
+    }
+}
+
+impl<Fut1, Fut2, F, Sp> Future for AndThenSpawn<Fut1, Fut2, F, Sp>
+    where Fut1: TryFuture,
+          Fut2: TryFuture<Error = Fut1::Error>,
+          F: FnOnce(Fut1::Ok, &mut Sp) -> Fut2,
+          Sp: Spawn,
+{
+    type Output = Result<Fut2::Ok, Fut2::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.try_chain().poll(cx, |result, (async_op, mut spawner)| {
+            match result {
+                Ok(ok) => TryChainAction::Future(async_op(ok, &mut spawner)),
+                Err(err) => TryChainAction::Output(Err(err)),
+            }
+        })
+    }
+}
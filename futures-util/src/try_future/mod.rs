@@ -6,7 +6,7 @@
 use core::pin::Pin;
 use futures_core::future::TryFuture;
 use futures_core::stream::TryStream;
-use futures_core::task::{Context, Poll};
+use futures_core::task::{Context, Poll, Spawn};
 #[cfg(feature = "sink")]
 use futures_sink::Sink;
 
@@ -31,10 +31,18 @@ mod select_ok;
 #[cfg(feature = "alloc")]
 pub use self::select_ok::{select_ok, SelectOk};
 
+#[cfg(feature = "alloc")]
+mod race_ok;
+#[cfg(feature = "alloc")]
+pub use self::race_ok::{race_ok_or_collect_errors, RaceOkOrCollectErrors};
+
 // Combinators
 mod and_then;
 pub use self::and_then::AndThen;
 
+mod and_then_spawn;
+pub use self::and_then_spawn::AndThenSpawn;
+
 mod err_into;
 pub use self::err_into::ErrInto;
 
@@ -289,6 +297,45 @@ pub trait TryFutureExt: TryFuture {
         AndThen::new(self, f)
     }
 
+    /// Executes another future after this one resolves successfully, handing
+    /// the closure creating it an executor on which side work can be spawned.
+    ///
+    /// This works like [`and_then`](TryFutureExt::and_then), except that `f`
+    /// also receives a mutable reference to `spawner`. Code in the middle of
+    /// a chain can then offload work which the rest of the chain does not
+    /// need to wait for, such as logging or cache updates, without relying
+    /// on a global executor. The spawner is dropped once `f` has been called,
+    /// or when this future resolves to an [`Err`] without calling `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::{block_on, ThreadPool};
+    /// use futures::future::{self, TryFutureExt};
+    /// use futures::channel::oneshot;
+    /// use futures::task::SpawnExt;
+    ///
+    /// let pool = ThreadPool::new().unwrap();
+    /// let (tx, rx) = oneshot::channel();
+    ///
+    /// let future = future::ready(Ok::<i32, i32>(1))
+    ///     .and_then_spawn(pool, |x, spawner| {
+    ///         spawner.spawn(future::lazy(move |_| tx.send(x).unwrap())).unwrap();
+    ///         future::ready(Ok(x + 3))
+    ///     });
+    ///
+    /// assert_eq!(block_on(future), Ok(4));
+    /// assert_eq!(block_on(rx), Ok(1));
+    /// ```
+    fn and_then_spawn<Sp, Fut, F>(self, spawner: Sp, f: F) -> AndThenSpawn<Self, Fut, F, Sp>
+        where F: FnOnce(Self::Ok, &mut Sp) -> Fut,
+              Fut: TryFuture<Error = Self::Error>,
+              Sp: Spawn,
+              Self: Sized,
+    {
+        AndThenSpawn::new(self, spawner, f)
+    }
+
     /// Executes another future if this one resolves to an error. The
     /// error value is passed to a closure to create this subsequent future.
     ///
@@ -0,0 +1,110 @@
+use crate::try_future::TryFutureExt;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::pin::Pin;
+use alloc::vec::Vec;
+use futures_core::future::{Future, TryFuture};
+use futures_core::task::{Context, Poll};
+
+/// Future for the [`race_ok_or_collect_errors`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct RaceOkOrCollectErrors<Fut: TryFuture> {
+    // Futures still running, along with their position in the input.
+    inner: Vec<(usize, Fut)>,
+    errors: Vec<(usize, Fut::Error)>,
+}
+
+impl<Fut: TryFuture + Unpin> Unpin for RaceOkOrCollectErrors<Fut> {}
+
+impl<Fut> fmt::Debug for RaceOkOrCollectErrors<Fut>
+where
+    Fut: TryFuture + fmt::Debug,
+    Fut::Error: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RaceOkOrCollectErrors")
+            .field("inner", &self.inner)
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
+
+/// Creates a new future which will select the first successful future over a
+/// list of futures, or collect the errors of all of them.
+///
+/// The returned future will wait for any future within `iter` to be ready and
+/// Ok, and resolve to its value, dropping the other futures. Unlike
+/// [`select_ok`](super::select_ok), which only reports the last failure, if
+/// all the futures fail the returned future resolves to every error, in the
+/// order of the futures in `iter`. This is useful for failing over between
+/// several endpoints, where all the failures matter when none succeeds.
+///
+/// This function is only available when the `std` or `alloc` feature of this
+/// library is activated, and it is activated by default.
+///
+/// # Panics
+///
+/// This function will panic if the iterator specified contains no items.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::future::{self, race_ok_or_collect_errors};
+///
+/// let futures = vec![future::err("refused"), future::ok(2), future::err("timed out")];
+/// assert_eq!(block_on(race_ok_or_collect_errors(futures)), Ok(2));
+///
+/// let futures = vec![future::err::<i32, _>("refused"), future::err("timed out")];
+/// assert_eq!(
+///     block_on(race_ok_or_collect_errors(futures)),
+///     Err(vec!["refused", "timed out"]),
+/// );
+/// ```
+pub fn race_ok_or_collect_errors<I>(iter: I) -> RaceOkOrCollectErrors<I::Item>
+    where I: IntoIterator,
+          I::Item: TryFuture + Unpin,
+{
+    let ret = RaceOkOrCollectErrors {
+        inner: iter.into_iter().enumerate().collect(),
+        errors: Vec::new(),
+    };
+    assert!(!ret.inner.is_empty());
+    ret
+}
+
+impl<Fut: TryFuture + Unpin> Future for RaceOkOrCollectErrors<Fut> {
+    type Output = Result<Fut::Ok, Vec<Fut::Error>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let mut i = 0;
+        while i < this.inner.len() {
+            match this.inner[i].1.try_poll_unpin(cx) {
+                Poll::Pending => i += 1,
+                Poll::Ready(Ok(v)) => {
+                    this.inner.clear();
+                    return Poll::Ready(Ok(v))
+                }
+                Poll::Ready(Err(e)) => {
+                    let (idx, _) = this.inner.remove(i);
+                    this.errors.push((idx, e));
+                }
+            }
+        }
+
+        if !this.inner.is_empty() {
+            return Poll::Pending
+        }
+        let mut errors = mem::replace(&mut this.errors, Vec::new());
+        errors.sort_by_key(|&(idx, _)| idx);
+        Poll::Ready(Err(errors.into_iter().map(|(_, e)| e).collect()))
+    }
+}
+
+impl<Fut: TryFuture + Unpin> FromIterator<Fut> for RaceOkOrCollectErrors<Fut> {
+    fn from_iter<T: IntoIterator<Item = Fut>>(iter: T) -> Self {
+        race_ok_or_collect_errors(iter)
+    }
+}
@@ -0,0 +1,95 @@
+use core::fmt;
+use core::pin::Pin;
+use futures_core::stream::{FusedStream, Stream, TryStream};
+use futures_core::task::{Context, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// Stream for the [`fallback`](super::TryStreamExt::fallback) method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Fallback<St1, St2, F> {
+    primary: Option<St1>,
+    secondary: Option<St2>,
+    f: Option<F>,
+}
+
+impl<St1: Unpin, St2: Unpin, F> Unpin for Fallback<St1, St2, F> {}
+
+impl<St1, St2, F> fmt::Debug for Fallback<St1, St2, F>
+where
+    St1: fmt::Debug,
+    St2: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Fallback")
+            .field("primary", &self.primary)
+            .field("secondary", &self.secondary)
+            .finish()
+    }
+}
+
+impl<St1, St2, F> Fallback<St1, St2, F> {
+    unsafe_pinned!(primary: Option<St1>);
+    unsafe_pinned!(secondary: Option<St2>);
+    unsafe_unpinned!(f: Option<F>);
+}
+
+impl<St1, St2, F> Fallback<St1, St2, F>
+    where St1: TryStream,
+          F: FnOnce(St1::Error) -> St2,
+          St2: TryStream<Ok = St1::Ok, Error = St1::Error>,
+{
+    pub(super) fn new(stream: St1, f: F) -> Self {
+        Self { primary: Some(stream), secondary: None, f: Some(f) }
+    }
+
+    /// Returns whether the primary stream failed, and this stream switched
+    /// over to the secondary one.
+    pub fn is_degraded(&self) -> bool {
+        self.f.is_none()
+    }
+}
+
+impl<St1, St2, F> FusedStream for Fallback<St1, St2, F>
+    where St1: TryStream,
+          F: FnOnce(St1::Error) -> St2,
+          St2: TryStream<Ok = St1::Ok, Error = St1::Error> + FusedStream,
+{
+    fn is_terminated(&self) -> bool {
+        match &self.secondary {
+            Some(secondary) => secondary.is_terminated(),
+            None => self.primary.is_none(),
+        }
+    }
+}
+
+impl<St1, St2, F> Stream for Fallback<St1, St2, F>
+    where St1: TryStream,
+          F: FnOnce(St1::Error) -> St2,
+          St2: TryStream<Ok = St1::Ok, Error = St1::Error>,
+{
+    type Item = Result<St1::Ok, St1::Error>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        if let Some(primary) = self.as_mut().primary().as_pin_mut() {
+            let error = match ready!(primary.try_poll_next(cx)) {
+                Some(Ok(item)) => return Poll::Ready(Some(Ok(item))),
+                Some(Err(error)) => error,
+                None => {
+                    self.as_mut().primary().set(None);
+                    return Poll::Ready(None);
+                }
+            };
+            self.as_mut().primary().set(None);
+            let secondary = (self.as_mut().f().take().unwrap())(error);
+            self.as_mut().secondary().set(Some(secondary));
+        }
+
+        match self.as_mut().secondary().as_pin_mut() {
+            Some(secondary) => secondary.try_poll_next(cx),
+            None => Poll::Ready(None),
+        }
+    }
+}
@@ -35,6 +35,9 @@ pub use self::map_err::MapErr;
 mod or_else;
 pub use self::or_else::OrElse;
 
+mod fallback;
+pub use self::fallback::Fallback;
+
 mod try_next;
 pub use self::try_next::TryNext;
 
@@ -66,14 +69,28 @@ cfg_target_has_atomic! {
     #[cfg(feature = "alloc")]
     mod try_buffer_unordered;
     #[cfg(feature = "alloc")]
-    pub use self::try_buffer_unordered::TryBufferUnordered;
+    pub use self::try_buffer_unordered::{ErrorPolicy, TryBufferUnordered};
 
     #[cfg(feature = "alloc")]
     mod try_for_each_concurrent;
     #[cfg(feature = "alloc")]
     pub use self::try_for_each_concurrent::TryForEachConcurrent;
+
+    #[cfg(feature = "sink")]
+    #[cfg(feature = "timer")]
+    mod reconnecting;
+    #[cfg(feature = "sink")]
+    #[cfg(feature = "timer")]
+    pub use self::reconnecting::ReconnectingStream;
 }
 
+#[cfg(feature = "channel")]
+#[cfg(feature = "std")]
+mod split_errors;
+#[cfg(feature = "channel")]
+#[cfg(feature = "std")]
+pub use self::split_errors::{FirstError, SplitErrors};
+
 #[cfg(feature = "io")]
 #[cfg(feature = "std")]
 mod into_async_read;
@@ -234,6 +251,41 @@ pub trait TryStreamExt: TryStream {
         OrElse::new(self, f)
     }
 
+    /// Switches over to a secondary stream once this stream fails, instead
+    /// of yielding the error.
+    ///
+    /// When this stream yields an error, it is dropped and the closure `f` is
+    /// called with the error to build the secondary stream, whose items are
+    /// yielded from then on. This lets read paths degrade, e.g. to cached
+    /// data, rather than fail. The closure is also the place to report the
+    /// failure through a side channel, since the error is not yielded. Errors
+    /// of the secondary stream are yielded as they are.
+    ///
+    /// ```
+    /// use futures::channel::oneshot;
+    /// use futures::executor::block_on;
+    /// use futures::stream::{self, StreamExt, TryStreamExt};
+    ///
+    /// let live = stream::iter(vec![Ok(1), Err("connection lost")]);
+    /// let cached = vec![Ok(2), Ok(3)];
+    ///
+    /// let (failure_tx, failure_rx) = oneshot::channel();
+    /// let reads = live.fallback(move |e| {
+    ///     let _ = failure_tx.send(e);
+    ///     stream::iter(cached)
+    /// });
+    ///
+    /// assert_eq!(block_on(reads.collect::<Vec<_>>()), vec![Ok(1), Ok(2), Ok(3)]);
+    /// assert_eq!(block_on(failure_rx), Ok("connection lost"));
+    /// ```
+    fn fallback<St, F>(self, f: F) -> Fallback<Self, St, F>
+        where F: FnOnce(Self::Error) -> St,
+              St: TryStream<Ok = Self::Ok, Error = Self::Error>,
+              Self: Sized,
+    {
+        Fallback::new(self, f)
+    }
+
     /// Do something with the success value of this stream, afterwards passing
     /// it on.
     ///
@@ -698,7 +750,11 @@ pub trait TryStreamExt: TryStream {
     ///
     /// The returned stream will be a stream of results, each containing either
     /// an error or a future's output. An error can be produced either by the
-    /// underlying stream itself or by one of the futures it yielded.
+    /// underlying stream itself or by one of the futures it yielded. What
+    /// happens to the errors of the futures can be configured with
+    /// [`error_policy`](TryBufferUnordered::error_policy), and each future can
+    /// be bounded in time with
+    /// [`item_timeout`](TryBufferUnordered::item_timeout).
     ///
     /// This method is only available when the `std` or `alloc` feature of this
     /// library is activated, and it is activated by default.
@@ -801,6 +857,38 @@ pub trait TryStreamExt: TryStream {
     }
 
 
+    /// Splits this stream into a stream of its successful values and a
+    /// future resolving to its first error.
+    ///
+    /// The returned stream yields the [`Ok`] values of this stream, and ends
+    /// at its first error, which is handed to the returned future instead.
+    /// This keeps the main consumption loop free of error handling, which
+    /// can happen in a single dedicated place. The future resolves to
+    /// [`None`] if the stream ends, or is dropped, without an error.
+    ///
+    /// This method is only available when the `std` feature of this
+    /// library is activated, and it is activated by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::stream::{self, StreamExt, TryStreamExt};
+    ///
+    /// let stream = stream::iter(vec![Ok(1), Ok(2), Err("broken"), Ok(3)]);
+    /// let (items, error) = stream.split_errors();
+    ///
+    /// assert_eq!(block_on(items.collect::<Vec<i32>>()), vec![1, 2]);
+    /// assert_eq!(block_on(error), Some("broken"));
+    /// ```
+    #[cfg(feature = "channel")]
+    #[cfg(feature = "std")]
+    fn split_errors(self) -> (SplitErrors<Self>, FirstError<Self::Error>)
+        where Self: Sized,
+    {
+        split_errors::new(self)
+    }
+
     /// Adapter that converts this stream into an [`AsyncRead`](crate::io::AsyncRead).
     ///
     /// Note that because `into_async_read` moves the stream, the [`Stream`](futures_core::stream::Stream) type must be
@@ -0,0 +1,300 @@
+use crate::future::FutureExt;
+use crate::sink::ResumeState;
+use crate::timer::{sleep, Sleep};
+use core::fmt;
+use core::pin::Pin;
+use futures_core::future::Future;
+use futures_core::stream::{FusedStream, Stream, TryStream};
+use futures_core::task::{Context, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::cmp;
+use std::time::Duration;
+
+/// A stream which replaces its underlying stream whenever it fails or ends.
+///
+/// `ReconnectingStream` builds its underlying stream with a connection
+/// factory, a closure returning a future which resolves to a new stream.
+/// Whenever the underlying stream yields an error or ends, it is discarded
+/// and the factory is invoked again to build a replacement, whose items are
+/// then yielded in turn. This suits subscriptions, which are expected to go
+/// on for as long as the consumer is interested in them.
+///
+/// Reconnections are bounded: once `max_retries` consecutive attempts have
+/// failed, the last error is yielded (or the stream simply ends, if the last
+/// underlying stream ended without an error) and the `ReconnectingStream`
+/// terminates. Errors from the factory's futures count as failed attempts
+/// too. The count is reset every time an item is received.
+///
+/// By default a replacement is requested immediately. A delay growing
+/// exponentially between consecutive attempts can be configured with
+/// [`with_backoff`](ReconnectingStream::with_backoff), and a marker item to
+/// yield after each successful reconnection with
+/// [`with_marker`](ReconnectingStream::with_marker).
+///
+/// The connection factory is handed the stream's [`ResumeState`], which
+/// records every item received, so that a replacement stream can resume
+/// where the previous one left off. [`new`](ReconnectingStream::new) uses
+/// `()`, which records nothing; a custom state can be supplied with
+/// [`with_state`](ReconnectingStream::with_state).
+#[must_use = "streams do nothing unless polled"]
+pub struct ReconnectingStream<St: TryStream, F, Fut, R = ()> {
+    connect: F,
+    resume_state: R,
+    connecting: Option<Fut>,
+    stream: Option<St>,
+    delay: Option<Sleep>,
+    marker: Option<St::Ok>,
+    connected: bool,
+    terminated: bool,
+    max_retries: usize,
+    retries: usize,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    backoff: Duration,
+}
+
+impl<St: TryStream + Unpin, F, Fut: Unpin, R> Unpin for ReconnectingStream<St, F, Fut, R> {}
+
+impl<St, F, Fut, R> fmt::Debug for ReconnectingStream<St, F, Fut, R>
+where
+    St: TryStream + fmt::Debug,
+    St::Ok: fmt::Debug,
+    Fut: fmt::Debug,
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReconnectingStream")
+            .field("resume_state", &self.resume_state)
+            .field("connecting", &self.connecting)
+            .field("stream", &self.stream)
+            .field("delay", &self.delay)
+            .field("marker", &self.marker)
+            .field("terminated", &self.terminated)
+            .field("max_retries", &self.max_retries)
+            .field("retries", &self.retries)
+            .field("backoff", &self.backoff)
+            .finish()
+    }
+}
+
+impl<St, F, Fut> ReconnectingStream<St, F, Fut>
+    where St: TryStream,
+          F: FnMut(&()) -> Fut,
+          Fut: Future<Output = Result<St, St::Error>>,
+{
+    /// Creates a new `ReconnectingStream` using `connect` to build its
+    /// underlying stream, making at most `max_retries` consecutive attempts
+    /// to replace it.
+    ///
+    /// No connection is made until the stream is first polled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::future;
+    /// use futures::stream::{self, ReconnectingStream, StreamExt};
+    ///
+    /// let mut connections = 0;
+    /// let stream = ReconnectingStream::new(move |_| {
+    ///     connections += 1;
+    ///     if connections <= 3 {
+    ///         future::ok(stream::iter(vec![Ok(connections), Err("lost")]))
+    ///     } else {
+    ///         future::err("refused")
+    ///     }
+    /// }, 2);
+    ///
+    /// let items = block_on(stream.collect::<Vec<_>>());
+    /// assert_eq!(items, vec![Ok(1), Ok(2), Ok(3), Err("refused")]);
+    /// ```
+    pub fn new(connect: F, max_retries: usize) -> Self {
+        ReconnectingStream::with_state(connect, (), max_retries)
+    }
+}
+
+impl<St, F, Fut, R> ReconnectingStream<St, F, Fut, R>
+    where St: TryStream,
+          F: FnMut(&R) -> Fut,
+          Fut: Future<Output = Result<St, St::Error>>,
+          R: ResumeState<St::Ok>,
+{
+    unsafe_unpinned!(resume_state: R);
+    unsafe_pinned!(connecting: Option<Fut>);
+    unsafe_pinned!(stream: Option<St>);
+    unsafe_unpinned!(delay: Option<Sleep>);
+    unsafe_unpinned!(connected: bool);
+    unsafe_unpinned!(terminated: bool);
+    unsafe_unpinned!(retries: usize);
+    unsafe_unpinned!(backoff: Duration);
+
+    /// Creates a new `ReconnectingStream` like
+    /// [`new`](ReconnectingStream::new), recording received items into
+    /// `state` and handing it to `connect` on every connection.
+    pub fn with_state(connect: F, state: R, max_retries: usize) -> Self {
+        ReconnectingStream {
+            connect,
+            resume_state: state,
+            connecting: None,
+            stream: None,
+            delay: None,
+            marker: None,
+            connected: false,
+            terminated: false,
+            max_retries,
+            retries: 0,
+            min_backoff: Duration::from_secs(0),
+            max_backoff: Duration::from_secs(0),
+            backoff: Duration::from_secs(0),
+        }
+    }
+
+    /// Waits before each attempt to replace the underlying stream, starting
+    /// with `min` and doubling the delay after each failed attempt, up to
+    /// `max`.
+    pub fn with_backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = cmp::max(min, max);
+        self.backoff = min;
+        self
+    }
+
+    /// Yields `marker` every time the underlying stream has been replaced,
+    /// letting consumers know that items may have been missed.
+    pub fn with_marker(mut self, marker: St::Ok) -> Self {
+        self.marker = Some(marker);
+        self
+    }
+
+    /// Acquires a reference to the current underlying stream, if connected.
+    pub fn get_ref(&self) -> Option<&St> {
+        self.stream.as_ref()
+    }
+
+    /// Acquires a mutable reference to the current underlying stream, if
+    /// connected.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> Option<&mut St> {
+        self.stream.as_mut()
+    }
+
+    /// Acquires a reference to the resumption state.
+    pub fn state(&self) -> &R {
+        &self.resume_state
+    }
+
+    fn reconnect(mut self: Pin<&mut Self>) {
+        // Safety: `connect` and `resume_state` are never pinned.
+        let this = unsafe { self.as_mut().get_unchecked_mut() };
+        let fut = (this.connect)(&this.resume_state);
+        self.as_mut().connecting().set(Some(fut));
+    }
+
+    // Records a failed attempt, scheduling another one unless the retries are
+    // exhausted, in which case the stream terminates and `true` is returned.
+    fn fail(mut self: Pin<&mut Self>) -> bool {
+        self.as_mut().stream().set(None);
+        if self.retries >= self.max_retries {
+            *self.as_mut().terminated() = true;
+            *self.as_mut().backoff() = self.min_backoff;
+            return true;
+        }
+        *self.as_mut().retries() += 1;
+        let backoff = self.backoff;
+        if backoff > Duration::from_secs(0) {
+            *self.as_mut().delay() = Some(sleep(backoff));
+            *self.as_mut().backoff() = cmp::min(backoff * 2, self.max_backoff);
+        }
+        false
+    }
+}
+
+impl<St, F, Fut, R> FusedStream for ReconnectingStream<St, F, Fut, R>
+    where St: TryStream,
+          F: FnMut(&R) -> Fut,
+          Fut: Future<Output = Result<St, St::Error>>,
+          St::Ok: Clone,
+          R: ResumeState<St::Ok>,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+impl<St, F, Fut, R> Stream for ReconnectingStream<St, F, Fut, R>
+    where St: TryStream,
+          F: FnMut(&R) -> Fut,
+          Fut: Future<Output = Result<St, St::Error>>,
+          St::Ok: Clone,
+          R: ResumeState<St::Ok>,
+{
+    type Item = Result<St::Ok, St::Error>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.terminated {
+                return Poll::Ready(None);
+            }
+
+            if let Some(delay) = self.as_mut().delay() {
+                ready!(delay.poll_unpin(cx));
+                *self.as_mut().delay() = None;
+            }
+
+            if let Some(fut) = self.as_mut().connecting().as_pin_mut() {
+                let result = ready!(fut.poll(cx));
+                self.as_mut().connecting().set(None);
+                match result {
+                    Ok(stream) => {
+                        self.as_mut().stream().set(Some(stream));
+                        let reconnected = self.connected;
+                        *self.as_mut().connected() = true;
+                        if reconnected {
+                            if let Some(marker) = self.marker.clone() {
+                                return Poll::Ready(Some(Ok(marker)));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        if self.as_mut().fail() {
+                            return Poll::Ready(Some(Err(e)));
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let item = match self.as_mut().stream().as_pin_mut() {
+                Some(stream) => ready!(stream.try_poll_next(cx)),
+                None => {
+                    self.as_mut().reconnect();
+                    continue;
+                }
+            };
+
+            match item {
+                Some(Ok(item)) => {
+                    self.as_mut().resume_state().record(&item);
+                    *self.as_mut().retries() = 0;
+                    *self.as_mut().backoff() = self.min_backoff;
+                    return Poll::Ready(Some(Ok(item)));
+                }
+                Some(Err(e)) => {
+                    if self.as_mut().fail() {
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+                None => {
+                    if self.as_mut().fail() {
+                        return Poll::Ready(None);
+                    }
+                }
+            }
+        }
+    }
+}
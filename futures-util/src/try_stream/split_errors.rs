@@ -0,0 +1,111 @@
+use core::fmt;
+use core::pin::Pin;
+use futures_channel::oneshot::{self, Receiver, Sender};
+use futures_core::future::Future;
+use futures_core::stream::{FusedStream, Stream, TryStream};
+use futures_core::task::{Context, Poll};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+
+/// Stream for the [`split_errors`](super::TryStreamExt::split_errors) method.
+#[must_use = "streams do nothing unless polled"]
+pub struct SplitErrors<St: TryStream> {
+    stream: St,
+    tx: Option<Sender<St::Error>>,
+}
+
+impl<St: TryStream + Unpin> Unpin for SplitErrors<St> {}
+
+impl<St> fmt::Debug for SplitErrors<St>
+where
+    St: TryStream + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SplitErrors")
+            .field("stream", &self.stream)
+            .field("terminated", &self.tx.is_none())
+            .finish()
+    }
+}
+
+/// Future for the [`split_errors`](super::TryStreamExt::split_errors) method,
+/// resolving to the first error of the stream.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct FirstError<E> {
+    rx: Receiver<E>,
+}
+
+pub(super) fn new<St: TryStream>(stream: St) -> (SplitErrors<St>, FirstError<St::Error>) {
+    let (tx, rx) = oneshot::channel();
+    (SplitErrors { stream, tx: Some(tx) }, FirstError { rx })
+}
+
+impl<St: TryStream> SplitErrors<St> {
+    unsafe_pinned!(stream: St);
+    unsafe_unpinned!(tx: Option<Sender<St::Error>>);
+
+    /// Acquires a reference to the underlying stream that this combinator is
+    /// pulling from.
+    pub fn get_ref(&self) -> &St {
+        &self.stream
+    }
+
+    /// Acquires a mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_mut(&mut self) -> &mut St {
+        &mut self.stream
+    }
+
+    /// Acquires a pinned mutable reference to the underlying stream that this
+    /// combinator is pulling from.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the
+    /// stream which may otherwise confuse this combinator.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut St> {
+        self.stream()
+    }
+}
+
+impl<St: TryStream> FusedStream for SplitErrors<St> {
+    fn is_terminated(&self) -> bool {
+        self.tx.is_none()
+    }
+}
+
+impl<St: TryStream> Stream for SplitErrors<St> {
+    type Item = St::Ok;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<St::Ok>> {
+        if self.tx.is_none() {
+            return Poll::Ready(None);
+        }
+
+        match ready!(self.as_mut().stream().try_poll_next(cx)) {
+            Some(Ok(item)) => Poll::Ready(Some(item)),
+            Some(Err(e)) => {
+                let tx = self.as_mut().tx().take().unwrap();
+                // The error is dropped if nobody is waiting for it anymore.
+                let _ = tx.send(e);
+                Poll::Ready(None)
+            }
+            None => {
+                *self.as_mut().tx() = None;
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+impl<E> Future for FirstError<E> {
+    type Output = Option<E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<E>> {
+        Pin::new(&mut self.rx).poll(cx).map(Result::ok)
+    }
+}
@@ -1,13 +1,39 @@
+#[cfg(feature = "timer")]
+use crate::future::FutureExt;
 use crate::stream::{Fuse, FuturesUnordered, StreamExt};
+#[cfg(feature = "timer")]
+use crate::timer::{sleep, Sleep, TimedOut};
 use crate::try_future::{IntoFuture, TryFutureExt};
 use crate::try_stream::IntoStream;
+use alloc::vec::Vec;
+#[cfg(feature = "timer")]
+use futures_core::future::Future;
 use futures_core::future::TryFuture;
 use futures_core::stream::{Stream, TryStream};
 use futures_core::task::{Context, Poll};
 #[cfg(feature = "sink")]
 use futures_sink::Sink;
 use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use core::mem;
 use core::pin::Pin;
+#[cfg(feature = "timer")]
+use std::time::Duration;
+
+/// What a [`TryBufferUnordered`] stream does with the errors of its futures,
+/// as set with [`error_policy`](TryBufferUnordered::error_policy).
+///
+/// Errors of the underlying stream itself are always yielded.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ErrorPolicy {
+    /// Yield the errors as items, and keep going. This is the default.
+    Continue,
+    /// Yield the first error, then end the stream, dropping the futures in
+    /// flight.
+    FailFast,
+    /// Keep the errors out of the stream, collecting them to be taken with
+    /// [`take_errors`](TryBufferUnordered::take_errors).
+    Collect,
+}
 
 /// Stream for the
 /// [`try_buffer_unordered`](super::TryStreamExt::try_buffer_unordered) method.
@@ -17,8 +43,16 @@ pub struct TryBufferUnordered<St>
     where St: TryStream
 {
     stream: Fuse<IntoStream<St>>,
-    in_progress_queue: FuturesUnordered<IntoFuture<St::Ok>>,
+    in_progress_queue: FuturesUnordered<Buffered<St>>,
     max: usize,
+    policy: ErrorPolicy,
+    errors: Vec<St::Error>,
+    failed: bool,
+    #[cfg(feature = "timer")]
+    item_timeout: Option<Duration>,
+    // Makes the error of the futures which time out.
+    #[cfg(feature = "timer")]
+    timed_out: Option<fn() -> St::Error>,
 }
 
 impl<St> Unpin for TryBufferUnordered<St>
@@ -30,15 +64,9 @@ impl<St> TryBufferUnordered<St>
           St::Ok: TryFuture,
 {
     unsafe_pinned!(stream: Fuse<IntoStream<St>>);
-    unsafe_unpinned!(in_progress_queue: FuturesUnordered<IntoFuture<St::Ok>>);
-
-    pub(super) fn new(stream: St, n: usize) -> Self {
-        TryBufferUnordered {
-            stream: IntoStream::new(stream).fuse(),
-            in_progress_queue: FuturesUnordered::new(),
-            max: n,
-        }
-    }
+    unsafe_unpinned!(in_progress_queue: FuturesUnordered<Buffered<St>>);
+    unsafe_unpinned!(errors: Vec<St::Error>);
+    unsafe_unpinned!(failed: bool);
 
     /// Acquires a reference to the underlying stream that this combinator is
     /// pulling from.
@@ -71,6 +99,72 @@ impl<St> TryBufferUnordered<St>
     pub fn into_inner(self) -> St {
         self.stream.into_inner().into_inner()
     }
+
+    /// Sets what to do with the errors of the buffered futures.
+    ///
+    /// See [`ErrorPolicy`] for the choices.
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Fails each buffered future which takes longer than `timeout` with a
+    /// [`TimedOut`] error, converted into the error type of the stream.
+    ///
+    /// The timeout of a future starts when it is taken from the underlying
+    /// stream, and timeout errors are handled like any other according to the
+    /// [`error_policy`](TryBufferUnordered::error_policy). The timeout only
+    /// applies to the futures taken from the stream after this call.
+    ///
+    /// This method is only available when the `timer` feature of this
+    /// library is activated.
+    #[cfg(feature = "timer")]
+    pub fn item_timeout(mut self, timeout: Duration) -> Self
+        where St::Error: From<TimedOut>,
+    {
+        self.item_timeout = Some(timeout);
+        self.timed_out = Some(|| TimedOut.into());
+        self
+    }
+
+    /// Takes the errors collected so far under [`ErrorPolicy::Collect`].
+    pub fn take_errors(&mut self) -> Vec<St::Error> {
+        mem::replace(&mut self.errors, Vec::new())
+    }
+}
+
+impl<St> TryBufferUnordered<St>
+    where St: TryStream,
+          St::Ok: TryFuture<Error = St::Error>,
+{
+    pub(super) fn new(stream: St, n: usize) -> Self {
+        TryBufferUnordered {
+            stream: IntoStream::new(stream).fuse(),
+            in_progress_queue: FuturesUnordered::new(),
+            max: n,
+            policy: ErrorPolicy::Continue,
+            errors: Vec::new(),
+            failed: false,
+            #[cfg(feature = "timer")]
+            item_timeout: None,
+            #[cfg(feature = "timer")]
+            timed_out: None,
+        }
+    }
+
+    // Wraps a future taken from the stream to be buffered.
+    #[cfg(feature = "timer")]
+    fn buffer(&self, future: IntoFuture<St::Ok>) -> Buffered<St> {
+        Timed {
+            future,
+            timeout: self.item_timeout.map(|timeout| (sleep(timeout), self.timed_out.unwrap())),
+        }
+    }
+
+    #[cfg(not(feature = "timer"))]
+    fn buffer(&self, future: IntoFuture<St::Ok>) -> Buffered<St> {
+        future
+    }
 }
 
 impl<St> Stream for TryBufferUnordered<St>
@@ -83,19 +177,43 @@ impl<St> Stream for TryBufferUnordered<St>
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        // First up, try to spawn off as many futures as possible by filling up
-        // our queue of futures. Propagate errors from the stream immediately.
-        while self.in_progress_queue.len() < self.max {
-            match self.as_mut().stream().poll_next(cx)? {
-                Poll::Ready(Some(fut)) => self.as_mut().in_progress_queue().push(fut.into_future()),
-                Poll::Ready(None) | Poll::Pending => break,
-            }
+        if self.failed {
+            return Poll::Ready(None);
         }
 
-        // Attempt to pull the next value from the in_progress_queue
-        match self.as_mut().in_progress_queue().poll_next_unpin(cx) {
-            x @ Poll::Pending | x @ Poll::Ready(Some(_)) => return x,
-            Poll::Ready(None) => {}
+        // Go around when an error is collected, as it makes room for another
+        // future.
+        loop {
+            // First up, try to spawn off as many futures as possible by filling
+            // up our queue of futures. Propagate errors from the stream
+            // immediately.
+            while self.in_progress_queue.len() < self.max {
+                match self.as_mut().stream().poll_next(cx)? {
+                    Poll::Ready(Some(fut)) => {
+                        let fut = self.buffer(fut.into_future());
+                        self.as_mut().in_progress_queue().push(fut)
+                    }
+                    Poll::Ready(None) | Poll::Pending => break,
+                }
+            }
+
+            // Attempt to pull the next value from the in_progress_queue
+            let output = match ready!(self.as_mut().in_progress_queue().poll_next_unpin(cx)) {
+                Some(output) => output,
+                None => break,
+            };
+            match output {
+                Err(e) => match self.policy {
+                    ErrorPolicy::Continue => return Poll::Ready(Some(Err(e))),
+                    ErrorPolicy::FailFast => {
+                        *self.as_mut().in_progress_queue() = FuturesUnordered::new();
+                        *self.as_mut().failed() = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                    ErrorPolicy::Collect => self.as_mut().errors().push(e),
+                },
+                Ok(item) => return Poll::Ready(Some(Ok(item))),
+            }
         }
 
         // If more values are still coming from the stream, we're not done yet
@@ -107,6 +225,45 @@ impl<St> Stream for TryBufferUnordered<St>
     }
 }
 
+// A future buffered by `TryBufferUnordered`.
+#[cfg(feature = "timer")]
+type Buffered<St> = Timed<IntoFuture<<St as TryStream>::Ok>, <St as TryStream>::Error>;
+#[cfg(not(feature = "timer"))]
+type Buffered<St> = IntoFuture<<St as TryStream>::Ok>;
+
+// A future which fails with the error made by the given function if its
+// timeout elapses first.
+#[cfg(feature = "timer")]
+#[derive(Debug)]
+struct Timed<Fut, E> {
+    future: Fut,
+    timeout: Option<(Sleep, fn() -> E)>,
+}
+
+#[cfg(feature = "timer")]
+impl<Fut, E> Timed<Fut, E> {
+    unsafe_pinned!(future: Fut);
+    unsafe_unpinned!(timeout: Option<(Sleep, fn() -> E)>);
+}
+
+#[cfg(feature = "timer")]
+impl<Fut, E> Future for Timed<Fut, E>
+    where Fut: TryFuture<Error = E>,
+{
+    type Output = Result<Fut::Ok, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.as_mut().future().try_poll(cx) {
+            return Poll::Ready(output);
+        }
+        if let Some((sleep, timed_out)) = self.as_mut().timeout() {
+            ready!(sleep.poll_unpin(cx));
+            return Poll::Ready(Err(timed_out()));
+        }
+        Poll::Pending
+    }
+}
+
 // Forwarding impl of Sink from the underlying stream
 #[cfg(feature = "sink")]
 impl<S, Item, E> Sink<Item> for TryBufferUnordered<S>
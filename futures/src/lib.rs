@@ -79,13 +79,15 @@ pub mod channel {
     //! Cross-task communication.
     //!
     //! Like threads, concurrent tasks sometimes need to communicate with each
-    //! other. This module contains two basic abstractions for doing so:
+    //! other. This module contains three basic abstractions for doing so:
     //!
     //! - [oneshot](crate::channel::oneshot), a way of sending a single value
     //!   from one task to another.
     //! - [mpsc](crate::channel::mpsc), a multi-producer, single-consumer
     //!   channel for sending values between tasks, analogous to the
     //!   similarly-named structure in the standard library.
+    //! - [multi_oneshot](crate::channel::multi_oneshot), a way of resolving
+    //!   many waiting tasks at once with clones of a single value.
     //!
     //! This module is only available when the `std` or `alloc` feature of this
     //! library is activated, and it is activated by default.
@@ -94,6 +96,9 @@ pub mod channel {
 
     #[cfg(feature = "std")]
     pub use futures_channel::mpsc;
+
+    #[cfg(feature = "std")]
+    pub use futures_channel::multi_oneshot;
 }
 
 #[cfg(feature = "compat")]
@@ -181,14 +186,27 @@ pub mod executor {
     //! [`block_on`](crate::executor::block_on), for simply running a future to
     //! completion on the current thread, while routing any spawned tasks
     //! to a global thread pool.
+    //!
+    //! # Default executor
+    //!
+    //! Applications which would rather not pass a spawner through every layer
+    //! just to occasionally spawn a fire-and-forget task can opt into a
+    //! process-wide default executor with
+    //! [`set_default_executor`](crate::executor::set_default_executor), and
+    //! then spawn onto it with [`spawn`](crate::executor::spawn()).
 
     pub use futures_executor::{
         BlockingStream,
         Enter, EnterError,
         LocalSpawner, LocalPool,
-        ThreadPool, ThreadPoolBuilder,
-        block_on, block_on_stream, enter,
+        SetDefaultExecutorError,
+        TaskId, ThreadPool, ThreadPoolBuilder,
+        block_on, block_on_stream, current_task_id, enter,
+        set_default_executor, spawn,
     };
+
+    #[cfg(feature = "load-samples")]
+    pub use futures_executor::{LoadSample, LoadSamples};
 }
 
 pub mod future {
@@ -219,6 +237,7 @@ pub mod future {
         poll_fn, PollFn,
         ready, ok, err, Ready,
         select, Select,
+        select_with_strategy, SelectWithStrategy, PollNext,
         join, join3, join4, join5,
         Join, Join3, Join4, Join5,
         Either,
@@ -242,7 +261,20 @@ pub mod future {
     )]
     #[cfg(feature = "alloc")]
     pub use futures_util::future::{
-        abortable, Abortable, AbortHandle, AbortRegistration, Aborted,
+        abortable, abortable_with_reason, Abortable, AbortableWithReason, AbortHandle,
+        AbortRegistration, Aborted, AbortedWithReason,
+        with_resource, with_resource_release, ResourceGuard, WithResource,
+    };
+
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "std")]
+    pub use futures_util::future::{
+        BatchDriver, BatchError, BatchExecutor, BatchResponse,
+        Correlator, CorrelatedResponse, CorrelationError,
+        EventHooks, Fire, HookError, HookId,
     };
 
     #[cfg(feature = "std")]
@@ -258,7 +290,7 @@ pub mod future {
         try_select, TrySelect,
 
         TryFutureExt,
-        AndThen, ErrInto, FlattenSink, IntoFuture, MapErr, MapOk, OrElse,
+        AndThen, AndThenSpawn, ErrInto, FlattenSink, IntoFuture, MapErr, MapOk, OrElse,
         InspectOk, InspectErr, TryFlattenStream, UnwrapOrElse,
     };
 
@@ -266,6 +298,7 @@ pub mod future {
     pub use futures_util::try_future::{
         try_join_all, TryJoinAll,
         select_ok, SelectOk,
+        race_ok_or_collect_errors, RaceOkOrCollectErrors,
     };
 }
 
@@ -302,8 +335,8 @@ pub mod io {
 
     pub use futures_util::io::{
         AsyncReadExt, AsyncWriteExt, AsyncSeekExt, AsyncBufReadExt, AllowStdIo,
-        BufReader, BufWriter, Close, CopyInto, CopyBufInto, Flush, IntoSink,
-        Lines, Read, ReadExact, ReadHalf, ReadLine, ReadToEnd, ReadToString,
+        BufReader, BufWriter, Close, CopyInto, CopyBufInto, Flush, FrameTooBig, IntoSink,
+        Lines, LinesSink, Read, ReadExact, ReadExactN, ReadExactVectored, ReadHalf, ReadLine, ReadToEnd, ReadToString,
         ReadUntil, ReadVectored, Seek, Window, Write, WriteAll, WriteHalf,
         WriteVectored,
     };
@@ -316,7 +349,9 @@ pub mod lock {
     //! This module is only available when the `std` feature of this
     //! library is activated, and it is activated by default.
 
-    pub use futures_util::lock::{Mutex, MutexLockFuture, MutexGuard};
+    pub use futures_util::lock::{LockAndRun, Mutex, MutexLockFuture, MutexGuard};
+    pub use futures_util::lock::{Acquire, Semaphore, SemaphorePermit};
+    pub use futures_util::lock::{Condvar, CondvarWait};
 }
 
 pub mod prelude {
@@ -369,13 +404,25 @@ pub mod sink {
     pub use futures_sink::Sink;
 
     pub use futures_util::sink::{
-        Close, Flush, Send, SendAll, SinkErrInto, SinkMapErr, With,
+        Close, Flush, Send, SendAll, SinkErrInto, SinkMapErr, SinkOrElse, With,
         SinkExt, Fanout, Drain, drain,
+        unfold, Unfold,
+        ReconnectingSink, ResumeState,
         WithFlatMap,
     };
 
     #[cfg(feature = "alloc")]
-    pub use futures_util::sink::Buffer;
+    pub use futures_util::sink::{Buffer, Commit, Transactional};
+
+    #[cfg(feature = "std")]
+    pub use futures_util::sink::{BackpressureProbe, PressureWatch};
+
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "std")]
+    pub use futures_util::sink::{Broadcast, Eviction, SinkRegistry};
 }
 
 pub mod stream {
@@ -409,15 +456,17 @@ pub mod stream {
 
         StreamExt,
         Chain, Collect, Concat, Enumerate, Filter, FilterMap, Flatten, Fold,
-        Forward, ForEach, Fuse, StreamFuture, Inspect, Map, Next,
-        SelectNextSome, Peekable, Skip, SkipWhile, Take, TakeWhile,
-        Then, Zip
+        Forward, ForEach, Fuse, StreamFuture, Inspect, Map, MapWhile, Next,
+        SelectNextSome, Peekable, Skip, SkipWhile, StartWith, SwitchMap, Take,
+        TakeBudget, TakeWhile, Then, Zip
     };
 
     #[cfg(feature = "alloc")]
     pub use futures_util::stream::{
+        StreamMap,
+
         // For StreamExt:
-        Chunks,
+        Chunks, DrainOnDrop,
     };
 
     #[cfg_attr(
@@ -439,12 +488,22 @@ pub mod stream {
     #[cfg(feature = "std")]
     pub use futures_util::stream::{
         // For StreamExt:
-        CatchUnwind,
+        CatchUnwind, MeasureSinceStamp, Stamp, Stamped,
+    };
+
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "std")]
+    pub use futures_util::stream::{
+        // For StreamExt:
+        ForEachKeyedConcurrent,
     };
 
     pub use futures_util::try_stream::{
         TryStreamExt,
-        AndThen, ErrInto, MapOk, MapErr, OrElse,
+        AndThen, ErrInto, Fallback, MapOk, MapErr, OrElse,
         InspectOk, InspectErr,
         TryNext, TryForEach, TryFilterMap, TryFlatten,
         TryCollect, TryFold, TrySkipWhile,
@@ -459,10 +518,93 @@ pub mod stream {
     pub use futures_util::try_stream::{
         // For TryStreamExt:
         TryBufferUnordered, TryForEachConcurrent,
+        ErrorPolicy,
+    };
+
+    #[cfg(feature = "std")]
+    pub use futures_util::try_stream::{
+        // For TryStreamExt:
+        FirstError, IntoAsyncRead, SplitErrors,
+    };
+
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "std")]
+    pub use futures_util::try_stream::ReconnectingStream;
+}
+
+#[cfg_attr(
+    feature = "cfg-target-has-atomic",
+    cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+)]
+#[cfg(feature = "std")]
+pub mod timer {
+    //! Timers.
+    //!
+    //! This module contains futures for waiting on the passage of time, all
+    //! driven by a single background thread that is shared by every timer in
+    //! the process.
+    //!
+    //! This module is only available when the `std` feature of this
+    //! library is activated, and it is activated by default.
+
+    pub use futures_util::timer::{
+        sleep, sleep_until, Sleep,
+        sleep_until_system_time, SleepUntilSystemTime,
+        ConnectionReaper, ReaperHandle,
+        interval, Interval, IntervalHandle,
+        poll_with_strategy, PollWithStrategy,
+        record, Record, replay, Replay, RecordedEvent,
+        schedule, Schedule, ScheduleSpec,
+        Timeout, TimeoutTotal, TimedOut,
+    };
+}
+
+pub mod transport {
+    //! Transports.
+    //!
+    //! This module contains the [`Transport`](crate::transport::Transport)
+    //! trait, which describes duplex connections of frames, and the
+    //! [`TransportExt`](crate::transport::TransportExt) trait, which provides
+    //! middleware layers for them, as well as the
+    //! [`multiplex`](crate::transport::multiplex) and
+    //! [`pipeline`](crate::transport::pipeline) modules, which implement
+    //! request/response protocols on top of them, and the
+    //! [`streaming`](crate::transport::streaming) module, which adds
+    //! streaming bodies to those.
+
+    pub use futures_util::transport::{
+        duplex, Duplex,
+        Transport, TransportExt,
     };
 
+    #[cfg(feature = "alloc")]
+    pub use futures_util::transport::{
+        Checksum, ChecksumError, ChecksumTrailer, Crc32, TRAILER_LEN,
+    };
+
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "std")]
+    pub use futures_util::transport::{Heartbeat, IdleTimeout};
+
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
     #[cfg(feature = "std")]
-    pub use futures_util::try_stream::IntoAsyncRead;
+    pub use futures_util::transport::{multiplex, pipeline, streaming};
+
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "std")]
+    pub use futures_util::transport::{serve, Serve, ShutdownHandle};
 }
 
 pub mod task {
@@ -503,6 +645,13 @@ pub mod task {
         cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
     )]
     pub use futures_util::task::AtomicWaker;
+
+    #[cfg_attr(
+        feature = "cfg-target-has-atomic",
+        cfg(all(target_has_atomic = "cas", target_has_atomic = "ptr"))
+    )]
+    #[cfg(feature = "std")]
+    pub use futures_util::task::{ConcurrencyLimiter, ConcurrencyLimitError};
 }
 
 pub mod never {
@@ -1,7 +1,7 @@
 use futures::channel::oneshot;
 use futures::executor::block_on;
-use futures::future::{abortable, Aborted, FutureExt};
-use futures::task::{Context, Poll};
+use futures::future::{abortable, abortable_with_reason, Aborted, FutureExt};
+use futures::task::{noop_waker_ref, Context, Poll};
 use futures_test::task::new_count_waker;
 
 #[test]
@@ -37,3 +37,63 @@ fn abortable_resolves() {
 
     assert_eq!(Ok(Ok(())), block_on(abortable_rx));
 }
+
+#[test]
+fn abortable_drops_future_on_abort() {
+    let (tx, a_rx) = oneshot::channel::<()>();
+    let (mut abortable_rx, abort_handle) = abortable(a_rx);
+
+    let mut cx = Context::from_waker(noop_waker_ref());
+    assert_eq!(Poll::Pending, abortable_rx.poll_unpin(&mut cx));
+    assert!(!tx.is_canceled());
+
+    abort_handle.abort();
+    assert_eq!(Poll::Ready(Err(Aborted)), abortable_rx.poll_unpin(&mut cx));
+    assert!(tx.is_canceled());
+}
+
+#[test]
+fn abortable_keeps_first_reason() {
+    let (_tx, a_rx) = oneshot::channel::<()>();
+    let (abortable_rx, abort_handle) = abortable(a_rx);
+
+    abort_handle.abort_with("timeout");
+    // Only the first reason is kept.
+    abort_handle.abort_with("shutdown");
+    assert_eq!(block_on(abortable_rx), Err(Aborted));
+    assert_eq!(abort_handle.reason(), Some("timeout"));
+}
+
+#[test]
+fn abortable_without_reason() {
+    let (_tx, a_rx) = oneshot::channel::<()>();
+    let (abortable_rx, abort_handle) = abortable(a_rx);
+
+    assert_eq!(abort_handle.reason(), None);
+    abort_handle.abort();
+    abort_handle.abort_with("shutdown");
+    assert_eq!(block_on(abortable_rx), Err(Aborted));
+    assert_eq!(abort_handle.reason(), None);
+}
+
+#[test]
+fn abortable_with_reason_carries_it_in_error() {
+    let (_tx, a_rx) = oneshot::channel::<()>();
+    let (abortable_rx, abort_handle) = abortable_with_reason(a_rx);
+
+    abort_handle.abort_with("timeout");
+    let err = block_on(abortable_rx).unwrap_err();
+    assert_eq!(err.reason(), Some("timeout"));
+    assert_eq!(err.to_string(), "future was aborted: timeout");
+}
+
+#[test]
+fn abortable_with_reason_without_reason() {
+    let (_tx, a_rx) = oneshot::channel::<()>();
+    let (abortable_rx, abort_handle) = abortable_with_reason(a_rx);
+
+    abort_handle.abort();
+    let err = block_on(abortable_rx).unwrap_err();
+    assert_eq!(err.reason(), None);
+    assert_eq!(err.to_string(), "future was aborted");
+}
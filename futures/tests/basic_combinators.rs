@@ -96,3 +96,27 @@ fn basic_try_future_combinators() {
     for i in 1..=12 { assert_eq!(rx.recv(), Ok(i)); } // Check it
     assert!(rx.recv().is_err()); // Should be done
 }
+
+#[test]
+fn and_then_spawn_offloads_side_work() {
+    use futures::executor::{block_on, LocalPool};
+    use futures::task::SpawnExt;
+
+    let mut pool = LocalPool::new();
+    let (tx, rx) = mpsc::channel();
+
+    let fut = future::ready(Ok::<i32, i32>(1))
+        .and_then_spawn(pool.spawner(), move |x, spawner| {
+            spawner.spawn(future::lazy(move |_| tx.send(x).unwrap())).unwrap();
+            future::ready(Ok(x + 1))
+        });
+
+    assert_eq!(block_on(fut), Ok(2));
+    assert!(rx.try_recv().is_err()); // Spawned, but not run yet
+    pool.run();
+    assert_eq!(rx.recv(), Ok(1));
+
+    let fut = future::ready(Err::<i32, i32>(1))
+        .and_then_spawn(pool.spawner(), |x, _| future::ready(Ok(x + 1)));
+    assert_eq!(block_on(fut), Err(1));
+}
@@ -0,0 +1,98 @@
+use futures::executor::block_on;
+use futures::future::{self, BatchError, BatchExecutor, Either, FutureExt};
+use futures_test::task::{new_count_waker, noop_context};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+#[test]
+fn coalesces_and_deduplicates_keys() {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let batches2 = batches.clone();
+    let (executor, driver) = BatchExecutor::new(10, Duration::from_millis(10), move |keys: Vec<u32>| {
+        batches2.lock().unwrap().push(keys.clone());
+        future::ok::<_, ()>(keys.into_iter().map(|key| key * 10).collect())
+    });
+    let responses = future::join_all(vec![executor.load(1), executor.load(2), executor.load(1)]);
+    drop(executor);
+
+    let (values, ()) = block_on(future::join(responses, driver));
+    assert_eq!(values, vec![Ok(10), Ok(20), Ok(10)]);
+    assert_eq!(*batches.lock().unwrap(), vec![vec![1, 2]]);
+}
+
+#[test]
+fn dispatches_full_batches_without_delay() {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let batches2 = batches.clone();
+    let (executor, mut driver) = BatchExecutor::new(2, Duration::from_secs(60), move |keys: Vec<u32>| {
+        batches2.lock().unwrap().push(keys.clone());
+        future::ok::<_, ()>(keys)
+    });
+    let mut first = executor.load(1);
+    let mut second = executor.load(2);
+    let mut third = executor.load(3);
+
+    assert!(driver.poll_unpin(&mut noop_context()).is_pending());
+    assert_eq!(*batches.lock().unwrap(), vec![vec![1, 2]]);
+    assert_eq!(first.poll_unpin(&mut noop_context()), Poll::Ready(Ok(1)));
+    assert_eq!(second.poll_unpin(&mut noop_context()), Poll::Ready(Ok(2)));
+    assert!(third.poll_unpin(&mut noop_context()).is_pending());
+
+    // The last, partial batch is flushed once the executor is gone.
+    drop(executor);
+    block_on(driver);
+    assert_eq!(block_on(third), Ok(3));
+    assert_eq!(*batches.lock().unwrap(), vec![vec![1, 2], vec![3]]);
+}
+
+#[test]
+fn takes_one_batch_of_requests_per_poll() {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let batches2 = batches.clone();
+    let (executor, mut driver) = BatchExecutor::new(2, Duration::from_secs(60), move |keys: Vec<u32>| {
+        batches2.lock().unwrap().push(keys.clone());
+        future::ok::<_, ()>(keys)
+    });
+    let _responses: Vec<_> = (1..=5).map(|key| executor.load(key)).collect();
+    let (waker, count) = new_count_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    assert!(driver.poll_unpin(&mut cx).is_pending());
+    assert_eq!(*batches.lock().unwrap(), vec![vec![1, 2]]);
+    assert_eq!(count, 1);
+
+    assert!(driver.poll_unpin(&mut cx).is_pending());
+    assert_eq!(*batches.lock().unwrap(), vec![vec![1, 2], vec![3, 4]]);
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn reports_failures_and_missing_values() {
+    let (executor, mut driver) = BatchExecutor::new(10, Duration::from_millis(10), |keys: Vec<u32>| {
+        if keys.contains(&0) {
+            future::err("bad key")
+        } else {
+            future::ok(vec!["only one"])
+        }
+    });
+
+    let responses = future::join_all(vec![executor.load(1), executor.load(2)]);
+    let values = match block_on(future::select(responses, &mut driver)) {
+        Either::Left((values, _)) => values,
+        Either::Right(_) => panic!("driver completed"),
+    };
+    assert_eq!(values, vec![Ok("only one"), Err(BatchError::Missing)]);
+
+    let responses = future::join_all(vec![executor.load(0), executor.load(1)]);
+    let values = match block_on(future::select(responses, &mut driver)) {
+        Either::Left((values, _)) => values,
+        Either::Right(_) => panic!("driver completed"),
+    };
+    assert_eq!(values, vec![Err(BatchError::Failed("bad key")), Err(BatchError::Failed("bad key"))]);
+
+    let canceled = executor.load(1);
+    drop(driver);
+    assert_eq!(block_on(canceled), Err(BatchError::Canceled));
+    assert_eq!(block_on(executor.load(1)), Err(BatchError::Canceled));
+}
@@ -1,8 +1,12 @@
 use futures::channel::{oneshot, mpsc};
 use futures::executor::{block_on, block_on_stream};
+use futures::future::{self, FutureExt};
 use futures::sink::SinkExt;
-use futures::stream::StreamExt;
+use futures::stream::{self, StreamExt};
+use futures_test::future::FutureTestExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc as std_mpsc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 #[test]
@@ -71,3 +75,31 @@ fn works() {
     t1.join().unwrap();
     t2.join().unwrap();
 }
+
+#[test]
+fn limits_futures_in_flight() {
+    const LIMIT: usize = 8;
+
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(Mutex::new(0));
+
+    let futures = (0..1000).map(|i| {
+        let in_flight = in_flight.clone();
+        let in_flight2 = in_flight.clone();
+        let max_in_flight = max_in_flight.clone();
+        future::lazy(move |_| {
+            let n = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut max_in_flight = max_in_flight.lock().unwrap();
+            if n > *max_in_flight {
+                *max_in_flight = n;
+            }
+        })
+        .then(move |()| future::ready(i).pending_once())
+        .inspect(move |_| { in_flight2.fetch_sub(1, Ordering::SeqCst); })
+    });
+
+    let mut results = block_on(stream::iter(futures).buffer_unordered(LIMIT).collect::<Vec<_>>());
+    results.sort();
+    assert_eq!(results, (0..1000).collect::<Vec<_>>());
+    assert_eq!(*max_in_flight.lock().unwrap(), LIMIT);
+}
@@ -0,0 +1,117 @@
+use futures::future::{self, FutureExt, FutureObj};
+use futures::task::{ConcurrencyLimitError, ConcurrencyLimiter, Spawn, SpawnError};
+use futures_test::task::noop_context;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+// A spawner which keeps the spawned tasks, to be polled by the test, until it
+// is shut down.
+#[derive(Clone, Default)]
+struct ManualSpawner {
+    tasks: Arc<Mutex<Vec<FutureObj<'static, ()>>>>,
+    shut_down: Arc<AtomicBool>,
+}
+
+impl Spawn for ManualSpawner {
+    fn spawn_obj(&mut self, future: FutureObj<'static, ()>) -> Result<(), SpawnError> {
+        if self.shut_down.load(Ordering::SeqCst) {
+            return Err(SpawnError::shutdown());
+        }
+        self.tasks.lock().unwrap().push(future);
+        Ok(())
+    }
+}
+
+impl ManualSpawner {
+    fn spawned(&self) -> usize {
+        self.tasks.lock().unwrap().len()
+    }
+
+    // Runs the `i`th spawned task to completion.
+    fn complete(&self, i: usize) {
+        let mut task = self.tasks.lock().unwrap().remove(i);
+        assert!(task.poll_unpin(&mut noop_context()).is_ready());
+    }
+}
+
+#[test]
+fn limits_running_tasks_per_category() {
+    let spawner = ManualSpawner::default();
+    let limiter = ConcurrencyLimiter::new(spawner.clone(), 1);
+    limiter.set_limit("b", 2);
+
+    for _ in 0..2 {
+        limiter.spawn("a", future::ready(())).unwrap();
+        limiter.spawn("b", future::ready(())).unwrap();
+    }
+    assert_eq!(spawner.spawned(), 3);
+    assert_eq!((limiter.running(&"a"), limiter.queued(&"a")), (1, 1));
+    assert_eq!((limiter.running(&"b"), limiter.queued(&"b")), (2, 0));
+
+    // Completing the task of "a" starts the queued one.
+    spawner.complete(0);
+    assert_eq!(spawner.spawned(), 3);
+    assert_eq!((limiter.running(&"a"), limiter.queued(&"a")), (1, 0));
+}
+
+#[test]
+fn queued_tasks_start_by_priority_then_fifo() {
+    let spawner = ManualSpawner::default();
+    let limiter = ConcurrencyLimiter::new(spawner.clone(), 1);
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    limiter.spawn("a", future::ready(())).unwrap();
+    for &(priority, name) in &[(0, "low 1"), (5, "high"), (0, "low 2")] {
+        let order = order.clone();
+        limiter.spawn_with_priority("a", priority, future::lazy(move |_| {
+            order.lock().unwrap().push(name);
+        })).unwrap();
+    }
+
+    for _ in 0..4 {
+        spawner.complete(0);
+    }
+    assert_eq!(*order.lock().unwrap(), vec!["high", "low 1", "low 2"]);
+    assert_eq!(limiter.running(&"a"), 0);
+}
+
+#[test]
+fn bounded_queue_rejects_tasks() {
+    let spawner = ManualSpawner::default();
+    let limiter = ConcurrencyLimiter::new(spawner.clone(), 1);
+    limiter.set_queue_bound("a", Some(1));
+
+    limiter.spawn("a", future::ready(())).unwrap();
+    limiter.spawn("a", future::ready(())).unwrap();
+    match limiter.spawn("a", future::ready(())) {
+        Err(ConcurrencyLimitError::QueueFull) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn dropped_task_frees_its_slot() {
+    let spawner = ManualSpawner::default();
+    let limiter = ConcurrencyLimiter::new(spawner.clone(), 1);
+
+    limiter.spawn("a", future::pending()).unwrap();
+    limiter.spawn("a", future::ready(())).unwrap();
+    let task = spawner.tasks.lock().unwrap().remove(0);
+    drop(task);
+    assert_eq!((limiter.running(&"a"), limiter.queued(&"a")), (1, 0));
+    assert_eq!(spawner.spawned(), 1);
+}
+
+#[test]
+fn shut_down_spawner_drops_queued_tasks() {
+    let spawner = ManualSpawner::default();
+    let limiter = ConcurrencyLimiter::new(spawner.clone(), 1);
+
+    limiter.spawn("a", future::ready(())).unwrap();
+    for _ in 0..100_000 {
+        limiter.spawn("a", future::ready(())).unwrap();
+    }
+    spawner.shut_down.store(true, Ordering::SeqCst);
+    spawner.complete(0);
+    assert_eq!((limiter.running(&"a"), limiter.queued(&"a")), (0, 0));
+}
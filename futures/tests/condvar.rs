@@ -0,0 +1,84 @@
+use futures::executor::block_on;
+use futures::future::FutureExt;
+use futures::lock::{Condvar, Mutex};
+use futures::task::Context;
+use futures_test::task::{new_count_waker, noop_context};
+
+#[test]
+fn condvar_wait_releases_lock() {
+    let mutex = Mutex::new(0);
+    let condvar = Condvar::new();
+
+    let guard = mutex.try_lock().unwrap();
+    let mut wait = condvar.wait(guard);
+    assert!(wait.poll_unpin(&mut noop_context()).is_pending());
+
+    *mutex.try_lock().unwrap() = 1;
+    condvar.notify_one();
+    let guard = block_on(wait);
+    assert_eq!(*guard, 1);
+}
+
+#[test]
+fn condvar_notify_before_poll_is_not_lost() {
+    let mutex = Mutex::new(());
+    let condvar = Condvar::new();
+
+    let wait = condvar.wait(mutex.try_lock().unwrap());
+    condvar.notify_one();
+    assert!(wait.now_or_never().is_some());
+}
+
+#[test]
+fn condvar_notify_one_in_order() {
+    let mutex = Mutex::new(());
+    let condvar = Condvar::new();
+
+    let (waker1, counter1) = new_count_waker();
+    let (waker2, counter2) = new_count_waker();
+    let mut wait1 = condvar.wait(mutex.try_lock().unwrap());
+    let mut wait2 = condvar.wait(mutex.try_lock().unwrap());
+    assert!(wait1.poll_unpin(&mut Context::from_waker(&waker1)).is_pending());
+    assert!(wait2.poll_unpin(&mut Context::from_waker(&waker2)).is_pending());
+
+    condvar.notify_one();
+    assert_eq!(counter1, 1);
+    assert_eq!(counter2, 0);
+
+    // Dropping the notified waiter passes the notification on.
+    drop(wait1);
+    assert_eq!(counter2, 1);
+    assert!(wait2.now_or_never().is_some());
+}
+
+#[test]
+fn condvar_notify_all() {
+    let mutex = Mutex::new(());
+    let condvar = Condvar::new();
+
+    let (waker, counter) = new_count_waker();
+    let mut waits: Vec<_> = (0..3).map(|_| condvar.wait(mutex.try_lock().unwrap())).collect();
+    for wait in &mut waits {
+        assert!(wait.poll_unpin(&mut Context::from_waker(&waker)).is_pending());
+    }
+
+    condvar.notify_all();
+    assert_eq!(counter, 3);
+    for wait in waits {
+        assert!(wait.now_or_never().is_some());
+    }
+}
+
+#[test]
+fn condvar_notified_waiter_waits_for_lock() {
+    let mutex = Mutex::new(());
+    let condvar = Condvar::new();
+
+    let mut wait = condvar.wait(mutex.try_lock().unwrap());
+    let guard = mutex.try_lock().unwrap();
+    condvar.notify_one();
+    assert!(wait.poll_unpin(&mut noop_context()).is_pending());
+
+    drop(guard);
+    assert!(wait.now_or_never().is_some());
+}
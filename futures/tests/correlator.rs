@@ -0,0 +1,66 @@
+use futures::executor::block_on;
+use futures::future::{Correlator, CorrelationError, FutureExt};
+use futures_test::task::noop_context;
+use std::task::Poll;
+use std::time::Duration;
+
+#[test]
+fn completes_out_of_order() {
+    let correlator = Correlator::<u32, String, ()>::new();
+    let a = correlator.register(1);
+    let b = correlator.register(2);
+    assert_eq!(correlator.len(), 2);
+
+    correlator.complete(&2, "b".to_string()).unwrap();
+    correlator.complete(&1, "a".to_string()).unwrap();
+    assert!(correlator.is_empty());
+    assert_eq!(block_on(b), Ok("b".to_string()));
+    assert_eq!(block_on(a), Ok("a".to_string()));
+
+    assert_eq!(correlator.complete(&1, "late".to_string()), Err("late".to_string()));
+}
+
+#[test]
+fn fail_all_broadcasts_error() {
+    let correlator = Correlator::<u32, (), &str>::new();
+    let responses: Vec<_> = (0..3).map(|id| correlator.register(id)).collect();
+
+    correlator.fail_all("connection reset");
+    assert!(correlator.is_empty());
+    for response in responses {
+        assert_eq!(block_on(response), Err(CorrelationError::Failed("connection reset")));
+    }
+}
+
+#[test]
+fn dropped_response_is_removed() {
+    let correlator = Correlator::<u32, (), ()>::new();
+    let response = correlator.register(1);
+    assert_eq!(correlator.len(), 1);
+    drop(response);
+    assert!(correlator.is_empty());
+    assert_eq!(correlator.complete(&1, ()), Err(()));
+}
+
+#[test]
+fn timed_out_response_is_removed() {
+    let correlator = Correlator::<u32, (), ()>::new();
+    let response = correlator.register_with_timeout(1, Duration::from_millis(10));
+    assert_eq!(block_on(response), Err(CorrelationError::TimedOut));
+    assert!(correlator.is_empty());
+}
+
+#[test]
+fn replaced_and_orphaned_requests_are_canceled() {
+    let correlator = Correlator::<u32, (), ()>::new();
+    let first = correlator.register(1);
+    let mut second = correlator.register(1);
+    assert_eq!(block_on(first), Err(CorrelationError::Canceled));
+
+    // Dropping the replaced request must not remove its replacement.
+    assert_eq!(correlator.len(), 1);
+    assert_eq!(second.poll_unpin(&mut noop_context()), Poll::Pending);
+
+    drop(correlator);
+    assert_eq!(block_on(second), Err(CorrelationError::Canceled));
+}
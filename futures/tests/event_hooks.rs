@@ -0,0 +1,54 @@
+use futures::channel::oneshot;
+use futures::executor::block_on;
+use futures::future::{self, EventHooks, FutureExt, HookError};
+use futures_test::task::noop_context;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn fire_waits_for_every_listener() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut hooks = EventHooks::<i32, ()>::new();
+    let (tx, rx) = oneshot::channel::<()>();
+    let mut rx = Some(rx);
+    hooks.register(move |_: &i32| rx.take().unwrap().map(|_| Ok(())));
+    let seen2 = seen.clone();
+    hooks.register(move |event: &i32| {
+        seen2.lock().unwrap().push(*event);
+        future::ok(())
+    });
+
+    let mut fire = hooks.fire(&7);
+    assert_eq!(*seen.lock().unwrap(), vec![7]);
+    assert!(fire.poll_unpin(&mut noop_context()).is_pending());
+    tx.send(()).unwrap();
+    assert_eq!(block_on(fire), vec![]);
+}
+
+#[test]
+fn fire_collects_errors_and_timeouts() {
+    let mut hooks = EventHooks::<(), &str>::new();
+    let ok = hooks.register(|_: &()| future::ok(()));
+    let failing = hooks.register(|_: &()| future::err("broken"));
+    let stuck = hooks.register_with_timeout(Duration::from_millis(20), |_: &()| future::pending());
+    assert_eq!(hooks.len(), 3);
+
+    let errors = block_on(hooks.fire(&()));
+    assert_eq!(errors, vec![(failing, HookError::Failed("broken")), (stuck, HookError::TimedOut)]);
+
+    assert!(hooks.unregister(failing));
+    assert!(!hooks.unregister(failing));
+    assert!(hooks.unregister(stuck));
+    assert!(hooks.unregister(ok));
+    assert!(hooks.is_empty());
+    assert_eq!(block_on(hooks.fire(&())), vec![]);
+}
+
+#[test]
+fn default_timeout_applies_to_listeners() {
+    let mut hooks = EventHooks::<(), ()>::with_timeout(Duration::from_millis(20));
+    let stuck = hooks.register(|_: &()| future::pending());
+    hooks.register_with_timeout(Duration::from_secs(60), |_: &()| future::ok(()));
+
+    assert_eq!(block_on(hooks.fire(&())), vec![(stuck, HookError::TimedOut)]);
+}
@@ -0,0 +1,70 @@
+use futures::channel::oneshot;
+use futures::future::FutureExt;
+use futures::stream::{self, StreamExt};
+use futures_test::task::noop_context;
+use std::cell::RefCell;
+
+#[test]
+fn serializes_items_with_the_same_key() {
+    let started = RefCell::new(Vec::new());
+    let (tx_a1, rx_a1) = oneshot::channel::<()>();
+    let (tx_b1, rx_b1) = oneshot::channel::<()>();
+    let (tx_a2, rx_a2) = oneshot::channel::<()>();
+    let items = vec![("a", 1, rx_a1), ("b", 1, rx_b1), ("a", 2, rx_a2)];
+
+    let mut fut = stream::iter(items).for_each_keyed_concurrent(
+        None,
+        |&(key, _, _)| key,
+        |(key, n, rx)| {
+            started.borrow_mut().push((key, n));
+            rx.map(drop)
+        },
+    );
+    let mut cx = noop_context();
+
+    assert!(fut.poll_unpin(&mut cx).is_pending());
+    assert_eq!(*started.borrow(), vec![("a", 1), ("b", 1)]);
+
+    // Completing another key does not let ("a", 2) through...
+    tx_b1.send(()).unwrap();
+    assert!(fut.poll_unpin(&mut cx).is_pending());
+    assert_eq!(started.borrow().len(), 2);
+
+    // ...but completing ("a", 1) does.
+    tx_a1.send(()).unwrap();
+    assert!(fut.poll_unpin(&mut cx).is_pending());
+    assert_eq!(*started.borrow(), vec![("a", 1), ("b", 1), ("a", 2)]);
+
+    tx_a2.send(()).unwrap();
+    assert!(fut.poll_unpin(&mut cx).is_ready());
+}
+
+#[test]
+fn limit_counts_waiting_items() {
+    let started = RefCell::new(Vec::new());
+    let (tx, rx) = oneshot::channel::<()>();
+    let mut rxs = vec![Some(rx)];
+    let items = vec![("a", 1), ("a", 2), ("b", 1)];
+
+    let mut fut = stream::iter(items).for_each_keyed_concurrent(
+        2,
+        |&(key, _)| key,
+        |item| {
+            started.borrow_mut().push(item);
+            match rxs.pop() {
+                Some(Some(rx)) => rx.map(drop).left_future(),
+                _ => futures::future::ready(()).right_future(),
+            }
+        },
+    );
+    let mut cx = noop_context();
+
+    // ("a", 2) waits for ("a", 1), which fills the limit, so ("b", 1) is not
+    // taken from the stream yet.
+    assert!(fut.poll_unpin(&mut cx).is_pending());
+    assert_eq!(*started.borrow(), vec![("a", 1)]);
+
+    tx.send(()).unwrap();
+    assert!(fut.poll_unpin(&mut cx).is_ready());
+    assert_eq!(*started.borrow(), vec![("a", 1), ("a", 2), ("b", 1)]);
+}
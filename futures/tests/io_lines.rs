@@ -1,11 +1,11 @@
 use futures::executor::block_on;
 use futures::future::{Future, FutureExt};
 use futures::stream::{self, StreamExt, TryStreamExt};
-use futures::io::AsyncBufReadExt;
+use futures::io::{AsyncBufReadExt, FrameTooBig};
 use futures::task::Poll;
 use futures_test::io::AsyncReadTestExt;
 use futures_test::task::noop_context;
-use std::io::Cursor;
+use std::io::{self, Cursor};
 
 macro_rules! block_on_next {
     ($expr:expr) => {
@@ -61,3 +61,28 @@ fn maybe_pending() {
     assert_eq!(run_next!(s), "".to_string());
     assert!(run(s.next()).is_none());
 }
+
+#[test]
+fn max_line_length() {
+    let buf = Cursor::new(&b"12\n3456789\nab\n"[..]);
+    let mut s = buf.lines().max_line_length(4);
+    assert_eq!(block_on_next!(s), "12".to_string());
+    let err = block_on(s.next()).unwrap().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let too_big = err.get_ref().unwrap().downcast_ref::<FrameTooBig>().unwrap();
+    assert_eq!(too_big.limit(), 4);
+    assert_eq!(block_on_next!(s), "ab".to_string());
+    assert!(block_on(s.next()).is_none());
+}
+
+#[test]
+fn max_line_length_maybe_pending() {
+    let buf = stream::iter(vec![&b"1234"[..], &b"5678"[..], &b"9\n"[..], &b"ab\n"[..]])
+        .map(Ok)
+        .into_async_read()
+        .interleave_pending();
+    let mut s = buf.lines().max_line_length(3);
+    assert!(run(s.next()).unwrap().is_err());
+    assert_eq!(run_next!(s), "ab".to_string());
+    assert!(run(s.next()).is_none());
+}
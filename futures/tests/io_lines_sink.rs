@@ -0,0 +1,83 @@
+use futures::executor::block_on;
+use futures::future::FutureExt;
+use futures::io::{AsyncWrite, AsyncWriteExt};
+use futures::stream::{self, StreamExt};
+use futures::task::{Context, Poll};
+use futures_test::io::AsyncWriteTestExt;
+use futures_test::task::noop_context;
+use std::io;
+use std::pin::Pin;
+
+#[test]
+fn writes_lines() {
+    let mut writer = vec![];
+    let stream = stream::iter(vec!["a", "", "bc"]).map(Ok);
+    block_on(stream.forward((&mut writer).lines_sink())).unwrap();
+    assert_eq!(writer, b"a\n\nbc\n");
+}
+
+// Writer which records what had been written at each flush
+#[derive(Default)]
+struct FlushLog {
+    data: Vec<u8>,
+    flushes: Vec<Vec<u8>>,
+}
+
+impl AsyncWrite for FlushLog {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.data.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let data = self.data.clone();
+        self.flushes.push(data);
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[test]
+fn flushes_per_line() {
+    let mut writer = FlushLog::default();
+    let stream = stream::iter(vec!["a", "b"]).map(Ok);
+    block_on(stream.forward((&mut writer).lines_sink())).unwrap();
+    assert_eq!(writer.flushes, vec![b"a\n".to_vec()]);
+    assert_eq!(writer.data, b"a\nb\n");
+}
+
+#[test]
+fn flushes_per_batch() {
+    let mut writer = FlushLog::default();
+    let stream = stream::iter(vec!["a", "b", "c", "d", "e"]).map(Ok);
+    block_on(stream.forward((&mut writer).lines_sink().flush_every(2))).unwrap();
+    assert_eq!(writer.flushes, vec![b"a\nb\n".to_vec(), b"a\nb\nc\nd\n".to_vec()]);
+    // The last, incomplete batch is written out when the sink is closed.
+    assert_eq!(writer.data, b"a\nb\nc\nd\ne\n");
+}
+
+#[test]
+fn maybe_pending() {
+    let mut writer = vec![].limited_write(1).interleave_pending_write();
+    {
+        let mut sink = (&mut writer).lines_sink().flush_every(3);
+        let mut fut = stream::iter(vec!["one", "two", "three", "four"])
+            .map(Ok)
+            .forward(&mut sink);
+        let mut cx = noop_context();
+        loop {
+            if let Poll::Ready(res) = fut.poll_unpin(&mut cx) {
+                res.unwrap();
+                break;
+            }
+        }
+    }
+    assert_eq!(writer.get_ref().get_ref(), b"one\ntwo\nthree\nfour\n");
+}
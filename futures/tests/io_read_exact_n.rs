@@ -0,0 +1,69 @@
+use futures::executor::block_on;
+use futures::io::AsyncReadExt;
+use futures_test::io::AsyncReadTestExt;
+use std::io;
+
+fn body_len(header: &[u8]) -> io::Result<usize> {
+    Ok(header[0] as usize)
+}
+
+#[test]
+fn read_header_then_body() {
+    let mut reader: &[u8] = &[3, 1, 2, 3, 2, 4, 5, 9];
+    let mut header = [0u8; 1];
+    let mut body = Vec::new();
+
+    block_on(reader.read_exact_n(&mut header, &mut body, body_len)).unwrap();
+    assert_eq!(header, [3]);
+    assert_eq!(body, vec![1, 2, 3]);
+
+    block_on(reader.read_exact_n(&mut header, &mut body, body_len)).unwrap();
+    assert_eq!(header, [2]);
+    assert_eq!(body, vec![4, 5]);
+    assert_eq!(reader, &[9]);
+}
+
+#[test]
+fn read_in_small_chunks() {
+    let data: &[u8] = &[0, 4, 1, 2, 3, 4];
+    let mut reader = data.interleave_pending().limited(1);
+    let mut header = [0u8; 2];
+    let mut body = Vec::new();
+
+    block_on(reader.read_exact_n(&mut header, &mut body, |h| Ok(h[1] as usize))).unwrap();
+    assert_eq!(body, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn empty_body() {
+    let mut reader: &[u8] = &[0, 7];
+    let mut header = [0u8; 1];
+    let mut body = vec![1, 2, 3];
+
+    block_on(reader.read_exact_n(&mut header, &mut body, body_len)).unwrap();
+    assert!(body.is_empty());
+    assert_eq!(reader, &[7]);
+}
+
+#[test]
+fn eof_in_body() {
+    let mut reader: &[u8] = &[3, 1, 2];
+    let mut header = [0u8; 1];
+    let mut body = Vec::new();
+
+    let err = block_on(reader.read_exact_n(&mut header, &mut body, body_len)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn rejected_header() {
+    let mut reader: &[u8] = &[255, 1, 2];
+    let mut header = [0u8; 1];
+    let mut body = Vec::new();
+
+    let err = block_on(reader.read_exact_n(&mut header, &mut body, |_| {
+        Err(io::Error::new(io::ErrorKind::InvalidData, "frame too large"))
+    })).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(reader, &[1, 2]);
+}
@@ -0,0 +1,39 @@
+use futures::executor::block_on;
+use futures::io::AsyncReadExt;
+use futures_test::io::AsyncReadTestExt;
+use std::io;
+
+#[test]
+fn fills_all_buffers() {
+    let mut reader: &[u8] = &[1, 2, 3, 4, 5, 6, 7];
+    let mut a = [0u8; 2];
+    let mut b = [0u8; 0];
+    let mut c = [0u8; 3];
+
+    block_on(reader.read_exact_vectored(&mut [&mut a, &mut b, &mut c])).unwrap();
+    assert_eq!(a, [1, 2]);
+    assert_eq!(c, [3, 4, 5]);
+    assert_eq!(reader, &[6, 7]);
+}
+
+#[test]
+fn fills_across_short_reads() {
+    let data: &[u8] = &[1, 2, 3, 4, 5];
+    let mut reader = data.interleave_pending().limited(2);
+    let mut a = [0u8; 3];
+    let mut b = [0u8; 2];
+
+    block_on(reader.read_exact_vectored(&mut [&mut a, &mut b])).unwrap();
+    assert_eq!(a, [1, 2, 3]);
+    assert_eq!(b, [4, 5]);
+}
+
+#[test]
+fn eof_before_full() {
+    let mut reader: &[u8] = &[1, 2, 3];
+    let mut a = [0u8; 2];
+    let mut b = [0u8; 2];
+
+    let err = block_on(reader.read_exact_vectored(&mut [&mut a, &mut b])).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
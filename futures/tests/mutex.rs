@@ -17,6 +17,16 @@ fn mutex_acquire_uncontested() {
     }
 }
 
+#[test]
+fn mutex_guard_debug() {
+    let mutex = Mutex::new(1);
+    let guard = mutex.try_lock().unwrap();
+    assert_eq!(
+        format!("{:?}", guard),
+        "MutexGuard { value: 1, mutex: Mutex { is_locked: true, has_waiters: false } }",
+    );
+}
+
 #[test]
 fn mutex_wakes_waiters() {
     let mutex = Mutex::new(());
@@ -67,3 +77,59 @@ fn mutex_contested() {
         assert_eq!(num_tasks, *lock);
     })
 }
+
+#[test]
+fn mutex_lock_and_run_releases_on_completion() {
+    let mutex = Mutex::new(0);
+    let output = futures::executor::block_on(mutex.lock_and_run(|mut guard| async move {
+        *guard += 1;
+        *guard
+    }));
+    assert_eq!(output, 1);
+    assert!(mutex.try_lock().is_some());
+}
+
+#[test]
+fn mutex_lock_and_run_cancelled_while_locking() {
+    let mutex = Mutex::new(());
+    let guard = mutex.try_lock().unwrap();
+
+    let (waker, counter) = new_count_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut critical = mutex.lock_and_run(|guard| {
+        drop(guard);
+        ready(())
+    });
+    let mut waiter = mutex.lock();
+    assert!(critical.poll_unpin(&mut cx).is_pending());
+    assert!(waiter.poll_unpin(&mut cx).is_pending());
+
+    // The critical section is woken up to take the lock, but cancelled
+    // before it does: the other waiter gets it instead.
+    drop(guard);
+    assert_eq!(counter, 1);
+    drop(critical);
+    assert_eq!(counter, 2);
+    assert!(waiter.poll_unpin(&mut panic_context()).is_ready());
+}
+
+#[test]
+fn mutex_lock_and_run_cancelled_at_each_await_point() {
+    for awaits in 0..3 {
+        let mutex = Mutex::new(0);
+        let mut critical = Box::pin(mutex.lock_and_run(|mut guard| async move {
+            for _ in 0..3 {
+                *guard += 1;
+                ready(()).pending_once().await;
+            }
+        }));
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+        for _ in 0..=awaits {
+            assert!(critical.poll_unpin(&mut cx).is_pending());
+        }
+        assert!(mutex.try_lock().is_none());
+
+        drop(critical);
+        assert_eq!(*mutex.try_lock().unwrap(), awaits + 1);
+    }
+}
@@ -0,0 +1,42 @@
+use futures::channel::oneshot;
+use futures::executor::block_on;
+use futures::future::{err, ok, race_ok_or_collect_errors, FutureExt, TryFutureExt};
+use futures_test::task::noop_context;
+
+#[test]
+fn first_ok() {
+    let v = vec![err(1), ok(2), err(3), ok(4)];
+    assert_eq!(block_on(race_ok_or_collect_errors(v)), Ok(2));
+}
+
+#[test]
+fn all_errors_in_order() {
+    let (tx1, rx1) = oneshot::channel::<Result<i32, i32>>();
+    let (tx2, rx2) = oneshot::channel::<Result<i32, i32>>();
+    let (tx3, rx3) = oneshot::channel::<Result<i32, i32>>();
+    let v = vec![rx1, rx2, rx3]
+        .into_iter()
+        .map(|rx| rx.map(Result::unwrap));
+    let mut fut = race_ok_or_collect_errors(v);
+    let cx = &mut noop_context();
+
+    tx2.send(Err(2)).unwrap();
+    assert!(fut.poll_unpin(cx).is_pending());
+    tx3.send(Err(3)).unwrap();
+    tx1.send(Err(1)).unwrap();
+    assert_eq!(block_on(fut), Err(vec![1, 2, 3]));
+}
+
+#[test]
+fn ok_after_errors() {
+    let (tx, rx) = oneshot::channel::<i32>();
+    let v = vec![
+        err::<i32, i32>(1).boxed(),
+        rx.map_err(|_| 2).boxed(),
+        err(3).boxed(),
+    ];
+    let mut fut = race_ok_or_collect_errors(v);
+    assert!(fut.poll_unpin(&mut noop_context()).is_pending());
+    tx.send(5).unwrap();
+    assert_eq!(block_on(fut), Ok(5));
+}
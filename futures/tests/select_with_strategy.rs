@@ -0,0 +1,32 @@
+use futures::channel::oneshot;
+use futures::executor::block_on;
+use futures::future::{self, select_with_strategy, Either, FutureExt, PollNext};
+use futures_test::task::noop_context;
+
+#[test]
+fn biased_strategy_favors_left() {
+    for _ in 0..3 {
+        let either = block_on(select_with_strategy(future::ready(1), future::ready(2), || PollNext::Left));
+        assert_eq!(either.factor_first().0, 1);
+    }
+}
+
+#[test]
+fn strategy_is_consulted_on_every_poll() {
+    let mut choices = Vec::new();
+    let (tx, rx) = oneshot::channel::<i32>();
+    let mut next = PollNext::Left;
+    let mut select = select_with_strategy(rx, future::pending::<Result<i32, oneshot::Canceled>>(), || {
+        let choice = next.toggle();
+        choices.push(choice);
+        choice
+    });
+
+    assert!(select.poll_unpin(&mut noop_context()).is_pending());
+    tx.send(3).unwrap();
+    match block_on(select) {
+        Either::Left((x, _)) => assert_eq!(x, Ok(3)),
+        Either::Right(_) => panic!("pending future completed"),
+    }
+    assert_eq!(choices, vec![PollNext::Left, PollNext::Right]);
+}
@@ -0,0 +1,96 @@
+use futures::executor::block_on;
+use futures::future::FutureExt;
+use futures::lock::Semaphore;
+use futures::task::{Context, Poll};
+use futures_test::task::{new_count_waker, noop_context, panic_context};
+
+#[test]
+fn semaphore_acquire_uncontested() {
+    let semaphore = Semaphore::new(2);
+    let a = semaphore.acquire().now_or_never().unwrap();
+    let b = semaphore.acquire().now_or_never().unwrap();
+    assert_eq!(semaphore.available_permits(), 0);
+    assert!(semaphore.try_acquire().is_none());
+    drop((a, b));
+    assert_eq!(semaphore.available_permits(), 2);
+}
+
+#[test]
+fn semaphore_acquire_many() {
+    let semaphore = Semaphore::new(10);
+    let permit = semaphore.try_acquire_many(7).unwrap();
+    assert_eq!(permit.permits(), 7);
+    assert!(semaphore.try_acquire_many(4).is_none());
+    assert!(semaphore.try_acquire_many(3).is_some());
+    drop(permit);
+    assert_eq!(semaphore.available_permits(), 10);
+}
+
+#[test]
+fn semaphore_wakes_waiters() {
+    let semaphore = Semaphore::new(4);
+    let held = semaphore.try_acquire_many(3).unwrap();
+
+    let (waker, counter) = new_count_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut waiter = semaphore.acquire_many(2);
+    assert!(waiter.poll_unpin(&mut cx).is_pending());
+    assert_eq!(counter, 0);
+
+    drop(held);
+    assert_eq!(counter, 1);
+    let permit = match waiter.poll_unpin(&mut panic_context()) {
+        Poll::Ready(permit) => permit,
+        Poll::Pending => panic!("permits should have been granted"),
+    };
+    assert_eq!(semaphore.available_permits(), 2);
+    drop(permit);
+    assert_eq!(semaphore.available_permits(), 4);
+}
+
+#[test]
+fn semaphore_is_fair_across_weights() {
+    let semaphore = Semaphore::new(4);
+    let held = semaphore.try_acquire_many(3).unwrap();
+
+    // A large request holds back smaller ones queued after it.
+    let mut large = semaphore.acquire_many(4);
+    assert!(large.poll_unpin(&mut noop_context()).is_pending());
+    let mut small = semaphore.acquire();
+    assert!(small.poll_unpin(&mut noop_context()).is_pending());
+    assert!(semaphore.try_acquire().is_none());
+
+    drop(held);
+    let large = block_on(large);
+    assert!(small.poll_unpin(&mut noop_context()).is_pending());
+    drop(large);
+    assert_eq!(block_on(small).permits(), 1);
+}
+
+#[test]
+fn semaphore_dropped_waiter_lets_others_through() {
+    let semaphore = Semaphore::new(2);
+    let held = semaphore.try_acquire().unwrap();
+
+    let mut large = semaphore.acquire_many(2);
+    assert!(large.poll_unpin(&mut noop_context()).is_pending());
+    let mut small = semaphore.acquire();
+    assert!(small.poll_unpin(&mut noop_context()).is_pending());
+
+    drop(large);
+    assert!(small.poll_unpin(&mut panic_context()).is_ready());
+    drop(held);
+}
+
+#[test]
+fn semaphore_dropped_granted_waiter_returns_permits() {
+    let semaphore = Semaphore::new(1);
+    let held = semaphore.try_acquire().unwrap();
+    let mut waiter = semaphore.acquire();
+    assert!(waiter.poll_unpin(&mut noop_context()).is_pending());
+
+    drop(held);
+    assert_eq!(semaphore.available_permits(), 0);
+    drop(waiter);
+    assert_eq!(semaphore.available_permits(), 1);
+}
@@ -3,10 +3,11 @@ use futures::executor::block_on;
 use futures::future::{self, Future, FutureExt, TryFutureExt};
 use futures::never::Never;
 use futures::ready;
-use futures::sink::{Sink, SinkErrInto, SinkExt};
+use futures::sink::{self, Sink, SinkErrInto, SinkExt};
 use futures::stream::{self, Stream, StreamExt};
 use futures::task::{self, ArcWake, Context, Poll, Waker};
-use futures_test::task::panic_context;
+use futures_test::future::FutureTestExt;
+use futures_test::task::{noop_context, panic_context};
 use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
 use std::fmt;
@@ -281,7 +282,7 @@ impl<T: Unpin> ManualFlush<T> {
 // but doesn't claim to be flushed until the underlying sink is
 #[test]
 fn with_flush_propagate() {
-    let mut sink = ManualFlush::new().with(|x| future::ok::<Option<i32>, ()>(x));
+    let mut sink = ManualFlush::new().with(future::ok::<Option<i32>, ()>);
     flag_cx(|flag, cx| {
         unwrap(Pin::new(&mut sink).poll_ready(cx));
         Pin::new(&mut sink).start_send(Some(0)).unwrap();
@@ -489,3 +490,303 @@ fn err_into() {
         Err(ErrIntoTest)
     );
 }
+
+#[test]
+fn unfold() {
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let sink = sink::unfold(received.clone(), |received, x: i32| {
+        received.borrow_mut().push(x);
+        future::ready(Ok::<_, ()>(received)).pending_once()
+    });
+    futures::pin_mut!(sink);
+
+    block_on(sink.send(1)).unwrap();
+    block_on(sink.send_all(&mut stream::iter(vec![2, 3]))).unwrap();
+    block_on(sink.close()).unwrap();
+    assert_eq!(*received.borrow(), vec![1, 2, 3]);
+}
+
+#[test]
+fn unfold_error() {
+    let sink = sink::unfold(0, |total, x: i32| {
+        future::ready(if x < 0 { Err(x) } else { Ok(total + x) })
+    });
+    futures::pin_mut!(sink);
+
+    assert_eq!(block_on(sink.send(1)), Ok(()));
+    assert_eq!(block_on(sink.send(-1)), Err(-1));
+}
+
+// Sink that records which items were written out by each flush
+#[derive(Default)]
+struct Batches {
+    pending: Vec<i32>,
+    flushed: Vec<Vec<i32>>,
+}
+
+impl Sink<i32> for Batches {
+    type Error = Never;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: i32) -> Result<(), Self::Error> {
+        self.pending.push(item);
+        Ok(())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if !self.pending.is_empty() {
+            let batch = mem::replace(&mut self.pending, Vec::new());
+            self.flushed.push(batch);
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
+
+#[test]
+fn send_all_flush_every() {
+    let mut sink = Batches::default();
+    block_on(sink.send_all(&mut stream::iter(0..5))).unwrap();
+    assert_eq!(sink.flushed, vec![vec![0, 1, 2, 3, 4]]);
+
+    let mut sink = Batches::default();
+    block_on(sink.send_all(&mut stream::iter(0..5)).flush_every(2)).unwrap();
+    assert_eq!(sink.flushed, vec![vec![0, 1], vec![2, 3], vec![4]]);
+}
+
+#[test]
+fn forward_flush_every() {
+    let mut sink = Batches::default();
+    let stream = stream::iter((0..5).map(Ok));
+    block_on(stream.forward(&mut sink).flush_every(3)).unwrap();
+    assert_eq!(sink.flushed, vec![vec![0, 1, 2], vec![3, 4]]);
+}
+
+#[test]
+fn transactional_commit_and_rollback() {
+    let mut sink = Vec::new().transactional();
+
+    block_on(sink.send_all(&mut stream::iter(vec![1, 2]))).unwrap();
+    assert_eq!(sink.staged_len(), 2);
+    assert!(sink.get_ref().is_empty());
+    block_on(sink.commit()).unwrap();
+    assert_eq!(sink.staged_len(), 0);
+    assert_eq!(sink.get_ref(), &vec![1, 2]);
+
+    block_on(sink.send(3)).unwrap();
+    sink.rollback();
+    block_on(sink.send(4)).unwrap();
+    block_on(sink.commit()).unwrap();
+    assert_eq!(sink.into_inner(), vec![1, 2, 4]);
+}
+
+#[test]
+fn transactional_commit_waits_for_readiness() {
+    let (inner, allow) = manual_allow::<i32>();
+    let mut sink = inner.transactional();
+    block_on(sink.send_all(&mut stream::iter(vec![1, 2]))).unwrap();
+
+    flag_cx(|flag, cx| {
+        let mut commit = sink.commit();
+        assert!(commit.poll_unpin(cx).is_pending());
+        assert!(!flag.get());
+        allow.start();
+        assert!(flag.get());
+        unwrap(commit.poll_unpin(cx));
+        assert_eq!(sink.get_ref().data, vec![1, 2]);
+    })
+}
+
+#[test]
+fn transactional_close_rolls_back() {
+    let (tx, rx) = mpsc::unbounded();
+    let mut sink = tx.transactional();
+
+    block_on(sink.send(1)).unwrap();
+    block_on(sink.commit()).unwrap();
+    block_on(sink.send(2)).unwrap();
+    block_on(sink.close()).unwrap();
+    assert_eq!(sink.staged_len(), 0);
+    assert_eq!(block_on(rx.collect::<Vec<_>>()), vec![1]);
+}
+
+#[test]
+fn sink_registry_evicts_failed_sinks() {
+    let (tx1, rx1) = mpsc::unbounded();
+    let (tx2, rx2) = mpsc::unbounded::<i32>();
+    let mut registry = sink::SinkRegistry::new();
+    registry.register(1, tx1);
+    registry.register(2, tx2);
+    drop(rx2);
+
+    let evicted = block_on(registry.broadcast(7));
+    assert_eq!(evicted.len(), 1);
+    assert_eq!(evicted[0].0, 2);
+    match &evicted[0].1 {
+        sink::Eviction::Failed(e) => assert!(e.is_disconnected()),
+        sink::Eviction::TimedOut => panic!("sink should have failed"),
+    }
+    assert!(registry.contains_key(&1));
+    assert!(!registry.contains_key(&2));
+
+    drop(registry);
+    assert_eq!(block_on(rx1.collect::<Vec<_>>()), vec![7]);
+}
+
+#[test]
+fn sink_registry_waits_for_slow_sinks() {
+    let (fast_tx, fast_rx) = mpsc::unbounded();
+    let (mut slow_tx, mut slow_rx) = mpsc::channel(0);
+    // Fill the slow sink up.
+    slow_tx.try_send(0).unwrap();
+
+    let mut registry = sink::SinkRegistry::new();
+    registry.register("fast", future::Either::Left(fast_tx.sink_map_err(|_| ())));
+    registry.register("slow", future::Either::Right(slow_tx.sink_map_err(|_| ())));
+
+    {
+        let mut broadcast = registry.broadcast(1);
+        assert!(broadcast.poll_unpin(&mut noop_context()).is_pending());
+        assert_eq!(block_on(slow_rx.next()), Some(0));
+        let (evicted, item) = block_on(future::join(broadcast, slow_rx.next()));
+        assert!(evicted.is_empty());
+        assert_eq!(item, Some(1));
+    }
+    assert_eq!(registry.len(), 2);
+
+    drop(registry);
+    assert_eq!(block_on(fast_rx.collect::<Vec<_>>()), vec![1]);
+}
+
+#[test]
+fn sink_registry_evicts_sinks_after_timeout() {
+    let (fast_tx, fast_rx) = mpsc::unbounded();
+    let (mut slow_tx, _slow_rx) = mpsc::channel(0);
+    slow_tx.try_send(0).unwrap();
+
+    let mut registry = sink::SinkRegistry::with_timeout(std::time::Duration::from_millis(20));
+    registry.register("fast", future::Either::Left(fast_tx.sink_map_err(|_| ())));
+    registry.register("slow", future::Either::Right(slow_tx.sink_map_err(|_| ())));
+
+    assert_eq!(block_on(registry.broadcast(1)), vec![("slow", sink::Eviction::TimedOut)]);
+    assert_eq!(registry.len(), 1);
+
+    drop(registry);
+    assert_eq!(block_on(fast_rx.collect::<Vec<_>>()), vec![1]);
+}
+
+#[test]
+fn backpressure_probe_measures_pressure() {
+    use std::time::Duration;
+
+    let (mut tx, mut rx) = mpsc::channel::<i32>(0);
+    tx.try_send(0).unwrap();
+    let (mut tx, watch) = tx.backpressure_probe();
+    let mut cx = noop_context();
+
+    assert!(!watch.is_under_pressure());
+    assert_eq!(watch.pressure_duration(), None);
+    assert!(!watch.under_pressure_within(Duration::from_secs(60)));
+
+    assert!(Pin::new(&mut tx).poll_ready(&mut cx).is_pending());
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(watch.pressure_duration().unwrap() >= Duration::from_millis(20));
+
+    assert_eq!(block_on(rx.next()), Some(0));
+    assert!(Pin::new(&mut tx).poll_ready(&mut cx).is_ready());
+    assert!(!watch.is_under_pressure());
+    assert!(watch.total_pressure() >= Duration::from_millis(20));
+    assert!(watch.under_pressure_within(Duration::from_secs(60)));
+}
+
+#[test]
+fn sink_or_else_recovers_or_propagates() {
+    // A sink rejecting negative items, while staying usable.
+    struct NonNegative(Vec<i32>);
+
+    impl Sink<i32> for NonNegative {
+        type Error = i32;
+
+        fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), i32>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(mut self: Pin<&mut Self>, item: i32) -> Result<(), i32> {
+            if item < 0 {
+                return Err(item);
+            }
+            self.0.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), i32>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), i32>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    let mut sink = NonNegative(Vec::new()).sink_or_else(|e: i32| {
+        future::ready(if e == -1 { Ok(()) } else { Err(format!("fatal {}", e)) })
+    });
+
+    block_on(sink.send(-1)).unwrap();
+    block_on(sink.send(1)).unwrap();
+    assert_eq!(block_on(sink.send(-2)), Err("fatal -2".to_string()));
+    block_on(sink.send(2)).unwrap();
+    assert_eq!(sink.get_ref().0, vec![1, 2]);
+}
+
+#[test]
+fn sink_or_else_sends_after_recovered_poll_ready() {
+    // A sink whose first `poll_ready` fails.
+    struct FailReadyOnce {
+        failed: bool,
+        items: Vec<i32>,
+    }
+
+    impl Sink<i32> for FailReadyOnce {
+        type Error = ();
+
+        fn poll_ready(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            if self.failed {
+                Poll::Ready(Ok(()))
+            } else {
+                self.failed = true;
+                Poll::Ready(Err(()))
+            }
+        }
+
+        fn start_send(mut self: Pin<&mut Self>, item: i32) -> Result<(), ()> {
+            self.items.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    let mut sink = FailReadyOnce { failed: false, items: Vec::new() }
+        .sink_or_else(|()| future::ok::<(), ()>(()));
+    let mut cx = noop_context();
+
+    // The error is recovered from, and the sink is then ready again before
+    // the item is sent, which must not be dropped.
+    assert_eq!(Pin::new(&mut sink).poll_ready(&mut cx), Poll::Ready(Ok(())));
+    assert_eq!(Pin::new(&mut sink).poll_ready(&mut cx), Poll::Ready(Ok(())));
+    Pin::new(&mut sink).start_send(1).unwrap();
+    assert_eq!(sink.get_ref().items, vec![1]);
+}
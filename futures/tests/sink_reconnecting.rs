@@ -0,0 +1,165 @@
+use futures::executor::block_on;
+use futures::future;
+use futures::sink::{self, ReconnectingSink, ResumeState, Sink, SinkExt};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+type Log = Rc<RefCell<Vec<(usize, i32)>>>;
+
+// A sink recording items along with the connection they were sent on, and
+// failing on `fail_on`.
+fn connection(id: usize, fail_on: Option<i32>, log: Log) -> impl Sink<i32, Error = ()> {
+    sink::unfold(log, move |log, x| {
+        if Some(x) == fail_on {
+            future::ready(Err(()))
+        } else {
+            log.borrow_mut().push((id, x));
+            future::ready(Ok(log))
+        }
+    })
+}
+
+#[test]
+fn replays_failed_item_on_new_connection() {
+    let log = Log::default();
+    let connections = Rc::new(Cell::new(0));
+
+    let log2 = log.clone();
+    let connections2 = connections.clone();
+    let mut sink = Box::pin(ReconnectingSink::new(move |_| {
+        let id = connections2.get();
+        connections2.set(id + 1);
+        let fail_on = if id == 0 { Some(2) } else { None };
+        future::ok(connection(id, fail_on, log2.clone()))
+    }, 1));
+
+    block_on(sink.send(1)).unwrap();
+    block_on(sink.send(2)).unwrap();
+    block_on(sink.send(3)).unwrap();
+
+    assert_eq!(*log.borrow(), vec![(0, 1), (1, 2), (1, 3)]);
+    assert_eq!(connections.get(), 2);
+}
+
+#[test]
+fn retries_failed_connections() {
+    let log = Log::default();
+    let attempts = Rc::new(Cell::new(0));
+
+    let log2 = log.clone();
+    let attempts2 = attempts.clone();
+    let mut sink = Box::pin(ReconnectingSink::new(move |_| {
+        let n = attempts2.get();
+        attempts2.set(n + 1);
+        future::ready(if n < 2 { Err(()) } else { Ok(connection(n, None, log2.clone())) })
+    }, 2));
+
+    block_on(sink.send(1)).unwrap();
+    assert_eq!(*log.borrow(), vec![(2, 1)]);
+    assert_eq!(attempts.get(), 3);
+}
+
+#[test]
+fn gives_up_after_max_retries() {
+    let log = Log::default();
+    let attempts = Rc::new(Cell::new(0));
+
+    let log2 = log.clone();
+    let attempts2 = attempts.clone();
+    let mut sink = Box::pin(ReconnectingSink::new(move |_| {
+        let n = attempts2.get();
+        attempts2.set(n + 1);
+        future::ready(if n < 10 { Err(()) } else { Ok(connection(n, None, log2.clone())) })
+    }, 3));
+
+    assert_eq!(block_on(sink.send(1)), Err(()));
+    assert_eq!(attempts.get(), 4);
+
+    // The retry budget is per item.
+    assert_eq!(block_on(sink.send(2)), Err(()));
+    assert_eq!(attempts.get(), 8);
+    assert!(log.borrow().is_empty());
+}
+
+#[test]
+fn hands_resume_state_to_factory() {
+    #[derive(Default)]
+    struct Offset(usize);
+
+    impl ResumeState<i32> for Offset {
+        fn record(&mut self, _item: &i32) {
+            self.0 += 1;
+        }
+    }
+
+    let log = Log::default();
+    let offsets = Rc::new(RefCell::new(Vec::new()));
+
+    let log2 = log.clone();
+    let offsets2 = offsets.clone();
+    let mut sink = Box::pin(ReconnectingSink::with_state(move |offset: &Offset| {
+        let id = offsets2.borrow().len();
+        offsets2.borrow_mut().push(offset.0);
+        let fail_on = if id == 0 { Some(3) } else { None };
+        future::ok(connection(id, fail_on, log2.clone()))
+    }, Offset::default(), 1));
+
+    for i in 1..=4 {
+        block_on(sink.send(i)).unwrap();
+    }
+
+    assert_eq!(*offsets.borrow(), vec![0, 2]);
+    assert_eq!(sink.state().0, 4);
+    assert_eq!(*log.borrow(), vec![(0, 1), (0, 2), (1, 3), (1, 4)]);
+}
+
+#[test]
+fn backs_off_between_attempts() {
+    let log = Log::default();
+    let attempts = Rc::new(RefCell::new(Vec::new()));
+
+    let log2 = log.clone();
+    let attempts2 = attempts.clone();
+    let mut sink = Box::pin(ReconnectingSink::new(move |_| {
+        let n = attempts2.borrow().len();
+        attempts2.borrow_mut().push(Instant::now());
+        future::ready(if n < 3 { Err(()) } else { Ok(connection(n, None, log2.clone())) })
+    }, 3).with_backoff(Duration::from_millis(10), Duration::from_millis(15)));
+
+    block_on(sink.send(1)).unwrap();
+    assert_eq!(*log.borrow(), vec![(3, 1)]);
+
+    // Waits 10ms, then 15ms twice, as the doubled delay is capped.
+    let attempts = attempts.borrow();
+    let waits: Vec<_> = attempts.windows(2).map(|w| w[1] - w[0]).collect();
+    assert!(waits[0] >= Duration::from_millis(10));
+    assert!(waits[1] >= Duration::from_millis(15));
+    assert!(waits[2] >= Duration::from_millis(15));
+}
+
+#[test]
+fn resets_backoff_after_giving_up() {
+    let log = Log::default();
+    let attempts = Rc::new(RefCell::new(Vec::new()));
+
+    let log2 = log.clone();
+    let attempts2 = attempts.clone();
+    let mut sink = Box::pin(ReconnectingSink::new(move |_| {
+        let n = attempts2.borrow().len();
+        attempts2.borrow_mut().push(Instant::now());
+        future::ready(if n < 10 { Err(()) } else { Ok(connection(n, None, log2.clone())) })
+    }, 8).with_backoff(Duration::from_millis(1), Duration::from_secs(10)));
+
+    // Gives up after waiting 1ms, then twice as long before each retry, up
+    // to 128ms.
+    assert_eq!(block_on(sink.send(1)), Err(()));
+    block_on(sink.send(2)).unwrap();
+    assert_eq!(*log.borrow(), vec![(10, 2)]);
+
+    // The next item starts over from the minimum delay, not from 256ms.
+    let attempts = attempts.borrow();
+    let wait = attempts[10] - attempts[9];
+    assert!(wait >= Duration::from_millis(1));
+    assert!(wait < Duration::from_millis(200));
+}
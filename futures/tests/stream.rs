@@ -17,3 +17,110 @@ fn select() {
     select_and_compare(vec![1, 2, 3], vec![4, 5], vec![1, 4, 2, 5, 3]);
     select_and_compare(vec![1, 2], vec![4, 5, 6], vec![1, 4, 2, 5, 6]);
 }
+
+#[test]
+fn boxed_erases_stream_types() {
+    use futures::stream::{BoxStream, LocalBoxStream};
+    use std::rc::Rc;
+
+    let streams: Vec<BoxStream<'static, u32>> = vec![
+        stream::iter(vec![1, 2]).boxed(),
+        stream::once(async { 3 }).boxed(),
+        stream::repeat(4).take(1).boxed(),
+    ];
+    let vec = block_on(stream::iter(streams).flatten().collect::<Vec<_>>());
+    assert_eq!(vec, vec![1, 2, 3, 4]);
+
+    let rc = Rc::new(5);
+    let streams: Vec<LocalBoxStream<'static, u32>> = vec![
+        stream::iter(vec![1]).map(move |x| x * *rc).boxed_local(),
+        stream::empty().boxed_local(),
+    ];
+    let vec = block_on(stream::iter(streams).flatten().collect::<Vec<_>>());
+    assert_eq!(vec, vec![5]);
+}
+
+#[test]
+fn map_while_ends_on_sentinel() {
+    use futures::stream::FusedStream;
+
+    let mut stream = stream::iter(vec![1, 2, 0, 3]).fuse()
+        .map_while(|x| if x == 0 { None } else { Some(x * 10) });
+    assert!(!stream.is_terminated());
+    assert_eq!(block_on(stream.next()), Some(10));
+    assert_eq!(block_on(stream.next()), Some(20));
+    assert_eq!(block_on(stream.next()), None);
+    assert!(stream.is_terminated());
+    assert_eq!(block_on(stream.next()), None);
+}
+
+#[test]
+fn start_with_yields_snapshot_before_updates() {
+    use futures::channel::mpsc;
+    use futures::stream::FusedStream;
+
+    let (tx, rx) = mpsc::unbounded();
+    let mut feed = rx.start_with(vec![1, 2]);
+    tx.unbounded_send(3).unwrap();
+    assert_eq!(block_on(feed.next()), Some(1));
+    assert_eq!(block_on(feed.next()), Some(2));
+    assert_eq!(block_on(feed.next()), Some(3));
+
+    drop(tx);
+    assert!(!feed.is_terminated());
+    assert_eq!(block_on(feed.next()), None);
+    assert!(feed.is_terminated());
+}
+
+#[test]
+fn take_budget() {
+    let mut stream = stream::iter(vec![vec![0u8; 4], vec![0; 4], vec![0; 1]])
+        .take_budget(8, |chunk| chunk.len() as u64);
+    assert_eq!(block_on(stream.next()).map(|c| c.len()), Some(4));
+    assert_eq!(block_on(stream.next()).map(|c| c.len()), Some(4));
+    assert_eq!(stream.remaining(), 0);
+    assert_eq!(block_on(stream.next()), None);
+    assert!(stream.exceeded());
+    assert_eq!(block_on(stream.next()), None);
+
+    // Ending within the budget is not an overrun.
+    let mut stream = stream::iter(vec!["a", "bc"]).take_budget(3, |s| s.len() as u64);
+    assert_eq!(block_on((&mut stream).collect::<Vec<_>>()), vec!["a", "bc"]);
+    assert!(!stream.exceeded());
+}
+
+#[test]
+fn split_errors() {
+    use futures::stream::TryStreamExt;
+
+    let (items, error) = stream::iter(vec![Ok(1), Err(2), Ok(3), Err(4)]).split_errors();
+    assert_eq!(block_on(items.collect::<Vec<i32>>()), vec![1]);
+    assert_eq!(block_on(error), Some(2));
+
+    let (items, error) = stream::iter(vec![Ok::<i32, i32>(1), Ok(2)]).split_errors();
+    assert_eq!(block_on(items.collect::<Vec<_>>()), vec![1, 2]);
+    assert_eq!(block_on(error), None);
+
+    let (items, error) = stream::iter(vec![Err::<i32, i32>(1)]).split_errors();
+    drop(items);
+    assert_eq!(block_on(error), None);
+}
+
+#[test]
+fn stamp_measures_latency_through_pipeline() {
+    use futures::stream::Stamped;
+    use std::time::Duration;
+
+    let mut latencies = Vec::new();
+    let stream = stream::iter(vec![1, 2])
+        .stamp()
+        .then(|stamped: Stamped<i32>| async move {
+            std::thread::sleep(Duration::from_millis(10));
+            stamped.map(|x| x + 1)
+        })
+        .measure_since_stamp(|latency| latencies.push(latency));
+
+    assert_eq!(block_on(stream.collect::<Vec<_>>()), vec![2, 3]);
+    assert_eq!(latencies.len(), 2);
+    assert!(latencies.iter().all(|l| *l >= Duration::from_millis(10)));
+}
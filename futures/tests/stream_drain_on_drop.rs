@@ -0,0 +1,52 @@
+use futures::executor::{block_on, LocalPool};
+use futures::future;
+use futures::stream::{self, StreamExt};
+use futures_test::task::RecordSpawner;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn drains_remaining_items_on_drop() {
+    let mut pool = LocalPool::new();
+    let drained = Arc::new(Mutex::new(Vec::new()));
+    let drained2 = drained.clone();
+
+    let mut stream = stream::iter(1..=5).drain_on_drop(pool.spawner(), 10, move |x| {
+        drained2.lock().unwrap().push(x);
+        future::ready(())
+    });
+    assert_eq!(block_on(stream.next()), Some(1));
+    assert_eq!(block_on(stream.next()), Some(2));
+    drop(stream);
+
+    pool.run();
+    assert_eq!(*drained.lock().unwrap(), vec![3, 4, 5]);
+}
+
+#[test]
+fn drain_is_bounded() {
+    let mut pool = LocalPool::new();
+    let drained = Arc::new(Mutex::new(Vec::new()));
+    let drained2 = drained.clone();
+
+    let stream = stream::iter(1..=100).drain_on_drop(pool.spawner(), 3, move |x| {
+        drained2.lock().unwrap().push(x);
+        future::ready(())
+    });
+    drop(stream);
+
+    pool.run();
+    assert_eq!(*drained.lock().unwrap(), vec![1, 2, 3]);
+}
+
+#[test]
+fn no_drain_after_termination() {
+    let mut spawner = RecordSpawner::new();
+
+    let stream = stream::iter(1..=3).drain_on_drop(&mut spawner, 10, |_| future::ready(()));
+    assert_eq!(block_on(stream.collect::<Vec<_>>()), vec![1, 2, 3]);
+    assert_eq!(spawner.spawned().len(), 0);
+
+    let stream = stream::iter(1..=3).drain_on_drop(&mut spawner, 0, |_| future::ready(()));
+    drop(stream);
+    assert_eq!(spawner.spawned().len(), 0);
+}
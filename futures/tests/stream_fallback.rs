@@ -0,0 +1,33 @@
+use futures::executor::block_on;
+use futures::stream::{self, FusedStream, StreamExt, TryStreamExt};
+
+#[test]
+fn primary_success_skips_fallback() {
+    let mut reads = stream::iter(vec![Ok::<_, ()>(1), Ok(2)])
+        .fallback(|()| -> stream::Iter<std::vec::IntoIter<Result<i32, ()>>> {
+            panic!("fallback should not be built")
+        });
+    assert_eq!(block_on((&mut reads).collect::<Vec<_>>()), vec![Ok(1), Ok(2)]);
+    assert!(!reads.is_degraded());
+}
+
+#[test]
+fn switches_to_secondary_on_error() {
+    let mut reads = stream::iter(vec![Ok(1), Err("down"), Ok(99)])
+        .fallback(|e| {
+            assert_eq!(e, "down");
+            stream::iter(vec![Ok(2), Err("stale"), Ok(3)]).fuse()
+        });
+
+    assert_eq!(block_on(reads.next()), Some(Ok(1)));
+    assert!(!reads.is_degraded());
+    assert_eq!(block_on(reads.next()), Some(Ok(2)));
+    assert!(reads.is_degraded());
+
+    // Errors of the secondary stream are passed through.
+    assert_eq!(block_on(reads.next()), Some(Err("stale")));
+    assert_eq!(block_on(reads.next()), Some(Ok(3)));
+    assert!(!reads.is_terminated());
+    assert_eq!(block_on(reads.next()), None);
+    assert!(reads.is_terminated());
+}
@@ -0,0 +1,94 @@
+use futures::channel::mpsc;
+use futures::executor::block_on_stream;
+use futures::stream::{self, FusedStream, StreamExt, StreamMap};
+use futures::task::Poll;
+use futures_test::task::noop_context;
+
+#[test]
+fn yields_items_with_keys() {
+    let map: StreamMap<_, _> = vec![
+        (1, stream::iter(vec!['a', 'b'])),
+        (2, stream::iter(vec!['c'])),
+    ].into_iter().collect();
+
+    let mut events: Vec<_> = block_on_stream(map).collect();
+    events.sort();
+    assert_eq!(events, vec![
+        (1, None),
+        (1, Some('a')),
+        (1, Some('b')),
+        (2, None),
+        (2, Some('c')),
+    ]);
+}
+
+#[test]
+fn insert_and_remove_while_running() {
+    let mut cx = noop_context();
+    let mut map = StreamMap::new();
+    let (tx_a, rx_a) = mpsc::unbounded();
+    let (tx_b, rx_b) = mpsc::unbounded();
+
+    assert!(map.insert("a", rx_a).is_none());
+    assert_eq!(map.poll_next_unpin(&mut cx), Poll::Pending);
+
+    tx_a.unbounded_send(1).unwrap();
+    assert!(map.insert("b", rx_b).is_none());
+    tx_b.unbounded_send(2).unwrap();
+    let mut events = vec![
+        map.poll_next_unpin(&mut cx),
+        map.poll_next_unpin(&mut cx),
+    ];
+    events.sort_by_key(|event| format!("{:?}", event));
+    assert_eq!(events, vec![
+        Poll::Ready(Some(("a", Some(1)))),
+        Poll::Ready(Some(("b", Some(2)))),
+    ]);
+
+    // A removed stream is neither polled nor reported as ended.
+    tx_a.unbounded_send(3).unwrap();
+    let mut rx_a = map.remove(&"a").unwrap();
+    assert!(!map.contains_key(&"a"));
+    assert_eq!(map.poll_next_unpin(&mut cx), Poll::Pending);
+    assert_eq!(rx_a.poll_next_unpin(&mut cx), Poll::Ready(Some(3)));
+
+    drop(tx_b);
+    assert_eq!(map.poll_next_unpin(&mut cx), Poll::Ready(Some(("b", None))));
+    assert!(map.is_empty());
+}
+
+#[test]
+fn insert_replaces_stream() {
+    let mut map = StreamMap::new();
+    map.insert("a", stream::iter(vec![1]));
+    let old = map.insert("a", stream::iter(vec![2]));
+    assert!(old.is_some());
+    assert_eq!(map.len(), 1);
+    assert_eq!(block_on_stream(map).collect::<Vec<_>>(), vec![("a", Some(2)), ("a", None)]);
+}
+
+#[test]
+fn polls_streams_fairly() {
+    let mut map = StreamMap::new();
+    map.insert("a", stream::repeat(()));
+    map.insert("b", stream::repeat(()));
+
+    let keys: Vec<_> = block_on_stream(map).take(4).map(|(key, _)| key).collect();
+    assert_eq!(keys, vec!["a", "b", "a", "b"]);
+}
+
+#[test]
+fn is_terminated() {
+    let mut cx = noop_context();
+    let mut map = StreamMap::new();
+
+    assert!(!map.is_terminated());
+    assert_eq!(map.poll_next_unpin(&mut cx), Poll::Ready(None));
+    assert!(map.is_terminated());
+
+    map.insert("a", stream::empty::<()>());
+    assert!(!map.is_terminated());
+    assert_eq!(map.poll_next_unpin(&mut cx), Poll::Ready(Some(("a", None))));
+    assert_eq!(map.poll_next_unpin(&mut cx), Poll::Ready(None));
+    assert!(map.is_terminated());
+}
@@ -0,0 +1,86 @@
+use futures::executor::block_on;
+use futures::future;
+use futures::sink::ResumeState;
+use futures::stream::{self, ReconnectingStream, StreamExt};
+use std::time::{Duration, Instant};
+
+#[test]
+fn reconnects_after_error_and_end() {
+    let mut connections = 0;
+    let stream = ReconnectingStream::new(move |_| {
+        connections += 1;
+        let items = match connections {
+            1 => vec![Ok(1), Err(())],
+            2 => vec![Ok(2)],
+            3 => vec![Ok(3), Err(())],
+            _ => vec![Err(())],
+        };
+        future::ok(stream::iter(items))
+    }, 1);
+
+    let items = block_on(stream.collect::<Vec<_>>());
+    assert_eq!(items, vec![Ok(1), Ok(2), Ok(3), Err(())]);
+}
+
+#[test]
+fn gives_up_after_max_retries() {
+    let mut connections = 0;
+    let mut stream = ReconnectingStream::new(move |_| {
+        connections += 1;
+        if connections == 1 {
+            future::ok(stream::iter(vec![Ok(1)]))
+        } else {
+            future::err(connections)
+        }
+    }, 3);
+
+    assert_eq!(block_on(stream.next()), Some(Ok(1)));
+    assert_eq!(block_on(stream.next()), Some(Err(4)));
+    assert_eq!(block_on(stream.next()), None);
+    assert!(futures::stream::FusedStream::is_terminated(&stream));
+}
+
+#[test]
+fn yields_marker_after_reconnecting() {
+    let mut connections = 0;
+    let stream = ReconnectingStream::new(move |_| {
+        connections += 1;
+        future::ok(stream::iter(vec![Ok::<_, ()>(connections)]))
+    }, 1).with_marker(0);
+
+    let items = block_on(stream.take(4).collect::<Vec<_>>());
+    assert_eq!(items, vec![Ok(1), Ok(0), Ok(2), Ok(0)]);
+}
+
+#[test]
+fn backs_off_between_attempts() {
+    let stream = ReconnectingStream::new(
+        |_: &()| future::err::<stream::Iter<std::vec::IntoIter<Result<(), ()>>>, _>(()),
+        2,
+    ).with_backoff(Duration::from_millis(20), Duration::from_millis(30));
+
+    let start = Instant::now();
+    let items = block_on(stream.collect::<Vec<_>>());
+    assert_eq!(items, vec![Err(())]);
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn resumes_from_recorded_cursor() {
+    struct Cursor(u32);
+
+    impl ResumeState<u32> for Cursor {
+        fn record(&mut self, item: &u32) {
+            self.0 = *item + 1;
+        }
+    }
+
+    let stream = ReconnectingStream::with_state(|cursor: &Cursor| {
+        // Each connection delivers two items from the cursor, then fails.
+        let start = cursor.0;
+        future::ok(stream::iter(vec![Ok(start), Ok(start + 1), Err(())]))
+    }, Cursor(10), 1);
+
+    let items = block_on(stream.take(6).collect::<Vec<_>>());
+    assert_eq!(items, vec![Ok(10), Ok(11), Ok(12), Ok(13), Ok(14), Ok(15)]);
+}
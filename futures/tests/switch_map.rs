@@ -0,0 +1,55 @@
+use futures::channel::{mpsc, oneshot};
+use futures::future::FutureExt;
+use futures::stream::{self, FusedStream, StreamExt};
+use futures_test::task::{new_count_waker, noop_context};
+use std::task::{Context, Poll};
+
+#[test]
+fn new_item_cancels_previous_stream() {
+    let (tx, rx) = mpsc::unbounded();
+    let mut results = rx.switch_map(|rx: oneshot::Receiver<i32>| stream::once(rx.map(Result::unwrap)));
+    let mut cx = noop_context();
+
+    let (tx1, rx1) = oneshot::channel();
+    tx.unbounded_send(rx1).unwrap();
+    assert_eq!(results.poll_next_unpin(&mut cx), Poll::Pending);
+    assert!(!tx1.is_canceled());
+
+    let (tx2, rx2) = oneshot::channel();
+    tx.unbounded_send(rx2).unwrap();
+    assert_eq!(results.poll_next_unpin(&mut cx), Poll::Pending);
+    assert!(tx1.is_canceled());
+
+    tx2.send(2).unwrap();
+    assert_eq!(results.poll_next_unpin(&mut cx), Poll::Ready(Some(2)));
+    assert_eq!(results.poll_next_unpin(&mut cx), Poll::Pending);
+
+    drop(tx);
+    assert_eq!(results.poll_next_unpin(&mut cx), Poll::Ready(None));
+    assert!(results.is_terminated());
+}
+
+#[test]
+fn ends_after_last_stream() {
+    let (tx, rx) = mpsc::unbounded();
+    let mut results = rx.switch_map(|n| stream::iter(vec![n; 2]));
+    let mut cx = noop_context();
+
+    tx.unbounded_send(1).unwrap();
+    assert_eq!(results.poll_next_unpin(&mut cx), Poll::Ready(Some(1)));
+    drop(tx);
+    assert_eq!(results.poll_next_unpin(&mut cx), Poll::Ready(Some(1)));
+    assert!(!results.is_terminated());
+    assert_eq!(results.poll_next_unpin(&mut cx), Poll::Ready(None));
+    assert!(results.is_terminated());
+}
+
+#[test]
+fn yields_to_always_ready_stream() {
+    let mut results = stream::repeat(1).switch_map(|n| stream::iter(vec![n]));
+    let (waker, counter) = new_count_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    assert_eq!(results.poll_next_unpin(&mut cx), Poll::Pending);
+    assert_eq!(counter, 1);
+}
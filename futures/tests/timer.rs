@@ -0,0 +1,413 @@
+use futures::channel::{mpsc, oneshot};
+use futures::executor::block_on;
+use futures::future::{self, Either, FutureExt};
+use futures::stream::{self, FusedStream, StreamExt};
+use futures::timer::{
+    interval, poll_with_strategy, record, replay, schedule, sleep, sleep_until, sleep_until_system_time, ConnectionReaper, RecordedEvent, ScheduleSpec, TimedOut,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+#[test]
+fn sleep_waits_for_deadline() {
+    let start = Instant::now();
+    block_on(sleep(Duration::from_millis(50)));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn sleep_zero_is_ready() {
+    assert_eq!(sleep(Duration::from_millis(0)).now_or_never(), Some(()));
+}
+
+#[test]
+fn sleep_overflowing_duration_never_completes() {
+    let mut fut = sleep(Duration::from_secs(std::u64::MAX));
+    assert_eq!(fut.deadline(), None);
+    assert_eq!((&mut fut).now_or_never(), None);
+}
+
+#[test]
+fn sleeps_complete_in_deadline_order() {
+    let long = sleep(Duration::from_millis(200));
+    let short = sleep(Duration::from_millis(20));
+    match block_on(future::select(long, short)) {
+        Either::Left(_) => panic!("long sleep completed first"),
+        Either::Right(((), long)) => block_on(long),
+    }
+}
+
+#[test]
+fn sleep_until_waits_for_deadline() {
+    let deadline = Instant::now() + Duration::from_millis(50);
+    let fut = sleep_until(deadline);
+    assert_eq!(fut.deadline(), Some(deadline));
+    block_on(fut);
+    assert!(Instant::now() >= deadline);
+}
+
+#[test]
+fn sleep_until_past_deadline_is_ready() {
+    let deadline = Instant::now() - Duration::from_millis(10);
+    assert_eq!(sleep_until(deadline).now_or_never(), Some(()));
+}
+
+#[test]
+fn sleep_until_system_time_waits_for_time() {
+    let time = SystemTime::now() + Duration::from_millis(50);
+    block_on(sleep_until_system_time(time));
+    assert!(SystemTime::now() >= time);
+}
+
+#[test]
+fn sleep_until_system_time_past_time_is_ready() {
+    let time = SystemTime::now() - Duration::from_secs(60);
+    assert_eq!(sleep_until_system_time(time).now_or_never(), Some(()));
+}
+
+#[test]
+fn sleep_until_system_time_reset() {
+    let mut fut = sleep_until_system_time(SystemTime::now() + Duration::from_secs(3600));
+    assert_eq!((&mut fut).now_or_never(), None);
+    let time = SystemTime::now() + Duration::from_millis(20);
+    fut.reset(time);
+    assert_eq!(fut.deadline(), time);
+    block_on(fut);
+    assert!(SystemTime::now() >= time);
+}
+
+#[test]
+fn interval_ticks_every_period() {
+    let start = Instant::now();
+    let ticks = interval(Duration::from_millis(20));
+    assert_eq!(block_on(ticks.take(3).collect::<Vec<_>>()), vec![(), (), ()]);
+    assert!(start.elapsed() >= Duration::from_millis(60));
+}
+
+#[test]
+fn interval_pause_and_resume() {
+    let mut ticks = interval(Duration::from_millis(10));
+    let handle = ticks.handle();
+    handle.pause();
+    assert!(handle.is_paused());
+
+    thread::sleep(Duration::from_millis(30));
+    assert_eq!(ticks.next().now_or_never(), None);
+
+    let resumer = handle.clone();
+    let t = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        resumer.resume();
+    });
+    let start = Instant::now();
+    assert_eq!(block_on(ticks.next()), Some(()));
+    assert!(start.elapsed() >= Duration::from_millis(30));
+    assert!(!handle.is_paused());
+    t.join().unwrap();
+}
+
+#[test]
+fn interval_set_period() {
+    let mut ticks = interval(Duration::from_secs(3600));
+    let handle = ticks.handle();
+    assert_eq!(ticks.next().now_or_never(), None);
+
+    handle.set_period(Duration::from_millis(10));
+    assert_eq!(handle.period(), Duration::from_millis(10));
+    assert_eq!(block_on(ticks.next()), Some(()));
+    let start = Instant::now();
+    assert_eq!(block_on(ticks.next()), Some(()));
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn poll_with_strategy_waits_for_chosen_delay() {
+    let mut n = 0;
+    let mut delays = Vec::new();
+    let start = Instant::now();
+    let stream = poll_with_strategy(
+        || {
+            n += 1;
+            future::ready(n)
+        },
+        |&n: &i32| {
+            delays.push(n);
+            Duration::from_millis(10 * n as u64)
+        },
+    );
+    assert_eq!(block_on(stream.take(3).collect::<Vec<_>>()), vec![1, 2, 3]);
+    // The first fetch is immediate, then they wait 10ms and 20ms.
+    assert!(start.elapsed() >= Duration::from_millis(30));
+    assert_eq!(delays, vec![1, 2, 3]);
+}
+
+#[test]
+fn poll_with_strategy_fetches_lazily() {
+    let (tx, rx) = oneshot::channel::<i32>();
+    let mut rx = Some(rx);
+    let mut stream = poll_with_strategy(
+        move || rx.take().unwrap().map(Result::unwrap),
+        |_: &i32| Duration::from_secs(3600),
+    );
+    assert_eq!(stream.next().now_or_never(), None);
+    tx.send(1).unwrap();
+    assert_eq!(block_on(stream.next()), Some(1));
+    assert_eq!(stream.next().now_or_never(), None);
+}
+
+#[test]
+fn poll_with_strategy_overflowing_delay_never_fetches_again() {
+    let mut fetches = 0;
+    let mut stream = poll_with_strategy(
+        || {
+            fetches += 1;
+            future::ready(())
+        },
+        |_: &()| Duration::from_secs(std::u64::MAX),
+    );
+    assert_eq!(stream.next().now_or_never(), Some(Some(())));
+    assert_eq!(stream.next().now_or_never(), None);
+    drop(stream);
+    assert_eq!(fetches, 1);
+}
+
+#[test]
+fn record_captures_timing() {
+    let (tx, rx) = mpsc::unbounded();
+    let source = stream::iter(vec![1, 2])
+        .then(|x| sleep(Duration::from_millis(20)).map(move |()| x));
+    assert_eq!(block_on(record(source, tx).collect::<Vec<_>>()), vec![1, 2]);
+
+    let events = block_on(rx.collect::<Vec<_>>());
+    assert_eq!(events.iter().map(|e| e.item).collect::<Vec<_>>(), vec![1, 2]);
+    assert!(events[0].at >= Duration::from_millis(20));
+    assert!(events[1].at >= events[0].at + Duration::from_millis(20));
+}
+
+#[test]
+fn record_survives_failed_log() {
+    let (tx, rx) = mpsc::unbounded();
+    drop(rx);
+    let recorded = record(stream::iter(1..=3), tx);
+    assert_eq!(block_on(recorded.collect::<Vec<_>>()), vec![1, 2, 3]);
+}
+
+#[test]
+fn replay_reproduces_timing() {
+    let events = vec![
+        RecordedEvent { at: Duration::from_millis(0), item: 1 },
+        RecordedEvent { at: Duration::from_millis(30), item: 2 },
+    ];
+    let mut replayed = replay(events, sleep_until);
+    assert_eq!(block_on(replayed.next()), Some(1));
+    assert_eq!(replayed.next().now_or_never(), None);
+    let start = Instant::now();
+    assert_eq!(block_on(replayed.next()), Some(2));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(block_on(replayed.next()), None);
+}
+
+#[test]
+fn replay_accelerated() {
+    let events = vec![RecordedEvent { at: Duration::from_secs(3600), item: () }];
+    let start = Instant::now();
+    let replayed = replay(events, sleep_until).accelerate(3600 * 100);
+    assert_eq!(block_on(replayed.collect::<Vec<_>>()), vec![()]);
+    assert!(start.elapsed() >= Duration::from_millis(10));
+}
+
+#[test]
+fn replay_with_custom_timer() {
+    let events = vec![
+        RecordedEvent { at: Duration::from_secs(3600), item: 1 },
+        RecordedEvent { at: Duration::from_secs(7200), item: 2 },
+    ];
+    let mut deadlines = Vec::new();
+    let replayed = replay(events, |deadline| {
+        deadlines.push(deadline);
+        future::ready(())
+    });
+    assert_eq!(block_on(replayed.collect::<Vec<_>>()), vec![1, 2]);
+    assert_eq!(deadlines[1] - deadlines[0], Duration::from_secs(3600));
+}
+
+#[test]
+fn schedule_ticks_every_period() {
+    let start = Instant::now();
+    let ticks = schedule(ScheduleSpec::every(Duration::from_millis(20)));
+    assert_eq!(block_on(ticks.take(3).collect::<Vec<_>>()), vec![(), (), ()]);
+    assert!(start.elapsed() >= Duration::from_millis(60));
+}
+
+#[test]
+fn schedule_skips_missed_ticks() {
+    let mut ticks = schedule(ScheduleSpec::every(Duration::from_millis(10)));
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(block_on(ticks.next()), Some(()));
+    let start = Instant::now();
+    assert_eq!(block_on(ticks.next()), Some(()));
+    assert!(start.elapsed() >= Duration::from_millis(5));
+}
+
+#[test]
+fn schedule_jitter_only_delays_ticks() {
+    let start = Instant::now();
+    let spec = ScheduleSpec::every(Duration::from_millis(20))
+        .with_jitter(Duration::from_millis(10));
+    let ticks = schedule(spec);
+    assert_eq!(block_on(ticks.take(2).collect::<Vec<_>>()).len(), 2);
+    assert!(start.elapsed() >= Duration::from_millis(40));
+}
+
+#[test]
+fn schedule_calendar_waits_for_next_time() {
+    let mut ticks = schedule(ScheduleSpec::daily_at(3, 30, 0));
+    assert_eq!(ticks.next().now_or_never(), None);
+    assert_eq!(ticks.spec(), &ScheduleSpec::daily_at(3, 30, 0));
+}
+
+#[test]
+#[should_panic]
+fn schedule_rejects_zero_period() {
+    ScheduleSpec::every(Duration::from_millis(0));
+}
+
+#[test]
+fn timeout_ok() {
+    let (tx, rx) = oneshot::channel::<i32>();
+    thread::spawn(move || tx.send(1).unwrap());
+    assert_eq!(block_on(rx.timeout(Duration::from_secs(10))), Ok(Ok(1)));
+}
+
+#[test]
+fn timeout_expires() {
+    let (_tx, rx) = oneshot::channel::<i32>();
+    assert_eq!(block_on(rx.timeout(Duration::from_millis(20))), Err(TimedOut));
+}
+
+#[test]
+fn timeout_drops_inner_future_on_expiry() {
+    struct SetOnDrop(Arc<AtomicBool>);
+
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let dropped = Arc::new(AtomicBool::new(false));
+    let guard = SetOnDrop(dropped.clone());
+    let inner = future::pending::<()>().map(move |()| drop(guard));
+    let mut timeout = inner.timeout(Duration::from_millis(20));
+
+    assert_eq!(block_on(&mut timeout), Err(TimedOut));
+    assert!(dropped.load(Ordering::SeqCst));
+}
+
+#[test]
+fn timeout_overflowing_duration_never_expires() {
+    let (tx, rx) = oneshot::channel::<i32>();
+    let mut timeout = rx.timeout(Duration::from_secs(std::u64::MAX));
+    assert_eq!(timeout.deadline(), None);
+    assert_eq!((&mut timeout).now_or_never(), None);
+    tx.send(1).unwrap();
+    assert_eq!(block_on(timeout), Ok(Ok(1)));
+}
+
+#[test]
+fn timeout_total_passes_items_through() {
+    let stream = stream::iter(1..=3).timeout_total(Duration::from_secs(10));
+    assert_eq!(block_on(stream.collect::<Vec<_>>()), vec![Ok(1), Ok(2), Ok(3)]);
+}
+
+#[test]
+fn timeout_total_overflowing_duration_never_expires() {
+    let stream = stream::iter(1..=3).timeout_total(Duration::from_secs(std::u64::MAX));
+    assert_eq!(stream.deadline(), None);
+    assert_eq!(block_on(stream.collect::<Vec<_>>()), vec![Ok(1), Ok(2), Ok(3)]);
+}
+
+#[test]
+fn timeout_total_bounds_the_whole_stream() {
+    let start = Instant::now();
+    // Every item arrives well within the deadline, but the stream as a whole
+    // does not.
+    let stream = stream::repeat(())
+        .then(|()| sleep(Duration::from_millis(5)))
+        .timeout_total(Duration::from_millis(50));
+    let items = block_on(stream.collect::<Vec<_>>());
+
+    assert_eq!(items.last(), Some(&Err(TimedOut)));
+    assert!(items[..items.len() - 1].iter().all(Result::is_ok));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn timeout_total_stops_always_ready_stream() {
+    let mut stream = stream::repeat(1).timeout_total(Duration::from_millis(20));
+    thread::sleep(Duration::from_millis(30));
+    assert_eq!(block_on(stream.next()), Some(Err(TimedOut)));
+    assert_eq!(block_on(stream.next()), None);
+    assert!(stream.is_terminated());
+}
+
+#[test]
+fn connection_reaper_yields_idle_keys() {
+    let mut reaper = ConnectionReaper::new(Duration::from_millis(50));
+    let handle = reaper.handle();
+    let start = Instant::now();
+    handle.touch(1);
+    handle.touch(2);
+    assert_eq!(handle.len(), 2);
+
+    // Activity on 1 postpones its expiry past the one of 2.
+    thread::sleep(Duration::from_millis(20));
+    handle.touch(1);
+    assert_eq!(block_on(reaper.next()), Some(2));
+    assert_eq!(block_on(reaper.next()), Some(1));
+    assert!(start.elapsed() >= Duration::from_millis(70));
+    assert!(handle.is_empty());
+}
+
+#[test]
+fn connection_reaper_skips_removed_keys() {
+    let mut reaper = ConnectionReaper::new(Duration::from_millis(20));
+    let handle = reaper.handle();
+    handle.touch("a");
+    handle.touch("b");
+    assert!(handle.remove(&"a"));
+    assert!(!handle.remove(&"a"));
+    assert!(!handle.contains_key(&"a"));
+
+    assert_eq!(block_on(reaper.next()), Some("b"));
+    assert!(reaper.next().now_or_never().is_none());
+
+    // A key tracked again after its removal expires once.
+    handle.touch("a");
+    assert_eq!(block_on(reaper.next()), Some("a"));
+    assert!(reaper.next().now_or_never().is_none());
+}
+
+#[test]
+fn connection_reaper_overflowing_timeout_never_expires() {
+    let mut reaper = ConnectionReaper::new(Duration::from_secs(std::u64::MAX));
+    let handle = reaper.handle();
+    handle.touch(1);
+    handle.touch(1);
+    assert!(handle.contains_key(&1));
+    assert!(reaper.next().now_or_never().is_none());
+    assert!(handle.remove(&1));
+}
+
+#[test]
+fn connection_reaper_waits_for_first_key() {
+    let mut reaper = ConnectionReaper::new(Duration::from_millis(10));
+    let handle = reaper.handle();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        handle.touch(7);
+    });
+    assert_eq!(block_on(reaper.next()), Some(7));
+}
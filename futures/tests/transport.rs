@@ -0,0 +1,500 @@
+use futures::channel::{mpsc, oneshot};
+use futures::executor::{block_on, ThreadPool};
+use futures::future::{self, Either, FutureExt};
+use futures::sink::{Sink, SinkExt};
+use futures::stream::{self, StreamExt};
+use futures::task::{Poll, SpawnExt};
+use futures::timer::{sleep, TimedOut};
+use futures::transport::streaming::{self, Body, BodyFrame, Frame};
+use futures::transport::{self, multiplex, pipeline, Checksum, ChecksumError, Transport, TransportExt};
+use futures_test::task::noop_context;
+use std::pin::Pin;
+use std::thread;
+use std::time::Duration;
+
+fn echo_twice<T>(mut transport: T) -> T
+where
+    T: Transport<u32, Item = u32> + Unpin,
+    <T as Sink<u32>>::Error: std::fmt::Debug,
+{
+    block_on(async {
+        while let Some(frame) = transport.next().await {
+            transport.send(frame * 2).await.unwrap();
+        }
+    });
+    transport
+}
+
+#[test]
+fn duplex_is_a_transport() {
+    let (in_tx, in_rx) = mpsc::unbounded();
+    let (out_tx, out_rx) = mpsc::unbounded();
+    in_tx.unbounded_send(1).unwrap();
+    in_tx.unbounded_send(2).unwrap();
+    drop(in_tx);
+
+    // Stream and sink adapters act as layers, keeping both halves.
+    let mut logged = Vec::new();
+    let transport = transport::duplex(in_rx, out_tx)
+        .inspect(|frame| logged.push(*frame))
+        .with(|frame: u32| future::ok::<_, mpsc::SendError>(frame + 1));
+    let (_, out_tx) = echo_twice(transport).into_inner().into_inner().into_inner();
+    drop(out_tx);
+
+    assert_eq!(logged, vec![1, 2]);
+    assert_eq!(block_on(out_rx.collect::<Vec<_>>()), vec![3, 5]);
+}
+
+#[test]
+fn heartbeat_when_idle() {
+    let (_in_tx, in_rx) = mpsc::unbounded::<()>();
+    let (out_tx, out_rx) = mpsc::unbounded();
+    let mut transport = transport::duplex(in_rx, out_tx)
+        .heartbeat(Duration::from_millis(10), || "PING");
+
+    let mut pings = out_rx.take(2);
+    let pinged = future::select(transport.next(), (&mut pings).collect::<Vec<_>>());
+    match block_on(pinged) {
+        Either::Right((frames, _)) => assert_eq!(frames, vec!["PING", "PING"]),
+        Either::Left(_) => panic!("inbound stream ended"),
+    }
+}
+
+#[test]
+fn heartbeat_delayed_by_outbound_frames() {
+    let (_in_tx, in_rx) = mpsc::unbounded::<()>();
+    let (out_tx, mut out_rx) = mpsc::unbounded();
+    let mut transport = transport::duplex(in_rx, out_tx)
+        .heartbeat(Duration::from_millis(50), || "PING");
+
+    // Keep sending well within the interval while polling the inbound half.
+    for _ in 0..5 {
+        block_on(transport.send("DATA")).unwrap();
+        assert!(transport.next().poll_unpin(&mut noop_context()).is_pending());
+        thread::sleep(Duration::from_millis(20));
+        assert!(transport.next().poll_unpin(&mut noop_context()).is_pending());
+    }
+    let mut cx = noop_context();
+    let mut frames = Vec::new();
+    while let Poll::Ready(Some(frame)) = out_rx.poll_next_unpin(&mut cx) {
+        frames.push(frame);
+    }
+    assert_eq!(frames, vec!["DATA"; 5]);
+}
+
+#[test]
+fn heartbeat_keeps_reserved_slot() {
+    let (_in_tx, in_rx) = mpsc::unbounded::<()>();
+    let (out_tx, mut out_rx) = mpsc::channel(0);
+    let mut transport = transport::duplex(in_rx, out_tx)
+        .heartbeat(Duration::from_millis(10), || "PING");
+
+    let mut cx = noop_context();
+    assert!(Pin::new(&mut transport).poll_ready(&mut cx).is_ready());
+    thread::sleep(Duration::from_millis(30));
+    assert!(transport.next().poll_unpin(&mut cx).is_pending());
+    Pin::new(&mut transport).start_send("DATA").unwrap();
+    assert_eq!(out_rx.try_next().unwrap(), Some("DATA"));
+}
+
+#[test]
+fn idle_timeout_reset_by_inbound_frames() {
+    let (in_tx, in_rx) = mpsc::unbounded();
+    let (out_tx, _out_rx) = mpsc::unbounded::<()>();
+    let mut transport = transport::duplex(in_rx, out_tx)
+        .idle_timeout(Duration::from_millis(50));
+
+    let sender = thread::spawn(move || {
+        for i in 0..4 {
+            thread::sleep(Duration::from_millis(20));
+            in_tx.unbounded_send(i).unwrap();
+        }
+        // Keep the channel open so that the transport goes idle.
+        thread::sleep(Duration::from_millis(200));
+        drop(in_tx);
+    });
+
+    let frames = block_on((&mut transport).take(5).collect::<Vec<_>>());
+    assert_eq!(frames, vec![Ok(0), Ok(1), Ok(2), Ok(3), Err(TimedOut)]);
+    assert_eq!(block_on(transport.next()), None);
+    sender.join().unwrap();
+}
+
+#[test]
+fn idle_timeout_keeps_sink_usable() {
+    let (_in_tx, in_rx) = mpsc::unbounded::<()>();
+    let (out_tx, out_rx) = mpsc::unbounded();
+    let mut transport = transport::duplex(in_rx, out_tx)
+        .idle_timeout(Duration::from_millis(10));
+
+    block_on(sleep(Duration::from_millis(20)));
+    assert_eq!(block_on(transport.next()), Some(Err(TimedOut)));
+    block_on(transport.send("BYE")).unwrap();
+    drop(transport);
+    assert_eq!(block_on(out_rx.collect::<Vec<_>>()), vec!["BYE"]);
+}
+
+#[test]
+fn multiplex_client_and_server() {
+    let (req_tx, req_rx) = mpsc::unbounded::<(u64, u32)>();
+    let (resp_tx, resp_rx) = mpsc::unbounded();
+    let (client, task) = multiplex::client(transport::duplex(resp_rx.map(Ok::<_, mpsc::SendError>), req_tx));
+    let server = multiplex::server(transport::duplex(req_rx.map(Ok), resp_tx));
+
+    // Answer the requests in reverse order of arrival, then keep serving
+    // until the client goes away.
+    let serve = async move {
+        let mut server = server;
+        let mut requests = Vec::new();
+        for _ in 0..3 {
+            requests.push(server.next().await.unwrap().unwrap());
+        }
+        while let Some((n, responder)) = requests.pop() {
+            responder.respond(n * 10).unwrap();
+        }
+        assert!(server.next().await.is_none());
+    };
+    let calls = future::join3(client.call(1), client.call(2), client.call(3));
+    drop(client);
+
+    let (responses, res, ()) = block_on(future::join3(calls, task, serve));
+    assert_eq!(responses, (Ok(10), Ok(20), Ok(30)));
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn multiplex_client_cancels_pending_calls() {
+    let (req_tx, _req_rx) = mpsc::unbounded::<(u64, u32)>();
+    let (resp_tx, resp_rx) = mpsc::unbounded::<(u64, u32)>();
+    let (client, task) = multiplex::client(transport::duplex(resp_rx.map(Ok::<_, mpsc::SendError>), req_tx));
+
+    let call = client.call(1);
+    drop(resp_tx);
+    assert_eq!(block_on(task), Ok(()));
+    assert_eq!(block_on(call), Err(multiplex::Canceled));
+    assert_eq!(block_on(client.call(2)), Err(multiplex::Canceled));
+    assert_eq!(format!("{:?}", client), "Client { pending: 0 }");
+}
+
+#[test]
+fn multiplex_server_drains_responses() {
+    let (req_tx, req_rx) = mpsc::unbounded::<(u64, &str)>();
+    let (resp_tx, resp_rx) = mpsc::unbounded();
+    let mut server = multiplex::server(transport::duplex(req_rx.map(Ok::<_, mpsc::SendError>), resp_tx));
+
+    req_tx.unbounded_send((3, "a")).unwrap();
+    req_tx.unbounded_send((5, "b")).unwrap();
+    drop(req_tx);
+
+    let (_, first) = block_on(server.next()).unwrap().unwrap();
+    let (_, second) = block_on(server.next()).unwrap().unwrap();
+    assert_eq!((first.id(), second.id()), (3, 5));
+
+    // The inbound stream has ended, but responses are still pending.
+    assert!(server.poll_next_unpin(&mut noop_context()).is_pending());
+    second.respond("B").unwrap();
+    first.respond("A").unwrap();
+    assert!(block_on(server.next()).is_none());
+    drop(server);
+    assert_eq!(block_on(resp_rx.collect::<Vec<_>>()), vec![(5, "B"), (3, "A")]);
+}
+
+#[test]
+fn pipeline_client_bounds_in_flight_requests() {
+    let (req_tx, mut req_rx) = mpsc::unbounded::<u32>();
+    let (resp_tx, resp_rx) = mpsc::unbounded();
+    let (client, mut task) = pipeline::client(transport::duplex(resp_rx.map(Ok::<_, mpsc::SendError>), req_tx), 2);
+    let cx = &mut noop_context();
+
+    let mut calls = (1..=3).map(|n| client.call(n)).collect::<Vec<_>>();
+    drop(client);
+    assert!(task.poll_unpin(cx).is_pending());
+    assert_eq!(req_rx.poll_next_unpin(cx), Poll::Ready(Some(1)));
+    assert_eq!(req_rx.poll_next_unpin(cx), Poll::Ready(Some(2)));
+    assert_eq!(req_rx.poll_next_unpin(cx), Poll::Pending);
+
+    // Answering the first request lets the third one go out.
+    resp_tx.unbounded_send(10).unwrap();
+    assert!(task.poll_unpin(cx).is_pending());
+    assert_eq!(req_rx.poll_next_unpin(cx), Poll::Ready(Some(3)));
+    assert_eq!(calls[0].poll_unpin(cx), Poll::Ready(Ok(10)));
+
+    resp_tx.unbounded_send(20).unwrap();
+    resp_tx.unbounded_send(30).unwrap();
+    assert_eq!(task.poll_unpin(cx), Poll::Ready(Ok(())));
+    assert_eq!(calls[1].poll_unpin(cx), Poll::Ready(Ok(20)));
+    assert_eq!(calls[2].poll_unpin(cx), Poll::Ready(Ok(30)));
+    assert_eq!(req_rx.poll_next_unpin(cx), Poll::Ready(None));
+}
+
+#[test]
+fn pipeline_client_cancels_pending_calls() {
+    let (req_tx, _req_rx) = mpsc::unbounded::<u32>();
+    let (resp_tx, resp_rx) = mpsc::unbounded::<u32>();
+    let (client, task) = pipeline::client(transport::duplex(resp_rx.map(Ok::<_, mpsc::SendError>), req_tx), 1);
+
+    let first = client.call(1);
+    let second = client.call(2);
+    drop(resp_tx);
+    assert_eq!(block_on(task), Ok(()));
+    assert_eq!(block_on(first), Err(pipeline::Canceled));
+    assert_eq!(block_on(second), Err(pipeline::Canceled));
+    assert_eq!(block_on(client.call(3)), Err(pipeline::Canceled));
+}
+
+#[test]
+fn pipeline_server_responds_in_order() {
+    let (req_tx, req_rx) = mpsc::unbounded::<u32>();
+    let (resp_tx, mut resp_rx) = mpsc::unbounded();
+    let mut server = pipeline::server(transport::duplex(req_rx.map(Ok::<_, mpsc::SendError>), resp_tx), 2);
+    let cx = &mut noop_context();
+
+    for n in 1..=3 {
+        req_tx.unbounded_send(n).unwrap();
+    }
+    drop(req_tx);
+
+    let (_, first) = block_on(server.next()).unwrap().unwrap();
+    let (_, second) = block_on(server.next()).unwrap().unwrap();
+    // The third request is held back until a response is sent.
+    assert!(server.poll_next_unpin(cx).is_pending());
+
+    second.respond(20).unwrap();
+    assert!(server.poll_next_unpin(cx).is_pending());
+    assert_eq!(resp_rx.poll_next_unpin(cx), Poll::Pending);
+
+    first.respond(10).unwrap();
+    let (n, third) = block_on(server.next()).unwrap().unwrap();
+    assert_eq!(n, 3);
+    assert_eq!(resp_rx.poll_next_unpin(cx), Poll::Ready(Some(10)));
+    assert_eq!(resp_rx.poll_next_unpin(cx), Poll::Ready(Some(20)));
+
+    third.respond(30).unwrap();
+    assert!(block_on(server.next()).is_none());
+    drop(server);
+    assert_eq!(block_on(resp_rx.collect::<Vec<_>>()), vec![30]);
+}
+
+#[test]
+fn streaming_inbound_bodies() {
+    let (in_tx, in_rx) = mpsc::unbounded();
+    let (out_tx, _out_rx) = mpsc::unbounded::<Frame<(), u32, ()>>();
+    let mut messages = streaming::messages(transport::duplex(in_rx.map(Ok::<_, mpsc::SendError>), out_tx), 0);
+    let cx = &mut noop_context();
+
+    for frame in vec![
+        Frame::Head("a"), Frame::Chunk(1), Frame::Chunk(2), Frame::Trailer(()),
+        Frame::Head("b"), Frame::Chunk(3), Frame::Trailer(()),
+        Frame::Head("c"), Frame::Chunk(4),
+    ] {
+        in_tx.unbounded_send(frame).unwrap();
+    }
+    drop(in_tx);
+
+    let (head, mut body) = block_on(messages.next()).unwrap().unwrap();
+    assert_eq!(head, "a");
+    // The body is full, so the next message cannot be read yet.
+    assert!(messages.poll_next_unpin(cx).is_pending());
+    assert_eq!(body.poll_next_unpin(cx), Poll::Ready(Some(BodyFrame::Chunk(1))));
+    assert!(messages.poll_next_unpin(cx).is_pending());
+    assert_eq!(body.poll_next_unpin(cx), Poll::Ready(Some(BodyFrame::Chunk(2))));
+
+    // Once the trailer is in the body, the next message can be read.
+    let (head, next_body) = block_on(messages.next()).unwrap().unwrap();
+    assert_eq!(head, "b");
+    assert_eq!(body.poll_next_unpin(cx), Poll::Ready(Some(BodyFrame::Trailer(()))));
+    assert_eq!(body.poll_next_unpin(cx), Poll::Ready(None));
+
+    // A dropped body is skipped.
+    drop(next_body);
+
+    // A body cut short by the end of the connection ends without a trailer.
+    let (head, body) = block_on(messages.next()).unwrap().unwrap();
+    assert_eq!(head, "c");
+    assert!(block_on(messages.next()).is_none());
+    assert_eq!(block_on(body.collect::<Vec<_>>()), vec![BodyFrame::Chunk(4)]);
+}
+
+#[test]
+fn streaming_outbound_bodies() {
+    let (_in_tx, in_rx) = mpsc::unbounded::<Frame<(), u32, &str>>();
+    let (out_tx, mut out_rx) = mpsc::unbounded();
+    let mut messages = streaming::messages(transport::duplex(in_rx.map(Ok::<_, mpsc::SendError>), out_tx), 1);
+    let cx = &mut noop_context();
+
+    let (mut body_tx, body) = streaming::body_channel(1);
+    assert_eq!(Pin::new(&mut messages).poll_ready(cx), Poll::Ready(Ok(())));
+    Pin::new(&mut messages).start_send(("a", body)).unwrap();
+    block_on(body_tx.send(BodyFrame::Chunk(1))).unwrap();
+
+    // Chunks are sent as they come, while the next message waits.
+    assert!(Pin::new(&mut messages).poll_flush(cx).is_pending());
+    assert_eq!(out_rx.poll_next_unpin(cx), Poll::Ready(Some(Frame::Head("a"))));
+    assert_eq!(out_rx.poll_next_unpin(cx), Poll::Ready(Some(Frame::Chunk(1))));
+    assert!(Pin::new(&mut messages).poll_ready(cx).is_pending());
+
+    block_on(body_tx.send(BodyFrame::Trailer("end"))).unwrap();
+    block_on(messages.send(("b", Body::empty("none")))).unwrap();
+    drop(messages);
+    assert_eq!(
+        block_on(out_rx.collect::<Vec<_>>()),
+        vec![Frame::Trailer("end"), Frame::Head("b"), Frame::Trailer("none")],
+    );
+}
+
+#[test]
+fn streaming_pipeline_responses() {
+    let (req_tx, req_rx) = mpsc::unbounded::<Frame<&str, u32, ()>>();
+    let (resp_tx, resp_rx) = mpsc::unbounded::<Frame<&str, u32, ()>>();
+    let client_transport = streaming::messages(transport::duplex(resp_rx.map(Ok::<_, mpsc::SendError>), req_tx), 1);
+    let server_transport = streaming::messages(transport::duplex(req_rx.map(Ok), resp_tx), 1);
+    let (client, task) = pipeline::client(client_transport, 1);
+    let mut server = pipeline::server(server_transport, 1);
+
+    let serve = async move {
+        while let Some(request) = server.next().await {
+            let ((head, _), responder) = request.unwrap();
+            // The body is only sent while the server is polled, so it must
+            // have room for every frame here.
+            let (mut body_tx, body) = streaming::body_channel(4);
+            responder.respond((head, body)).unwrap();
+            for n in 0..3 {
+                body_tx.send(BodyFrame::Chunk(n)).await.unwrap();
+            }
+            body_tx.send(BodyFrame::Trailer(())).await.unwrap();
+        }
+    };
+    let call = async move {
+        let (head, body) = client.call(("GET", Body::empty(()))).await.unwrap();
+        (head, body.collect::<Vec<_>>().await)
+    };
+
+    let ((head, body), res, ()) = block_on(future::join3(call, task, serve));
+    assert_eq!(head, "GET");
+    assert_eq!(body, vec![
+        BodyFrame::Chunk(0), BodyFrame::Chunk(1), BodyFrame::Chunk(2), BodyFrame::Trailer(()),
+    ]);
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn serve_limits_connections() {
+    let pool = ThreadPool::new().unwrap();
+    let (started_tx, mut started_rx) = mpsc::unbounded();
+    let (release_txs, release_rxs): (Vec<_>, Vec<_>) = (0..3).map(|_| oneshot::channel::<()>()).unzip();
+    let mut release_rxs = release_rxs.into_iter();
+
+    let server = transport::serve(stream::iter(0..3), pool.clone(), move |conn| {
+        let started_tx = started_tx.clone();
+        let release = release_rxs.next().unwrap();
+        async move {
+            started_tx.unbounded_send(conn).unwrap();
+            let _ = release.await;
+        }
+    })
+    .max_connections(2);
+    let server = pool.clone().spawn_with_handle(server).unwrap();
+
+    assert_eq!(block_on(started_rx.next()), Some(0));
+    assert_eq!(block_on(started_rx.next()), Some(1));
+    thread::sleep(Duration::from_millis(50));
+    assert!(started_rx.try_next().is_err());
+
+    let mut release_txs = release_txs.into_iter();
+    release_txs.next().unwrap().send(()).unwrap();
+    assert_eq!(block_on(started_rx.next()), Some(2));
+    drop(release_txs);
+    block_on(server).unwrap();
+}
+
+#[test]
+fn serve_drains_connections_on_shutdown() {
+    let pool = ThreadPool::new().unwrap();
+    let (started_tx, mut started_rx) = mpsc::unbounded();
+    let (release_tx, release_rx) = oneshot::channel::<()>();
+    let mut release_rx = Some(release_rx);
+
+    let incoming = stream::once(future::ready(1)).chain(stream::pending());
+    let server = transport::serve(incoming, pool.clone(), move |conn| {
+        let started_tx = started_tx.clone();
+        let release = release_rx.take().unwrap();
+        async move {
+            started_tx.unbounded_send(conn).unwrap();
+            let _ = release.await;
+        }
+    });
+    let shutdown = server.shutdown_handle();
+    let mut server = pool.clone().spawn_with_handle(server).unwrap();
+
+    assert_eq!(block_on(started_rx.next()), Some(1));
+    shutdown.shutdown();
+    // The connection in flight is waited for.
+    thread::sleep(Duration::from_millis(50));
+    assert!(server.poll_unpin(&mut noop_context()).is_pending());
+
+    release_tx.send(()).unwrap();
+    block_on(server).unwrap();
+}
+
+#[test]
+fn serve_cancels_connections_after_drain_timeout() {
+    let pool = ThreadPool::new().unwrap();
+    let (started_tx, mut started_rx) = mpsc::unbounded();
+    let (canceled_tx, canceled_rx) = oneshot::channel::<()>();
+    let mut canceled_tx = Some(canceled_tx);
+
+    let incoming = stream::once(future::ready(1)).chain(stream::pending());
+    let server = transport::serve(incoming, pool.clone(), move |conn| {
+        let started_tx = started_tx.clone();
+        // Dropped along with the task of the connection.
+        let canceled_tx = canceled_tx.take().unwrap();
+        async move {
+            started_tx.unbounded_send(conn).unwrap();
+            future::pending::<()>().await;
+            drop(canceled_tx);
+        }
+    })
+    .drain_timeout(Duration::from_millis(20));
+    let shutdown = server.shutdown_handle();
+    let server = pool.clone().spawn_with_handle(server).unwrap();
+
+    assert_eq!(block_on(started_rx.next()), Some(1));
+    shutdown.shutdown();
+    block_on(server).unwrap();
+    assert_eq!(block_on(canceled_rx), Err(oneshot::Canceled));
+}
+
+#[test]
+fn crc32_matches_reference_value() {
+    assert_eq!(transport::Crc32.checksum(b"123456789"), 0xCBF4_3926);
+    assert_eq!(transport::Crc32.checksum(b""), 0);
+}
+
+#[test]
+fn checksum_trailer_rejects_bad_frames() {
+    struct Sum;
+    impl Checksum for Sum {
+        fn checksum(&self, data: &[u8]) -> u32 {
+            data.iter().map(|&b| u32::from(b)).sum()
+        }
+    }
+
+    let (inbound_tx, inbound_rx) = mpsc::unbounded();
+    let (outbound_tx, mut outbound_rx) = mpsc::unbounded();
+    let mut transport = transport::duplex(inbound_rx, outbound_tx).checksum_trailer_with(Sum);
+
+    block_on(transport.send(vec![1, 2])).unwrap();
+    assert_eq!(block_on(outbound_rx.next()), Some(vec![1, 2, 0, 0, 0, 3]));
+
+    inbound_tx.unbounded_send(vec![0, 0, 1]).unwrap();
+    inbound_tx.unbounded_send(vec![1, 2, 0, 0, 0, 4]).unwrap();
+    inbound_tx.unbounded_send(vec![5, 0, 0, 0, 5]).unwrap();
+    drop(inbound_tx);
+    assert_eq!(block_on(transport.next()), Some(Err(ChecksumError::Truncated { len: 3 })));
+    assert_eq!(
+        block_on(transport.next()),
+        Some(Err(ChecksumError::Mismatch { expected: 4, actual: 3 })),
+    );
+    assert_eq!(block_on(transport.next()), Some(Ok(vec![5])));
+    assert_eq!(block_on(transport.next()), None);
+}
@@ -0,0 +1,78 @@
+use futures::channel::oneshot;
+use futures::executor::block_on;
+use futures::future::{self, FutureExt, TryFutureExt};
+use futures::stream::{self, ErrorPolicy, StreamExt, TryStreamExt};
+use futures::timer::{sleep, TimedOut};
+use futures_test::task::noop_context;
+use std::time::Duration;
+
+fn results() -> Vec<Result<future::Ready<Result<i32, i32>>, i32>> {
+    vec![
+        Ok(future::ok(1)),
+        Ok(future::err(-1)),
+        Ok(future::ok(2)),
+        Ok(future::err(-2)),
+    ]
+}
+
+#[test]
+fn continue_yields_errors() {
+    let buffered = stream::iter(results()).try_buffer_unordered(1);
+    assert_eq!(block_on(buffered.collect::<Vec<_>>()), vec![Ok(1), Err(-1), Ok(2), Err(-2)]);
+}
+
+#[test]
+fn fail_fast_ends_at_first_error() {
+    let buffered = stream::iter(results())
+        .try_buffer_unordered(1)
+        .error_policy(ErrorPolicy::FailFast);
+    assert_eq!(block_on(buffered.collect::<Vec<_>>()), vec![Ok(1), Err(-1)]);
+}
+
+#[test]
+fn fail_fast_drops_futures_in_flight() {
+    let (tx, rx) = oneshot::channel::<i32>();
+    let futures = vec![
+        Ok(rx.map_err(|_| 0).boxed()),
+        Ok(future::err(-1).boxed()),
+    ];
+    let buffered = stream::iter(futures)
+        .try_buffer_unordered(2)
+        .error_policy(ErrorPolicy::FailFast);
+    assert_eq!(block_on(buffered.collect::<Vec<_>>()), vec![Err(-1)]);
+    assert!(tx.is_canceled());
+}
+
+#[test]
+fn collect_keeps_errors_out_of_the_stream() {
+    let mut buffered = stream::iter(results())
+        .try_buffer_unordered(1)
+        .error_policy(ErrorPolicy::Collect);
+    assert_eq!(block_on((&mut buffered).collect::<Vec<_>>()), vec![Ok(1), Ok(2)]);
+    assert_eq!(buffered.take_errors(), vec![-1, -2]);
+    assert!(buffered.take_errors().is_empty());
+}
+
+#[derive(Debug, PartialEq)]
+enum Error {
+    TimedOut,
+}
+
+impl From<TimedOut> for Error {
+    fn from(_: TimedOut) -> Error {
+        Error::TimedOut
+    }
+}
+
+#[test]
+fn item_timeout_fails_slow_futures() {
+    let futures = vec![
+        Ok(future::pending::<Result<i32, Error>>().boxed()),
+        Ok(sleep(Duration::from_millis(10)).map(|()| Ok(1)).boxed()),
+    ];
+    let mut buffered = stream::iter(futures)
+        .try_buffer_unordered(2)
+        .item_timeout(Duration::from_millis(50));
+    assert!(buffered.poll_next_unpin(&mut noop_context()).is_pending());
+    assert_eq!(block_on(buffered.collect::<Vec<_>>()), vec![Ok(1), Err(Error::TimedOut)]);
+}
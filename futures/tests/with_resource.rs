@@ -0,0 +1,85 @@
+use futures::channel::oneshot;
+use futures::executor::block_on;
+use futures::future::{self, with_resource, with_resource_release, FutureExt};
+use futures_test::task::noop_context;
+use std::cell::RefCell;
+use std::sync::Arc;
+
+#[derive(Debug)]
+struct Resource<'a> {
+    log: &'a RefCell<Vec<&'static str>>,
+}
+
+impl Drop for Resource<'_> {
+    fn drop(&mut self) {
+        self.log.borrow_mut().push("dropped");
+    }
+}
+
+fn release(resource: Resource<'_>) -> future::Ready<()> {
+    resource.log.borrow_mut().push("released");
+    future::ready(())
+}
+
+#[test]
+fn with_resource_releases_after_body() {
+    let log = RefCell::new(Vec::new());
+    let future = with_resource_release(
+        future::lazy(|_| Resource { log: &log }),
+        release,
+        |resource| {
+            resource.log.borrow_mut().push("used");
+            future::ready(1)
+        },
+    );
+
+    assert_eq!(block_on(future), 1);
+    assert_eq!(*log.borrow(), vec!["used", "released", "dropped"]);
+}
+
+#[test]
+fn with_resource_drops_by_default() {
+    let log = RefCell::new(Vec::new());
+    let future = with_resource(
+        future::ready(Resource { log: &log }),
+        |_| future::ready(Err::<(), _>("failed")),
+    );
+
+    assert_eq!(block_on(future), Err("failed"));
+    assert_eq!(*log.borrow(), vec!["dropped"]);
+}
+
+#[test]
+fn with_resource_releases_on_cancellation() {
+    let log = RefCell::new(Vec::new());
+    let (_tx, rx) = oneshot::channel::<()>();
+    let mut future = with_resource_release(
+        future::ready(Resource { log: &log }),
+        release,
+        |_| rx,
+    );
+
+    assert!(future.poll_unpin(&mut noop_context()).is_pending());
+    assert!(log.borrow().is_empty());
+    drop(future);
+    assert_eq!(*log.borrow(), vec!["released", "dropped"]);
+}
+
+#[test]
+fn with_resource_releases_leaked_handle_on_drop() {
+    let log = RefCell::new(Vec::new());
+    let leaked = RefCell::new(None);
+    let future = with_resource_release(
+        future::ready(Resource { log: &log }),
+        release,
+        |resource| {
+            *leaked.borrow_mut() = Some(Arc::clone(&resource));
+            future::ready(1)
+        },
+    );
+
+    assert_eq!(block_on(future), 1);
+    assert!(log.borrow().is_empty());
+    drop(leaked);
+    assert_eq!(*log.borrow(), vec!["released", "dropped"]);
+}